@@ -8,6 +8,8 @@ var<uniform> c1: vec4f;
 var<uniform> c2: vec4f;
 @group(1) @binding(2)
 var<uniform> time: f32;
+@group(1) @binding(3)
+var<uniform> scroll_speed: f32;
 #define_import_path bevy_shader_utils::simplex_noise_2d
 
 fn mod289_(x: vec2<f32>) -> vec2<f32> {
@@ -80,7 +82,7 @@ fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
   // var scaled_frag_pos = vec2f(in.uv.x, y * 18.0);
   // scaled_frag_pos -= vec2f(0.0, scroll_speed * globals.time);
   let grid = 80.0;
-  let uv = vec2f((in.uv.x + time * 0.1) * ar, in.uv.y);
+  let uv = vec2f((in.uv.x + time * scroll_speed) * ar, in.uv.y);
   var g = floor(uv * grid) / grid;
 
   let v = simplex_noise_2d(g) * 0.1;
@@ -0,0 +1,30 @@
+#import bevy_core_pipeline::fullscreen_vertex_shader::FullscreenVertexOutput
+
+@group(0) @binding(0)
+var screen_texture: texture_2d<f32>;
+@group(0) @binding(1)
+var screen_sampler: sampler;
+
+struct PostProcessSettings {
+  vignette: f32,
+  aberration: f32,
+  desaturation: f32,
+}
+@group(0) @binding(2)
+var<uniform> settings: PostProcessSettings;
+
+@fragment
+fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
+  // split off the red channel, away from the center
+  let from_center = in.uv - vec2f(0.5, 0.5);
+  let offset = from_center * settings.aberration;
+  let base = textureSample(screen_texture, screen_sampler, in.uv);
+  let red = textureSample(screen_texture, screen_sampler, in.uv + offset).r;
+  var color = vec3f(max(base.r, red), base.g, base.b);
+
+  let luma = dot(color, vec3f(0.2126, 0.7152, 0.0722));
+  color = mix(color, vec3f(luma), settings.desaturation);
+
+  let vignette = 1.0 - settings.vignette * dot(from_center, from_center) * 2.0;
+  return vec4f(color * clamp(vignette, 0.0, 1.0), base.a);
+}
@@ -0,0 +1,153 @@
+//! Benchmarks for spawning and cleanup hot paths, run against a headless world.
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+use bevy_rapier2d::prelude::*;
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
+use rustyrocket::{
+    accessibility::MotionSettings,
+    barrier::BarrierAssets,
+    dying_player::{explode_player, PlayerDiedEvent},
+    level::{remove_invisible_objects, LevelSettings, RemoveWhenLeft},
+    obstacle::{factory::spawn_tunnel_at, TunnelParams},
+    player::{DecomposedSprite, OutOfBoundsEvent, Player},
+    score::ScoreDepletedEvent,
+    util::Containers,
+    GameState, WorldSettings,
+};
+
+/// World with the resources shared by the spawning systems.
+fn headless_world() -> World {
+    let mut world = World::new();
+    world.insert_resource(WorldSettings {
+        bounds: Rect::new(-512.0, -288.0, 512.0, 288.0),
+        ..default()
+    });
+    world.init_resource::<Assets<Mesh>>();
+    world.init_resource::<Assets<ColorMaterial>>();
+    world.init_resource::<BarrierAssets>();
+    world.init_resource::<Containers>();
+    world
+}
+
+/// Number of items for a system to spawn.
+#[derive(Resource)]
+struct SpawnCount(usize);
+
+fn spawn_tunnels(
+    mut commands: Commands,
+    count: Res<SpawnCount>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    play_world: Res<WorldSettings>,
+    obs_mat: Res<BarrierAssets>,
+    containers: Res<Containers>,
+) {
+    let params = TunnelParams {
+        gap_center: 0.0,
+        gap_height: 250.0,
+        obstacle_width: 96.0,
+        scoring_gap_width: 32.0,
+        score_delta: 1,
+    };
+    for i in 0..count.0 {
+        spawn_tunnel_at(
+            &mut commands,
+            containers.obstacles,
+            &params,
+            i as f32 * 200.0,
+            Vec2::new(-200.0, 0.0),
+            &mut meshes,
+            &play_world,
+            &obs_mat,
+        );
+    }
+}
+
+fn bench_spawn_tunnel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn_tunnel");
+    for count in [1, 100, 1000] {
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, &count| {
+            b.iter_batched(
+                || {
+                    let mut world = headless_world();
+                    world.insert_resource(SpawnCount(count));
+                    world
+                },
+                |mut world| world.run_system_once(spawn_tunnels),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_remove_invisible_objects(c: &mut Criterion) {
+    let mut group = c.benchmark_group("remove_invisible_objects");
+    for count in [1000, 10000] {
+        let setup = move || {
+            let mut world = headless_world();
+            // half of the entities have left the screen.
+            world.spawn_batch((0..count).map(|i| {
+                let x = if i % 2 == 0 { -2000.0 } else { 0.0 };
+                (GlobalTransform::from_xyz(x, 0.0, 0.0), RemoveWhenLeft(96.0))
+            }));
+            world
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(count), &count, |b, _| {
+            b.iter_batched(
+                setup,
+                |mut world| world.run_system_once(remove_invisible_objects),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn bench_explode_player(c: &mut Criterion) {
+    let mut group = c.benchmark_group("explode_player");
+    for pixels in [64, 256, 1024] {
+        let setup = move || {
+            let mut world = World::new();
+            world.init_resource::<State<GameState>>();
+            world.init_resource::<NextState<GameState>>();
+            world.init_resource::<MotionSettings>();
+            world.init_resource::<Containers>();
+            world.init_resource::<Events<PlayerDiedEvent>>();
+            world.init_resource::<Events<OutOfBoundsEvent>>();
+            world.init_resource::<Events<ScoreDepletedEvent>>();
+            world.insert_resource(LevelSettings {
+                explosion_speed: 600.0,
+                ..default()
+            });
+            world.insert_resource(DecomposedSprite {
+                pixels: (0..pixels)
+                    .map(|i| (Vec2::new((i % 32) as f32, (i / 32) as f32), Color::WHITE))
+                    .collect(),
+                pixel_size: 1.0,
+            });
+            world.spawn((
+                Player,
+                Transform::default(),
+                Velocity::default(),
+                TextureAtlasSprite::default(),
+                Handle::<TextureAtlas>::default(),
+            ));
+            world
+        };
+        group.bench_with_input(BenchmarkId::from_parameter(pixels), &pixels, |b, _| {
+            b.iter_batched(
+                setup,
+                |mut world| world.run_system_once(explode_player),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_spawn_tunnel,
+    bench_remove_invisible_objects,
+    bench_explode_player
+);
+criterion_main!(benches);
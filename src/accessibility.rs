@@ -0,0 +1,80 @@
+//! Options for players who need the game to look or behave differently.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::Settings;
+
+/// Which moving effects to show, for players sensitive to motion.
+///
+/// Effect systems check this themselves, so each effect has a still
+/// alternative rather than just switching off.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct MotionSettings {
+    /// Freeze the background, stop the gravity arrows scrolling, and fade
+    /// the player out on death instead of exploding it.
+    pub reduced: bool,
+}
+
+impl MotionSettings {
+    /// Whether effects that move for decoration should run.
+    pub fn animate(&self) -> bool {
+        !self.reduced
+    }
+}
+
+/// Speed of gameplay, relative to normal.
+///
+/// Applied as the relative speed of [`Time<Virtual>`], which spawner timers,
+/// tweens and physics all run on, so nothing else should scale by it.
+/// Animations that should stay at full speed use [`Time<Real>`].
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GameSpeed {
+    pub relative: f32,
+}
+
+impl GameSpeed {
+    pub const MIN: f32 = 0.5;
+    pub const MAX: f32 = 1.0;
+
+    /// Change between adjacent settings in the pause menu.
+    pub const STEP: f32 = 0.1;
+
+    /// Speed within the allowed range.
+    pub fn clamped(&self) -> f32 {
+        self.relative.clamp(Self::MIN, Self::MAX)
+    }
+
+    /// Move the speed by a number of steps, staying in range.
+    pub fn step(&mut self, steps: i32) {
+        let stepped = self.clamped() + steps as f32 * Self::STEP;
+        // round, so that repeated steps land back on whole percentages
+        self.relative = ((stepped / Self::STEP).round() * Self::STEP).clamp(Self::MIN, Self::MAX);
+    }
+}
+
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self { relative: 1.0 }
+    }
+}
+
+/// Run gameplay at the chosen speed.
+fn apply_game_speed(settings: Res<Settings>, mut time: ResMut<Time<Virtual>>) {
+    time.set_relative_speed(settings.game_speed.clamped());
+}
+
+pub struct AccessibilityPlugin;
+
+impl Plugin for AccessibilityPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<MotionSettings>()
+            .init_resource::<MotionSettings>()
+            .register_type::<GameSpeed>()
+            .add_systems(
+                PreUpdate,
+                apply_game_speed.run_if(resource_changed::<Settings>()),
+            );
+    }
+}
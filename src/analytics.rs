@@ -0,0 +1,198 @@
+//! Heatmap of where the player is when passing gates and dying, gathered
+//! over many runs, so designers can check that the spread of tunnel gaps is
+//! fair.
+//!
+//! Positions are kept in level space, relative to the level bounds, so that
+//! runs in either orientation land in the same map. Recording is off unless
+//! enabled, and the map is kept in [`DATA_DIR`].
+use std::{fmt::Write, path::Path};
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dying_player::PlayerDiedEvent,
+    persistence::{write_atomic, PersistencePlugin, Persistent},
+    player::Player,
+    scoring_region::GatePassedEvent,
+    WorldSettings,
+};
+
+/// Directory that analytics are stored and exported in.
+pub const DATA_DIR: &str = "analytics";
+
+/// Number of heatmap cells along the level.
+const GRID_WIDTH: usize = 64;
+
+/// Number of heatmap cells across the level.
+const GRID_HEIGHT: usize = 36;
+
+/// Size of each cell in the exported image, in pixels.
+const PIXELS_PER_CELL: usize = 8;
+
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct AnalyticsSettings {
+    /// Record positions into the heatmap.
+    pub enabled: bool,
+}
+
+/// Counts of gates passed and deaths in each cell of a grid over the level
+/// bounds, stored row by row from the bottom of the level.
+#[derive(Resource, Reflect, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct Heatmap {
+    pub gates: Vec<u32>,
+    pub deaths: Vec<u32>,
+}
+
+impl Default for Heatmap {
+    fn default() -> Self {
+        Self {
+            gates: vec![0; GRID_WIDTH * GRID_HEIGHT],
+            deaths: vec![0; GRID_WIDTH * GRID_HEIGHT],
+        }
+    }
+}
+
+impl Persistent for Heatmap {
+    const PATH: &'static str = "analytics/heatmap.ron";
+    const VERSION: u32 = 1;
+}
+
+impl Heatmap {
+    /// Index of the cell containing a world position, if it is in bounds.
+    fn cell(position: Vec2, play_world: &WorldSettings) -> Option<usize> {
+        let bounds = play_world.level_bounds();
+        let rel = (play_world.scroll_axis.to_level(position) - bounds.min) / bounds.size();
+        if !(0.0..1.0).contains(&rel.x) || !(0.0..1.0).contains(&rel.y) {
+            return None;
+        }
+        let x = (rel.x * GRID_WIDTH as f32) as usize;
+        let y = (rel.y * GRID_HEIGHT as f32) as usize;
+        Some(y * GRID_WIDTH + x)
+    }
+
+    /// Position of the center of a cell, relative to the level bounds as in
+    /// [`PlayerStart`](crate::obstacle::spawner_settings::PlayerStart).
+    fn cell_center(x: usize, y: usize) -> Vec2 {
+        let grid = Vec2::new(GRID_WIDTH as f32, GRID_HEIGHT as f32);
+        (Vec2::new(x as f32, y as f32) + 0.5) / grid * 2.0 - 1.0
+    }
+
+    /// Save the heatmap as CSV, with one row per cell.
+    pub fn export_csv(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let mut csv = String::from("x,y,gates,deaths\n");
+        for y in 0..GRID_HEIGHT {
+            for x in 0..GRID_WIDTH {
+                let i = y * GRID_WIDTH + x;
+                let center = Self::cell_center(x, y);
+                writeln!(
+                    csv,
+                    "{:.4},{:.4},{},{}",
+                    center.x, center.y, self.gates[i], self.deaths[i]
+                )?;
+            }
+        }
+        write_atomic(path, csv.as_bytes())?;
+        Ok(())
+    }
+
+    /// Save the heatmap as a PNG, with gates in green and deaths in red,
+    /// each scaled to its busiest cell.
+    ///
+    /// Missing parent directories are created.
+    pub fn export_png(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let max_gates = self.gates.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let max_deaths = self.deaths.iter().copied().max().unwrap_or(0).max(1) as f32;
+        let (width, height) = (GRID_WIDTH * PIXELS_PER_CELL, GRID_HEIGHT * PIXELS_PER_CELL);
+
+        let mut data = Vec::with_capacity(width * height * 4);
+        for py in 0..height {
+            // image rows run from the top, grid rows from the bottom.
+            let y = GRID_HEIGHT - 1 - py / PIXELS_PER_CELL;
+            for px in 0..width {
+                let i = y * GRID_WIDTH + px / PIXELS_PER_CELL;
+                let red = self.deaths[i] as f32 / max_deaths;
+                let green = self.gates[i] as f32 / max_gates;
+                data.extend([(red * 255.0) as u8, (green * 255.0) as u8, 0, 255]);
+            }
+        }
+        let image = Image::new(
+            Extent3d {
+                width: width as u32,
+                height: height as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        image.try_into_dynamic()?.save(path)?;
+        Ok(())
+    }
+}
+
+/// Count the cells that the player passed gates and died in.
+fn record_positions(
+    mut gates: EventReader<GatePassedEvent>,
+    mut deaths: EventReader<PlayerDiedEvent>,
+    player: Query<&Transform, With<Player>>,
+    play_world: Res<WorldSettings>,
+    mut heatmap: ResMut<Heatmap>,
+) {
+    let player_pos = player.get_single().ok().map(|t| t.translation.truncate());
+    for _ in gates.read() {
+        if let Some(i) = player_pos.and_then(|pos| Heatmap::cell(pos, &play_world)) {
+            heatmap.gates[i] += 1;
+        }
+    }
+    for death in deaths.read() {
+        if let Some(i) = Heatmap::cell(death.position, &play_world) {
+            heatmap.deaths[i] += 1;
+        }
+    }
+}
+
+/// Export the heatmap to [`DATA_DIR`] as both a PNG and a CSV.
+pub fn export_heatmap(heatmap: Res<Heatmap>) -> anyhow::Result<()> {
+    let dir = Path::new(DATA_DIR);
+    heatmap.export_png(dir.join("heatmap.png"))?;
+    heatmap.export_csv(dir.join("heatmap.csv"))?;
+    bevy::log::info!("exported heatmap to {}", DATA_DIR);
+    Ok(())
+}
+
+/// Run condition for recording analytics.
+fn analytics_enabled(settings: Res<AnalyticsSettings>) -> bool {
+    settings.enabled
+}
+
+/// Start over if the saved heatmap was recorded on a different grid.
+fn check_grid_size(mut heatmap: ResMut<Heatmap>) {
+    let cells = GRID_WIDTH * GRID_HEIGHT;
+    if heatmap.gates.len() != cells || heatmap.deaths.len() != cells {
+        bevy::log::warn!("discarding heatmap recorded on a different grid");
+        *heatmap = Heatmap::default();
+    }
+}
+
+pub struct AnalyticsPlugin;
+
+impl Plugin for AnalyticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AnalyticsSettings>()
+            .register_type::<Heatmap>()
+            .init_resource::<AnalyticsSettings>()
+            .add_plugins(PersistencePlugin::<Heatmap>::default())
+            .add_systems(PostStartup, check_grid_size)
+            .add_systems(Update, record_positions.run_if(analytics_enabled));
+    }
+}
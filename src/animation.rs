@@ -0,0 +1,104 @@
+//! Frame animation of texture atlas sprites, for anything that flips
+//! through a sprite sheet at a fixed rate: the rocket's exhaust flame,
+//! spinning pickups, flickering hazards.
+//!
+//! A [`SpriteAnimation`] plays a range of the atlas's frames, once or on a
+//! loop, and sets the sprite's index as it goes. Animations only advance
+//! while playing, so they freeze along with everything else on the game
+//! over screen.
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+/// Plays the frames of a texture atlas sprite.
+#[derive(Component, Reflect, Clone, Debug)]
+#[reflect(Component)]
+pub struct SpriteAnimation {
+    /// Atlas indices of the frames, in the order they play.
+    pub frames: Range<usize>,
+
+    /// Frames shown per second.
+    pub fps: f32,
+
+    /// Start over after the last frame, rather than holding it.
+    pub looping: bool,
+
+    /// Time since the animation started, in seconds.
+    elapsed: f32,
+}
+
+impl SpriteAnimation {
+    /// Play `frames` over and over.
+    pub fn looping(frames: Range<usize>, fps: f32) -> Self {
+        Self {
+            frames,
+            fps,
+            looping: true,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Play `frames` once, then hold the last one.
+    pub fn once(frames: Range<usize>, fps: f32) -> Self {
+        Self {
+            looping: false,
+            ..Self::looping(frames, fps)
+        }
+    }
+
+    /// Frames into the animation, counting every loop.
+    fn frames_played(&self) -> usize {
+        (self.elapsed * self.fps) as usize
+    }
+
+    /// Atlas index of the frame showing.
+    pub fn frame(&self) -> usize {
+        let len = self.frames.len().max(1);
+        let played = self.frames_played();
+        let offset = if self.looping {
+            played % len
+        } else {
+            played.min(len - 1)
+        };
+        self.frames.start + offset
+    }
+
+    /// Whether an animation that plays once has shown its last frame.
+    pub fn finished(&self) -> bool {
+        !self.looping && self.frames_played() >= self.frames.len()
+    }
+
+    /// Start the animation over from its first frame.
+    pub fn restart(&mut self) {
+        self.elapsed = 0.0;
+    }
+}
+
+/// Advance sprite animations, and show their frames. Systems that override
+/// the frame shown run after this.
+pub fn animate_sprites(
+    mut sprites: Query<(&mut SpriteAnimation, &mut TextureAtlasSprite)>,
+    time: Res<Time>,
+) {
+    for (mut anim, mut sprite) in sprites.iter_mut() {
+        if !anim.finished() {
+            anim.elapsed += time.delta_seconds();
+        }
+        let frame = anim.frame();
+        // only write on a change, to keep change detection quiet.
+        if sprite.index != frame {
+            sprite.index = frame;
+        }
+    }
+}
+
+pub struct SpriteAnimationPlugin;
+
+impl Plugin for SpriteAnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SpriteAnimation>()
+            .add_systems(Update, animate_sprites.run_if(in_state(GameState::Playing)));
+    }
+}
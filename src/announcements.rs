@@ -0,0 +1,135 @@
+//! Spoken announcements of game state for screen readers, through AccessKit.
+//!
+//! Text tagged with [`AccessibleText`] is mirrored into the accessibility
+//! tree, so it can be read on demand. Key moments are sent as
+//! [`AnnounceEvent`]s to a live region, which screen readers speak as soon
+//! as it changes.
+use bevy::{
+    a11y::{
+        accesskit::{Live, NodeBuilder, Role},
+        AccessibilityNode, AccessibilitySystem,
+    },
+    prelude::*,
+};
+
+use crate::{
+    score::{award_style_bonus, Score, ScoreMilestoneEvent},
+    GameState,
+};
+
+/// Ask for a message to be spoken.
+#[derive(Event)]
+pub struct AnnounceEvent(pub String);
+
+/// Text whose contents are mirrored into the accessibility tree, while
+/// visible.
+#[derive(Component, Reflect, Default)]
+#[reflect(Component)]
+pub struct AccessibleText;
+
+/// Live region that speaks each announcement.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Announcer;
+
+fn spawn_announcer(mut commands: Commands) {
+    let mut node = NodeBuilder::new(Role::StaticText);
+    node.set_live(Live::Polite);
+    commands.spawn((AccessibilityNode(node), Announcer, Name::new("announcer")));
+}
+
+fn add_text_nodes(mut commands: Commands, texts: Query<Entity, Added<AccessibleText>>) {
+    for ent in texts.iter() {
+        commands
+            .entity(ent)
+            .insert(AccessibilityNode(NodeBuilder::new(Role::StaticText)));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn mirror_accessible_text(
+    mut texts: Query<
+        (&Text, &Visibility, &mut AccessibilityNode),
+        (
+            With<AccessibleText>,
+            Or<(Changed<Text>, Changed<Visibility>)>,
+        ),
+    >,
+) {
+    for (text, vis, mut node) in texts.iter_mut() {
+        if *vis == Visibility::Hidden {
+            node.clear_name();
+            continue;
+        }
+        let value: String = text.sections.iter().map(|s| s.value.as_str()).collect();
+        node.set_name(value.replace('\n', ". "));
+    }
+}
+
+fn speak_announcements(
+    mut announcements: EventReader<AnnounceEvent>,
+    mut announcer: Query<&mut AccessibilityNode, With<Announcer>>,
+) {
+    let Some(AnnounceEvent(message)) = announcements.read().last() else {
+        return;
+    };
+    for mut node in announcer.iter_mut() {
+        node.set_name(message.clone());
+    }
+}
+
+fn announce_ready(mut announce: EventWriter<AnnounceEvent>) {
+    announce.send(AnnounceEvent("Ready. Press jump to start.".to_string()));
+}
+
+fn announce_start(mut announce: EventWriter<AnnounceEvent>) {
+    announce.send(AnnounceEvent("Go".to_string()));
+}
+
+/// Announce each time the score passes another milestone.
+fn announce_score_milestones(
+    mut milestones: EventReader<ScoreMilestoneEvent>,
+    mut announce: EventWriter<AnnounceEvent>,
+) {
+    // only the latest, if several were passed at once.
+    if let Some(milestone) = milestones.read().last() {
+        announce.send(AnnounceEvent(format!("Score {}", milestone.points)));
+    }
+}
+
+fn announce_game_over(score: Res<Score>, mut announce: EventWriter<AnnounceEvent>) {
+    announce.send(AnnounceEvent(format!(
+        "Game over. Final score {}, with {} gravity flips.",
+        score.points(),
+        score.gravity_flips
+    )));
+}
+
+pub struct AnnouncementsPlugin;
+
+impl Plugin for AnnouncementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AccessibleText>()
+            .register_type::<Announcer>()
+            .add_event::<AnnounceEvent>()
+            .add_systems(Startup, spawn_announcer)
+            .add_systems(OnEnter(GameState::Ready), announce_ready)
+            .add_systems(OnEnter(GameState::Playing), announce_start)
+            .add_systems(
+                OnEnter(GameState::Dying),
+                announce_game_over.after(award_style_bonus),
+            )
+            .add_systems(
+                Update,
+                announce_score_milestones.run_if(on_event::<ScoreMilestoneEvent>()),
+            )
+            .add_systems(
+                PostUpdate,
+                (
+                    (add_text_nodes, apply_deferred, mirror_accessible_text).chain(),
+                    speak_announcements,
+                )
+                    .before(AccessibilitySystem::Update),
+            );
+    }
+}
@@ -0,0 +1,127 @@
+//! Error screen for assets that fail to load.
+//!
+//! When any asset in a collection fails, the loading state moves to
+//! [`GameState::LoadingFailed`] without inserting any collection. The
+//! collections registered with [`GameCollectionAppExt::add_game_collection`]
+//! are then requested again, so the failed paths can be listed.
+use bevy::{asset::LoadState, prelude::*};
+use bevy_asset_loader::{asset_collection::AssetCollection, loading_state::LoadingStateAppExt};
+
+use crate::{
+    util::{spawn_named_child, Containers},
+    GameState,
+};
+
+const ERROR_COLOR: Color = Color::rgb(1.0, 0.4, 0.4);
+
+/// Functions requesting the assets of each game collection.
+#[derive(Resource, Default)]
+struct CollectionLoaders(Vec<fn(&mut World) -> Vec<UntypedHandle>>);
+
+/// Assets requested again after loading failed, and the paths of those
+/// found to have failed so far.
+#[derive(Resource, Default)]
+pub struct FailedAssets {
+    handles: Vec<UntypedHandle>,
+    pub paths: Vec<String>,
+}
+
+/// Text listing the failed assets.
+#[derive(Component)]
+struct AssetErrorText;
+
+pub trait GameCollectionAppExt {
+    /// Load an asset collection during [`GameState::AssetLoading`], and
+    /// report its failed assets on the error screen.
+    fn add_game_collection<C: AssetCollection>(&mut self) -> &mut Self;
+}
+
+impl GameCollectionAppExt for App {
+    fn add_game_collection<C: AssetCollection>(&mut self) -> &mut Self {
+        self.init_resource::<CollectionLoaders>();
+        self.world
+            .resource_mut::<CollectionLoaders>()
+            .0
+            .push(C::load);
+        self.add_collection_to_loading_state::<_, C>(GameState::AssetLoading)
+    }
+}
+
+/// Request the assets of every collection again, to find the failed ones.
+fn request_collections(world: &mut World) {
+    let loaders = world.resource::<CollectionLoaders>().0.clone();
+    let handles = loaders.into_iter().flat_map(|load| load(world)).collect();
+    world.insert_resource(FailedAssets {
+        handles,
+        paths: vec![],
+    });
+}
+
+fn spawn_error_screen(mut commands: Commands, containers: Res<Containers>) {
+    // the game fonts may be among the failed assets, so use the default font.
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "asset_error",
+        (
+            Text2dBundle {
+                text: Text::from_section(
+                    "Failed to load assets",
+                    TextStyle {
+                        font_size: 24.0,
+                        color: ERROR_COLOR,
+                        ..default()
+                    },
+                )
+                .with_alignment(TextAlignment::Center),
+                ..default()
+            },
+            AssetErrorText,
+        ),
+    );
+}
+
+/// Collect the paths of failed assets, and list them on the error screen.
+fn list_failed_assets(
+    mut failed: ResMut<FailedAssets>,
+    asset_server: Res<AssetServer>,
+    mut text: Query<&mut Text, With<AssetErrorText>>,
+) {
+    let paths: Vec<String> = failed
+        .handles
+        .iter()
+        .filter(|h| asset_server.get_load_state(h.id()) == Some(LoadState::Failed))
+        .filter_map(|h| h.path().map(|p| p.to_string()))
+        .collect();
+    if paths == failed.paths {
+        return;
+    }
+    for path in paths.iter().filter(|p| !failed.paths.contains(p)) {
+        bevy::log::error!("failed to load {}", path);
+    }
+    failed.paths = paths;
+
+    let mut message = "Failed to load assets:\n".to_string();
+    for path in failed.paths.iter() {
+        message.push_str(&format!("\n{}", path));
+    }
+    for mut text in text.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+pub struct AssetErrorsPlugin;
+
+impl Plugin for AssetErrorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CollectionLoaders>()
+            .add_systems(
+                OnEnter(GameState::LoadingFailed),
+                (request_collections, spawn_error_screen),
+            )
+            .add_systems(
+                Update,
+                list_failed_assets.run_if(in_state(GameState::LoadingFailed)),
+            );
+    }
+}
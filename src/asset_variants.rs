@@ -0,0 +1,99 @@
+//! Selection between the standard and double resolution (`@2x`) variants of
+//! sprite assets, based on the window scale factor.
+//!
+//! Collections refer to variant assets by dynamic key, and plugins register
+//! the base path for each key with [`AssetVariantAppExt::add_asset_variant`].
+//! The paths are resolved once the window is known, before loading starts.
+use bevy::{prelude::*, window::PrimaryWindow};
+use bevy_asset_loader::{
+    dynamic_asset::DynamicAssets, standard_dynamic_asset::StandardDynamicAsset,
+};
+
+/// Window scale factor from which double resolution assets are used.
+const DOUBLE_SCALE_THRESHOLD: f64 = 1.5;
+
+/// Resolution of the variant assets in use.
+#[derive(Resource, Reflect, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Resource)]
+pub enum AssetScale {
+    #[default]
+    Standard,
+    Double,
+}
+
+impl AssetScale {
+    /// Texture pixels per logical pixel.
+    pub fn factor(&self) -> f32 {
+        match self {
+            AssetScale::Standard => 1.0,
+            AssetScale::Double => 2.0,
+        }
+    }
+
+    /// Path of the variant of `path` at this scale, e.g.
+    /// `images/rocketman@2x.png` for `images/rocketman.png`.
+    pub fn variant_path(&self, path: &str) -> String {
+        match self {
+            AssetScale::Standard => path.to_string(),
+            AssetScale::Double => match path.rsplit_once('.') {
+                Some((stem, ext)) => format!("{}@2x.{}", stem, ext),
+                None => format!("{}@2x", path),
+            },
+        }
+    }
+}
+
+/// Base paths of the variant assets, by dynamic asset key.
+#[derive(Resource, Default)]
+struct AssetVariants(Vec<(String, String)>);
+
+pub trait AssetVariantAppExt {
+    /// Register the asset at `path` (or its `@2x` variant) under the dynamic
+    /// asset `key`.
+    fn add_asset_variant(&mut self, key: &str, path: &str) -> &mut Self;
+}
+
+impl AssetVariantAppExt for App {
+    fn add_asset_variant(&mut self, key: &str, path: &str) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(AssetVariants::default)
+            .0
+            .push((key.to_string(), path.to_string()));
+        self
+    }
+}
+
+/// Pick the asset scale from the window, and register the matching paths.
+fn register_asset_variants(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    variants: Res<AssetVariants>,
+    mut scale: ResMut<AssetScale>,
+    mut dynamic_assets: ResMut<DynamicAssets>,
+) {
+    if let Ok(window) = windows.get_single() {
+        if window.resolution.scale_factor() >= DOUBLE_SCALE_THRESHOLD {
+            *scale = AssetScale::Double;
+        }
+    }
+    bevy::log::info!("using {:?} resolution assets", *scale);
+
+    for (key, path) in &variants.0 {
+        dynamic_assets.register_asset(
+            key.clone(),
+            Box::new(StandardDynamicAsset::File {
+                path: scale.variant_path(path),
+            }),
+        );
+    }
+}
+
+pub struct AssetVariantsPlugin;
+
+impl Plugin for AssetVariantsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AssetScale>()
+            .init_resource::<AssetScale>()
+            .init_resource::<AssetVariants>()
+            .add_systems(Startup, register_asset_variants);
+    }
+}
@@ -0,0 +1,156 @@
+//! Sound effects.
+//!
+//! Barriers whoosh as they pass the player. The whoosh is spatial, so it is
+//! panned by where the barrier is relative to a listener that follows the
+//! player, and louder the closer the barrier passes.
+use bevy::{audio::Volume, prelude::*};
+use bevy_asset_loader::asset_collection::AssetCollection;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    asset_errors::GameCollectionAppExt,
+    barrier::Barrier,
+    combo::{ComboFeedback, ComboStepEvent},
+    player::Player,
+    GameState, WorldSettings,
+};
+
+/// Distance between the listener's ears, in world units.
+const EAR_GAP: f32 = 400.0;
+
+/// Scale from world units into audio space, so that the ears are a unit
+/// apart and only far off barriers are quieted by distance.
+pub const SPATIAL_SCALE: f32 = 1.0 / EAR_GAP;
+
+/// How far ahead of the player a barrier starts its whoosh, so that the
+/// sound peaks as it passes.
+const WHOOSH_LEAD: f32 = 120.0;
+
+/// Barriers passing further than this from the player are silent.
+const WHOOSH_RANGE: f32 = 200.0;
+
+#[derive(AssetCollection, Resource)]
+pub struct SoundEffects {
+    /// chime for passing through a gate
+    #[asset(path = "sounds/gate.wav")]
+    pub gate: Handle<AudioSource>,
+
+    /// rush of air from a barrier passing by
+    #[asset(path = "sounds/whoosh.wav")]
+    pub whoosh: Handle<AudioSource>,
+}
+
+/// Marker for the spatial audio listener.
+#[derive(Component)]
+struct Listener;
+
+/// Marker for barriers that have already whooshed past the player.
+#[derive(Component)]
+struct Whooshed;
+
+fn spawn_listener(mut commands: Commands) {
+    commands.spawn((
+        SpatialBundle::default(),
+        SpatialListener::new(EAR_GAP),
+        Listener,
+        Name::new("listener"),
+    ));
+}
+
+/// Keep the listener on the player. It isn't a child of the player, so that
+/// the ears don't turn as the rocket tilts.
+fn follow_player(
+    player: Query<&Transform, (With<Player>, Without<Listener>)>,
+    mut listener: Query<&mut Transform, With<Listener>>,
+) {
+    let (Ok(player), Ok(mut listener)) = (player.get_single(), listener.get_single_mut()) else {
+        return;
+    };
+    listener.translation = player.translation;
+}
+
+/// Volume of a whoosh from a barrier passing `gap` units from the player.
+fn whoosh_volume(gap: f32) -> f32 {
+    (1.0 - gap / WHOOSH_RANGE).max(0.0).powi(2)
+}
+
+/// Whoosh each barrier once, as it comes up on the player.
+#[allow(clippy::type_complexity)]
+fn play_whoosh_sounds(
+    mut commands: Commands,
+    barriers: Query<(Entity, &GlobalTransform, &Collider), (With<Barrier>, Without<Whooshed>)>,
+    player: Query<&Transform, With<Player>>,
+    play_world: Res<WorldSettings>,
+    sounds: Res<SoundEffects>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    let axis = play_world.scroll_axis;
+    let player_pos = axis.to_level(player.translation.truncate());
+    for (barrier, transform, collider) in barriers.iter() {
+        let Some(half) = collider.as_cuboid().map(|c| c.half_extents()) else {
+            continue;
+        };
+        let offset = axis.to_level(transform.translation().truncate()) - player_pos;
+        // wait until the barrier is close, and skip any already behind.
+        if offset.x - half.x > WHOOSH_LEAD || offset.x + half.x < 0.0 {
+            continue;
+        }
+        commands.entity(barrier).insert(Whooshed);
+
+        let volume = whoosh_volume((offset.y.abs() - half.y).max(0.0));
+        if volume <= 0.0 {
+            continue;
+        }
+        // a child of the barrier, so the sound pans along with it.
+        commands.entity(barrier).with_children(|parent| {
+            parent.spawn((
+                AudioBundle {
+                    source: sounds.whoosh.clone(),
+                    settings: PlaybackSettings::DESPAWN
+                        .with_volume(Volume::new_relative(volume))
+                        .with_spatial(true),
+                },
+                SpatialBundle::default(),
+                Name::new("whoosh"),
+            ));
+        });
+    }
+}
+
+/// Play the gate chime, pitched up along the streak.
+fn play_gate_sounds(
+    mut commands: Commands,
+    mut steps: EventReader<ComboStepEvent>,
+    feedback: Res<ComboFeedback>,
+    sounds: Res<SoundEffects>,
+) {
+    for step in steps.read() {
+        // left at the top level, since finished sounds are despawned
+        // without updating their parent.
+        commands.spawn((
+            AudioBundle {
+                source: sounds.gate.clone(),
+                settings: PlaybackSettings::DESPAWN.with_speed(feedback.pitch(step.streak)),
+            },
+            Name::new("gate_sound"),
+        ));
+    }
+}
+
+pub struct GameAudioPlugin;
+
+impl Plugin for GameAudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_collection::<SoundEffects>()
+            .add_systems(Startup, spawn_listener)
+            .add_systems(
+                Update,
+                (
+                    follow_player,
+                    (play_gate_sounds, play_whoosh_sounds).run_if(in_state(GameState::Playing)),
+                ),
+            );
+    }
+}
@@ -1,16 +1,29 @@
 use bevy::{
     prelude::{shape::Quad, *},
-    reflect::{TypePath, TypeUuid},
+    reflect::TypeUuid,
     render::render_resource::AsBindGroup,
     sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
 };
+use bevy_asset_loader::loading_state::LoadingStateAppExt;
 use bevy_rapier2d::prelude::RapierConfiguration;
 
-use crate::{GameState, WorldSet, WorldSettings};
+use crate::{
+    accessibility::MotionSettings, theme_pack::ThemePack, GameState, WorldSettings,
+    ON_ASSETS_LOADED,
+};
+
+/// Background colors of each level in a run in the classic theme, starting
+/// with the base level.
+pub const LEVEL_PALETTES: [[Color; 2]; 4] = [
+    [Color::rgb(0.4, 0.4, 0.4), Color::rgb(0.7, 0.7, 0.7)],
+    [Color::rgb(0.25, 0.32, 0.5), Color::rgb(0.5, 0.6, 0.8)],
+    [Color::rgb(0.45, 0.25, 0.45), Color::rgb(0.75, 0.5, 0.7)],
+    [Color::rgb(0.5, 0.3, 0.2), Color::rgb(0.85, 0.6, 0.4)],
+];
 
-#[derive(AsBindGroup, Clone, TypeUuid, TypePath, Debug, Asset)]
+#[derive(AsBindGroup, Clone, TypeUuid, Reflect, Debug, Asset)]
 #[uuid = "476f30fe-bed3-4495-9603-aaedb35ba69b"]
-struct BackgroundMaterial {
+pub struct BackgroundMaterial {
     #[uniform(0)]
     pub c1: Color,
     #[uniform(1)]
@@ -18,6 +31,14 @@ struct BackgroundMaterial {
 
     #[uniform(2)]
     time: f32,
+
+    /// Speed that the pattern scrolls at, in screen widths per second of
+    /// background time.
+    #[uniform(3)]
+    pub scroll_speed: f32,
+
+    /// Rate that background time passes, relative to the game.
+    pub time_scale: f32,
 }
 
 impl Material2d for BackgroundMaterial {
@@ -26,30 +47,49 @@ impl Material2d for BackgroundMaterial {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Background;
 
-fn spawn_background(
-    mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut mats: ResMut<Assets<BackgroundMaterial>>,
-    world: Res<WorldSettings>,
-) {
-    println!("world bounds: {}", world.bounds.size());
+/// Mesh and material for the background, built during asset loading.
+#[derive(Resource)]
+struct BackgroundAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<BackgroundMaterial>,
+}
+
+impl FromWorld for BackgroundAssets {
+    fn from_world(world: &mut World) -> Self {
+        let size = world.resource::<WorldSettings>().bounds.size();
+        debug!("world bounds: {}", size);
+        let [c1, c2] = world.resource::<ThemePack>().palette(0);
+        BackgroundAssets {
+            mesh: world
+                .resource_mut::<Assets<Mesh>>()
+                .add(Mesh::from(Quad::new(size))),
+            material: world
+                .resource_mut::<Assets<BackgroundMaterial>>()
+                .add(BackgroundMaterial {
+                    c1,
+                    c2,
+                    time: 0.0,
+                    scroll_speed: 0.1,
+                    time_scale: 1.0,
+                }),
+        }
+    }
+}
+
+fn spawn_background(mut commands: Commands, assets: Res<BackgroundAssets>) {
     commands.spawn((
         MaterialMesh2dBundle {
-            mesh: meshes
-                .add(Mesh::from(Quad::new(world.bounds.size())))
-                .into(),
-            material: mats.add(BackgroundMaterial {
-                c1: Color::rgba(0.4, 0.4, 0.4, 1.0),
-                c2: Color::rgba(0.7, 0.7, 0.7, 1.0),
-                time: 0.0,
-            }),
+            mesh: assets.mesh.clone().into(),
+            material: assets.material.clone(),
             transform: Transform::from_translation(Vec3::new(0.0, 0.0, 0.0)),
             ..default()
         },
         Background,
+        Name::new("background"),
     ));
 }
 
@@ -57,15 +97,29 @@ fn update_background(
     mut mats: ResMut<Assets<BackgroundMaterial>>,
     back: Query<&Handle<BackgroundMaterial>, With<Background>>,
     rapier: Res<RapierConfiguration>,
+    motion: Res<MotionSettings>,
     time: Res<Time>,
 ) {
-    let mat_handle = back.single();
+    let Ok(mat_handle) = back.get_single() else {
+        return;
+    };
     let mat = mats.get_mut(mat_handle).unwrap();
-    if rapier.physics_pipeline_active {
-        mat.time += time.delta_seconds();
+    if rapier.physics_pipeline_active && motion.animate() {
+        mat.time += time.delta_seconds() * mat.time_scale;
     }
 }
 
+/// Fit the background to new world bounds.
+fn resize_background(
+    mut meshes: ResMut<Assets<Mesh>>,
+    assets: Res<BackgroundAssets>,
+    play_world: Res<WorldSettings>,
+) {
+    let size = play_world.bounds.size();
+    debug!("world bounds: {}", size);
+    meshes.insert(&assets.mesh, Mesh::from(Quad::new(size)));
+}
+
 fn reset_background(
     mut mats: ResMut<Assets<BackgroundMaterial>>,
     back: Query<&Handle<BackgroundMaterial>, With<Background>>,
@@ -80,8 +134,18 @@ pub struct GameBackgroundPlugin;
 impl Plugin for GameBackgroundPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(Material2dPlugin::<BackgroundMaterial>::default())
-            .add_systems(Startup, spawn_background.after(WorldSet))
+            .register_asset_reflect::<BackgroundMaterial>()
+            .register_type::<Background>()
+            .init_resource_after_loading_state::<_, BackgroundAssets>(GameState::AssetLoading)
+            .add_systems(ON_ASSETS_LOADED, spawn_background)
             .add_systems(Update, update_background)
+            .add_systems(
+                Update,
+                resize_background.run_if(
+                    resource_exists::<BackgroundAssets>()
+                        .and_then(resource_changed::<WorldSettings>()),
+                ),
+            )
             .add_systems(OnEnter(GameState::Ready), reset_background);
     }
 }
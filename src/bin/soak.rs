@@ -0,0 +1,306 @@
+//! Soak test for long sessions: plays run after run of the game's levels
+//! headlessly, much faster than real time, checking that entities and
+//! meshes don't pile up.
+//!
+//! The app is built from the game's own level, spawner, run, score and
+//! physics plugins, so every kind of item the levels spawn is scrolled by
+//! rapier, removed once out of sight, and cleared by the same reset as a
+//! restart. A scripted player steers through the nearest tunnel gap, so
+//! runs score and move on to later levels. It can't crash, and each run
+//! ends after [`RUN_SECS`] with a reset.
+//!
+//! Run with `cargo run --release --bin soak -- --minutes 120`.
+use std::{panic, process::ExitCode, time::Duration};
+
+use anyhow::Result;
+use bevy::{prelude::*, time::TimeUpdateStrategy, utils::Instant};
+use bevy_rapier2d::prelude::*;
+use rustyrocket::{
+    controls::{Action, InputTimestamp},
+    game_snapshot::{GameSnapshot, GameSnapshotPlugin},
+    level::{LevelPlugin, RemoveOnReset},
+    mods::Mods,
+    mutators::ActiveMutators,
+    obstacle::{custom::ObstacleSet, spawner_settings::SpawnerSettings, SpawnParams},
+    obstacle_spawner::{Levels, ObstacleSpawner, ObstacleSpawnerPlugin},
+    physics::{PhysicsSettings, PhysicsStepsPlugin},
+    player::{JumpEvent, Player},
+    player_collision_groups, player_solver_groups,
+    run::{RunClock, RunConfig, RunPlugin},
+    score::{Score, ScorePlugin},
+    scoring_region::ScoringRegionPlugin,
+    test_support::insert_blank_item_assets,
+    training::TrainingSettings,
+    util::ContainersPlugin,
+    GameState, ResetEvent, WorldSettings,
+};
+
+/// Game time covered by each update.
+const STEP_SECS: f64 = 1.0 / 60.0;
+
+/// Game time that each run lasts before it is reset. Long enough for the
+/// screen to fill with items, and for items to be removed as they leave it.
+const RUN_SECS: f32 = 120.0;
+
+/// Most entities that may exist at once. A screen holds only a handful of
+/// items, so anything near this is a leak.
+const MAX_ENTITIES: u32 = 2000;
+
+/// Most meshes that may exist at once.
+const MAX_MESHES: usize = 500;
+
+const DEFAULT_MINUTES: f64 = 60.0;
+
+/// Half the size of the scripted player's collider, in pixels.
+const PLAYER_HALF_EXTENTS: Vec2 = Vec2::new(16.0, 12.0);
+
+/// How quickly the scripted player closes in on where it steers to, per
+/// second.
+const STEER_RATE: f32 = 8.0;
+
+/// Fastest that the scripted player moves, in pixels per second.
+const MAX_STEER_SPEED: f32 = 600.0;
+
+/// Counts of what the soak has played through.
+#[derive(Resource, Default)]
+struct SoakStats {
+    runs: u64,
+    items: u64,
+    points: u64,
+}
+
+/// Start each run straight from the ready prompt, in place of the jump
+/// that starts it in the game.
+fn start_run(mut app_state: ResMut<NextState<GameState>>) {
+    app_state.set(GameState::Playing);
+}
+
+fn count_run(mut stats: ResMut<SoakStats>) {
+    stats.runs += 1;
+}
+
+/// Spawn the scripted player where the level starts the rocket. It is a
+/// sensor, as the rocket is, so it passes gates without being pushed.
+fn spawn_scripted_player(
+    mut commands: Commands,
+    spawners: Query<&ObstacleSpawner>,
+    play_world: Res<WorldSettings>,
+) {
+    let start = spawners
+        .get_single()
+        .map(|spawner| spawner.level().player_start.level_position(&play_world))
+        .unwrap_or_default();
+    commands.spawn((
+        SpatialBundle::from_transform(play_world.level_transform(start, 10.0)),
+        Collider::cuboid(PLAYER_HALF_EXTENTS.x, PLAYER_HALF_EXTENTS.y),
+        RigidBody::Dynamic,
+        GravityScale(0.0),
+        Velocity::zero(),
+        Sensor,
+        Player,
+        player_collision_groups(),
+        player_solver_groups(),
+        RemoveOnReset,
+        Name::new("Scripted player"),
+    ));
+}
+
+/// Steer the scripted player towards the middle of the nearest tunnel gap,
+/// keeping it at the level's start position along the scroll axis.
+fn steer_scripted_player(
+    snapshot: Res<GameSnapshot>,
+    spawners: Query<&ObstacleSpawner>,
+    play_world: Res<WorldSettings>,
+    mut player: Query<(&Transform, &mut Velocity), With<Player>>,
+) {
+    let axis = play_world.scroll_axis;
+    let Ok((t, mut vel)) = player.get_single_mut() else {
+        return;
+    };
+    let pos = axis.to_level(t.translation.truncate());
+    let start = spawners
+        .get_single()
+        .map_or(pos, |s| s.level().player_start.level_position(&play_world));
+    let target = Vec2::new(
+        start.x,
+        snapshot.nearest_gap.map_or(start.y, |gap| gap.center),
+    );
+    let steer = ((target - pos) * STEER_RATE).clamp_length_max(MAX_STEER_SPEED);
+    vel.linvel = axis.to_world(steer);
+}
+
+/// End the run once it has lasted long enough, as the restart action does.
+fn end_run(
+    clock: Res<RunClock>,
+    score: Res<Score>,
+    mut stats: ResMut<SoakStats>,
+    mut resets: EventWriter<ResetEvent>,
+) {
+    if clock.elapsed().as_secs_f32() >= RUN_SECS {
+        stats.points += score.points() as u64;
+        resets.send(ResetEvent);
+    }
+}
+
+fn count_items(items: Query<(), Added<SpawnParams>>, mut stats: ResMut<SoakStats>) {
+    stats.items += items.iter().len() as u64;
+}
+
+/// Peak counts seen over the soak.
+#[derive(Default)]
+struct Report {
+    steps: u64,
+    peak_entities: u32,
+    peak_meshes: usize,
+}
+
+fn read_level(path: &str) -> Result<SpawnerSettings> {
+    Ok(ron::de::from_str(&std::fs::read_to_string(path)?)?)
+}
+
+fn build_app() -> Result<App> {
+    let physics = PhysicsSettings::default();
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+        HierarchyPlugin,
+        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(physics.pixels_per_meter),
+        PhysicsStepsPlugin,
+        ContainersPlugin,
+        RunPlugin,
+        LevelPlugin,
+        ObstacleSpawnerPlugin,
+        ScorePlugin,
+        ScoringRegionPlugin,
+        GameSnapshotPlugin,
+    ))
+    .add_state::<GameState>()
+    .add_event::<Action>()
+    .add_event::<JumpEvent>()
+    .add_event::<ResetEvent>()
+    .init_resource::<Input<KeyCode>>()
+    .init_resource::<Input<GamepadButton>>()
+    .init_resource::<InputTimestamp>()
+    .init_resource::<ActiveMutators>()
+    .init_resource::<TrainingSettings>()
+    .init_resource::<Mods>()
+    .init_resource::<SoakStats>()
+    .init_asset::<SpawnerSettings>()
+    .init_asset::<ObstacleSet>()
+    .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+        STEP_SECS,
+    )))
+    .insert_resource(WorldSettings {
+        bounds: Rect::new(-512.0, -288.0, 512.0, 288.0),
+        ..default()
+    })
+    .insert_resource(RunConfig {
+        fixed_seed: Some(0),
+        ..default()
+    })
+    .add_systems(Update, start_run.run_if(in_state(GameState::Ready)))
+    .add_systems(
+        OnEnter(GameState::Playing),
+        (count_run, spawn_scripted_player),
+    )
+    .add_systems(
+        Update,
+        (steer_scripted_player, end_run, count_items).run_if(in_state(GameState::Playing)),
+    );
+    app.world
+        .resource_mut::<RapierConfiguration>()
+        .timestep_mode = physics.timestep_mode();
+    app.insert_resource(physics);
+
+    // what the loading state does once assets are ready.
+    let mut levels = app.world.resource_mut::<Assets<SpawnerSettings>>();
+    let base_level = levels.add(read_level("assets/levels/base.spawner.ron")?);
+    let fast_level = levels.add(read_level("assets/levels/fast.spawner.ron")?);
+    app.insert_resource(Levels {
+        base_level,
+        fast_level,
+    });
+    insert_blank_item_assets(&mut app);
+    app.world
+        .resource_mut::<NextState<GameState>>()
+        .set(GameState::Ready);
+
+    app.finish();
+    app.cleanup();
+    Ok(app)
+}
+
+fn soak(minutes: f64) -> Result<Report> {
+    let mut app = build_app()?;
+    let steps = (minutes * 60.0 / STEP_SECS).ceil() as u64;
+    let mut report = Report::default();
+    for step in 0..steps {
+        app.update();
+        report.steps = step + 1;
+
+        let entities = app.world.entities().len();
+        let meshes = app.world.resource::<Assets<Mesh>>().len();
+        report.peak_entities = report.peak_entities.max(entities);
+        report.peak_meshes = report.peak_meshes.max(meshes);
+        let game_secs = report.steps as f64 * STEP_SECS;
+        assert!(
+            entities <= MAX_ENTITIES,
+            "{} entities after {:.0}s",
+            entities,
+            game_secs
+        );
+        assert!(
+            meshes <= MAX_MESHES,
+            "{} meshes after {:.0}s",
+            meshes,
+            game_secs
+        );
+    }
+    let stats = app.world.resource::<SoakStats>();
+    println!(
+        "played {} runs, spawning {} items and scoring {} points",
+        stats.runs, stats.items, stats.points
+    );
+    Ok(report)
+}
+
+fn main() -> ExitCode {
+    let minutes = std::env::args()
+        .skip_while(|arg| arg != "--minutes")
+        .nth(1)
+        .and_then(|m| m.parse().ok())
+        .unwrap_or(DEFAULT_MINUTES);
+
+    println!("soaking for {} minutes of game time", minutes);
+    let start = Instant::now();
+    let result = panic::catch_unwind(|| soak(minutes));
+    let wall_secs = start.elapsed().as_secs_f64();
+
+    match result {
+        Ok(Ok(report)) => {
+            let game_secs = report.steps as f64 * STEP_SECS;
+            println!("game time:     {:.0}s", game_secs);
+            println!(
+                "wall time:     {:.0}s ({:.0}x real time)",
+                wall_secs,
+                game_secs / wall_secs.max(f64::EPSILON)
+            );
+            println!("peak entities: {}", report.peak_entities);
+            println!("peak meshes:   {}", report.peak_meshes);
+            println!("PASSED");
+            ExitCode::SUCCESS
+        }
+        Ok(Err(e)) => {
+            eprintln!("couldn't start soak: {:#}", e);
+            ExitCode::FAILURE
+        }
+        // the panic message has already been printed by the panic hook.
+        Err(_) => {
+            println!("FAILED after {:.0}s", wall_secs);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Bloom on the main camera, and bright flashes for it to pick up when
+//! the player scores.
+//!
+//! Colors brighter than 1.0 only glow with HDR enabled, which
+//! [`BloomConfig::enabled`] turns off on slower machines. The flashes still
+//! show without it, just without the glow.
+use bevy::{core_pipeline::bloom::BloomSettings, prelude::*};
+
+use crate::{
+    combo::ComboStepEvent,
+    level::RemoveOnReset,
+    util::{spawn_named_child, Containers},
+    GameState, MainCamera,
+};
+
+/// How long a gate flash lasts.
+const FLASH_SECS: f32 = 0.3;
+
+/// Size of a gate flash at its start, in pixels.
+const FLASH_SIZE: f32 = 48.0;
+
+/// How much gate flashes grow over their lifetime.
+const FLASH_GROWTH: f32 = 1.5;
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct BloomConfig {
+    pub enabled: bool,
+
+    /// Strength of the bloom.
+    pub intensity: f32,
+
+    /// Color of the flash when passing a gate. Components over 1.0 glow.
+    pub flash_color: Color,
+}
+
+impl Default for BloomConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            intensity: 0.2,
+            flash_color: Color::rgb(3.0, 2.4, 1.0),
+        }
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct GateFlash {
+    timer: Timer,
+}
+
+/// Turn HDR and bloom on the main camera on or off, to match the config.
+fn sync_bloom(
+    mut commands: Commands,
+    config: Res<BloomConfig>,
+    mut cameras: Query<(Entity, &mut Camera, Option<&mut BloomSettings>), With<MainCamera>>,
+) {
+    for (ent, mut camera, bloom) in cameras.iter_mut() {
+        if camera.hdr != config.enabled {
+            camera.hdr = config.enabled;
+        }
+        match (config.enabled, bloom) {
+            (true, Some(mut bloom)) => {
+                if bloom.intensity != config.intensity {
+                    bloom.intensity = config.intensity;
+                }
+            }
+            (true, None) => {
+                commands.entity(ent).insert(BloomSettings {
+                    intensity: config.intensity,
+                    ..BloomSettings::NATURAL
+                });
+            }
+            (false, Some(_)) => {
+                commands.entity(ent).remove::<BloomSettings>();
+            }
+            (false, None) => {}
+        }
+    }
+}
+
+/// Flash at each gate passed.
+fn spawn_gate_flashes(
+    mut commands: Commands,
+    mut steps: EventReader<ComboStepEvent>,
+    config: Res<BloomConfig>,
+    containers: Res<Containers>,
+) {
+    for step in steps.read() {
+        spawn_named_child(
+            &mut commands,
+            containers.effects,
+            "gate_flash",
+            (
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: config.flash_color,
+                        custom_size: Some(Vec2::splat(FLASH_SIZE)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(step.position.extend(14.0)),
+                    ..default()
+                },
+                GateFlash {
+                    timer: Timer::from_seconds(FLASH_SECS, TimerMode::Once),
+                },
+                RemoveOnReset,
+            ),
+        );
+    }
+}
+
+/// Grow and fade out gate flashes.
+fn update_gate_flashes(
+    mut commands: Commands,
+    mut flashes: Query<(Entity, &mut GateFlash, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (ent, mut flash, mut t, mut sprite) in flashes.iter_mut() {
+        if flash.timer.tick(time.delta()).finished() {
+            commands.entity(ent).despawn_recursive();
+            continue;
+        }
+        t.scale = Vec3::splat(1.0 + (FLASH_GROWTH - 1.0) * flash.timer.percent());
+        sprite.color.set_a(flash.timer.percent_left());
+    }
+}
+
+pub struct BloomPlugin;
+
+impl Plugin for BloomPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<BloomConfig>()
+            .register_type::<GateFlash>()
+            .init_resource::<BloomConfig>()
+            .add_systems(Update, sync_bloom.run_if(resource_changed::<BloomConfig>()))
+            .add_systems(
+                Update,
+                (spawn_gate_flashes, update_gate_flashes).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
@@ -0,0 +1,117 @@
+//! Walls along the top and bottom of the world, for modes where the player
+//! bounces or slides along the edges instead of flying out of bounds.
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    barrier_collision_groups,
+    player::Player,
+    run::{GameMode, RunConfig},
+    WorldSettings,
+};
+
+/// Thickness of the boundary walls, in pixels.
+const WALL_THICKNESS: f32 = 20.0;
+
+/// Which modes have boundary walls, and how the player reacts to them.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct BoundaryWalls {
+    /// Modes that are played with walls.
+    pub modes: Vec<GameMode>,
+
+    /// How much of the player's speed is kept when bouncing off a wall.
+    pub restitution: f32,
+
+    /// Friction against the wall, where 0.0 lets the player slide freely.
+    pub friction: f32,
+}
+
+impl Default for BoundaryWalls {
+    fn default() -> Self {
+        Self {
+            modes: vec![GameMode::Easy],
+            restitution: 0.3,
+            friction: 0.0,
+        }
+    }
+}
+
+impl BoundaryWalls {
+    /// Whether the current mode is played with walls.
+    pub fn enabled(&self, config: &RunConfig) -> bool {
+        self.modes.contains(&config.mode)
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct BoundaryWall;
+
+/// Rebuild the walls to match the world bounds and the current mode.
+fn sync_boundary_walls(
+    mut commands: Commands,
+    walls: Query<Entity, With<BoundaryWall>>,
+    settings: Res<BoundaryWalls>,
+    config: Res<RunConfig>,
+    play_world: Res<WorldSettings>,
+) {
+    for ent in walls.iter() {
+        commands.entity(ent).despawn();
+    }
+    if !settings.enabled(&config) {
+        return;
+    }
+    for (transform, half_extents) in play_world.level_edges(WALL_THICKNESS) {
+        commands.spawn((
+            TransformBundle::from_transform(transform),
+            RigidBody::Fixed,
+            Collider::cuboid(half_extents.x, half_extents.y),
+            barrier_collision_groups(),
+            Restitution::coefficient(settings.restitution),
+            Friction::coefficient(settings.friction),
+            BoundaryWall,
+            Name::new("boundary_wall"),
+        ));
+    }
+}
+
+/// The player is normally a sensor; make it solid in modes with walls, so
+/// that it gets pushed back by them.
+fn make_player_solid(
+    mut commands: Commands,
+    player: Query<Entity, Added<Player>>,
+    settings: Res<BoundaryWalls>,
+    config: Res<RunConfig>,
+) {
+    if !settings.enabled(&config) {
+        return;
+    }
+    for ent in player.iter() {
+        commands
+            .entity(ent)
+            .remove::<Sensor>()
+            .insert(LockedAxes::ROTATION_LOCKED);
+    }
+}
+
+pub struct BoundaryPlugin;
+
+impl Plugin for BoundaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<BoundaryWalls>()
+            .register_type::<BoundaryWall>()
+            .init_resource::<BoundaryWalls>()
+            .add_systems(
+                Update,
+                (
+                    sync_boundary_walls.run_if(
+                        resource_changed::<WorldSettings>()
+                            .or_else(resource_changed::<RunConfig>())
+                            .or_else(resource_changed::<BoundaryWalls>()),
+                    ),
+                    make_player_solid,
+                ),
+            );
+    }
+}
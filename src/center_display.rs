@@ -1,39 +1,187 @@
 pub use bevy::prelude::*;
+use bevy_asset_loader::asset_collection::AssetCollection;
+use bevy_tweening::{
+    lens::TransformScaleLens, Animator, EaseFunction, RepeatCount, RepeatStrategy, Tween,
+};
+use std::time::Duration;
 
-use crate::{fonts::FontsCollection, GameState};
+use crate::{
+    announcements::AccessibleText,
+    asset_errors::GameCollectionAppExt,
+    controls::{InputDevice, LastInputDevice},
+    fonts::FontsCollection,
+    level::PendingRestart,
+    score::Score,
+    ui_theme::{ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    GameState, ON_ASSETS_LOADED,
+};
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct CenterDisplay;
 
-pub fn spawn_display(mut commands: Commands, fonts: Res<FontsCollection>) {
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_section(
-                "",
-                TextStyle {
-                    font: fonts.menu_font.clone(),
-                    font_size: 70.0,
-                    color: Color::ANTIQUE_WHITE,
-                },
-            ),
-            transform: Transform::from_xyz(0.0, 0.0, 20.0),
-            ..default()
+/// Icon of the jump input, shown under the ready prompt.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct InputHint;
+
+/// Icons for the jump input on each device.
+#[derive(AssetCollection, Resource)]
+pub struct InputIcons {
+    #[asset(path = "images/input/key_space.png")]
+    pub jump_key: Handle<Image>,
+
+    #[asset(path = "images/input/gamepad_south.png")]
+    pub jump_button: Handle<Image>,
+}
+
+impl InputIcons {
+    fn jump_icon(&self, device: InputDevice) -> Handle<Image> {
+        match device {
+            InputDevice::Keyboard => self.jump_key.clone(),
+            InputDevice::Gamepad => self.jump_button.clone(),
+        }
+    }
+}
+
+/// Gently pulse the scale of the ready prompt.
+///
+/// Tweens run on virtual time, so the pulse is sped back up to make up for
+/// a slowed down game.
+fn pulse_animator(time: &Time<Virtual>) -> Animator<Transform> {
+    Animator::new(
+        Tween::new(
+            EaseFunction::SineInOut,
+            Duration::from_millis(700),
+            TransformScaleLens {
+                start: Vec3::ONE,
+                end: Vec3::splat(1.1),
+            },
+        )
+        .with_repeat_count(RepeatCount::Infinite)
+        .with_repeat_strategy(RepeatStrategy::MirroredRepeat),
+    )
+    .with_speed(1.0 / time.relative_speed())
+}
+
+pub fn spawn_display(
+    mut commands: Commands,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    containers: Res<Containers>,
+) {
+    let (text, themed) = theme.text(
+        "",
+        fonts.menu_font.clone(),
+        ThemedText {
+            size: 70.0,
+            color: Color::ANTIQUE_WHITE,
         },
-        CenterDisplay,
-    ));
+    );
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "center_display",
+        (
+            Text2dBundle {
+                text,
+                transform: Transform::from_xyz(0.0, 0.0, 20.0),
+                ..default()
+            },
+            CenterDisplay,
+            AccessibleText,
+            themed,
+        ),
+    );
 }
 
-pub fn show_game_over(mut text: Query<(&mut Text, &mut Visibility), With<CenterDisplay>>) {
+pub fn show_game_over(
+    score: Res<Score>,
+    mut text: Query<(&mut Text, &mut Visibility), With<CenterDisplay>>,
+) {
     for (mut t, mut v) in text.iter_mut() {
         *v = Visibility::Visible;
-        t.sections[0].value = "GAME OVER".to_string();
+        t.sections[0].value = format!(
+            "GAME OVER\nFLIPS: {} (+{})",
+            score.gravity_flips,
+            score.style_bonus()
+        );
     }
 }
 
-pub fn show_ready(mut text: Query<(&mut Text, &mut Visibility), With<CenterDisplay>>) {
-    for (mut t, mut v) in text.iter_mut() {
+pub fn show_ready(
+    mut commands: Commands,
+    mut text: Query<(Entity, &mut Text, &mut Visibility), With<CenterDisplay>>,
+    icons: Res<InputIcons>,
+    device: Res<LastInputDevice>,
+    time: Res<Time<Virtual>>,
+    containers: Res<Containers>,
+) {
+    for (ent, mut t, mut v) in text.iter_mut() {
         *v = Visibility::Visible;
         t.sections[0].value = "READY".to_string();
+        commands.entity(ent).insert(pulse_animator(&time));
+    }
+
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "input_hint",
+        (
+            SpriteBundle {
+                texture: icons.jump_icon(device.0),
+                transform: Transform::from_xyz(0.0, -100.0, 20.0).with_scale(Vec3::splat(3.0)),
+                ..default()
+            },
+            InputHint,
+        ),
+    );
+}
+
+/// Swap the input hint when the player switches devices.
+fn update_input_hint(
+    icons: Res<InputIcons>,
+    device: Res<LastInputDevice>,
+    mut hints: Query<&mut Handle<Image>, With<InputHint>>,
+) {
+    if device.is_changed() {
+        for mut texture in hints.iter_mut() {
+            *texture = icons.jump_icon(device.0);
+        }
+    }
+}
+
+/// Stop animating the ready prompt, and remove the input hint.
+fn hide_ready(
+    mut commands: Commands,
+    mut text: Query<(Entity, &mut Transform), With<CenterDisplay>>,
+    hints: Query<Entity, With<InputHint>>,
+) {
+    for (ent, mut t) in text.iter_mut() {
+        commands.entity(ent).remove::<Animator<Transform>>();
+        t.scale = Vec3::ONE;
+    }
+    for ent in hints.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+/// Ask for confirmation while a restart is pending during play.
+fn show_restart_confirmation(
+    pending: Res<PendingRestart>,
+    mut text: Query<(&mut Text, &mut Visibility), With<CenterDisplay>>,
+) {
+    if !pending.is_changed() {
+        return;
+    }
+    for (mut t, mut v) in text.iter_mut() {
+        if pending.is_pending() {
+            *v = Visibility::Visible;
+            t.sections[0].value = "RESTART?".to_string();
+        } else {
+            *v = Visibility::Hidden;
+        }
     }
 }
 
@@ -47,10 +195,18 @@ pub struct CenterDisplayPlugin;
 
 impl Plugin for CenterDisplayPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(GameState::AssetLoading), spawn_display)
+        app.add_game_collection::<InputIcons>()
+            .register_type::<CenterDisplay>()
+            .register_type::<InputHint>()
+            .add_systems(ON_ASSETS_LOADED, spawn_display)
             .add_systems(OnEnter(GameState::Dying), show_game_over)
             .add_systems(OnExit(GameState::Dying), hide_display)
             .add_systems(OnEnter(GameState::Ready), show_ready)
-            .add_systems(OnExit(GameState::Ready), hide_display);
+            .add_systems(OnExit(GameState::Ready), (hide_display, hide_ready))
+            .add_systems(Update, update_input_hint.run_if(in_state(GameState::Ready)))
+            .add_systems(
+                Update,
+                show_restart_confirmation.run_if(in_state(GameState::Playing)),
+            );
     }
 }
@@ -0,0 +1,130 @@
+//! Streaks of consecutive gates, and how the feedback for passing a gate
+//! escalates along a streak.
+use bevy::prelude::*;
+
+use crate::{barrier::HitBarrierEvent, scoring_region::GatePassedEvent, ResetEvent};
+
+/// Current streak of gates passed without hitting anything.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct Combo {
+    pub streak: u32,
+}
+
+/// Sent for each gate passed, with the streak it extended.
+#[derive(Event)]
+pub struct ComboStepEvent {
+    pub streak: u32,
+
+    /// World position of the gate.
+    pub position: Vec2,
+
+    pub score_delta: i32,
+}
+
+/// Maps a streak to the feedback for passing a gate, for the audio and
+/// popups to share.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct ComboFeedback {
+    /// Playback speed of the gate sound at the start of a streak.
+    pub base_pitch: f32,
+
+    /// Playback speed added for each step of the streak.
+    pub pitch_step: f32,
+
+    /// Step from which the feedback stops escalating.
+    pub max_steps: u32,
+
+    /// Popup color at the start of a streak.
+    pub start_color: Color,
+
+    /// Popup color once the streak reaches `max_steps`.
+    pub peak_color: Color,
+}
+
+impl Default for ComboFeedback {
+    fn default() -> Self {
+        Self {
+            base_pitch: 1.0,
+            pitch_step: 0.06,
+            max_steps: 10,
+            start_color: Color::WHITE,
+            peak_color: Color::ORANGE_RED,
+        }
+    }
+}
+
+impl ComboFeedback {
+    /// Number of escalation steps for a streak, starting at 0 for the first
+    /// gate.
+    fn steps(&self, streak: u32) -> u32 {
+        streak.saturating_sub(1).min(self.max_steps)
+    }
+
+    /// Fraction of the way to the peak feedback.
+    pub fn intensity(&self, streak: u32) -> f32 {
+        if self.max_steps == 0 {
+            return 1.0;
+        }
+        self.steps(streak) as f32 / self.max_steps as f32
+    }
+
+    /// Playback speed of the gate sound, raising its pitch.
+    pub fn pitch(&self, streak: u32) -> f32 {
+        self.base_pitch + self.pitch_step * self.steps(streak) as f32
+    }
+
+    /// Color of the gate popup.
+    pub fn color(&self, streak: u32) -> Color {
+        let t = self.intensity(streak);
+        let [r0, g0, b0, a0] = self.start_color.as_rgba_f32();
+        let [r1, g1, b1, a1] = self.peak_color.as_rgba_f32();
+        Color::rgba(
+            r0 + (r1 - r0) * t,
+            g0 + (g1 - g0) * t,
+            b0 + (b1 - b0) * t,
+            a0 + (a1 - a0) * t,
+        )
+    }
+}
+
+fn extend_combo(
+    mut combo: ResMut<Combo>,
+    mut gates: EventReader<GatePassedEvent>,
+    mut steps: EventWriter<ComboStepEvent>,
+) {
+    for gate in gates.read() {
+        combo.streak += 1;
+        steps.send(ComboStepEvent {
+            streak: combo.streak,
+            position: gate.position,
+            score_delta: gate.score_delta,
+        });
+    }
+}
+
+/// A hit breaks the streak.
+fn break_combo(mut combo: ResMut<Combo>) {
+    combo.streak = 0;
+}
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Combo>()
+            .register_type::<ComboFeedback>()
+            .init_resource::<Combo>()
+            .init_resource::<ComboFeedback>()
+            .add_event::<ComboStepEvent>()
+            .add_systems(
+                Update,
+                (
+                    extend_combo,
+                    break_combo.run_if(on_event::<HitBarrierEvent>()),
+                    break_combo.run_if(on_event::<ResetEvent>()),
+                ),
+            );
+    }
+}
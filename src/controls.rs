@@ -0,0 +1,155 @@
+//! Input bindings, and tracking of which input device the player is using.
+use bevy::{input::InputSystem, prelude::*, utils::Instant};
+
+/// Keyboard key used to jump.
+pub const JUMP_KEY: KeyCode = KeyCode::Space;
+
+/// Gamepad button used to jump.
+pub const JUMP_BUTTON: GamepadButtonType = GamepadButtonType::South;
+
+/// Keyboard key used to flip gravity, in modes that allow it. Not an arrow
+/// key, as those move through the pages that can be opened during play.
+pub const FLIP_KEY: KeyCode = KeyCode::ShiftLeft;
+
+/// Gamepad button used to flip gravity, in modes that allow it.
+pub const FLIP_BUTTON: GamepadButtonType = GamepadButtonType::East;
+
+/// Keyboard key used to restart.
+pub const RESTART_KEY: KeyCode = KeyCode::R;
+
+/// Gamepad button used to restart.
+pub const RESTART_BUTTON: GamepadButtonType = GamepadButtonType::Select;
+
+/// Game actions, sent when any input bound to them is pressed.
+#[derive(Event, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    /// Abandon the current run and start a new one.
+    Restart,
+
+    /// Flip gravity, in modes that allow it.
+    Flip,
+}
+
+/// Kind of device that input came from.
+#[derive(Reflect, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputDevice {
+    #[default]
+    Keyboard,
+    Gamepad,
+}
+
+/// Device that the player last pressed something on, for showing matching
+/// input hints.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct LastInputDevice(pub InputDevice);
+
+/// When this frame's input was read, for measuring input latency.
+#[derive(Resource, Deref)]
+pub struct InputTimestamp(pub Instant);
+
+impl Default for InputTimestamp {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+/// Options that depend on the control scheme in use.
+#[derive(Reflect, Default, Clone)]
+pub struct ControlScheme {
+    /// Continuously tilt the rocket with its velocity, rather than only
+    /// flipping on gravity shifts.
+    pub velocity_tilt: bool,
+}
+
+/// Control scheme options for each device.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct ControlSchemes {
+    pub keyboard: ControlScheme,
+    pub gamepad: ControlScheme,
+}
+
+impl ControlSchemes {
+    /// Options for the scheme of the given device.
+    pub fn get(&self, device: InputDevice) -> &ControlScheme {
+        match device {
+            InputDevice::Keyboard => &self.keyboard,
+            InputDevice::Gamepad => &self.gamepad,
+        }
+    }
+}
+
+/// Whether jump was just pressed, on any device.
+pub fn jump_pressed(keys: &Input<KeyCode>, buttons: &Input<GamepadButton>) -> bool {
+    keys.just_pressed(JUMP_KEY)
+        || buttons
+            .get_just_pressed()
+            .any(|b| b.button_type == JUMP_BUTTON)
+}
+
+/// Run condition for [`jump_pressed`].
+pub fn jump_just_pressed(keys: Res<Input<KeyCode>>, buttons: Res<Input<GamepadButton>>) -> bool {
+    jump_pressed(&keys, &buttons)
+}
+
+/// Translate this frame's inputs into actions.
+fn send_actions(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mut actions: EventWriter<Action>,
+) {
+    if keys.just_pressed(RESTART_KEY)
+        || buttons
+            .get_just_pressed()
+            .any(|b| b.button_type == RESTART_BUTTON)
+    {
+        actions.send(Action::Restart);
+    }
+    if keys.just_pressed(FLIP_KEY)
+        || buttons
+            .get_just_pressed()
+            .any(|b| b.button_type == FLIP_BUTTON)
+    {
+        actions.send(Action::Flip);
+    }
+}
+
+fn stamp_input(mut stamp: ResMut<InputTimestamp>) {
+    stamp.0 = Instant::now();
+}
+
+fn track_input_device(
+    keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mut last: ResMut<LastInputDevice>,
+) {
+    let device = if buttons.get_just_pressed().next().is_some() {
+        InputDevice::Gamepad
+    } else if keys.get_just_pressed().next().is_some() {
+        InputDevice::Keyboard
+    } else {
+        return;
+    };
+    // only flag a change when the device actually switches.
+    if last.0 != device {
+        last.0 = device;
+    }
+}
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LastInputDevice>()
+            .register_type::<ControlSchemes>()
+            .init_resource::<LastInputDevice>()
+            .init_resource::<ControlSchemes>()
+            .init_resource::<InputTimestamp>()
+            .add_event::<Action>()
+            .add_systems(
+                PreUpdate,
+                (stamp_input, track_input_device, send_actions).after(InputSystem),
+            );
+    }
+}
@@ -0,0 +1,151 @@
+//! Crash reports, written from a panic hook.
+//!
+//! On a panic, the message, a backtrace, the recent [`game_log`] and some
+//! system information are saved to [`CRASH_DIR`]. The game then relaunches
+//! itself with `--crash-report <file>`, which opens a small window saying
+//! where the report went, rather than just disappearing.
+//!
+//! [`game_log`]: crate::game_log
+use std::{
+    backtrace::Backtrace,
+    fmt::Write,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        OnceLock,
+    },
+};
+
+use bevy::{prelude::*, render::renderer::RenderAdapterInfo, window::close_on_esc};
+
+use crate::{game_log, persistence::write_atomic};
+
+/// Directory that crash reports are saved in.
+pub const CRASH_DIR: &str = "crash_reports";
+
+/// Graphics adapter in use, once the renderer has started.
+static RENDERER: OnceLock<String> = OnceLock::new();
+
+/// Reports written so far, to tell apart panics in the same millisecond,
+/// such as on several threads at once.
+static REPORTS: AtomicU32 = AtomicU32::new(0);
+
+/// Whether the crash screen has been launched, so that only the first of
+/// several panics opens one.
+static SCREEN_LAUNCHED: AtomicBool = AtomicBool::new(false);
+
+/// Save a report for a panic, given its message.
+fn write_report(panic: &str) -> anyhow::Result<PathBuf> {
+    let mut report = format!(
+        "Rusty Rocket {} crashed\n\n{}\n\n{}\n\nSYSTEM\nos: {} ({})\ncpus: {}\nrenderer: {}\n\nRECENT EVENTS\n",
+        env!("CARGO_PKG_VERSION"),
+        panic,
+        Backtrace::force_capture(),
+        std::env::consts::OS,
+        std::env::consts::ARCH,
+        std::thread::available_parallelism().map_or(0, |n| n.get()),
+        RENDERER.get().map_or("not started", |r| r.as_str()),
+    );
+    for entry in game_log::recent() {
+        writeln!(report, "{}", entry)?;
+    }
+
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let count = REPORTS.fetch_add(1, Ordering::Relaxed);
+    let path = Path::new(CRASH_DIR).join(format!("crash-{}-{}.txt", millis, count));
+    write_atomic(&path, report.as_bytes())?;
+    Ok(path)
+}
+
+/// Relaunch the game to show the crash screen for a report.
+fn launch_crash_screen(report: &Path) -> std::io::Result<()> {
+    std::process::Command::new(std::env::current_exe()?)
+        .arg("--crash-report")
+        .arg(report)
+        .spawn()?;
+    Ok(())
+}
+
+/// Save a crash report on panic, and show where it went.
+///
+/// The default hook still runs first, so the panic is printed as usual.
+/// Every panic gets a report, but only the first opens the crash screen.
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        default_hook(info);
+        match write_report(&info.to_string()) {
+            Ok(path) => {
+                eprintln!("crash report saved to {}", path.display());
+                if SCREEN_LAUNCHED.swap(true, Ordering::Relaxed) {
+                    return;
+                }
+                if let Err(e) = launch_crash_screen(&path) {
+                    eprintln!("couldn't show the crash screen: {}", e);
+                }
+            }
+            Err(e) => eprintln!("couldn't save a crash report: {}", e),
+        }
+    }));
+}
+
+fn record_renderer(adapter: Option<Res<RenderAdapterInfo>>) {
+    if let Some(adapter) = adapter {
+        let _ = RENDERER.set(format!("{} ({:?})", adapter.name, adapter.backend));
+    }
+}
+
+/// Keeps the system information for crash reports up to date.
+pub struct CrashReportPlugin;
+
+impl Plugin for CrashReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, record_renderer);
+    }
+}
+
+#[derive(Resource)]
+struct CrashReportPath(PathBuf);
+
+fn spawn_crash_message(mut commands: Commands, report: Res<CrashReportPath>) {
+    commands.spawn(Camera2dBundle::default());
+    commands.spawn(Text2dBundle {
+        text: Text::from_section(
+            format!(
+                "The game crashed.\n\nA report was saved to\n{}\n\nPress Esc to close.",
+                report.0.display()
+            ),
+            TextStyle {
+                font_size: 20.0,
+                color: Color::ANTIQUE_WHITE,
+                ..default()
+            },
+        )
+        .with_alignment(TextAlignment::Center),
+        ..default()
+    });
+}
+
+/// Run a minimal app showing where a crash report was saved.
+///
+/// Loads none of the game's assets, so that it still works when they were
+/// the cause of the crash.
+pub fn run_crash_screen(report: PathBuf) {
+    App::new()
+        .add_plugins(DefaultPlugins.set(WindowPlugin {
+            primary_window: Some(Window {
+                title: "Rusty Rocket crashed".to_string(),
+                resolution: (640.0, 240.0).into(),
+                resizable: false,
+                ..default()
+            }),
+            ..default()
+        }))
+        .insert_resource(CrashReportPath(report))
+        .add_systems(Startup, spawn_crash_message)
+        .add_systems(Update, close_on_esc)
+        .run();
+}
@@ -0,0 +1,137 @@
+//! Ghost markers where the player died in earlier runs this session.
+//!
+//! Deaths are logged by the [`ScrollOdometer`] distance, so each marker
+//! scrolls into view in step with the obstacles it died at, as long as the
+//! run uses the same seed.
+use bevy::prelude::*;
+
+use crate::{
+    dying_player::PlayerDiedEvent,
+    level::RemoveOnReset,
+    obstacle_spawner::ScrollOdometer,
+    player::{PlayerAtlases, PLAYER_FRAME_SIZE, PLAYER_SCALE},
+    util::{spawn_named_child, Containers},
+    GameState, WorldSettings,
+};
+
+/// Most deaths kept in the log; older ones are dropped.
+const MAX_DEATHS: usize = 20;
+
+const MARKER_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.3);
+
+/// Where a run ended.
+#[derive(Reflect, Clone, Copy, Debug)]
+pub struct DeathRecord {
+    /// Distance scrolled when the player died.
+    pub distance: f32,
+
+    /// Index of the next item that would have spawned.
+    pub spawn_index: u64,
+
+    /// Level space position of the player.
+    pub position: Vec2,
+}
+
+/// Deaths in the runs of this session, oldest first.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct DeathLog {
+    pub deaths: Vec<DeathRecord>,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DeathMarker(DeathRecord);
+
+fn log_deaths(
+    mut log: ResMut<DeathLog>,
+    mut deaths: EventReader<PlayerDiedEvent>,
+    odometer: Res<ScrollOdometer>,
+    play_world: Res<WorldSettings>,
+) {
+    for death in deaths.read() {
+        if log.deaths.len() == MAX_DEATHS {
+            log.deaths.remove(0);
+        }
+        log.deaths.push(DeathRecord {
+            distance: odometer.distance,
+            spawn_index: odometer.spawn_index,
+            position: play_world.scroll_axis.to_level(death.position),
+        });
+    }
+}
+
+/// Add a marker for every logged death, ahead of the new run.
+fn spawn_death_markers(
+    mut commands: Commands,
+    log: Res<DeathLog>,
+    atlases: Res<PlayerAtlases>,
+    play_world: Res<WorldSettings>,
+    odometer: Res<ScrollOdometer>,
+    containers: Res<Containers>,
+) {
+    for death in log.deaths.iter() {
+        spawn_named_child(
+            &mut commands,
+            containers.effects,
+            "death_marker",
+            (
+                SpriteSheetBundle {
+                    sprite: TextureAtlasSprite {
+                        custom_size: Some(PLAYER_FRAME_SIZE * PLAYER_SCALE),
+                        color: MARKER_COLOR,
+                        index: 0,
+                        ..default()
+                    },
+                    texture_atlas: atlases.player_atlas.clone(),
+                    transform: play_world
+                        .level_transform(marker_position(death, odometer.distance), 1.0),
+                    ..default()
+                },
+                DeathMarker(*death),
+                RemoveOnReset,
+            ),
+        );
+    }
+}
+
+/// Level space position of a marker, once the run has scrolled `distance`.
+fn marker_position(death: &DeathRecord, distance: f32) -> Vec2 {
+    death.position + Vec2::new(death.distance - distance, 0.0)
+}
+
+/// Scroll markers with the level, and drop them once they leave the view.
+fn move_death_markers(
+    mut commands: Commands,
+    mut markers: Query<(Entity, &DeathMarker, &mut Transform)>,
+    play_world: Res<WorldSettings>,
+    odometer: Res<ScrollOdometer>,
+) {
+    let min_x = play_world.level_bounds().min.x - PLAYER_FRAME_SIZE.x * PLAYER_SCALE;
+    for (ent, marker, mut t) in markers.iter_mut() {
+        let pos = marker_position(&marker.0, odometer.distance);
+        if pos.x < min_x {
+            commands.entity(ent).despawn_recursive();
+            continue;
+        }
+        t.translation = play_world.scroll_axis.to_world(pos).extend(t.translation.z);
+    }
+}
+
+pub struct DeathMarkersPlugin;
+
+impl Plugin for DeathMarkersPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<DeathLog>()
+            .register_type::<DeathMarker>()
+            .init_resource::<DeathLog>()
+            .add_systems(OnEnter(GameState::Ready), spawn_death_markers)
+            .add_systems(
+                Update,
+                (
+                    move_death_markers.run_if(in_state(GameState::Playing)),
+                    log_deaths,
+                ),
+            );
+    }
+}
@@ -0,0 +1,160 @@
+//! Second window with a zoomed out overview of the whole obstacle field,
+//! for debugging spawn spacing.
+//!
+//! The overview camera also renders [`DEBUG_LAYER`], where entity labels
+//! live, so the primary window keeps showing the normal game view. Labels
+//! follow their items rather than being their children, to keep them out
+//! of scene exports. Gizmos stay on the default layer, which both windows
+//! see, as the gizmo config is shared by every camera.
+use bevy::{
+    prelude::*,
+    render::{camera::RenderTarget, view::RenderLayers},
+    window::WindowRef,
+};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    obstacle::Obstacle, obstacle_spawner::ObstacleSpawner, physics::PhysicsSettings, WorldSettings,
+};
+
+/// Render layer only seen by the overview camera.
+pub const DEBUG_LAYER: u8 = 7;
+
+/// How far the overview is zoomed out, relative to the game view.
+const OVERVIEW_ZOOM: f32 = 3.0;
+
+const BOUNDS_COLOR: Color = Color::LIME_GREEN;
+const SPAWN_LINE_COLOR: Color = Color::CYAN;
+const ITEM_COLOR: Color = Color::YELLOW;
+
+/// Marker for the overview camera.
+#[derive(Component)]
+pub struct OverviewCamera;
+
+/// Label showing the name and id of an entity in the overview, which
+/// follows the entity around.
+#[derive(Component)]
+struct OverviewLabel(Entity);
+
+/// Height of labels above the items they follow.
+const LABEL_Z: f32 = 30.0;
+
+fn open_overview_window(mut commands: Commands) {
+    let window = commands
+        .spawn(Window {
+            title: "Rusty Rocket - overview".to_string(),
+            resolution: (800.0, 450.0).into(),
+            ..default()
+        })
+        .id();
+
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                target: RenderTarget::Window(WindowRef::Entity(window)),
+                ..default()
+            },
+            projection: OrthographicProjection {
+                scale: OVERVIEW_ZOOM,
+                ..default()
+            },
+            ..default()
+        },
+        RenderLayers::from_layers(&[0, DEBUG_LAYER]),
+        OverviewCamera,
+    ));
+}
+
+/// Label newly spawned items with their name and entity id.
+fn label_items(mut commands: Commands, items: Query<(Entity, Option<&Name>), Added<Obstacle>>) {
+    for (ent, name) in items.iter() {
+        let label = match name {
+            Some(name) => format!("{} {:?}", name, ent),
+            None => format!("{:?}", ent),
+        };
+        commands.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font_size: 36.0,
+                        color: ITEM_COLOR,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_xyz(0.0, 0.0, LABEL_Z),
+                ..default()
+            },
+            RenderLayers::layer(DEBUG_LAYER),
+            OverviewLabel(ent),
+            Name::new("overview_label"),
+        ));
+    }
+}
+
+/// Move labels along with their items, and remove the labels of items
+/// that are gone.
+fn follow_items(
+    mut commands: Commands,
+    mut labels: Query<(Entity, &OverviewLabel, &mut Transform)>,
+    items: Query<&GlobalTransform>,
+) {
+    for (ent, label, mut transform) in labels.iter_mut() {
+        match items.get(label.0) {
+            Ok(global) => {
+                transform.translation = global.translation().truncate().extend(LABEL_Z);
+            }
+            Err(_) => commands.entity(ent).despawn(),
+        }
+    }
+}
+
+/// Outline the visible world, the spawn line, every item and every box
+/// collider.
+fn draw_overview(
+    mut gizmos: Gizmos,
+    play_world: Res<WorldSettings>,
+    physics: Res<PhysicsSettings>,
+    spawners: Query<&ObstacleSpawner>,
+    items: Query<&GlobalTransform, With<Obstacle>>,
+    colliders: Query<(&GlobalTransform, &Collider)>,
+) {
+    let bounds = play_world.bounds;
+    gizmos.rect_2d(bounds.center(), 0.0, bounds.size(), BOUNDS_COLOR);
+
+    let axis = play_world.scroll_axis;
+    let level_bounds = play_world.level_bounds();
+    if let Ok(spawner) = spawners.get_single() {
+        let x = spawner.start_offset_x(&play_world, &physics);
+        gizmos.line_2d(
+            axis.to_world(Vec2::new(x, level_bounds.min.y)),
+            axis.to_world(Vec2::new(x, level_bounds.max.y)),
+            SPAWN_LINE_COLOR,
+        );
+    }
+
+    for global in items.iter() {
+        gizmos.circle_2d(global.translation().truncate(), 6.0, ITEM_COLOR);
+    }
+    for (global, collider) in colliders.iter() {
+        let Some(cuboid) = collider.as_cuboid() else {
+            continue;
+        };
+        let (_, rotation, translation) = global.to_scale_rotation_translation();
+        gizmos.rect_2d(
+            translation.truncate(),
+            rotation.to_euler(EulerRot::XYZ).2,
+            cuboid.half_extents() * 2.0,
+            ITEM_COLOR,
+        );
+    }
+}
+
+pub struct DebugViewPlugin;
+
+impl Plugin for DebugViewPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, open_overview_window)
+            .add_systems(Update, (label_items, follow_items, draw_overview));
+    }
+}
@@ -0,0 +1,90 @@
+//! Gameplay health counters, kept as bevy diagnostics so that they show on
+//! the `F3` overlay and can be logged over long soak tests.
+//!
+//! Each diagnostic is registered and measured by the module it watches. This
+//! module holds the ones without a better home, and what they all share.
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics, LogDiagnosticsPlugin, RegisterDiagnostic},
+    prelude::*,
+};
+use bevy_rapier2d::prelude::Collider;
+
+use crate::{obstacle_spawner::OBSTACLE_SPAWN_RATE, score::SCORE_RATE, ResetEvent};
+
+/// Number of colliders in the world.
+pub const ACTIVE_COLLIDERS: DiagnosticId =
+    DiagnosticId::from_u128(192401038634818917855176083106394578913);
+
+/// Number of resets since launch.
+pub const RESETS: DiagnosticId = DiagnosticId::from_u128(124148363063870710552530818762309938360);
+
+/// Gameplay diagnostics, in the order they are shown.
+pub const GAMEPLAY_DIAGNOSTICS: [DiagnosticId; 4] =
+    [OBSTACLE_SPAWN_RATE, ACTIVE_COLLIDERS, SCORE_RATE, RESETS];
+
+/// Length of the windows that rates are measured over, in seconds of game
+/// time.
+const RATE_WINDOW_SECS: f32 = 1.0;
+
+/// Measures how quickly a running total grows, over windows of game time.
+#[derive(Default)]
+pub struct RateWindow {
+    elapsed: f32,
+    start: Option<f64>,
+}
+
+impl RateWindow {
+    /// Advance the window by `delta` seconds, with the current `total`.
+    /// Returns the rate per second as each window closes.
+    ///
+    /// A total that drops, as on a reset, starts a new window.
+    pub fn sample(&mut self, delta: f32, total: f64) -> Option<f64> {
+        let start = match self.start {
+            Some(start) if start <= total => start,
+            _ => {
+                self.elapsed = 0.0;
+                self.start = Some(total);
+                return None;
+            }
+        };
+        self.elapsed += delta;
+        if self.elapsed < RATE_WINDOW_SECS {
+            return None;
+        }
+        let rate = (total - start) / self.elapsed as f64;
+        self.elapsed = 0.0;
+        self.start = Some(total);
+        Some(rate)
+    }
+}
+
+fn measure_colliders(mut diagnostics: Diagnostics, colliders: Query<(), With<Collider>>) {
+    diagnostics.add_measurement(ACTIVE_COLLIDERS, || colliders.iter().count() as f64);
+}
+
+fn measure_resets(
+    mut diagnostics: Diagnostics,
+    mut resets: EventReader<ResetEvent>,
+    mut count: Local<u64>,
+) {
+    *count += resets.read().count() as u64;
+    diagnostics.add_measurement(RESETS, || *count as f64);
+}
+
+pub struct GameplayDiagnosticsPlugin {
+    /// Whether to log the gameplay diagnostics every second.
+    pub log: bool,
+}
+
+impl Plugin for GameplayDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_diagnostic(Diagnostic::new(ACTIVE_COLLIDERS, "active_colliders", 20))
+            .register_diagnostic(Diagnostic::new(RESETS, "resets", 1))
+            .add_systems(Update, (measure_colliders, measure_resets));
+        if self.log {
+            app.add_plugins(LogDiagnosticsPlugin::filtered(
+                GAMEPLAY_DIAGNOSTICS.to_vec(),
+            ));
+        }
+    }
+}
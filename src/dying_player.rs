@@ -4,22 +4,184 @@ use rand::Rng;
 use std::time::Duration;
 
 use crate::{
+    accessibility::MotionSettings,
     barrier::HitBarrierEvent,
+    barrier_collision_groups, death_piece_collision_groups,
     level::LevelSettings,
-    player::{DecomposedSprite, OutOfBoundsEvent, Player, PLAYER_SCALE},
-    GameState, ResetEvent,
+    physics::PhysicsSettings,
+    player::{DecomposedSprite, OutOfBoundsEvent, Player, RocketScale, PLAYER_SCALE},
+    run::{GameMode, RunConfig},
+    score::{Score, ScoreDepletedEvent},
+    util::{spawn_named_child, Containers},
+    GameState, ResetEvent, WorldSettings,
 };
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct PlayerDeathAnim {
     death_time: Timer,
 }
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct PlayerDeathPiece;
 
+/// Copy of the player sprite that fades out in place of the explosion, when
+/// motion is reduced.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct PlayerDeathFade {
+    timer: Timer,
+}
+
+/// How long the reduced motion death fade takes.
+const DEATH_FADE_SECS: f32 = 1.0;
+
+/// Invisible floor or ceiling that death pieces bounce off while dying.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DeathBound;
+
+/// Thickness of the death bounds, in pixels.
+const DEATH_BOUND_THICKNESS: f32 = 20.0;
+
+/// How bouncy death pieces are.
+const DEATH_PIECE_RESTITUTION: f32 = 0.4;
+
+/// Speed that the player is bounced away from a forgiven barrier hit, in m/s.
+const FORGIVEN_BOUNCE_SPEED: f32 = 3.125;
+
+/// How long the player can't be hit after a forgiven barrier hit.
+const FORGIVEN_INVULNERABLE_SECS: f32 = 1.5;
+
+/// Score lost for a forgiven barrier hit.
+const FORGIVEN_HIT_PENALTY: i32 = 1;
+
+/// Marker for a player that has already used up their forgiven hit in easy
+/// mode.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct HitForgiven;
+
+/// Player can't be hit by barriers until the timer runs out.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Invulnerable(Timer);
+
+impl Invulnerable {
+    pub fn for_secs(secs: f32) -> Self {
+        Self(Timer::from_seconds(secs, TimerMode::Once))
+    }
+}
+
+/// What ended a run.
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DeathCause {
+    Barrier,
+    OutOfBounds,
+    ScoreDepleted,
+}
+
+impl DeathCause {
+    /// Short description, for display.
+    pub fn describe(&self) -> &'static str {
+        match self {
+            DeathCause::Barrier => "HIT BARRIER",
+            DeathCause::OutOfBounds => "OUT OF BOUNDS",
+            DeathCause::ScoreDepleted => "SCORE RAN OUT",
+        }
+    }
+}
+
+/// Sent when the player explodes, with its world position.
+#[derive(Event)]
+pub struct PlayerDiedEvent {
+    pub position: Vec2,
+    pub cause: DeathCause,
+}
+
+/// Event sent when a barrier hit should kill the player.
+#[derive(Event, Default)]
+pub struct FatalHitEvent;
+
+/// Decide whether barrier hits kill the player, or are forgiven under the
+/// current game mode.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn resolve_barrier_hits(
+    mut commands: Commands,
+    mut hits: EventReader<HitBarrierEvent>,
+    mut fatal_hits: EventWriter<FatalHitEvent>,
+    mut player: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Velocity,
+            Has<HitForgiven>,
+            Has<Invulnerable>,
+        ),
+        With<Player>,
+    >,
+    barriers: Query<&GlobalTransform>,
+    config: Res<RunConfig>,
+    level: Res<LevelSettings>,
+    play_world: Res<WorldSettings>,
+    physics: Res<PhysicsSettings>,
+    mut score: ResMut<Score>,
+) {
+    // a single hit per frame is enough to decide.
+    let Some(hit) = hits.read().last() else {
+        return;
+    };
+    let Ok((ent, t, mut vel, forgiven, invulnerable)) = player.get_single_mut() else {
+        return;
+    };
+    if invulnerable {
+        return;
+    }
+    if config.mode != GameMode::Easy || forgiven {
+        fatal_hits.send(FatalHitEvent);
+        return;
+    }
+
+    // bounce away from the barrier along the gravity axis, or against
+    // gravity when the barrier is unknown.
+    let axis = play_world.scroll_axis;
+    let player_y = axis.to_level(t.translation.truncate()).y;
+    let dir = match hit.barrier.and_then(|b| barriers.get(b).ok()) {
+        Some(barrier) => (player_y - axis.to_level(barrier.translation().truncate()).y).signum(),
+        None => -level.gravity_vector().y.signum(),
+    };
+    let speed = physics.to_pixels(FORGIVEN_BOUNCE_SPEED);
+    vel.linvel = axis.to_world(Vec2::new(0.0, dir * speed));
+
+    score.take(FORGIVEN_HIT_PENALTY as f64);
+    commands.entity(ent).insert((
+        HitForgiven,
+        Invulnerable::for_secs(FORGIVEN_INVULNERABLE_SECS),
+    ));
+}
+
+/// Blink the player while invulnerable, and remove the invulnerability when
+/// it runs out.
+fn update_invulnerability(
+    mut commands: Commands,
+    mut player: Query<(Entity, &mut Invulnerable, &mut TextureAtlasSprite)>,
+    time: Res<Time>,
+) {
+    for (ent, mut invulnerable, mut sprite) in player.iter_mut() {
+        invulnerable.0.tick(time.delta());
+        if invulnerable.0.finished() {
+            sprite.color.set_a(1.0);
+            commands.entity(ent).remove::<Invulnerable>();
+        } else {
+            let blink_on = (invulnerable.0.elapsed_secs() * 10.0) as u32 % 2 == 0;
+            sprite.color.set_a(if blink_on { 0.3 } else { 1.0 });
+        }
+    }
+}
+
 /// Update the timer, and change the state when it ends
 pub fn update_death_timer(
     mut da: Query<&mut PlayerDeathAnim>,
@@ -39,55 +201,164 @@ pub fn kill_death_anim(
     mut commands: Commands,
     da: Query<Entity, With<PlayerDeathAnim>>,
     dap: Query<Entity, With<PlayerDeathPiece>>,
+    fades: Query<Entity, With<PlayerDeathFade>>,
+    bounds: Query<Entity, With<DeathBound>>,
+) {
+    for ent in da
+        .iter()
+        .chain(dap.iter())
+        .chain(fades.iter())
+        .chain(bounds.iter())
+    {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+/// Add a floor and ceiling just outside the world bounds, so death pieces
+/// bounce and settle instead of falling away.
+fn spawn_death_bounds(
+    mut commands: Commands,
+    containers: Res<Containers>,
+    play_world: Res<WorldSettings>,
 ) {
-    for ent in da.iter() {
-        commands.entity(ent).despawn();
+    for (transform, half_extents) in play_world.level_edges(DEATH_BOUND_THICKNESS) {
+        spawn_named_child(
+            &mut commands,
+            containers.effects,
+            "death_bound",
+            (
+                TransformBundle::from_transform(transform),
+                RigidBody::Fixed,
+                Collider::cuboid(half_extents.x, half_extents.y),
+                barrier_collision_groups(),
+                DeathBound,
+            ),
+        );
     }
-    for ent in dap.iter() {
-        commands.entity(ent).despawn();
+}
+
+/// Fade out the copy of the player left by a reduced motion death.
+fn update_death_fade(
+    mut fades: Query<(&mut PlayerDeathFade, &mut TextureAtlasSprite)>,
+    time: Res<Time>,
+) {
+    for (mut fade, mut sprite) in fades.iter_mut() {
+        fade.timer.tick(time.delta());
+        sprite.color.set_a(fade.timer.percent_left());
     }
 }
 
+/// The systems that kill the player, one for each way to die.
+#[derive(Clone, PartialEq, Eq, Hash, Debug, SystemSet)]
+pub struct ExplodeSet;
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 pub fn explode_player(
     mut commands: Commands,
-    player: Query<(Entity, &Transform, &Velocity), With<Player>>,
+    player: Query<
+        (
+            Entity,
+            &Transform,
+            &Velocity,
+            &TextureAtlasSprite,
+            &Handle<TextureAtlas>,
+            Option<&RocketScale>,
+        ),
+        With<Player>,
+    >,
     level: Res<LevelSettings>,
+    motion: Res<MotionSettings>,
     mut next_state: ResMut<NextState<GameState>>,
+    mut deaths: EventWriter<PlayerDiedEvent>,
+    mut out_of_bounds: EventReader<OutOfBoundsEvent>,
+    mut depleted: EventReader<ScoreDepletedEvent>,
     ds: Res<DecomposedSprite>,
+    containers: Res<Containers>,
 ) {
     let mut rng = rand::thread_rng();
+    // anything other than leaving the world or running out of score is a
+    // fatal barrier hit
+    let cause = if out_of_bounds.read().count() > 0 {
+        DeathCause::OutOfBounds
+    } else if depleted.read().count() > 0 {
+        DeathCause::ScoreDepleted
+    } else {
+        DeathCause::Barrier
+    };
     // Get the existing player
-    for (ent, t, v) in player.iter() {
+    for (ent, t, v, sprite, atlas, rocket_scale) in player.iter() {
         let trans = t.translation;
+        // pieces match the rocket's size, if a power up changed it.
+        let scale = PLAYER_SCALE * rocket_scale.map_or(1.0, |s| s.0);
+        deaths.send(PlayerDiedEvent {
+            position: trans.truncate(),
+            cause,
+        });
+        spawn_named_child(
+            &mut commands,
+            containers.effects,
+            "death_anim",
+            PlayerDeathAnim {
+                death_time: Timer::new(Duration::from_secs(3), TimerMode::Once),
+            },
+        );
+        commands.entity(ent).despawn_recursive();
+
+        if !motion.animate() {
+            spawn_named_child(
+                &mut commands,
+                containers.effects,
+                "death_fade",
+                (
+                    SpriteSheetBundle {
+                        sprite: sprite.clone(),
+                        texture_atlas: atlas.clone(),
+                        transform: *t,
+                        ..default()
+                    },
+                    PlayerDeathFade {
+                        timer: Timer::from_seconds(DEATH_FADE_SECS, TimerMode::Once),
+                    },
+                ),
+            );
+            continue;
+        }
+
         // spawn the sprites around the velocity
-        commands.spawn((PlayerDeathAnim {
-            death_time: Timer::new(Duration::from_secs(3), TimerMode::Once),
-        },));
         for pix in &ds.pixels {
             let rand_dir = Vec2::from_angle(rng.gen::<f32>() * std::f32::consts::TAU);
-            commands.spawn((
-                SpriteBundle {
-                    sprite: Sprite {
-                        custom_size: Some(Vec2::splat(2.0)),
-                        color: pix.1,
+            spawn_named_child(
+                &mut commands,
+                containers.effects,
+                "death_piece",
+                (
+                    SpriteBundle {
+                        sprite: Sprite {
+                            custom_size: Some(Vec2::splat(ds.pixel_size * scale)),
+                            color: pix.1,
+                            ..default()
+                        },
+                        transform: Transform::from_translation(
+                            trans + t.rotation * (-pix.0 * scale).extend(10.0),
+                        ),
+                        ..default()
+                    },
+                    Velocity {
+                        linvel: v.linvel + rand_dir * level.explosion_speed,
                         ..default()
                     },
-                    transform: Transform::from_translation(
-                        trans + t.rotation * (-pix.0 * PLAYER_SCALE).extend(10.0),
-                    ),
-                    ..default()
-                },
-                Velocity {
-                    linvel: v.linvel + rand_dir * level.explosion_speed,
-                    ..default()
-                },
-                RigidBody::Dynamic,
-                PlayerDeathPiece,
-                Collider::cuboid(PLAYER_SCALE / 2.0, PLAYER_SCALE / 2.0),
-                ColliderMassProperties::Density(1.0),
-            ));
+                    RigidBody::Dynamic,
+                    // keep falling the way the player was.
+                    GravityScale(level.rocket_gravity_mult()),
+                    PlayerDeathPiece,
+                    death_piece_collision_groups(),
+                    Collider::cuboid(ds.pixel_size * scale / 2.0, ds.pixel_size * scale / 2.0),
+                    ColliderMassProperties::Density(1.0),
+                    Restitution::coefficient(DEATH_PIECE_RESTITUTION),
+                    Friction::coefficient(0.8),
+                ),
+            );
         }
-        commands.entity(ent).despawn_recursive();
     }
     next_state.set(GameState::Dying);
 }
@@ -96,14 +367,36 @@ pub struct DyingPlayerPlugin;
 
 impl Plugin for DyingPlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (
-                explode_player.run_if(on_event::<HitBarrierEvent>()),
-                explode_player.run_if(on_event::<OutOfBoundsEvent>()),
-                update_death_timer.run_if(in_state(GameState::Dying)),
-            ),
-        )
-        .add_systems(OnExit(GameState::Dying), kill_death_anim);
+        app.register_type::<PlayerDeathPiece>()
+            .register_type::<PlayerDeathFade>()
+            .register_type::<DeathBound>()
+            .register_type::<PlayerDeathAnim>()
+            .register_type::<HitForgiven>()
+            .register_type::<Invulnerable>()
+            .register_type::<DeathCause>()
+            .add_event::<FatalHitEvent>()
+            .add_event::<PlayerDiedEvent>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        resolve_barrier_hits.run_if(on_event::<HitBarrierEvent>()),
+                        explode_player
+                            .run_if(on_event::<FatalHitEvent>())
+                            .in_set(ExplodeSet),
+                    )
+                        .chain(),
+                    update_invulnerability.run_if(in_state(GameState::Playing)),
+                    explode_player
+                        .run_if(on_event::<OutOfBoundsEvent>())
+                        .in_set(ExplodeSet),
+                    explode_player
+                        .run_if(on_event::<ScoreDepletedEvent>())
+                        .in_set(ExplodeSet),
+                    (update_death_timer, update_death_fade).run_if(in_state(GameState::Dying)),
+                ),
+            )
+            .add_systems(OnEnter(GameState::Dying), spawn_death_bounds)
+            .add_systems(OnExit(GameState::Dying), kill_death_anim);
     }
 }
@@ -0,0 +1,146 @@
+//! Assets compiled into the binary, so it runs without an `assets` folder.
+//!
+//! [`EmbeddedAssetsPlugin`] replaces the default asset source with one that
+//! reads from the `assets` folder when it can, and falls back to the
+//! embedded copies otherwise. It has to be added before `DefaultPlugins`.
+use std::path::Path;
+
+use bevy::{
+    asset::io::{
+        memory::{Dir, MemoryAssetReader},
+        AssetReader, AssetReaderError, AssetSource, AssetSourceId, PathStream, Reader,
+    },
+    prelude::*,
+    utils::BoxedFuture,
+};
+
+/// Embed each asset, keyed by its path relative to the `assets` folder.
+macro_rules! embedded_assets {
+    ($($path:literal),* $(,)?) => {
+        &[$(($path, include_bytes!(concat!("../assets/", $path)) as &[u8])),*]
+    };
+}
+
+/// Every asset the game loads, including those only used by theme packs.
+const EMBEDDED_ASSETS: &[(&str, &[u8])] = embedded_assets![
+    "fonts/PixelOperatorMono-Bold.ttf",
+    "fonts/PixelOperatorMono.ttf",
+    "images/flame.png",
+    "images/flame@2x.png",
+    "images/grav_arrow_down.png",
+    "images/grav_arrow_down@2x.png",
+    "images/themes/space/grav_chevron_down.png",
+    "images/themes/space/grav_chevron_down@2x.png",
+    "images/themes/space/hull_plating.png",
+    "images/themes/space/hull_plating@2x.png",
+    "images/input/gamepad_south.png",
+    "images/input/key_space.png",
+    "images/rocketman.png",
+    "images/rocketman@2x.png",
+    "levels/base.spawner.ron",
+    "levels/fast.spawner.ron",
+    "levels/training.scenarios.ron",
+    "shaders/anim.wgsl",
+    "shaders/background.wgsl",
+    "shaders/post_process.wgsl",
+    "sounds/gate.wav",
+    "sounds/whoosh.wav",
+    "themes/neon.theme.ron",
+    "themes/space.theme.ron",
+    "themes/underwater.theme.ron",
+];
+
+/// Embedded copy of the asset at `path`, relative to the `assets` folder.
+pub fn embedded_asset(path: &str) -> Option<&'static [u8]> {
+    EMBEDDED_ASSETS
+        .iter()
+        .find(|(p, _)| *p == path)
+        .map(|(_, bytes)| *bytes)
+}
+
+/// Paths of the embedded assets in `dir`, relative to it.
+pub fn embedded_files(dir: &str) -> impl Iterator<Item = &'static str> + '_ {
+    EMBEDDED_ASSETS
+        .iter()
+        .filter_map(move |(path, _)| path.strip_prefix(dir)?.strip_prefix('/'))
+}
+
+/// Reads assets from disk, falling back to the embedded copies.
+struct FallbackAssetReader {
+    files: Box<dyn AssetReader>,
+    embedded: MemoryAssetReader,
+}
+
+impl FallbackAssetReader {
+    fn new() -> Self {
+        let root = Dir::default();
+        for (path, bytes) in EMBEDDED_ASSETS {
+            root.insert_asset(Path::new(path), *bytes);
+        }
+        Self {
+            files: AssetSource::get_default_reader("assets".to_string())(),
+            embedded: MemoryAssetReader { root },
+        }
+    }
+}
+
+impl AssetReader for FallbackAssetReader {
+    fn read<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            match self.files.read(path).await {
+                Err(AssetReaderError::NotFound(_)) => self.embedded.read(path).await,
+                result => result,
+            }
+        })
+    }
+
+    fn read_meta<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<Reader<'a>>, AssetReaderError>> {
+        Box::pin(async move {
+            match self.files.read_meta(path).await {
+                Err(AssetReaderError::NotFound(_)) => self.embedded.read_meta(path).await,
+                result => result,
+            }
+        })
+    }
+
+    fn read_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<Box<PathStream>, AssetReaderError>> {
+        Box::pin(async move {
+            match self.files.read_directory(path).await {
+                Err(AssetReaderError::NotFound(_)) => self.embedded.read_directory(path).await,
+                result => result,
+            }
+        })
+    }
+
+    fn is_directory<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> BoxedFuture<'a, Result<bool, AssetReaderError>> {
+        Box::pin(async move {
+            match self.files.is_directory(path).await {
+                Err(AssetReaderError::NotFound(_)) => self.embedded.is_directory(path).await,
+                result => result,
+            }
+        })
+    }
+}
+
+pub struct EmbeddedAssetsPlugin;
+
+impl Plugin for EmbeddedAssetsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_source(
+            AssetSourceId::Default,
+            AssetSource::build().with_reader(|| Box::new(FallbackAssetReader::new())),
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Gravity flips on input, in the modes that allow them.
+//!
+//! In flip charge mode, gravity regions don't flip gravity as they are
+//! passed. Each one grants a flip charge instead, and the player spends
+//! charges with the flip key to flip gravity when they choose. In manual
+//! flip mode there are no regions at all, and the flip key flips gravity
+//! whenever it is off cooldown.
+use bevy::prelude::*;
+
+use crate::{
+    controls::Action,
+    gravity_shift::{FlipChargeEvent, GravityEvent},
+    level::LevelSettings,
+    player::PlayerSet,
+    replay::live_input,
+    run::{GameMode, RunConfig},
+    score::Score,
+    GameState, ResetEvent,
+};
+
+/// Most charges that can be held at once. Regions passed with a full
+/// meter grant nothing.
+pub const MAX_FLIP_CHARGES: u32 = 3;
+
+/// Time between flips in manual flip mode.
+pub const MANUAL_FLIP_COOLDOWN_SECS: f32 = 0.75;
+
+/// Request to flip gravity with the flip key.
+#[derive(Event, Default)]
+pub struct FlipEvent;
+
+/// Flip charges held in the current run.
+#[derive(Resource, Reflect, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Resource)]
+pub struct FlipCharges(pub u32);
+
+/// Time until the next manual flip is allowed.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct FlipCooldown(Option<Timer>);
+
+impl FlipCooldown {
+    pub fn ready(&self) -> bool {
+        self.0.as_ref().map_or(true, |t| t.finished())
+    }
+}
+
+/// Run condition for flip charge mode.
+pub fn flip_charge_mode(config: Res<RunConfig>) -> bool {
+    config.mode == GameMode::FlipCharge
+}
+
+/// Run condition for manual flip mode.
+pub fn manual_flip_mode(config: Res<RunConfig>) -> bool {
+    config.mode == GameMode::ManualFlip
+}
+
+/// Run condition for the modes where the flip key flips gravity.
+pub fn flips_on_input(config: Res<RunConfig>) -> bool {
+    matches!(config.mode, GameMode::FlipCharge | GameMode::ManualFlip)
+}
+
+fn request_flips(mut actions: EventReader<Action>, mut flips: EventWriter<FlipEvent>) {
+    for _ in actions.read().filter(|a| **a == Action::Flip) {
+        flips.send(FlipEvent);
+    }
+}
+
+fn grant_flip_charges(mut grants: EventReader<FlipChargeEvent>, mut charges: ResMut<FlipCharges>) {
+    for _ in grants.read() {
+        charges.0 = (charges.0 + 1).min(MAX_FLIP_CHARGES);
+    }
+}
+
+/// Flip gravity for each requested flip that there is a charge for.
+fn spend_flip_charges(
+    mut flips: EventReader<FlipEvent>,
+    mut charges: ResMut<FlipCharges>,
+    level: Res<LevelSettings>,
+    mut score: ResMut<Score>,
+    mut gevs: EventWriter<GravityEvent>,
+) {
+    let mut gravity_mult = level.gravity_mult;
+    for _ in flips.read() {
+        if charges.0 == 0 {
+            debug!("no flip charge to spend");
+            continue;
+        }
+        charges.0 -= 1;
+        gravity_mult = -gravity_mult;
+        score.add_gravity_flip();
+        gevs.send(GravityEvent {
+            region: None,
+            gravity_mult,
+        });
+    }
+}
+
+/// Flip gravity on request, when the cooldown has run out.
+fn flip_off_cooldown(
+    time: Res<Time>,
+    mut flips: EventReader<FlipEvent>,
+    mut cooldown: ResMut<FlipCooldown>,
+    level: Res<LevelSettings>,
+    mut score: ResMut<Score>,
+    mut gevs: EventWriter<GravityEvent>,
+) {
+    if let Some(timer) = cooldown.0.as_mut() {
+        timer.tick(time.delta());
+    }
+    // several presses in one frame only flip once.
+    if flips.read().count() == 0 {
+        return;
+    }
+    if !cooldown.ready() {
+        debug!("flip still on cooldown");
+        return;
+    }
+    cooldown.0 = Some(Timer::from_seconds(
+        MANUAL_FLIP_COOLDOWN_SECS,
+        TimerMode::Once,
+    ));
+    score.add_gravity_flip();
+    gevs.send(GravityEvent {
+        region: None,
+        gravity_mult: -level.gravity_mult,
+    });
+}
+
+fn reset_flips(mut charges: ResMut<FlipCharges>, mut cooldown: ResMut<FlipCooldown>) {
+    *charges = FlipCharges::default();
+    *cooldown = FlipCooldown::default();
+}
+
+pub struct FlipChargePlugin;
+
+impl Plugin for FlipChargePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<FlipCharges>()
+            .register_type::<FlipCooldown>()
+            .init_resource::<FlipCharges>()
+            .init_resource::<FlipCooldown>()
+            .add_event::<FlipEvent>()
+            .add_systems(
+                Update,
+                (
+                    request_flips.run_if(live_input),
+                    (grant_flip_charges, spend_flip_charges)
+                        .chain()
+                        .run_if(flip_charge_mode),
+                    flip_off_cooldown.run_if(manual_flip_mode),
+                )
+                    .chain()
+                    // with the jumps, so replays record flips in step with them.
+                    .in_set(PlayerSet)
+                    .run_if(in_state(GameState::Playing).and_then(flips_on_input)),
+            )
+            .add_systems(PostUpdate, reset_flips.run_if(on_event::<ResetEvent>()));
+    }
+}
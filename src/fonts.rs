@@ -1,16 +1,16 @@
 use bevy::prelude::*;
-use bevy_asset_loader::{asset_collection::AssetCollection, loading_state::LoadingStateAppExt};
+use bevy_asset_loader::asset_collection::AssetCollection;
 
-use crate::GameState;
+use crate::asset_errors::GameCollectionAppExt;
 
 #[derive(AssetCollection, Resource)]
 pub struct FontsCollection {
     /// font for displaying the score
-    #[asset(path = "fonts/PixelOperatorMono-Bold.ttf")]
+    #[asset(key = "fonts.score")]
     pub score_font: Handle<Font>,
 
     /// font for the pause menu, possibly
-    #[asset(path = "fonts/PixelOperatorMono-Bold.ttf")]
+    #[asset(key = "fonts.menu")]
     pub menu_font: Handle<Font>,
 }
 
@@ -18,6 +18,6 @@ pub struct GameFontsPlugin;
 
 impl Plugin for GameFontsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_collection_to_loading_state::<_, FontsCollection>(GameState::AssetLoading);
+        app.add_game_collection::<FontsCollection>();
     }
 }
@@ -0,0 +1,72 @@
+//! Short history of notable game events, kept in memory so that crash
+//! reports can show what led up to a crash.
+//!
+//! The log is global rather than a resource, so that it can still be read
+//! from a panic hook once the app is gone.
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, OnceLock},
+    time::Instant,
+};
+
+use bevy::prelude::*;
+
+use crate::{dying_player::PlayerDiedEvent, score::Score, GameState, ResetEvent};
+
+/// Number of entries kept.
+const MAX_ENTRIES: usize = 200;
+
+static ENTRIES: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// Add an entry, stamped with the time since launch.
+pub fn record(message: impl AsRef<str>) {
+    let secs = START.get_or_init(Instant::now).elapsed().as_secs_f32();
+    // a panic while the log was held shouldn't lose it.
+    let mut entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    if entries.len() == MAX_ENTRIES {
+        entries.pop_front();
+    }
+    entries.push_back(format!("[{:9.3}] {}", secs, message.as_ref()));
+}
+
+/// Entries in the log, oldest first.
+pub fn recent() -> Vec<String> {
+    let entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    entries.iter().cloned().collect()
+}
+
+fn log_launch() {
+    record(format!("launched version {}", env!("CARGO_PKG_VERSION")));
+}
+
+fn log_state_changes(state: Res<State<GameState>>) {
+    if state.is_changed() {
+        record(format!("entered {:?}", state.get()));
+    }
+}
+
+fn log_deaths(mut deaths: EventReader<PlayerDiedEvent>, score: Res<Score>) {
+    for death in deaths.read() {
+        record(format!(
+            "died: {} with score {}",
+            death.cause.describe(),
+            score.points()
+        ));
+    }
+}
+
+fn log_resets(mut resets: EventReader<ResetEvent>) {
+    if resets.read().count() > 0 {
+        record("reset");
+    }
+}
+
+pub struct GameLogPlugin;
+
+impl Plugin for GameLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, log_launch)
+            .add_systems(Update, (log_state_changes, log_deaths, log_resets));
+    }
+}
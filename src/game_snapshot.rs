@@ -0,0 +1,119 @@
+//! Summary of the game state, refreshed once a frame, for consumers that
+//! only watch the game, such as overlay tools, the autopilot and the
+//! minimap.
+//!
+//! Reading the snapshot keeps them from each querying the player, score and
+//! obstacle components themselves, which are free to change shape.
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    level::LevelSettings, obstacle::SpawnParams, obstacle_spawner::SpeedController, player::Player,
+    score::Score, WorldSettings,
+};
+
+/// Gap of the nearest tunnel the player hasn't passed yet.
+#[derive(Reflect, Clone, Copy, PartialEq, Debug, Serialize)]
+pub struct GapSnapshot {
+    /// Level space distance from the player to the middle of the tunnel.
+    /// Negative while the player is inside it.
+    pub distance: f32,
+
+    /// Level space y position of the middle of the gap.
+    pub center: f32,
+    pub height: f32,
+}
+
+/// State of the game as of the end of the last frame. Positions and
+/// velocities are in level space, in pixels.
+#[derive(Resource, Reflect, Clone, PartialEq, Debug, Serialize)]
+#[reflect(Resource)]
+pub struct GameSnapshot {
+    /// Position of the player, while there is one.
+    pub player_position: Option<Vec2>,
+    pub player_velocity: Option<Vec2>,
+
+    pub score: i32,
+    pub gravity_mult: f32,
+
+    /// Scroll speed of the obstacles, in pixels per second.
+    pub speed: f32,
+
+    pub nearest_gap: Option<GapSnapshot>,
+}
+
+impl Default for GameSnapshot {
+    fn default() -> Self {
+        Self {
+            player_position: None,
+            player_velocity: None,
+            score: 0,
+            gravity_mult: 1.0,
+            speed: 0.0,
+            nearest_gap: None,
+        }
+    }
+}
+
+fn update_game_snapshot(
+    mut snapshot: ResMut<GameSnapshot>,
+    player: Query<(&Transform, &Velocity), With<Player>>,
+    tunnels: Query<(&Transform, &SpawnParams)>,
+    score: Res<Score>,
+    level: Res<LevelSettings>,
+    speed: Res<SpeedController>,
+    play_world: Res<WorldSettings>,
+) {
+    let axis = play_world.scroll_axis;
+    let player = player.get_single().ok().map(|(t, vel)| {
+        (
+            axis.to_level(t.translation.truncate()),
+            axis.to_level(vel.linvel),
+        )
+    });
+
+    let nearest_gap = player.and_then(|(pos, _)| {
+        tunnels
+            .iter()
+            .filter_map(|(t, params)| {
+                let SpawnParams::Tunnel(tunnel) = params else {
+                    return None;
+                };
+                // drifting tunnels carry their gap with them.
+                let tunnel_pos = axis.to_level(t.translation.truncate());
+                let distance = tunnel_pos.x - pos.x;
+                (distance + tunnel.obstacle_width / 2.0 > 0.0).then_some(GapSnapshot {
+                    distance,
+                    center: tunnel.gap_center + tunnel_pos.y,
+                    height: tunnel.gap_height,
+                })
+            })
+            .min_by(|a, b| a.distance.total_cmp(&b.distance))
+    });
+
+    let new_snapshot = GameSnapshot {
+        player_position: player.map(|(pos, _)| pos),
+        player_velocity: player.map(|(_, vel)| vel),
+        score: score.points(),
+        gravity_mult: level.gravity_mult,
+        speed: speed.current.length(),
+        nearest_gap,
+    };
+    // only mark the resource changed when something moved.
+    snapshot.set_if_neq(new_snapshot);
+}
+
+pub struct GameSnapshotPlugin;
+
+impl Plugin for GameSnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GameSnapshot>()
+            .register_type::<GapSnapshot>()
+            .init_resource::<GameSnapshot>()
+            .add_systems(
+                PostUpdate,
+                update_game_snapshot.after(PhysicsSet::Writeback),
+            );
+    }
+}
@@ -0,0 +1,351 @@
+//! Ghosts of other players' runs, loaded by dropping a replay file onto the
+//! game window.
+//!
+//! While a ghost is loaded, every run is played on the ghost's seed, and a
+//! translucent rocket replays its jumps alongside the player. Ghosts don't
+//! collide with anything, and keep gravity of their own: the gravity
+//! regions they pass and the flips of their replay flip them, apart from
+//! the player.
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use bevy_tweening::Animator;
+
+use crate::{
+    flip_charge::{MANUAL_FLIP_COOLDOWN_SECS, MAX_FLIP_CHARGES},
+    level::{LevelSettings, RemoveOnReset},
+    obstacle::SpawnParams,
+    obstacle_spawner::{ObstacleSpawner, SpawnIndex},
+    physics::PhysicsSettings,
+    player::{
+        gravity_scale, refill_jumps, rocket_body, rotate_rocket, JumpsLeft, PlayerAnim,
+        PlayerAtlases, PlayerSet,
+    },
+    replay::{Replay, ReplayInput},
+    run::{tick_run_clock, GameMode, RunClock, RunConfig},
+    GameState, ResetEvent, WorldSettings,
+};
+
+/// Tint of ghost rockets.
+const GHOST_COLOR: Color = Color::rgba(0.6, 0.8, 1.0, 0.4);
+
+/// Replay that ghosts are played from.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct GhostReplay {
+    replay: Replay,
+}
+
+/// Rocket replaying a loaded run.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Ghost {
+    /// Index of the next input to replay.
+    next: usize,
+
+    /// Spawn index of the first tunnel the ghost hasn't passed yet.
+    next_gate: u64,
+
+    /// Gravity mult of the ghost, as the player's was at the same point of
+    /// the replayed run.
+    gravity_mult: f32,
+
+    /// Gravity region the ghost is passing through, so that each one takes
+    /// effect once.
+    region: Option<Entity>,
+
+    /// Flip charges held, in flip charge mode.
+    flip_charges: u32,
+
+    /// Physics step of the last flip, in manual flip mode.
+    last_flip_step: Option<u64>,
+}
+
+impl Ghost {
+    fn new(gravity_mult: f32) -> Self {
+        Self {
+            next: 0,
+            next_gate: 0,
+            gravity_mult,
+            region: None,
+            flip_charges: 0,
+            last_flip_step: None,
+        }
+    }
+
+    /// Flip gravity for a flip replayed at physics step `step`, when the
+    /// ghost has a charge to spend or is off cooldown, as the player's
+    /// flips are.
+    fn flip(&mut self, step: u64, mode: GameMode, physics: &PhysicsSettings) {
+        match mode {
+            GameMode::FlipCharge => {
+                if self.flip_charges == 0 {
+                    return;
+                }
+                self.flip_charges -= 1;
+            }
+            GameMode::ManualFlip => {
+                let cooldown = (MANUAL_FLIP_COOLDOWN_SECS * physics.steps_per_second) as u64;
+                if self
+                    .last_flip_step
+                    .is_some_and(|last| step < last + cooldown)
+                {
+                    return;
+                }
+                self.last_flip_step = Some(step);
+            }
+            _ => return,
+        }
+        self.gravity_mult = -self.gravity_mult;
+    }
+}
+
+/// Load replays dropped onto the window as ghosts, if they were played in
+/// the current mode.
+fn load_dropped_ghosts(
+    mut commands: Commands,
+    mut drops: EventReader<FileDragAndDrop>,
+    mut config: ResMut<RunConfig>,
+    play_world: Res<WorldSettings>,
+    level: Res<LevelSettings>,
+    state: Res<State<GameState>>,
+    mut resets: EventWriter<ResetEvent>,
+) {
+    for drop in drops.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = drop else {
+            continue;
+        };
+        let replay = match Replay::load(path_buf) {
+            Ok(replay) => replay,
+            Err(e) => {
+                bevy::log::warn!("couldn't load ghost {}: {}", path_buf.display(), e);
+                continue;
+            }
+        };
+        if replay.mode != config.mode
+            || replay.scroll_axis != play_world.scroll_axis
+            || replay.max_jumps != level.max_jumps
+        {
+            bevy::log::warn!(
+                "ghost {} was played in a different mode",
+                path_buf.display()
+            );
+            continue;
+        }
+
+        bevy::log::info!("racing ghost {}", path_buf.display());
+        config.fixed_seed = Some(replay.seed);
+        commands.insert_resource(GhostReplay { replay });
+        // the waiting run was already seeded, so set up a new one on the
+        // ghost's seed. Runs in progress switch over from the next one.
+        if *state.get() == GameState::Ready {
+            resets.send(ResetEvent);
+        }
+    }
+}
+
+fn spawn_ghost(
+    mut commands: Commands,
+    sprites: Res<PlayerAtlases>,
+    spawners: Query<&ObstacleSpawner>,
+    clock: Res<RunClock>,
+    world: Res<WorldSettings>,
+    physics: Res<PhysicsSettings>,
+    level: Res<LevelSettings>,
+) {
+    // continued runs are already under way, so the ghost can't catch up.
+    if !clock.elapsed().is_zero() {
+        return;
+    }
+    let start = spawners
+        .get_single()
+        .map(|spawner| spawner.level().player_start.clone())
+        .unwrap_or_default();
+    commands.spawn((
+        rocket_body(&sprites, &world, &start, &physics),
+        CollisionGroups::new(Group::NONE, Group::NONE),
+        Ghost::new(level.gravity_mult),
+        RemoveOnReset,
+        Name::new("ghost"),
+    ));
+}
+
+fn tint_ghosts(mut ghosts: Query<&mut TextureAtlasSprite, Added<Ghost>>) {
+    for mut sprite in ghosts.iter_mut() {
+        sprite.color = GHOST_COLOR;
+    }
+}
+
+/// Jump and flip with the inputs of the ghost's replay that are due.
+fn replay_ghost_inputs(
+    ghost_replay: Res<GhostReplay>,
+    clock: Res<RunClock>,
+    mut ghosts: Query<(&mut Ghost, &mut PlayerAnim, &mut JumpsLeft, &mut Velocity)>,
+    level: Res<LevelSettings>,
+    world: Res<WorldSettings>,
+    config: Res<RunConfig>,
+    physics: Res<PhysicsSettings>,
+) {
+    let step = clock.physics_steps();
+    let inputs = &ghost_replay.replay.inputs;
+    for (mut ghost, mut anim, mut jumps_left, mut vel) in ghosts.iter_mut() {
+        while let Some(timed) = inputs.get(ghost.next) {
+            if timed.step > step {
+                break;
+            }
+            match timed.input {
+                ReplayInput::Jump => {
+                    if anim.start_jump(&mut jumps_left) {
+                        let jump = level.jump_vector_for(ghost.gravity_mult);
+                        vel.linvel = world.scroll_axis.to_world(jump);
+                    }
+                }
+                ReplayInput::Flip => ghost.flip(timed.step, config.mode, &physics),
+            }
+            ghost.next += 1;
+        }
+    }
+}
+
+/// Give ghosts back the extra jumps of their chain as they pass gates, as
+/// the player gets them. Ghosts don't touch scoring regions, so a gate
+/// counts as passed once its tunnel has scrolled past the ghost.
+fn refill_ghost_jumps_on_gates(
+    mut ghosts: Query<(&mut Ghost, &Transform, &mut JumpsLeft)>,
+    tunnels: Query<(&Transform, &SpawnParams, &SpawnIndex)>,
+    level: Res<LevelSettings>,
+    world: Res<WorldSettings>,
+) {
+    let axis = world.scroll_axis;
+    for (mut ghost, t, mut jumps_left) in ghosts.iter_mut() {
+        let ghost_x = axis.to_level(t.translation.truncate()).x;
+        let passed = tunnels
+            .iter()
+            .filter(|(tunnel_t, params, index)| {
+                matches!(params, SpawnParams::Tunnel(_))
+                    && index.0 >= ghost.next_gate
+                    && axis.to_level(tunnel_t.translation.truncate()).x <= ghost_x
+            })
+            .map(|(_, _, index)| index.0)
+            .max();
+        if let Some(index) = passed {
+            ghost.next_gate = index + 1;
+            refill_jumps(&mut jumps_left, &level);
+        }
+    }
+}
+
+/// Flip ghosts as they pass gravity regions, as the player is flipped, or
+/// grant them a flip charge in flip charge mode. Ghosts don't touch the
+/// regions, so a ghost is passing a region while its x lies within it.
+fn pass_ghosts_through_regions(
+    mut ghosts: Query<(&mut Ghost, &Transform)>,
+    regions: Query<(Entity, &Transform, &SpawnParams)>,
+    config: Res<RunConfig>,
+    world: Res<WorldSettings>,
+) {
+    let axis = world.scroll_axis;
+    for (mut ghost, t) in ghosts.iter_mut() {
+        let ghost_x = axis.to_level(t.translation.truncate()).x;
+        let inside = regions.iter().find_map(|(ent, region_t, params)| {
+            let SpawnParams::Gravity {
+                gravity_mult,
+                width,
+                ..
+            } = params
+            else {
+                return None;
+            };
+            let x = axis.to_level(region_t.translation.truncate()).x;
+            ((x - ghost_x).abs() <= width / 2.0).then_some((ent, *gravity_mult))
+        });
+        let entered = inside.filter(|(ent, _)| ghost.region != Some(*ent));
+        ghost.region = inside.map(|(ent, _)| ent);
+        if let Some((_, gravity_mult)) = entered {
+            if config.mode == GameMode::FlipCharge {
+                ghost.flip_charges = (ghost.flip_charges + 1).min(MAX_FLIP_CHARGES);
+            } else {
+                ghost.gravity_mult = gravity_mult;
+            }
+        }
+    }
+}
+
+fn update_ghost_gravity(
+    mut ghosts: Query<(&Ghost, &Velocity, &mut GravityScale)>,
+    level: Res<LevelSettings>,
+    world: Res<WorldSettings>,
+) {
+    for (ghost, vel, mut gs) in ghosts.iter_mut() {
+        gs.0 = gravity_scale(vel.linvel, &level, ghost.gravity_mult, world.scroll_axis);
+    }
+}
+
+/// Turn ghosts to face away from their own gravity.
+#[allow(clippy::type_complexity)]
+fn turn_ghosts(
+    mut commands: Commands,
+    mut ghosts: Query<(
+        Entity,
+        &Ghost,
+        &Transform,
+        &mut PlayerAnim,
+        Option<&Animator<Transform>>,
+    )>,
+    world: Res<WorldSettings>,
+) {
+    for (ent, ghost, t, mut anim, animator) in ghosts.iter_mut() {
+        rotate_rocket(
+            &mut commands,
+            ent,
+            t,
+            &mut anim,
+            animator,
+            ghost.gravity_mult,
+            world.scroll_axis,
+        );
+    }
+}
+
+/// Remove ghosts once they leave the world.
+fn remove_lost_ghosts(
+    mut commands: Commands,
+    ghosts: Query<(Entity, &Transform), With<Ghost>>,
+    play_world: Res<WorldSettings>,
+) {
+    for (ent, t) in ghosts.iter() {
+        if !play_world.bounds.contains(t.translation.truncate()) {
+            commands.entity(ent).despawn_recursive();
+        }
+    }
+}
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GhostReplay>()
+            .register_type::<Ghost>()
+            .add_systems(Update, load_dropped_ghosts)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                spawn_ghost.run_if(resource_exists::<GhostReplay>()),
+            )
+            .add_systems(
+                Update,
+                (
+                    replay_ghost_inputs
+                        .after(tick_run_clock)
+                        .after(refill_ghost_jumps_on_gates)
+                        .after(pass_ghosts_through_regions)
+                        .before(PlayerSet)
+                        .run_if(resource_exists::<GhostReplay>()),
+                    refill_ghost_jumps_on_gates,
+                    pass_ghosts_through_regions,
+                    tint_ghosts,
+                    (update_ghost_gravity, turn_ghosts).after(replay_ghost_inputs),
+                    remove_lost_ghosts,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
@@ -0,0 +1,400 @@
+//! Graphics settings: the display mode, resolution, vsync and frame rate cap
+//! of the game window, changed at runtime from a page opened with `F2`, and
+//! kept in the settings file. The page also picks the theme pack, and lists
+//! the mods found at launch so they can be turned on or off.
+//!
+//! The resolution is the size the game is rendered at. The window keeps the
+//! same logical size, so the play area is the same at every resolution.
+use bevy::{
+    ecs::schedule::common_conditions::not,
+    input::common_conditions::input_just_pressed,
+    prelude::*,
+    sprite::Anchor,
+    window::{PresentMode, PrimaryWindow, WindowMode, WindowResolution},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fonts::FontsCollection,
+    mods::Mods,
+    settings::Settings,
+    theme_pack::{ThemePack, ThemePacks},
+    ui_theme::{ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    ScrollAxis, WorldSettings, ON_ASSETS_LOADED,
+};
+
+/// Logical size of the window, for the horizontal layout. The vertical
+/// layout swaps the sides.
+pub const BASE_RESOLUTION: Vec2 = Vec2::new(1024.0, 576.0);
+
+/// Resolutions that can be picked, for the horizontal layout.
+const RESOLUTIONS: [[u32; 2]; 5] = [
+    [1024, 576],
+    [1280, 720],
+    [1600, 900],
+    [1920, 1080],
+    [2560, 1440],
+];
+
+/// Frame rate caps that can be picked, after no cap.
+const FPS_CAPS: [u32; 4] = [30, 60, 120, 144];
+
+const PAGE_TEXT: ThemedText = ThemedText {
+    size: 28.0,
+    color: Color::ANTIQUE_WHITE,
+};
+
+#[derive(Reflect, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+
+    /// A borderless window covering the monitor.
+    Borderless,
+
+    /// Exclusive fullscreen, at the video mode closest to the resolution.
+    Fullscreen,
+}
+
+impl DisplayMode {
+    const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::Borderless,
+        DisplayMode::Fullscreen,
+    ];
+
+    fn window_mode(self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::SizedFullscreen,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "WINDOWED",
+            DisplayMode::Borderless => "BORDERLESS",
+            DisplayMode::Fullscreen => "FULLSCREEN",
+        }
+    }
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct GraphicsSettings {
+    pub display_mode: DisplayMode,
+
+    /// Width and height to render at, for the horizontal layout. When not
+    /// set, the window is sized by the system's scale factor.
+    pub resolution: Option<[u32; 2]>,
+
+    pub vsync: bool,
+
+    /// Most frames to draw per second. When not set, the frame rate is only
+    /// limited by vsync.
+    pub fps_cap: Option<u32>,
+}
+
+impl Default for GraphicsSettings {
+    fn default() -> Self {
+        Self {
+            display_mode: DisplayMode::Windowed,
+            resolution: None,
+            vsync: true,
+            fps_cap: None,
+        }
+    }
+}
+
+impl GraphicsSettings {
+    pub fn window_mode(&self) -> WindowMode {
+        self.display_mode.window_mode()
+    }
+
+    pub fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::AutoVsync
+        } else {
+            PresentMode::AutoNoVsync
+        }
+    }
+
+    /// Resolution of the game window for a layout.
+    pub fn window_resolution(&self, axis: ScrollAxis) -> WindowResolution {
+        let size = axis.to_world(BASE_RESOLUTION).abs();
+        let mut resolution = WindowResolution::new(size.x, size.y);
+        // every resolution has the base aspect ratio, so the height alone
+        // sets the scale.
+        if let Some([_, height]) = self.resolution {
+            resolution.set_scale_factor_override(Some(height as f64 / BASE_RESOLUTION.y as f64));
+        }
+        resolution
+    }
+
+    fn resolution_label(&self) -> String {
+        match self.resolution {
+            Some([width, height]) => format!("{}X{}", width, height),
+            None => "AUTO".to_string(),
+        }
+    }
+
+    /// Move the display mode along the list of modes.
+    fn step_display_mode(&mut self, step: isize) {
+        let idx = DisplayMode::ALL
+            .iter()
+            .position(|m| *m == self.display_mode)
+            .unwrap_or(0);
+        self.display_mode = DisplayMode::ALL[wrap_index(idx, step, DisplayMode::ALL.len())];
+    }
+
+    /// Move the frame rate cap along the list of caps, with no cap first.
+    fn step_fps_cap(&mut self, step: isize) {
+        let idx = self
+            .fps_cap
+            .and_then(|cap| FPS_CAPS.iter().position(|c| *c == cap))
+            .map_or(0, |i| i + 1);
+        let idx = wrap_index(idx, step, FPS_CAPS.len() + 1);
+        self.fps_cap = idx.checked_sub(1).map(|i| FPS_CAPS[i]);
+    }
+
+    /// Move the resolution along the list of resolutions, with automatic
+    /// sizing first.
+    fn step_resolution(&mut self, step: isize) {
+        // 0 is automatic, and the rest are offset by one.
+        let idx = self
+            .resolution
+            .and_then(|r| RESOLUTIONS.iter().position(|o| *o == r))
+            .map_or(0, |i| i + 1);
+        let idx = wrap_index(idx, step, RESOLUTIONS.len() + 1);
+        self.resolution = idx.checked_sub(1).map(|i| RESOLUTIONS[i]);
+    }
+}
+
+fn wrap_index(idx: usize, step: isize, len: usize) -> usize {
+    (idx as isize + step).rem_euclid(len as isize) as usize
+}
+
+/// Rows of the graphics page.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum PageRow {
+    DisplayMode,
+    Resolution,
+    Vsync,
+    FpsCap,
+
+    /// Theme pack, from the next launch.
+    Theme,
+
+    /// A mod, by its index in [`Mods`].
+    Mod(usize),
+}
+
+impl PageRow {
+    const GRAPHICS: [PageRow; 5] = [
+        PageRow::DisplayMode,
+        PageRow::Resolution,
+        PageRow::Vsync,
+        PageRow::FpsCap,
+        PageRow::Theme,
+    ];
+
+    /// Every row, with one for each mod after the graphics settings.
+    fn all(mods: &Mods) -> Vec<PageRow> {
+        let mut rows = Self::GRAPHICS.to_vec();
+        rows.extend((0..mods.mods.len()).map(PageRow::Mod));
+        rows
+    }
+}
+
+/// Whether the graphics page is open, and its selected row.
+#[derive(Resource, Default)]
+pub struct GraphicsPage {
+    open: bool,
+    selected: usize,
+}
+
+/// Run condition for input that the graphics page takes over while open.
+pub fn graphics_page_closed(page: Res<GraphicsPage>) -> bool {
+    !page.open
+}
+
+#[derive(Component)]
+struct GraphicsPageText;
+
+fn spawn_page(
+    mut commands: Commands,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    containers: Res<Containers>,
+) {
+    let (text, themed) = theme.text("", fonts.menu_font.clone(), PAGE_TEXT);
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "graphics_page",
+        (
+            Text2dBundle {
+                text: text.with_alignment(TextAlignment::Left),
+                text_anchor: Anchor::Center,
+                transform: Transform::from_xyz(0.0, 0.0, 35.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GraphicsPageText,
+            themed,
+        ),
+    );
+}
+
+fn toggle_page(mut page: ResMut<GraphicsPage>) {
+    page.open = !page.open;
+}
+
+/// Pick a row with up and down, and change it with left and right.
+fn edit_settings(
+    keys: Res<Input<KeyCode>>,
+    mut page: ResMut<GraphicsPage>,
+    mut settings: ResMut<Settings>,
+    mods: Res<Mods>,
+    themes: Res<ThemePacks>,
+) {
+    let rows = PageRow::all(&mods);
+    if keys.just_pressed(KeyCode::Up) {
+        page.selected = wrap_index(page.selected, -1, rows.len());
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        page.selected = wrap_index(page.selected, 1, rows.len());
+    }
+    let step = match (
+        keys.just_pressed(KeyCode::Left),
+        keys.just_pressed(KeyCode::Right),
+    ) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _ => return,
+    };
+    let settings = &mut *settings;
+    let graphics = &mut settings.graphics;
+    match rows[page.selected] {
+        PageRow::DisplayMode => graphics.step_display_mode(step),
+        PageRow::Resolution => graphics.step_resolution(step),
+        PageRow::Vsync => graphics.vsync = !graphics.vsync,
+        PageRow::FpsCap => graphics.step_fps_cap(step),
+        PageRow::Theme => {
+            settings.theme = Some(themes.step(settings.theme_name(), step).to_string());
+        }
+        PageRow::Mod(i) => settings.mods.toggle(&mods.mods[i].name),
+    }
+}
+
+fn update_page(
+    page: Res<GraphicsPage>,
+    settings: Res<Settings>,
+    mods: Res<Mods>,
+    active_theme: Res<ThemePack>,
+    mut text: Query<(&mut Text, &mut Visibility), With<GraphicsPageText>>,
+) {
+    for (mut text, mut vis) in text.iter_mut() {
+        if !page.open {
+            *vis = Visibility::Hidden;
+            continue;
+        }
+        *vis = Visibility::Visible;
+
+        let graphics = &settings.graphics;
+        let rows = PageRow::all(&mods).into_iter().map(|row| match row {
+            PageRow::DisplayMode => format!("DISPLAY: {}", graphics.display_mode.label()),
+            PageRow::Resolution => format!("RESOLUTION: {}", graphics.resolution_label()),
+            PageRow::Vsync => format!("VSYNC: {}", if graphics.vsync { "ON" } else { "OFF" }),
+            PageRow::FpsCap => match graphics.fps_cap {
+                Some(cap) => format!("FPS CAP: {}", cap),
+                None => "FPS CAP: OFF".to_string(),
+            },
+            PageRow::Theme if settings.theme_name() == active_theme.name => {
+                format!("THEME: {}", active_theme.name.to_uppercase())
+            }
+            PageRow::Theme => format!(
+                "THEME: {} (FROM NEXT LAUNCH)",
+                settings.theme_name().to_uppercase()
+            ),
+            PageRow::Mod(i) => {
+                let m = &mods.mods[i];
+                let enabled = settings.mods.is_enabled(&m.name);
+                format!(
+                    "{}: {} ({})",
+                    m.name.to_uppercase(),
+                    if enabled { "ON" } else { "OFF" },
+                    m.status_label()
+                )
+            }
+        });
+        let mut value = "GRAPHICS\n\n".to_string();
+        for (i, row) in rows.enumerate() {
+            if i == PageRow::GRAPHICS.len() {
+                value.push_str("\nMODS (FROM NEXT LAUNCH)\n");
+            }
+            let cursor = if i == page.selected { "> " } else { "  " };
+            value.push_str(&format!("{}{}\n", cursor, row));
+        }
+        value.push_str("\nARROWS TO CHANGE\nF2 TO CLOSE");
+        text.sections[0].value = value;
+    }
+}
+
+/// Apply the graphics settings to the primary window.
+fn apply_graphics_settings(
+    settings: Res<Settings>,
+    play_world: Res<WorldSettings>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = window.get_single_mut() else {
+        return;
+    };
+    let graphics = &settings.graphics;
+    let resolution = graphics.window_resolution(play_world.scroll_axis);
+    let mode = graphics.window_mode();
+    let present_mode = graphics.present_mode();
+
+    // only touch what changed, since any change to the window is sent on to
+    // the windowing backend.
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+    if window.resolution.scale_factor_override() != resolution.scale_factor_override() {
+        window
+            .resolution
+            .set_scale_factor_override(resolution.scale_factor_override());
+    }
+    if window.resolution.width() != resolution.width()
+        || window.resolution.height() != resolution.height()
+    {
+        window
+            .resolution
+            .set(resolution.width(), resolution.height());
+    }
+}
+
+pub struct GraphicsPlugin;
+
+impl Plugin for GraphicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GraphicsSettings>()
+            .init_resource::<GraphicsPage>()
+            .add_systems(ON_ASSETS_LOADED, spawn_page)
+            .add_systems(
+                Update,
+                (
+                    toggle_page.run_if(input_just_pressed(KeyCode::F2)),
+                    edit_settings.run_if(not(graphics_page_closed)),
+                    update_page,
+                    apply_graphics_settings.run_if(resource_changed::<Settings>()),
+                )
+                    .chain(),
+            );
+    }
+}
@@ -0,0 +1,105 @@
+//! Graze scoring: points earned continuously for flying close to barriers
+//! without touching them.
+//!
+//! Points come in fractions each frame, and are added to the score through
+//! the score as [`ScoreSource::Graze`] points.
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    barrier::Barrier,
+    obstacle_spawner::SpeedController,
+    player::Player,
+    player_collision_groups,
+    power_ups::SizeChange,
+    score::{Score, ScoreSource},
+    GameState,
+};
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct GrazeSettings {
+    /// Distance from a barrier within which the player grazes it, in pixels.
+    pub distance: f32,
+
+    /// Points per second while grazing, before the level speed and any size
+    /// change scale them.
+    pub points_per_sec: f32,
+}
+
+impl Default for GrazeSettings {
+    fn default() -> Self {
+        Self {
+            distance: 24.0,
+            points_per_sec: 1.0,
+        }
+    }
+}
+
+/// Award graze points while the player is near a barrier, but not touching
+/// one.
+fn award_graze_points(
+    rapier: Res<RapierContext>,
+    player: Query<(Entity, &Transform, &Collider, Option<&SizeChange>), With<Player>>,
+    barriers: Query<(), With<Barrier>>,
+    settings: Res<GrazeSettings>,
+    speed: Res<SpeedController>,
+    time: Res<Time>,
+    mut score: ResMut<Score>,
+) {
+    let Ok((ent, t, collider, size_change)) = player.get_single() else {
+        return;
+    };
+    let Some(half) = collider.as_cuboid().map(|c| c.half_extents()) else {
+        return;
+    };
+
+    // the player's collider, grown by the graze distance.
+    let reach = half + Vec2::splat(settings.distance);
+    let shape = Collider::cuboid(reach.x, reach.y);
+    let is_barrier = |e: Entity| barriers.contains(e);
+    let filter = QueryFilter::new()
+        .groups(player_collision_groups())
+        .exclude_collider(ent)
+        .predicate(&is_barrier);
+
+    let mut grazing = false;
+    let mut touching = false;
+    rapier.intersections_with_shape(
+        t.translation.truncate(),
+        t.rotation.to_euler(EulerRot::XYZ).2,
+        &shape,
+        filter,
+        |barrier| {
+            grazing = true;
+            // the player is a sensor in most modes, and solid in easy mode.
+            touching |= rapier.intersection_pair(ent, barrier) == Some(true)
+                || rapier
+                    .contact_pair(ent, barrier)
+                    .is_some_and(|pair| pair.has_any_active_contacts());
+            !touching
+        },
+    );
+    if !grazing || touching {
+        return;
+    }
+
+    let scale = speed.relative() * size_change.map_or(1.0, |s| s.score_multiplier());
+    score.add(
+        ScoreSource::Graze,
+        (settings.points_per_sec * scale * time.delta_seconds()) as f64,
+    );
+}
+
+pub struct GrazePlugin;
+
+impl Plugin for GrazePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GrazeSettings>()
+            .init_resource::<GrazeSettings>()
+            .add_systems(
+                Update,
+                award_graze_points.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
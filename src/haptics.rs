@@ -0,0 +1,92 @@
+//! Gamepad rumble for key moments in a run.
+use std::time::Duration;
+
+use bevy::{
+    input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest},
+    prelude::*,
+};
+
+use crate::{
+    controls::{InputDevice, LastInputDevice},
+    dying_player::PlayerDiedEvent,
+    gravity_shift::GravityEvent,
+};
+
+/// Strength and length of a single rumble.
+struct Rumble {
+    strong: f32,
+    weak: f32,
+    secs: f32,
+}
+
+/// Long, heavy rumble when the player explodes.
+const DEATH_RUMBLE: Rumble = Rumble {
+    strong: 1.0,
+    weak: 0.6,
+    secs: 0.6,
+};
+
+/// Short, light buzz when a gravity region flips gravity.
+const GRAVITY_FLIP_RUMBLE: Rumble = Rumble {
+    strong: 0.0,
+    weak: 0.5,
+    secs: 0.15,
+};
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct HapticsSettings {
+    /// Rumble gamepads at all.
+    pub enabled: bool,
+}
+
+impl Default for HapticsSettings {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Rumble every connected gamepad, while playing with a gamepad.
+fn rumble_on_events(
+    settings: Res<HapticsSettings>,
+    device: Res<LastInputDevice>,
+    gamepads: Res<Gamepads>,
+    mut deaths: EventReader<PlayerDiedEvent>,
+    mut gravity_events: EventReader<GravityEvent>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+) {
+    let mut rumbles = Vec::new();
+    if deaths.read().count() > 0 {
+        rumbles.push(&DEATH_RUMBLE);
+    }
+    // events without a region restore gravity, rather than flip it.
+    if gravity_events.read().any(|ev| ev.region.is_some()) {
+        rumbles.push(&GRAVITY_FLIP_RUMBLE);
+    }
+
+    if !settings.enabled || device.0 != InputDevice::Gamepad {
+        return;
+    }
+    for rumble in rumbles {
+        for gamepad in gamepads.iter() {
+            requests.send(GamepadRumbleRequest::Add {
+                duration: Duration::from_secs_f32(rumble.secs),
+                intensity: GamepadRumbleIntensity {
+                    strong_motor: rumble.strong,
+                    weak_motor: rumble.weak,
+                },
+                gamepad,
+            });
+        }
+    }
+}
+
+pub struct HapticsPlugin;
+
+impl Plugin for HapticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<HapticsSettings>()
+            .init_resource::<HapticsSettings>()
+            .add_systems(Update, rumble_on_events);
+    }
+}
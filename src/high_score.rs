@@ -0,0 +1,173 @@
+//! Persistent table of the best scores, along with the parameters of the run
+//! that produced each one.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    level::LevelSettings,
+    mutators::{ActiveMutators, Mutator},
+    obstacle_spawner::ObstacleSpawner,
+    persistence::{PersistencePlugin, Persistent},
+    run::{GameMode, RunConfig, RunRng},
+    score::{award_style_bonus, Score},
+    training::training_active,
+    GameState,
+};
+
+/// Number of records to keep.
+const MAX_RECORDS: usize = 10;
+
+/// A single high score, with everything needed to compare or replay the run.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct ScoreRecord {
+    pub score: i32,
+
+    /// Seed the run was played with. Not known for migrated records.
+    pub seed: Option<u64>,
+
+    /// Names of the levels reached, in order.
+    pub levels: Vec<String>,
+
+    pub mode: GameMode,
+
+    /// Mutators the run was played with. Runs are only ranked against runs
+    /// with the same mutators.
+    #[serde(default)]
+    pub mutators: Vec<Mutator>,
+
+    /// Jumps that could be chained. Runs are only ranked against runs with
+    /// the same number, and records made before multi-jump had one.
+    #[serde(default = "default_max_jumps")]
+    pub max_jumps: u32,
+}
+
+fn default_max_jumps() -> u32 {
+    1
+}
+
+impl ScoreRecord {
+    /// Whether the two runs are ranked against each other.
+    fn same_table(&self, other: &ScoreRecord) -> bool {
+        self.mutators == other.mutators && self.max_jumps == other.max_jumps
+    }
+}
+
+/// Format of the high score table before it was saved through
+/// [`crate::persistence`].
+#[derive(Deserialize)]
+enum LegacyHighScoreFile {
+    /// Bare scores, before run parameters were recorded.
+    V1(Vec<i32>),
+    V2(Vec<ScoreRecord>),
+}
+
+impl LegacyHighScoreFile {
+    /// Migrate to the latest record format.
+    fn into_records(self) -> Vec<ScoreRecord> {
+        match self {
+            LegacyHighScoreFile::V1(scores) => scores
+                .into_iter()
+                .map(|score| ScoreRecord {
+                    score,
+                    seed: None,
+                    levels: Vec::new(),
+                    mode: GameMode::default(),
+                    mutators: Vec::new(),
+                    max_jumps: default_max_jumps(),
+                })
+                .collect(),
+            LegacyHighScoreFile::V2(records) => records,
+        }
+    }
+}
+
+/// Best scores, highest first.
+#[derive(Resource, Reflect, Default, Serialize, Deserialize)]
+#[reflect(Resource)]
+pub struct HighScores {
+    #[serde(default)]
+    pub records: Vec<ScoreRecord>,
+}
+
+impl Persistent for HighScores {
+    const PATH: &'static str = "high_scores.ron";
+    const VERSION: u32 = 1;
+
+    fn load_unversioned(contents: &str) -> Option<Self> {
+        let file = ron::from_str::<LegacyHighScoreFile>(contents).ok()?;
+        Some(HighScores {
+            records: file.into_records(),
+        })
+    }
+}
+
+impl HighScores {
+    /// Insert a record, keeping the table sorted and bounded. Each set of
+    /// mutators and number of jumps has a table of its own.
+    ///
+    /// Returns true if the record made it into the table.
+    fn insert(&mut self, record: ScoreRecord) -> bool {
+        let idx = self.records.partition_point(|r| r.score >= record.score);
+        let rank = self.records[..idx]
+            .iter()
+            .filter(|r| r.same_table(&record))
+            .count();
+        if rank >= MAX_RECORDS {
+            return false;
+        }
+        let table = record.clone();
+        self.records.insert(idx, record);
+        // drop the lowest of the table, if it overflowed.
+        let mut kept = 0;
+        self.records.retain(|r| {
+            if !r.same_table(&table) {
+                return true;
+            }
+            kept += 1;
+            kept <= MAX_RECORDS
+        });
+        true
+    }
+}
+
+/// Record the score of the run that just ended.
+fn record_high_score(
+    score: Res<Score>,
+    config: Res<RunConfig>,
+    run_rng: Res<RunRng>,
+    spawners: Query<&ObstacleSpawner>,
+    mutators: Res<ActiveMutators>,
+    level: Res<LevelSettings>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    let record = ScoreRecord {
+        score: score.points(),
+        seed: Some(run_rng.seed()),
+        levels: spawners
+            .get_single()
+            .map(|s| s.level_history().to_vec())
+            .unwrap_or_default(),
+        mode: config.mode,
+        mutators: mutators.list().to_vec(),
+        max_jumps: level.max_jumps,
+    };
+    if high_scores.insert(record) {
+        bevy::log::info!("new high score: {}", score.points());
+    }
+}
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<HighScores>()
+            .register_type::<ScoreRecord>()
+            .add_plugins(PersistencePlugin::<HighScores>::default())
+            .add_systems(
+                OnEnter(GameState::Dying),
+                record_high_score
+                    .after(award_style_bonus)
+                    .run_if(not(training_active)),
+            );
+    }
+}
@@ -0,0 +1,122 @@
+//! Impact effects for fatal barrier hits: a brief white flash over the
+//! screen, and a short hitstop that freezes gameplay.
+//!
+//! The explosion is spawned on the frame of the hit as usual; the hitstop
+//! only pauses [`Time<Virtual>`], so the pieces hang in place for a moment
+//! before flying apart.
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::MotionSettings,
+    dying_player::{ExplodeSet, FatalHitEvent},
+    util::{spawn_named_child, Containers},
+    WorldSettings,
+};
+
+/// How long gameplay freezes for, in real time.
+const HITSTOP_SECS: f32 = 0.08;
+
+/// Number of frames the flash is shown for.
+const FLASH_FRAMES: u32 = 3;
+
+const FLASH_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.8);
+
+/// Freeze of gameplay after a fatal hit.
+#[derive(Resource, Default)]
+pub struct Hitstop {
+    timer: Option<Timer>,
+}
+
+impl Hitstop {
+    /// Whether gameplay is currently frozen by a hitstop, rather than by
+    /// the player pausing.
+    pub fn is_active(&self) -> bool {
+        self.timer.is_some()
+    }
+}
+
+#[derive(Component)]
+struct ImpactFlash {
+    frames_left: u32,
+}
+
+/// Flash the screen and freeze gameplay, once the explosion has spawned.
+fn start_impact(
+    mut commands: Commands,
+    mut hits: EventReader<FatalHitEvent>,
+    mut hitstop: ResMut<Hitstop>,
+    mut time: ResMut<Time<Virtual>>,
+    motion: Res<MotionSettings>,
+    play_world: Res<WorldSettings>,
+    containers: Res<Containers>,
+) {
+    if hits.read().count() == 0 {
+        return;
+    }
+    if !time.is_paused() {
+        time.pause();
+        hitstop.timer = Some(Timer::from_seconds(HITSTOP_SECS, TimerMode::Once));
+    }
+    if motion.animate() {
+        spawn_named_child(
+            &mut commands,
+            containers.effects,
+            "impact_flash",
+            (
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: FLASH_COLOR,
+                        custom_size: Some(play_world.bounds.size()),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(
+                        play_world.bounds.center().extend(100.0),
+                    ),
+                    ..default()
+                },
+                ImpactFlash {
+                    frames_left: FLASH_FRAMES,
+                },
+            ),
+        );
+    }
+}
+
+/// Remove the flash once it has been shown for its frames.
+fn update_impact_flash(mut commands: Commands, mut flashes: Query<(Entity, &mut ImpactFlash)>) {
+    for (ent, mut flash) in flashes.iter_mut() {
+        flash.frames_left = flash.frames_left.saturating_sub(1);
+        if flash.frames_left == 0 {
+            commands.entity(ent).despawn_recursive();
+        }
+    }
+}
+
+/// Resume gameplay once the hitstop is over.
+fn end_hitstop(
+    mut hitstop: ResMut<Hitstop>,
+    real: Res<Time<Real>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    let Some(timer) = hitstop.timer.as_mut() else {
+        return;
+    };
+    if timer.tick(real.delta()).finished() {
+        hitstop.timer = None;
+        time.unpause();
+    }
+}
+
+pub struct HitstopPlugin;
+
+impl Plugin for HitstopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Hitstop>().add_systems(
+            Update,
+            (
+                (end_hitstop, update_impact_flash).before(start_impact),
+                start_impact.after(ExplodeSet),
+            ),
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Local TCP listener letting external tools (e.g. a chat bot) interact with
+//! the game.
+//!
+//! Each connection sends one JSON command per line, such as
+//! `{"command": "spawn_gravity", "direction": "up"}` or
+//! `{"command": "speed", "percent": 10}`. Commands are rate limited, and
+//! translated into the game's own events. Only a few connections are served
+//! at once.
+use std::{
+    io::{BufRead, BufReader},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+};
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    obstacle_spawner::{SpawnRequestEvent, SpeedScaleEvent},
+    GameState,
+};
+
+/// Most connections served at once. Further connections are closed
+/// straight away.
+const MAX_CONNECTIONS: usize = 4;
+
+/// Range of the speed scale a single speed command can apply.
+const SPEED_SCALE_RANGE: (f32, f32) = (0.5, 2.0);
+
+/// Direction to switch gravity to.
+#[derive(Deserialize, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum GravityDirection {
+    Up,
+    Down,
+}
+
+/// Commands accepted from external integrations.
+#[derive(Deserialize, Clone, Debug)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IntegrationCommand {
+    /// Spawn a gravity region as the next item.
+    SpawnGravity { direction: GravityDirection },
+
+    /// Change the level speed by a percentage, from -50% to +100%.
+    Speed { percent: f32 },
+}
+
+/// Commands received by the listener, waiting to be applied.
+#[derive(Resource)]
+struct IntegrationCommands(Mutex<Receiver<IntegrationCommand>>);
+
+/// Token bucket limiting how often external commands are applied.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct IntegrationRateLimit {
+    pub capacity: f32,
+    pub per_second: f32,
+    tokens: f32,
+}
+
+impl IntegrationRateLimit {
+    fn refill(&mut self, secs: f32) {
+        self.tokens = (self.tokens + secs * self.per_second).min(self.capacity);
+    }
+
+    fn try_take(&mut self) -> bool {
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Read commands from a single connection until it closes.
+fn handle_connection(stream: TcpStream, sender: Sender<IntegrationCommand>) {
+    let peer = stream.peer_addr().ok();
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        match serde_json::from_str(&line) {
+            Ok(command) => {
+                if sender.send(command).is_err() {
+                    break;
+                }
+            }
+            Err(e) => bevy::log::warn!("bad integration command from {:?}: {}", peer, e),
+        }
+    }
+}
+
+/// Accept connections on the local port, forwarding their commands to the game.
+fn listen(port: u16, sender: Sender<IntegrationCommand>) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            bevy::log::error!("could not listen for integrations on port {}: {}", port, e);
+            return;
+        }
+    };
+    bevy::log::info!("listening for integrations on port {}", port);
+    let open = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming().flatten() {
+        if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            open.fetch_sub(1, Ordering::SeqCst);
+            bevy::log::warn!(
+                "refusing integration connection from {:?}, as {} are already open",
+                stream.peer_addr().ok(),
+                MAX_CONNECTIONS
+            );
+            continue;
+        }
+        let sender = sender.clone();
+        let open = open.clone();
+        std::thread::spawn(move || {
+            handle_connection(stream, sender);
+            open.fetch_sub(1, Ordering::SeqCst);
+        });
+    }
+}
+
+/// Apply received commands, within the rate limit.
+fn apply_integration_commands(
+    time: Res<Time<Real>>,
+    commands: Res<IntegrationCommands>,
+    mut rate_limit: ResMut<IntegrationRateLimit>,
+    mut spawn_requests: EventWriter<SpawnRequestEvent>,
+    mut speed_changes: EventWriter<SpeedScaleEvent>,
+) {
+    rate_limit.refill(time.delta_seconds());
+    let Ok(receiver) = commands.0.lock() else {
+        return;
+    };
+    for command in receiver.try_iter() {
+        if !rate_limit.try_take() {
+            bevy::log::debug!("dropping rate limited command {:?}", command);
+            continue;
+        }
+        match command {
+            IntegrationCommand::SpawnGravity { direction } => {
+                spawn_requests.send(SpawnRequestEvent::Gravity {
+                    down: matches!(direction, GravityDirection::Down),
+                })
+            }
+            IntegrationCommand::Speed { percent } => {
+                let (min, max) = SPEED_SCALE_RANGE;
+                speed_changes.send(SpeedScaleEvent((1.0 + percent / 100.0).clamp(min, max)))
+            }
+        }
+    }
+}
+
+/// Drop anything sent while not playing, so it doesn't pile up.
+fn drain_integration_commands(commands: Res<IntegrationCommands>) {
+    if let Ok(receiver) = commands.0.lock() {
+        for _ in receiver.try_iter() {}
+    }
+}
+
+pub struct IntegrationPlugin {
+    pub port: u16,
+}
+
+impl Default for IntegrationPlugin {
+    fn default() -> Self {
+        Self { port: 7878 }
+    }
+}
+
+impl Plugin for IntegrationPlugin {
+    fn build(&self, app: &mut App) {
+        let (sender, receiver) = mpsc::channel();
+        let port = self.port;
+        std::thread::spawn(move || listen(port, sender));
+
+        app.insert_resource(IntegrationCommands(Mutex::new(receiver)))
+            .insert_resource(IntegrationRateLimit {
+                capacity: 5.0,
+                per_second: 0.5,
+                tokens: 5.0,
+            })
+            .register_type::<IntegrationRateLimit>()
+            .add_systems(
+                Update,
+                (
+                    apply_integration_commands.run_if(in_state(GameState::Playing)),
+                    drain_integration_commands.run_if(not(in_state(GameState::Playing))),
+                ),
+            );
+    }
+}
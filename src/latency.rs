@@ -0,0 +1,211 @@
+//! Dev overlay measuring the input path: how long a jump press takes to
+//! reach the player's velocity and then the physics step, along with frame
+//! time percentiles and the gameplay diagnostics.
+use std::collections::VecDeque;
+
+use bevy::{diagnostic::DiagnosticsStore, prelude::*, sprite::Anchor, utils::Instant};
+use bevy_rapier2d::plugin::PhysicsSet;
+
+use crate::{
+    diagnostics::GAMEPLAY_DIAGNOSTICS,
+    physics::PhysicsSteps,
+    player::{JumpedEvent, PlayerSet},
+    util::{spawn_named_child, Containers},
+    WorldSettings, ON_ASSETS_LOADED,
+};
+
+/// Number of jumps that latencies are summarised over.
+const JUMP_WINDOW: usize = 30;
+
+/// Number of frames that frame time percentiles are taken over.
+const FRAME_WINDOW: usize = 240;
+
+const OVERLAY_COLOR: Color = Color::LIME_GREEN;
+
+/// Timings of a single jump, in milliseconds after its input was read.
+#[derive(Reflect, Clone, Copy, Debug)]
+pub struct JumpLatency {
+    /// Until the jump was written to the player's velocity.
+    pub to_velocity: f32,
+
+    /// Until the physics step that moved the player with it finished.
+    pub to_physics: f32,
+}
+
+/// Recent input latencies and frame times.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct InputLatency {
+    pub jumps: VecDeque<JumpLatency>,
+
+    /// Recent frame times, in milliseconds.
+    pub frame_times: VecDeque<f32>,
+
+    /// Jump applied this frame, waiting on the physics step: when its input
+    /// was read, and its latency to the velocity write.
+    #[reflect(ignore)]
+    pending: Option<(Instant, f32)>,
+}
+
+impl InputLatency {
+    /// Frame time below which `fraction` of recent frames fall.
+    pub fn frame_time_percentile(&self, fraction: f32) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        let mut sorted: Vec<f32> = self.frame_times.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+        let idx = ((sorted.len() - 1) as f32 * fraction).round() as usize;
+        sorted[idx]
+    }
+
+    fn record_jump(&mut self, latency: JumpLatency) {
+        if self.jumps.len() == JUMP_WINDOW {
+            self.jumps.pop_front();
+        }
+        self.jumps.push_back(latency);
+    }
+
+    fn record_frame(&mut self, ms: f32) {
+        if self.frame_times.len() == FRAME_WINDOW {
+            self.frame_times.pop_front();
+        }
+        self.frame_times.push_back(ms);
+    }
+}
+
+fn millis_since(start: Instant) -> f32 {
+    start.elapsed().as_secs_f32() * 1000.0
+}
+
+/// Note when each jump reached the player's velocity, straight after it was
+/// written.
+fn time_jump_velocity(mut jumps: EventReader<JumpedEvent>, mut latency: ResMut<InputLatency>) {
+    if let Some(jump) = jumps.read().last() {
+        latency.pending = Some((jump.input_at, millis_since(jump.input_at)));
+    }
+}
+
+/// Whether rapier ran a physics step this frame.
+fn physics_stepped(steps: Res<PhysicsSteps>) -> bool {
+    steps.last_frame > 0
+}
+
+/// Complete the pending jump once physics has stepped with it.
+fn time_jump_physics(mut latency: ResMut<InputLatency>) {
+    if let Some((input_at, to_velocity)) = latency.pending.take() {
+        latency.record_jump(JumpLatency {
+            to_velocity,
+            to_physics: millis_since(input_at),
+        });
+    }
+}
+
+fn record_frame_time(time: Res<Time<Real>>, mut latency: ResMut<InputLatency>) {
+    latency.record_frame(time.delta_seconds() * 1000.0);
+}
+
+#[derive(Component)]
+struct LatencyOverlay;
+
+fn spawn_overlay(
+    mut commands: Commands,
+    play_world: Res<WorldSettings>,
+    containers: Res<Containers>,
+) {
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "latency_overlay",
+        (
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 16.0,
+                        color: OVERLAY_COLOR,
+                        ..default()
+                    },
+                ),
+                text_anchor: Anchor::BottomLeft,
+                transform: Transform::from_translation(
+                    (play_world.bounds.min + Vec2::splat(8.0)).extend(40.0),
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            LatencyOverlay,
+        ),
+    );
+}
+
+/// Show or hide the overlay.
+pub fn toggle_latency_overlay(mut overlay: Query<&mut Visibility, With<LatencyOverlay>>) {
+    for mut vis in overlay.iter_mut() {
+        *vis = match *vis {
+            Visibility::Hidden => Visibility::Visible,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+fn update_overlay(
+    latency: Res<InputLatency>,
+    diagnostics: Res<DiagnosticsStore>,
+    mut overlay: Query<(&mut Text, &Visibility), With<LatencyOverlay>>,
+) {
+    for (mut text, vis) in overlay.iter_mut() {
+        if *vis == Visibility::Hidden {
+            continue;
+        }
+        let summary = |f: fn(&JumpLatency) -> f32| {
+            let count = latency.jumps.len().max(1) as f32;
+            let avg = latency.jumps.iter().map(f).sum::<f32>() / count;
+            let max = latency.jumps.iter().map(f).fold(0.0, f32::max);
+            format!("avg {:.1} / max {:.1} ms", avg, max)
+        };
+        text.sections[0].value = format!(
+            "JUMPS ({})\ninput -> velocity: {}\ninput -> physics: {}\nframe p50 {:.1} / p95 {:.1} / p99 {:.1} ms",
+            latency.jumps.len(),
+            summary(|j| j.to_velocity),
+            summary(|j| j.to_physics),
+            latency.frame_time_percentile(0.5),
+            latency.frame_time_percentile(0.95),
+            latency.frame_time_percentile(0.99),
+        );
+        for diagnostic in GAMEPLAY_DIAGNOSTICS
+            .iter()
+            .filter_map(|id| diagnostics.get(*id))
+        {
+            if let Some(value) = diagnostic.smoothed() {
+                text.sections[0].value +=
+                    &format!("\n{}: {:.1}{}", diagnostic.name, value, diagnostic.suffix);
+            }
+        }
+    }
+}
+
+pub struct LatencyOverlayPlugin;
+
+impl Plugin for LatencyOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<InputLatency>()
+            .register_type::<JumpLatency>()
+            .init_resource::<InputLatency>()
+            .add_systems(ON_ASSETS_LOADED, spawn_overlay)
+            .add_systems(
+                Update,
+                (
+                    record_frame_time,
+                    time_jump_velocity.after(PlayerSet),
+                    update_overlay,
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                time_jump_physics
+                    .run_if(physics_stepped)
+                    .after(PhysicsSet::Writeback),
+            );
+    }
+}
@@ -0,0 +1,191 @@
+//! Table of the runs played since launch, shown after each death.
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    dying_player::{DeathCause, PlayerDiedEvent},
+    fonts::FontsCollection,
+    mutators::{mutator_tags, ActiveMutators, Mutator},
+    run::RunClock,
+    score::{award_style_bonus, Score},
+    ui_theme::{ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    GameState, ON_ASSETS_LOADED,
+};
+
+/// Number of runs shown in the table.
+const MAX_RUNS: usize = 10;
+
+/// Size of the table text, before the theme is applied.
+const ROW_TEXT: ThemedText = ThemedText {
+    size: 14.0,
+    color: Color::ANTIQUE_WHITE,
+};
+
+/// Color of the row for the run that just ended.
+const CURRENT_ROW_COLOR: Color = Color::GOLD;
+
+/// Summary of a finished run.
+#[derive(Reflect, Clone, Debug)]
+pub struct RunSummary {
+    /// Number of the run within the session, starting from 1.
+    pub number: u32,
+    pub score: i32,
+    pub duration: Duration,
+    pub cause: DeathCause,
+    pub mutators: Vec<Mutator>,
+}
+
+/// Runs finished since launch, oldest first.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct SessionHistory {
+    /// The last [`MAX_RUNS`] runs.
+    pub runs: VecDeque<RunSummary>,
+
+    /// Total number of runs finished.
+    pub total: u32,
+}
+
+impl SessionHistory {
+    fn push(&mut self, score: i32, duration: Duration, cause: DeathCause, mutators: Vec<Mutator>) {
+        self.total += 1;
+        if self.runs.len() == MAX_RUNS {
+            self.runs.pop_front();
+        }
+        self.runs.push_back(RunSummary {
+            number: self.total,
+            score,
+            duration,
+            cause,
+            mutators,
+        });
+    }
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Leaderboard;
+
+fn spawn_leaderboard(
+    mut commands: Commands,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    containers: Res<Containers>,
+) {
+    let (text, themed) = theme.text("", fonts.menu_font.clone(), ROW_TEXT);
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "leaderboard",
+        (
+            Text2dBundle {
+                text: text.with_alignment(TextAlignment::Center),
+                text_anchor: Anchor::TopCenter,
+                transform: Transform::from_xyz(0.0, -95.0, 20.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            Leaderboard,
+            themed,
+        ),
+    );
+}
+
+/// Add the run that just ended to the history.
+fn record_run(
+    score: Res<Score>,
+    clock: Res<RunClock>,
+    mut deaths: EventReader<PlayerDiedEvent>,
+    mutators: Res<ActiveMutators>,
+    mut history: ResMut<SessionHistory>,
+) {
+    let cause = deaths
+        .read()
+        .last()
+        .map_or(DeathCause::Barrier, |death| death.cause);
+    history.push(
+        score.points(),
+        clock.elapsed(),
+        cause,
+        mutators.list().to_vec(),
+    );
+}
+
+/// Runs with mutators are tagged with them, as their scores aren't
+/// comparable with the rest.
+fn format_row(run: &RunSummary) -> String {
+    let secs = run.duration.as_secs();
+    let mut row = format!(
+        "\n{}.  {}  {}:{:02}  {}",
+        run.number,
+        run.score,
+        secs / 60,
+        secs % 60,
+        run.cause.describe()
+    );
+    if !run.mutators.is_empty() {
+        row.push_str(&format!("  [{}]", mutator_tags(&run.mutators)));
+    }
+    row
+}
+
+/// Fill in and show the table, with the latest run highlighted.
+fn show_leaderboard(
+    history: Res<SessionHistory>,
+    theme: Res<UiTheme>,
+    fonts: Res<FontsCollection>,
+    mut table: Query<(&mut Text, &mut Visibility), With<Leaderboard>>,
+) {
+    let row_style = theme.text_style(fonts.menu_font.clone(), ROW_TEXT);
+    let current_style = theme.text_style(
+        fonts.menu_font.clone(),
+        ThemedText {
+            color: CURRENT_ROW_COLOR,
+            ..ROW_TEXT
+        },
+    );
+    let current = history.runs.back().map(|run| run.number);
+
+    for (mut text, mut vis) in table.iter_mut() {
+        *vis = Visibility::Visible;
+        text.sections = std::iter::once(TextSection::new(
+            "RUN  SCORE  TIME  CAUSE",
+            row_style.clone(),
+        ))
+        .chain(history.runs.iter().map(|run| {
+            if Some(run.number) == current {
+                TextSection::new(format!("{} <", format_row(run)), current_style.clone())
+            } else {
+                TextSection::new(format_row(run), row_style.clone())
+            }
+        }))
+        .collect();
+    }
+}
+
+fn hide_leaderboard(mut table: Query<&mut Visibility, With<Leaderboard>>) {
+    for mut vis in table.iter_mut() {
+        *vis = Visibility::Hidden;
+    }
+}
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SessionHistory>()
+            .register_type::<RunSummary>()
+            .register_type::<Leaderboard>()
+            .init_resource::<SessionHistory>()
+            .add_systems(ON_ASSETS_LOADED, spawn_leaderboard)
+            .add_systems(
+                OnEnter(GameState::Dying),
+                (record_run, show_leaderboard)
+                    .chain()
+                    .after(award_style_bonus),
+            )
+            .add_systems(OnExit(GameState::Dying), hide_leaderboard);
+    }
+}
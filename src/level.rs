@@ -1,11 +1,18 @@
-use std::time::Duration;
-
-use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::{send_event, GameState, LevelSet, ResetEvent, WorldSet, WorldSettings};
+use crate::{
+    controls::{jump_just_pressed, Action, InputTimestamp},
+    mutators::ActiveMutators,
+    physics::PhysicsSettings,
+    player::JumpEvent,
+    run::RunConfig,
+    send_event, GameState, LevelSet, ResetEvent, ScrollAxis, WorldSet, WorldSettings,
+    ON_ASSETS_LOADED,
+};
 
 #[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
 pub struct LevelSettings {
     /// Base velocity jump vector (set when initializing a jump). Can
     /// be modified by the gravity mult.
@@ -13,6 +20,9 @@ pub struct LevelSettings {
 
     pub explosion_speed: f32,
 
+    /// Jumps that the player can chain, from [`PhysicsSettings::max_jumps`].
+    pub max_jumps: u32,
+
     /// Base gravity acceleration vector. Typically not modified in
     /// game, but is effectively tranformed by gravity mult.
     base_gravity: Vec2,
@@ -21,6 +31,10 @@ pub struct LevelSettings {
     pub start_offset: f32,
 
     pub gravity_mult: f32,
+
+    /// Flip the gravity of every body with the gravity mult, rather than
+    /// only the rocket's, as set by the level.
+    pub flip_world_gravity: bool,
 }
 
 impl LevelSettings {
@@ -29,62 +43,145 @@ impl LevelSettings {
         self.gravity_mult = 1.0;
     }
 
-    /// Return the current jump vector, taking the gravity mult into account.
+    /// Return the current jump vector, taking the gravity mult into account,
+    /// so that jumps push against the player's own gravity.
     pub fn jump_vector(&self) -> Vec2 {
-        self.base_jump_vel * self.gravity_mult
+        self.jump_vector_for(self.gravity_mult)
+    }
+
+    /// Jump of a rocket with its own gravity mult, such as a ghost.
+    pub fn jump_vector_for(&self, gravity_mult: f32) -> Vec2 {
+        self.base_jump_vel * gravity_mult
     }
 
     /// return the current gravity vector, taking the gravity mult into account.
     pub fn gravity_vector(&self) -> Vec2 {
-        self.base_gravity * self.gravity_mult
+        self.gravity_vector_for(self.gravity_mult)
+    }
+
+    /// Gravity of a rocket with its own gravity mult, such as a ghost.
+    pub fn gravity_vector_for(&self, gravity_mult: f32) -> Vec2 {
+        self.base_gravity * gravity_mult
+    }
+
+    /// Gravity of every body in the level. This leaves out the gravity
+    /// mult, which only applies to rockets through their gravity scale,
+    /// unless the level flips world gravity.
+    pub fn world_gravity(&self) -> Vec2 {
+        if self.flip_world_gravity {
+            self.gravity_vector()
+        } else {
+            self.base_gravity
+        }
+    }
+
+    /// Part of the gravity mult that rockets apply through their gravity
+    /// scale, on top of the world gravity.
+    pub fn rocket_gravity_mult(&self) -> f32 {
+        if self.flip_world_gravity {
+            1.0
+        } else {
+            self.gravity_mult
+        }
+    }
+
+    /// [`rocket_gravity_mult`](Self::rocket_gravity_mult) of a rocket with
+    /// its own gravity mult, such as a ghost. When the world gravity is
+    /// flipped, the rocket makes up the difference from the level's.
+    pub fn rocket_gravity_mult_for(&self, gravity_mult: f32) -> f32 {
+        if !self.flip_world_gravity {
+            gravity_mult
+        } else if gravity_mult == self.gravity_mult {
+            1.0
+        } else {
+            gravity_mult / self.gravity_mult
+        }
+    }
+
+    /// Set the base gravity and jump from the physics settings, or from the
+    /// `gravity` and `jump_speed` overrides of a level, in m/s^2 and m/s,
+    /// then apply the mutators.
+    pub fn set_base_physics(
+        &mut self,
+        physics: &PhysicsSettings,
+        gravity: Option<f32>,
+        jump_speed: Option<f32>,
+        mutators: &ActiveMutators,
+    ) {
+        let jump_speed = jump_speed.unwrap_or(physics.jump_speed) * mutators.jump_scale();
+        let gravity = gravity.unwrap_or(physics.gravity) * mutators.gravity_scale();
+        self.base_jump_vel = Vec2::new(0.0, physics.to_pixels(jump_speed));
+        self.base_gravity = Vec2::new(0.0, -physics.to_pixels(gravity));
     }
 
-    /// Sync the level settings to rapier.
-    pub fn sync_to_rapier(&self, rc: &mut ResMut<RapierConfiguration>) {
-        rc.gravity = self.base_gravity * self.gravity_mult;
+    /// Sync the level's world gravity to rapier.
+    pub fn sync_to_rapier(&self, rc: &mut ResMut<RapierConfiguration>, axis: ScrollAxis) {
+        rc.gravity = axis.to_world(self.world_gravity());
     }
 }
 
 /// Market component for objects that should be removed when the reach
-/// the left of the screen.
-#[derive(Component)]
+/// the trailing edge of the screen along the scroll axis, or drift a full
+/// level height off the top or bottom.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct RemoveWhenLeft(pub f32);
 
 /// Market component for objects that should be removed when the game is reset.
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct RemoveOnReset;
 
 /// Initialize the level settings.
 fn setup_level_settings(
     world_settings: Res<WorldSettings>,
+    physics: Res<PhysicsSettings>,
+    mutators: Res<ActiveMutators>,
     mut level_settings: ResMut<LevelSettings>,
 ) {
     level_settings.reset();
 
-    level_settings.base_jump_vel = Vec2::new(0.0, 300.0);
-    level_settings.explosion_speed = 600.0;
-    level_settings.base_gravity = Vec2::new(0.0, -500.0);
-    level_settings.start_offset = world_settings.bounds.max.x + 100.0;
+    level_settings.set_base_physics(&physics, None, None, &mutators);
+    level_settings.explosion_speed = physics.to_pixels(physics.explosion_speed);
+    level_settings.max_jumps = physics.max_jumps.max(1);
+    level_settings.start_offset = start_offset(&world_settings);
+}
+
+/// Level space x position just past the leading edge of the view.
+fn start_offset(world_settings: &WorldSettings) -> f32 {
+    world_settings.level_bounds().max.x + 100.0
+}
+
+/// Move the start offset along with the world bounds, when the window is
+/// resized.
+fn follow_world_bounds(
+    world_settings: Res<WorldSettings>,
+    mut level_settings: ResMut<LevelSettings>,
+) {
+    level_settings.start_offset = start_offset(&world_settings);
 }
 
 /// Remove obstacles once they move out of the world view.
-fn remove_invisible_objects(
+pub fn remove_invisible_objects(
     mut commands: Commands,
-    query: Query<
-        (
-            Entity,
-            &GlobalTransform,
-            &RemoveWhenLeft,
-            Option<&Handle<Mesh>>,
-        ),
-        With<Collider>,
-    >,
+    query: Query<(
+        Entity,
+        &GlobalTransform,
+        &RemoveWhenLeft,
+        Option<&Handle<Mesh>>,
+    )>,
     mut meshes: ResMut<Assets<Mesh>>,
     play_world: Res<WorldSettings>,
 ) {
+    let level_bounds = play_world.level_bounds();
     for (ent, global, rwl, maybe_mesh) in query.iter() {
-        if global.translation().x < play_world.bounds.min.x - rwl.0 {
-            commands.entity(ent).despawn();
+        let level_pos = play_world
+            .scroll_axis
+            .to_level(global.translation().truncate());
+        // items are at most a level high, so are out of sight by then.
+        let drifted_off = (level_pos.y - level_bounds.center().y).abs() > level_bounds.height();
+        if level_pos.x < level_bounds.min.x - rwl.0 || drifted_off {
+            commands.entity(ent).despawn_recursive();
             if let Some(mesh_handle) = maybe_mesh {
                 meshes.remove(mesh_handle);
             }
@@ -99,28 +196,110 @@ fn reset_level(
     mut level: ResMut<LevelSettings>,
     mut rapier_config: ResMut<RapierConfiguration>,
     mut app_state: ResMut<NextState<GameState>>,
+    world: Res<WorldSettings>,
 ) {
     for ent in items.iter() {
-        commands.entity(ent).despawn();
+        commands.entity(ent).despawn_recursive();
     }
     level.reset();
-    level.sync_to_rapier(&mut rapier_config);
+    level.sync_to_rapier(&mut rapier_config, world.scroll_axis);
 
     app_state.set(GameState::Ready);
 }
 
+/// Time allowed to confirm a restart during play.
+const RESTART_CONFIRM_SECS: f32 = 1.0;
+
+/// Restart requested during play, waiting to be confirmed by repeating the
+/// restart action.
+#[derive(Resource, Default)]
+pub struct PendingRestart(Option<Timer>);
+
+impl PendingRestart {
+    pub fn is_pending(&self) -> bool {
+        self.0.is_some()
+    }
+}
+
+/// Restart on the restart action. During play the action has to be repeated
+/// to confirm, so a stray press doesn't throw away a run; anywhere else, it
+/// restarts immediately.
+fn restart_on_action(
+    mut actions: EventReader<Action>,
+    state: Res<State<GameState>>,
+    time: Res<Time<Real>>,
+    mut pending: ResMut<PendingRestart>,
+    mut resets: EventWriter<ResetEvent>,
+) {
+    if let Some(timer) = pending.0.as_mut() {
+        if timer.tick(time.delta()).finished() || *state.get() != GameState::Playing {
+            pending.0 = None;
+        }
+    }
+
+    if !actions.read().any(|a| *a == Action::Restart) {
+        return;
+    }
+    match state.get() {
+        GameState::AssetLoading | GameState::LoadingFailed => {}
+        GameState::Playing if !pending.is_pending() => {
+            pending.0 = Some(Timer::from_seconds(RESTART_CONFIRM_SECS, TimerMode::Once));
+        }
+        _ => {
+            pending.0 = None;
+            resets.send(ResetEvent);
+        }
+    }
+}
+
 fn in_ready_level(mut rapier: ResMut<RapierConfiguration>) {
     rapier.physics_pipeline_active = false;
-    bevy::log::warn!(rapier.physics_pipeline_active);
+    bevy::log::debug!(
+        active = false,
+        "physics pipeline paused for the ready prompt"
+    );
 }
 
 fn in_start_level(mut rapier: ResMut<RapierConfiguration>) {
     rapier.physics_pipeline_active = true;
-    bevy::log::warn!(rapier.physics_pipeline_active);
+    bevy::log::debug!(active = true, "physics pipeline resumed for play");
 }
 
-fn start_level(mut app_state: ResMut<NextState<GameState>>) {
+/// Set when a quick restart should skip the ready prompt.
+#[derive(Resource, Default)]
+struct QuickStart(bool);
+
+fn start_level(
+    mut app_state: ResMut<NextState<GameState>>,
+    config: Res<RunConfig>,
+    stamp: Res<InputTimestamp>,
+    mut jumps: EventWriter<JumpEvent>,
+) {
     app_state.set(GameState::Playing);
+    // with quick restart, the input that starts the run also jumps.
+    if config.quick_restart {
+        jumps.send(JumpEvent::from_input(&stamp));
+    }
+}
+
+/// Restart as soon as jump is pressed after dying, without waiting for the
+/// death animation.
+fn quick_restart(mut resets: EventWriter<ResetEvent>, mut quick_start: ResMut<QuickStart>) {
+    resets.send(ResetEvent);
+    quick_start.0 = true;
+}
+
+/// Start playing straight after a quick restart, jumping with the input
+/// that restarted.
+fn skip_ready(
+    mut quick_start: ResMut<QuickStart>,
+    mut app_state: ResMut<NextState<GameState>>,
+    mut jumps: EventWriter<JumpEvent>,
+) {
+    if std::mem::take(&mut quick_start.0) {
+        app_state.set(GameState::Playing);
+        jumps.send(JumpEvent::default());
+    }
 }
 
 /// struct for level-based plugins
@@ -128,29 +307,40 @@ pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
     fn build(&self, app: &mut App) {
-        let initial_secs_per_item = 2.0;
-
-        let mut timer = Timer::from_seconds(initial_secs_per_item, TimerMode::Repeating);
-        timer.tick(Duration::from_secs_f32(initial_secs_per_item - 0.01));
-
         app.insert_resource(LevelSettings::default())
+            .register_type::<LevelSettings>()
+            .register_type::<RemoveWhenLeft>()
+            .register_type::<RemoveOnReset>()
+            .init_resource::<PendingRestart>()
+            .init_resource::<QuickStart>()
             .add_systems(
                 Startup,
                 setup_level_settings.in_set(LevelSet).after(WorldSet),
             )
-            .add_systems(OnExit(GameState::AssetLoading), send_event::<ResetEvent>)
+            .add_systems(ON_ASSETS_LOADED, send_event::<ResetEvent>)
+            .add_systems(
+                Update,
+                follow_world_bounds.run_if(resource_changed::<WorldSettings>()),
+            )
             .add_systems(
                 Update,
                 (remove_invisible_objects,).run_if(in_state(GameState::Playing)),
             )
-            .add_systems(OnEnter(GameState::Ready), in_ready_level)
+            .add_systems(OnEnter(GameState::Ready), (in_ready_level, skip_ready))
+            .add_systems(
+                Update,
+                start_level.run_if(in_state(GameState::Ready).and_then(jump_just_pressed)),
+            )
             .add_systems(
                 Update,
-                start_level.run_if(
-                    in_state(GameState::Ready).and_then(input_just_pressed(KeyCode::Space)),
+                quick_restart.run_if(
+                    in_state(GameState::Dying)
+                        .and_then(|config: Res<RunConfig>| config.quick_restart)
+                        .and_then(jump_just_pressed),
                 ),
             )
             .add_systems(OnEnter(GameState::Playing), in_start_level)
+            .add_systems(Update, restart_on_action)
             .add_systems(PostUpdate, reset_level.run_if(on_event::<ResetEvent>()));
     }
 }
@@ -0,0 +1,242 @@
+//! Transitions between levels: when a new level takes effect, a banner
+//! slides across announcing it, the background shifts to the level's
+//! palette, and the player is briefly invulnerable, so that a speed jump
+//! doesn't kill them in the middle of a gap.
+use bevy::prelude::*;
+
+use crate::{
+    accessibility::MotionSettings,
+    background::{Background, BackgroundMaterial},
+    dying_player::Invulnerable,
+    fonts::FontsCollection,
+    level::RemoveOnReset,
+    obstacle_spawner::{reset_obstacle_spawner, LevelUpEvent, ObstacleSpawner},
+    player::Player,
+    theme_pack::ThemePack,
+    ui_theme::{ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    GameState, ResetEvent, WorldSettings,
+};
+
+/// How long the banner takes to cross the screen, and the palette to shift.
+const TRANSITION_SECS: f32 = 1.5;
+
+/// How long the player can't be hit after a level change.
+const TRANSITION_INVULNERABLE_SECS: f32 = 1.0;
+
+/// Distance past the screen edges that the banner starts and ends at.
+const BANNER_MARGIN: f32 = 300.0;
+
+/// Banner announcing a new level.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LevelBanner;
+
+/// Coordinates the banner and background of a level transition.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct LevelTransition {
+    /// Running while a transition plays.
+    timer: Option<Timer>,
+
+    palette_from: [Color; 2],
+    palette_to: [Color; 2],
+
+    /// Scroll speed of the current level, in m/s.
+    level_speed: f32,
+}
+
+impl LevelTransition {
+    /// Fraction of the transition played so far.
+    fn progress(&self) -> f32 {
+        self.timer.as_ref().map_or(1.0, |t| t.percent())
+    }
+}
+
+fn mix(a: Color, b: Color, t: f32) -> Color {
+    Color::from(Vec4::from(a).lerp(Vec4::from(b), t))
+}
+
+/// Banner text for a level change, with the speed change as a percentage.
+fn banner_text(from_speed: f32, to_speed: f32) -> String {
+    let change = if from_speed > 0.0 {
+        ((to_speed / from_speed - 1.0) * 100.0).round() as i32
+    } else {
+        0
+    };
+    if change > 0 {
+        format!("LEVEL UP\nSPEED +{}%", change)
+    } else {
+        "LEVEL UP".to_string()
+    }
+}
+
+fn current_palette(
+    mats: &Assets<BackgroundMaterial>,
+    back: &Query<&Handle<BackgroundMaterial>, With<Background>>,
+    pack: &ThemePack,
+) -> [Color; 2] {
+    back.get_single()
+        .ok()
+        .and_then(|handle| mats.get(handle))
+        .map_or(pack.palette(0), |mat| [mat.c1, mat.c2])
+}
+
+/// Start a transition as a new level takes effect.
+#[allow(clippy::too_many_arguments)]
+fn start_transition(
+    mut commands: Commands,
+    spawners: Query<&ObstacleSpawner>,
+    player: Query<Entity, (With<Player>, Without<Invulnerable>)>,
+    mut transition: ResMut<LevelTransition>,
+    banners: Query<Entity, With<LevelBanner>>,
+    mats: Res<Assets<BackgroundMaterial>>,
+    back: Query<&Handle<BackgroundMaterial>, With<Background>>,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    pack: Res<ThemePack>,
+    containers: Res<Containers>,
+) {
+    let Ok(spawner) = spawners.get_single() else {
+        return;
+    };
+    let speed = spawner.level().item_vel.length();
+    let text = banner_text(transition.level_speed, speed);
+    let level_index = spawner.level_history().len().saturating_sub(1);
+
+    transition.level_speed = speed;
+    transition.palette_from = current_palette(&mats, &back, &pack);
+    transition.palette_to = pack.palette(level_index);
+    transition.timer = Some(Timer::from_seconds(TRANSITION_SECS, TimerMode::Once));
+
+    if let Ok(player) = player.get_single() {
+        commands
+            .entity(player)
+            .insert(Invulnerable::for_secs(TRANSITION_INVULNERABLE_SECS));
+    }
+
+    // a quick second change replaces the banner of the first.
+    for ent in banners.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+    let (text, themed) = theme.text(
+        text,
+        fonts.menu_font.clone(),
+        ThemedText {
+            size: 48.0,
+            color: Color::GOLD,
+        },
+    );
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "level_banner",
+        (
+            Text2dBundle {
+                text: text.with_alignment(TextAlignment::Center),
+                // placed by update_transition before it is drawn.
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            themed,
+            LevelBanner,
+            RemoveOnReset,
+        ),
+    );
+}
+
+/// Slide the banner across and shift the background palette.
+///
+/// With reduced motion, the banner stays in the middle and fades out
+/// instead.
+#[allow(clippy::too_many_arguments)]
+fn update_transition(
+    mut commands: Commands,
+    mut transition: ResMut<LevelTransition>,
+    mut banners: Query<(Entity, &mut Transform, &mut Text, &mut Visibility), With<LevelBanner>>,
+    mut mats: ResMut<Assets<BackgroundMaterial>>,
+    back: Query<&Handle<BackgroundMaterial>, With<Background>>,
+    motion: Res<MotionSettings>,
+    play_world: Res<WorldSettings>,
+    time: Res<Time>,
+) {
+    let Some(timer) = transition.timer.as_mut() else {
+        return;
+    };
+    let finished = timer.tick(time.delta()).finished();
+    let t = transition.progress();
+
+    if let Some(mat) = back.get_single().ok().and_then(|h| mats.get_mut(h)) {
+        mat.c1 = mix(transition.palette_from[0], transition.palette_to[0], t);
+        mat.c2 = mix(transition.palette_from[1], transition.palette_to[1], t);
+    }
+
+    let bounds = play_world.bounds;
+    for (ent, mut transform, mut text, mut vis) in banners.iter_mut() {
+        if finished {
+            commands.entity(ent).despawn_recursive();
+            continue;
+        }
+        *vis = Visibility::Visible;
+        let y = bounds.center().y + bounds.height() * 0.25;
+        if motion.animate() {
+            // quick in and out, lingering in the middle to be read.
+            let eased = 0.5 + 0.5 * (2.0 * t - 1.0).powi(3);
+            let start = bounds.max.x + BANNER_MARGIN;
+            let end = bounds.min.x - BANNER_MARGIN;
+            transform.translation = Vec3::new(start + (end - start) * eased, y, 25.0);
+        } else {
+            transform.translation = Vec3::new(bounds.center().x, y, 25.0);
+            for section in text.sections.iter_mut() {
+                section.style.color.set_a(1.0 - t * t);
+            }
+        }
+    }
+
+    if finished {
+        transition.timer = None;
+    }
+}
+
+/// Go back to the base level's palette and speed for a new run.
+fn reset_transition(
+    mut transition: ResMut<LevelTransition>,
+    spawners: Query<&ObstacleSpawner>,
+    mut mats: ResMut<Assets<BackgroundMaterial>>,
+    back: Query<&Handle<BackgroundMaterial>, With<Background>>,
+    pack: Res<ThemePack>,
+) {
+    transition.timer = None;
+    if let Ok(spawner) = spawners.get_single() {
+        transition.level_speed = spawner.level().item_vel.length();
+    }
+    if let Some(mat) = back.get_single().ok().and_then(|h| mats.get_mut(h)) {
+        [mat.c1, mat.c2] = pack.palette(0);
+    }
+}
+
+pub struct LevelTransitionPlugin;
+
+impl Plugin for LevelTransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LevelTransition>()
+            .register_type::<LevelBanner>()
+            .init_resource::<LevelTransition>()
+            .add_systems(
+                Update,
+                (
+                    start_transition.run_if(on_event::<LevelUpEvent>()),
+                    update_transition,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            // after the spawner is back on the base level.
+            .add_systems(
+                PostUpdate,
+                reset_transition
+                    .after(reset_obstacle_spawner)
+                    .run_if(on_event::<ResetEvent>()),
+            );
+    }
+}
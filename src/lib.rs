@@ -1,24 +1,156 @@
-#![allow(clippy::type_complexity)]
+pub mod accessibility;
+pub mod analytics;
+pub mod animation;
+pub mod announcements;
+pub mod asset_errors;
+pub mod asset_variants;
+pub mod audio;
 pub mod background;
+pub mod bloom;
+pub mod boundary;
 pub mod center_display;
+pub mod combo;
+pub mod controls;
+pub mod crash;
+pub mod death_markers;
+#[cfg(feature = "debug_view")]
+pub mod debug_view;
+pub mod diagnostics;
 pub mod dying_player;
+#[cfg(feature = "embedded_assets")]
+pub mod embedded;
+pub mod flip_charge;
 pub mod fonts;
+pub mod game_log;
+pub mod game_snapshot;
+pub mod ghost;
+pub mod graphics;
+pub mod graze;
+pub mod haptics;
+pub mod high_score;
+pub mod hitstop;
+#[cfg(feature = "integration")]
+pub mod integration;
+pub mod latency;
+pub mod leaderboard;
 pub mod level;
+pub mod level_transition;
+pub mod loading_screen;
+pub mod logging;
+pub mod minimap;
+pub mod mods;
+pub mod mutators;
 pub mod obstacle;
 pub mod obstacle_spawner;
+pub mod pause_menu;
+pub mod persistence;
+pub mod physics;
 pub mod player;
+pub mod popups;
+pub mod post_process;
+pub mod power;
+pub mod power_ups;
+pub mod replay;
+pub mod rewind;
+pub mod run;
+pub mod scene_export;
 pub mod score;
 pub mod score_display;
 pub mod scoring_region;
+pub mod session;
+pub mod settings;
+pub mod test_support;
+pub mod theme_pack;
+pub mod training;
+pub mod ui_theme;
 pub mod util;
 use bevy::prelude::*;
+use bevy_rapier2d::geometry::{CollisionGroups, Group, SolverGroups};
+use serde::{Deserialize, Serialize};
 
 pub use obstacle::{barrier, gravity_shift};
 
+/// Axis along which the level scrolls.
+///
+/// Level code is written in "level space", where items always scroll
+/// towards -x and gravity acts along y. The scroll axis maps level
+/// space onto the world.
+#[derive(Reflect, Default, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ScrollAxis {
+    /// Obstacles scroll to the left, and the player jumps up and down.
+    #[default]
+    Horizontal,
+    /// Obstacles scroll downwards, and the player jumps left and right.
+    Vertical,
+}
+
+impl ScrollAxis {
+    /// Rotation taking level space into world space.
+    pub fn rotation(&self) -> Quat {
+        match self {
+            ScrollAxis::Horizontal => Quat::IDENTITY,
+            ScrollAxis::Vertical => Quat::from_rotation_z(std::f32::consts::FRAC_PI_2),
+        }
+    }
+
+    /// Map a level space vector into world space.
+    pub fn to_world(&self, v: Vec2) -> Vec2 {
+        match self {
+            ScrollAxis::Horizontal => v,
+            ScrollAxis::Vertical => Vec2::new(-v.y, v.x),
+        }
+    }
+
+    /// Map a world space vector into level space.
+    pub fn to_level(&self, v: Vec2) -> Vec2 {
+        match self {
+            ScrollAxis::Horizontal => v,
+            ScrollAxis::Vertical => Vec2::new(v.y, -v.x),
+        }
+    }
+}
+
 #[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
 pub struct WorldSettings {
     /// Visible / bounds of the level world.
     pub bounds: Rect,
+
+    /// Direction that obstacles scroll in.
+    pub scroll_axis: ScrollAxis,
+}
+
+impl WorldSettings {
+    /// Bounds of the world, in level space.
+    pub fn level_bounds(&self) -> Rect {
+        Rect::from_corners(
+            self.scroll_axis.to_level(self.bounds.min),
+            self.scroll_axis.to_level(self.bounds.max),
+        )
+    }
+
+    /// Transform placing an item at a level space position.
+    pub fn level_transform(&self, pos: Vec2, z: f32) -> Transform {
+        Transform::from_translation(self.scroll_axis.to_world(pos).extend(z))
+            .with_rotation(self.scroll_axis.rotation())
+    }
+
+    /// Transforms and half extents of boxes lining the bottom and top edges
+    /// of the level, just outside the bounds.
+    pub fn level_edges(&self, thickness: f32) -> [(Transform, Vec2); 2] {
+        let level_bounds = self.level_bounds();
+        let half_extents = Vec2::new(level_bounds.width(), thickness / 2.0);
+        [
+            level_bounds.min.y - half_extents.y,
+            level_bounds.max.y + half_extents.y,
+        ]
+        .map(|y| {
+            (
+                self.level_transform(Vec2::new(level_bounds.center().x, y), 0.0),
+                half_extents,
+            )
+        })
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
@@ -28,11 +160,24 @@ pub enum GameState {
     Ready,
     Playing,
     Dying,
+    /// Some assets failed to load; shows the error screen.
+    LoadingFailed,
 }
 
+/// Schedule run once all assets have loaded. Unlike
+/// `OnExit(GameState::AssetLoading)`, it doesn't run when loading fails.
+pub const ON_ASSETS_LOADED: OnTransition<GameState> = OnTransition {
+    from: GameState::AssetLoading,
+    to: GameState::Ready,
+};
+
 #[derive(Event, Default)]
 pub struct ResetEvent;
 
+/// Marker for the camera showing the game.
+#[derive(Component)]
+pub struct MainCamera;
+
 /// Generic mechanism for sending default events.
 pub fn send_event<T: Event + Default>(mut ev: EventWriter<T>) {
     ev.send(T::default());
@@ -44,9 +189,66 @@ pub struct WorldSet;
 #[derive(Clone, PartialEq, Eq, Debug, Hash, SystemSet)]
 pub struct LevelSet;
 
-#[allow(unused)]
-const OTHER_COLLISION_LAYER: u32 = 0b001;
-#[allow(unused)]
-const PLAYER_COLLISION_LAYER: u32 = 0b010;
-#[allow(unused)]
-const WORLD_COLLISION_LAYER: u32 = 0b100;
+// Collision layers, and which layers detect each other:
+//
+// | layer  | members                                    | detects       |
+// |--------|--------------------------------------------|---------------|
+// | PLAYER | player body                                | WORLD         |
+// | WORLD  | scoring regions, gravity regions           | PLAYER        |
+// |        | barriers, boundary walls, death bounds     | PLAYER, OTHER |
+// | OTHER  | death pieces                               | WORLD         |
+// |        | other effects                              | nothing       |
+//
+// Detection must be mutual, so OTHER colliders never collide with each
+// other, and death pieces only collide with barriers and death bounds, not
+// with regions. Separately, only OTHER colliders get contact forces from WORLD
+// colliders (solver groups), so the player is never pushed around by the
+// level, even where it isn't a sensor. Boundary walls are the exception: the
+// player is solid in modes with walls, and bounces off them.
+
+/// Collision layer for anything that isn't the player's body or a level item.
+pub const OTHER_COLLISION_LAYER: Group = Group::GROUP_1;
+/// Collision layer for the player's body.
+pub const PLAYER_COLLISION_LAYER: Group = Group::GROUP_2;
+/// Collision layer for level items: barriers, scoring regions and gravity
+/// regions.
+pub const WORLD_COLLISION_LAYER: Group = Group::GROUP_3;
+
+/// Collision groups for the player's body.
+pub fn player_collision_groups() -> CollisionGroups {
+    CollisionGroups::new(PLAYER_COLLISION_LAYER, WORLD_COLLISION_LAYER)
+}
+
+/// Collision groups for level items.
+pub fn world_collision_groups() -> CollisionGroups {
+    CollisionGroups::new(WORLD_COLLISION_LAYER, PLAYER_COLLISION_LAYER)
+}
+
+/// Collision groups for level items that death pieces bounce off.
+pub fn barrier_collision_groups() -> CollisionGroups {
+    CollisionGroups::new(
+        WORLD_COLLISION_LAYER,
+        PLAYER_COLLISION_LAYER | OTHER_COLLISION_LAYER,
+    )
+}
+
+/// Solver groups for level items.
+pub fn world_solver_groups() -> SolverGroups {
+    SolverGroups::new(WORLD_COLLISION_LAYER, OTHER_COLLISION_LAYER)
+}
+
+/// Solver groups for the player's body. Without them, the player would be in
+/// every solver group, including the one level items push.
+pub fn player_solver_groups() -> SolverGroups {
+    SolverGroups::new(PLAYER_COLLISION_LAYER, Group::ALL)
+}
+
+/// Collision groups for the pieces of an exploded player.
+pub fn death_piece_collision_groups() -> CollisionGroups {
+    CollisionGroups::new(OTHER_COLLISION_LAYER, WORLD_COLLISION_LAYER)
+}
+
+/// Collision groups for colliders that don't interact with anything.
+pub fn other_collision_groups() -> CollisionGroups {
+    CollisionGroups::new(OTHER_COLLISION_LAYER, Group::NONE)
+}
@@ -0,0 +1,132 @@
+//! Progress bar and spinner shown while assets load.
+//!
+//! Drawn with plain sprites, since the game fonts may not be loaded yet.
+use bevy::prelude::*;
+use iyes_progress::ProgressCounter;
+
+use crate::{
+    util::{spawn_named_child, Containers},
+    GameState,
+};
+
+/// Size of the full progress bar, in pixels.
+const BAR_SIZE: Vec2 = Vec2::new(400.0, 16.0);
+
+/// Size of the spinner, in pixels.
+const SPINNER_SIZE: f32 = 24.0;
+
+/// Spinner speed, in radians per second.
+const SPINNER_SPEED: f32 = 4.0;
+
+const BAR_BACKGROUND_COLOR: Color = Color::rgb(0.2, 0.2, 0.2);
+const BAR_COLOR: Color = Color::rgb(0.9, 0.6, 0.1);
+
+/// Marker for everything on the loading screen.
+#[derive(Component)]
+struct LoadingScreen;
+
+/// Filled part of the progress bar.
+#[derive(Component)]
+struct LoadingBar;
+
+#[derive(Component)]
+struct LoadingSpinner;
+
+fn spawn_loading_screen(mut commands: Commands, containers: Res<Containers>) {
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "loading_bar_background",
+        (
+            SpriteBundle {
+                sprite: Sprite {
+                    color: BAR_BACKGROUND_COLOR,
+                    custom_size: Some(BAR_SIZE),
+                    ..default()
+                },
+                ..default()
+            },
+            LoadingScreen,
+        ),
+    );
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "loading_bar",
+        (
+            SpriteBundle {
+                sprite: Sprite {
+                    color: BAR_COLOR,
+                    custom_size: Some(BAR_SIZE),
+                    anchor: bevy::sprite::Anchor::CenterLeft,
+                    ..default()
+                },
+                transform: Transform::from_xyz(-BAR_SIZE.x / 2.0, 0.0, 1.0)
+                    .with_scale(Vec3::new(0.0, 1.0, 1.0)),
+                ..default()
+            },
+            LoadingBar,
+            LoadingScreen,
+        ),
+    );
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "loading_spinner",
+        (
+            SpriteBundle {
+                sprite: Sprite {
+                    color: BAR_COLOR,
+                    custom_size: Some(Vec2::splat(SPINNER_SIZE)),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, BAR_SIZE.y + SPINNER_SIZE * 1.5, 1.0),
+                ..default()
+            },
+            LoadingSpinner,
+            LoadingScreen,
+        ),
+    );
+}
+
+/// Fill the bar with the fraction of loading tasks done.
+fn update_loading_bar(
+    progress: Option<Res<ProgressCounter>>,
+    mut bar: Query<&mut Transform, With<LoadingBar>>,
+) {
+    let Some(progress) = progress.map(|counter| counter.progress()) else {
+        return;
+    };
+    if progress.total == 0 {
+        return;
+    }
+    let fraction = progress.done as f32 / progress.total as f32;
+    for mut t in bar.iter_mut() {
+        t.scale.x = fraction.clamp(0.0, 1.0);
+    }
+}
+
+fn spin_spinner(mut spinner: Query<&mut Transform, With<LoadingSpinner>>, time: Res<Time<Real>>) {
+    for mut t in spinner.iter_mut() {
+        t.rotate_z(SPINNER_SPEED * time.delta_seconds());
+    }
+}
+
+fn despawn_loading_screen(mut commands: Commands, items: Query<Entity, With<LoadingScreen>>) {
+    for ent in items.iter() {
+        commands.entity(ent).despawn_recursive();
+    }
+}
+
+pub struct LoadingScreenPlugin;
+
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::AssetLoading), spawn_loading_screen)
+            .add_systems(
+                Update,
+                (update_loading_bar, spin_spinner).run_if(in_state(GameState::AssetLoading)),
+            )
+            .add_systems(OnExit(GameState::AssetLoading), despawn_loading_screen);
+    }
+}
@@ -0,0 +1,185 @@
+//! Logging, configured from the settings file: a base level, per module
+//! level filters, and optional output to rolling log files.
+//!
+//! Replaces bevy's `LogPlugin`, which only takes a single level and can't
+//! write to a file. As with `LogPlugin`, `RUST_LOG` overrides the configured
+//! levels when it is set.
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::{File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::{prelude::*, EnvFilter, Registry};
+
+/// Directory that log files are written to.
+pub const LOG_DIR: &str = "logs";
+
+/// Size that a log file grows to before it is rolled over, in bytes.
+const MAX_LOG_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Number of rolled over log files kept, besides the current one.
+const KEPT_LOG_FILES: u32 = 3;
+
+/// Filters for noisy dependencies, applied under the configured ones.
+const DEPENDENCY_FILTERS: &str = "wgpu=error,naga=warn";
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct LogSettings {
+    /// Level for everything without a filter of its own.
+    pub level: String,
+
+    /// Levels for individual modules, by module path, such as
+    /// `"rustyrocket::obstacle_spawner": "debug"`.
+    pub filters: HashMap<String, String>,
+
+    /// Also write the log to files in [`LOG_DIR`].
+    pub file: bool,
+}
+
+impl Default for LogSettings {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            filters: HashMap::new(),
+            file: false,
+        }
+    }
+}
+
+impl LogSettings {
+    /// Filter directives, in `RUST_LOG` syntax.
+    fn directives(&self) -> String {
+        let mut directives = vec![self.level.clone(), DEPENDENCY_FILTERS.to_string()];
+        directives.extend(
+            self.filters
+                .iter()
+                .map(|(module, level)| format!("{}={}", module, level)),
+        );
+        directives.join(",")
+    }
+}
+
+/// Log file that is rolled over once it gets too big, keeping a few of the
+/// previous files as `<name>.1`, `<name>.2` and so on.
+struct RollingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+}
+
+impl RollingFile {
+    fn open(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(Self {
+            path,
+            file,
+            written,
+        })
+    }
+
+    fn rolled_path(&self, n: u32) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", n));
+        path.into()
+    }
+
+    fn roll_over(&mut self) -> std::io::Result<()> {
+        for n in (1..KEPT_LOG_FILES).rev() {
+            // older files may not exist yet.
+            let _ = std::fs::rename(self.rolled_path(n), self.rolled_path(n + 1));
+        }
+        std::fs::rename(&self.path, self.rolled_path(1))?;
+        self.file = File::create(&self.path)?;
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > MAX_LOG_FILE_BYTES {
+            self.roll_over()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Sets up logging from [`LogSettings`]. Use in place of bevy's `LogPlugin`,
+/// before any other plugins so that their logs aren't lost.
+pub struct LoggingPlugin {
+    pub settings: LogSettings,
+}
+
+impl Plugin for LoggingPlugin {
+    fn build(&self, _app: &mut App) {
+        let directives = self.settings.directives();
+        let filter = EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(&directives))
+            .unwrap_or_else(|e| {
+                eprintln!("invalid log filters {:?}: {}", directives, e);
+                EnvFilter::new("info")
+            });
+
+        let file_layer = if self.settings.file {
+            match RollingFile::open(Path::new(LOG_DIR).join("rustyrocket.log")) {
+                Ok(file) => Some(
+                    tracing_subscriber::fmt::Layer::default()
+                        .with_ansi(false)
+                        .with_writer(Mutex::new(file)),
+                ),
+                Err(e) => {
+                    eprintln!("couldn't open a log file in {}: {}", LOG_DIR, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        let subscriber = Registry::default()
+            .with(filter)
+            .with(tracing_subscriber::fmt::Layer::default().with_writer(std::io::stderr))
+            .with(file_layer);
+        if subscriber.try_init().is_err() {
+            eprintln!("a logger was already set up; ignoring the log settings");
+        }
+    }
+}
+
+/// Log how many entities there are this frame, by name.
+pub fn log_entity_summary(world: &mut World) {
+    let mut by_name = BTreeMap::<String, usize>::new();
+    let mut unnamed = 0;
+    let mut names = world.query::<Option<&Name>>();
+    for name in names.iter(world) {
+        match name {
+            Some(name) => *by_name.entry(name.as_str().to_string()).or_default() += 1,
+            None => unnamed += 1,
+        }
+    }
+
+    info!(
+        total = by_name.values().sum::<usize>() + unnamed,
+        unnamed, "entity summary"
+    );
+    for (name, count) in by_name {
+        info!(name = %name, count, "entities");
+    }
+}
@@ -1,55 +1,155 @@
+use std::time::Duration;
+
 use bevy::{
+    audio::{AudioPlugin, SpatialScale},
     input::common_conditions::{input_just_pressed, input_toggle_active},
-    log::{Level, LogPlugin},
+    log::LogPlugin,
     prelude::*,
     render::texture::{ImageFilterMode, ImageSamplerDescriptor},
-    window::{close_on_esc, WindowResolution},
+    window::{close_on_esc, ExitCondition, PrimaryWindow, WindowResized},
 };
 use bevy_asset_loader::loading_state::{LoadingState, LoadingStateAppExt};
-use bevy_inspector_egui::quick::ResourceInspectorPlugin;
+use bevy_inspector_egui::quick::{
+    AssetInspectorPlugin, FilterQueryInspectorPlugin, ResourceInspectorPlugin,
+};
 use bevy_rapier2d::{prelude::*, render::RapierDebugRenderPlugin};
 use bevy_tweening::TweeningPlugin;
+use iyes_progress::ProgressPlugin;
 use rustyrocket::{
-    background::GameBackgroundPlugin,
+    accessibility::{AccessibilityPlugin, MotionSettings},
+    analytics::{export_heatmap, AnalyticsPlugin, AnalyticsSettings},
+    animation::SpriteAnimationPlugin,
+    announcements::AnnouncementsPlugin,
+    asset_errors::AssetErrorsPlugin,
+    asset_variants::AssetVariantsPlugin,
+    audio::{GameAudioPlugin, SPATIAL_SCALE},
+    background::{BackgroundMaterial, GameBackgroundPlugin},
     barrier::{BarrierPlugin, HitBarrierEvent},
+    bloom::{BloomConfig, BloomPlugin},
+    boundary::BoundaryPlugin,
     center_display::CenterDisplayPlugin,
+    combo::ComboPlugin,
+    controls::{ControlScheme, ControlSchemes, ControlsPlugin},
+    crash::{install_panic_hook, run_crash_screen, CrashReportPlugin},
+    death_markers::DeathMarkersPlugin,
+    diagnostics::GameplayDiagnosticsPlugin,
     dying_player::DyingPlayerPlugin,
+    flip_charge::FlipChargePlugin,
     fonts::GameFontsPlugin,
-    gravity_shift::GravityShiftPlugin,
+    game_log::GameLogPlugin,
+    game_snapshot::GameSnapshotPlugin,
+    ghost::GhostPlugin,
+    graphics::GraphicsPlugin,
+    gravity_shift::{GravityShiftMaterial, GravityShiftPlugin},
+    graze::GrazePlugin,
+    haptics::{HapticsPlugin, HapticsSettings},
+    high_score::HighScorePlugin,
+    hitstop::{Hitstop, HitstopPlugin},
+    latency::{toggle_latency_overlay, LatencyOverlayPlugin},
+    leaderboard::LeaderboardPlugin,
     level::{LevelPlugin, LevelSettings},
+    level_transition::LevelTransitionPlugin,
+    loading_screen::LoadingScreenPlugin,
+    logging::{log_entity_summary, LoggingPlugin},
+    minimap::MinimapPlugin,
+    mods::ModsPlugin,
+    mutators::{ActiveMutators, MutatorsPlugin},
+    obstacle::audit::{SpawnAudit, SpawnAuditPlugin},
     obstacle::spawner_settings::SpawnerSettingsPlugin,
-    obstacle_spawner::ObstacleSpawnerPlugin,
+    obstacle::{
+        behavior::BehaviorPlugin, custom::ObstacleSetPlugin, door::DoorPlugin, drift::DriftPlugin,
+        enemy::EnemyPlugin, laser::LaserPlugin, region::RegionPlugin, scenario::ScenarioPlugin,
+    },
+    obstacle_spawner::{DifficultyMetrics, ObstacleSpawner, ObstacleSpawnerPlugin},
+    pause_menu::PauseMenuPlugin,
+    persistence::load_or_default,
+    physics::{PhysicsSettings, PhysicsStepsPlugin, PhysicsTimestep},
     player::PlayerPlugin,
+    popups::ScorePopupPlugin,
+    post_process::{PostProcessConfig, PostProcessPlugin},
+    power::PowerPlugin,
+    power_ups::{grant_gravity_immunity, grant_grow, grant_shrink, PowerUpsPlugin},
+    replay::{Replay, ReplayPlayback, ReplayPlugin},
+    rewind::RewindPlugin,
+    run::{GameMode, RunConfig, RunPlugin},
+    scene_export::export_scene,
     score::{Score, ScorePlugin},
     score_display::ScoreDisplayPlugin,
     scoring_region::ScoringRegionPlugin,
-    send_event, ResetEvent, WorldSet, WorldSettings,
+    send_event,
+    session::SessionPlugin,
+    settings::{Settings, SettingsPlugin},
+    theme_pack::ThemePackPlugin,
+    training::{TrainingPlugin, TrainingSettings},
+    ui_theme::{HudLayout, UiTheme, UiThemePlugin, TEXT_SCALE_RANGE},
+    util::ContainersPlugin,
+    MainCamera, ResetEvent, ScrollAxis, WorldSet, WorldSettings,
 };
 
 use rustyrocket::GameState;
 
 fn setup_camera(mut commands: Commands) {
     let camera = Camera2dBundle::default();
-    commands.spawn(camera);
+    commands.spawn((camera, MainCamera, Name::new("main_camera")));
 }
 
 fn setup_physics(
     mut physics: ResMut<WorldSettings>,
+    physics_settings: Res<PhysicsSettings>,
     mut rapier_config: ResMut<RapierConfiguration>,
+    mut time: ResMut<Time<Virtual>>,
     window: Query<&Window>,
 ) {
     let w = window.single();
-    rapier_config.gravity = Vec2::new(0.0, -500.0);
+    let gravity = physics_settings.to_pixels(physics_settings.gravity);
+    rapier_config.gravity = physics.scroll_axis.to_world(Vec2::new(0.0, -gravity));
+    rapier_config.timestep_mode = physics_settings.timestep_mode();
+    if physics_settings.timestep == PhysicsTimestep::Variable {
+        time.set_max_delta(Duration::from_secs_f32(physics_settings.max_frame_secs()));
+    }
 
     physics.bounds.max = Vec2::new(w.width() / 2.0, w.height() / 2.0);
     physics.bounds.min = -physics.bounds.max;
 }
 
+/// Keep the world bounds matching the primary window.
+fn resize_world_bounds(
+    mut play_world: ResMut<WorldSettings>,
+    mut resized: EventReader<WindowResized>,
+    primary: Query<(), With<PrimaryWindow>>,
+) {
+    for ev in resized.read() {
+        if primary.contains(ev.window) {
+            play_world.bounds.max = Vec2::new(ev.width / 2.0, ev.height / 2.0);
+            play_world.bounds.min = -play_world.bounds.max;
+        }
+    }
+}
+
 fn enable_physics_debugging(mut debug_context: ResMut<DebugRenderContext>) {
     debug_context.enabled = !debug_context.enabled;
 }
 
-fn toggle_time(mut time: ResMut<Time<Virtual>>) {
+fn toggle_spawn_audit(mut audit: ResMut<SpawnAudit>) {
+    audit.enabled = !audit.enabled;
+}
+
+/// Log shader changes picked up by hot reloading.
+fn log_shader_reloads(mut events: EventReader<AssetEvent<Shader>>, asset_server: Res<AssetServer>) {
+    for ev in events.read() {
+        if let AssetEvent::Modified { id } = ev {
+            if let Some(path) = asset_server.get_path(*id) {
+                bevy::log::info!("reloaded shader {}", path);
+            }
+        }
+    }
+}
+
+fn toggle_time(mut time: ResMut<Time<Virtual>>, hitstop: Res<Hitstop>) {
+    // the hitstop resumes the game itself.
+    if hitstop.is_active() {
+        return;
+    }
     if time.is_paused() {
         time.unpause();
     } else {
@@ -57,91 +157,359 @@ fn toggle_time(mut time: ResMut<Time<Virtual>>) {
     }
 }
 
+/// Optional integrations and debugging tools, enabled by cargo features.
+struct IntegrationsPlugin;
+
+impl Plugin for IntegrationsPlugin {
+    fn build(&self, _app: &mut App) {
+        #[cfg(feature = "integration")]
+        _app.add_plugins(rustyrocket::integration::IntegrationPlugin::default());
+        #[cfg(feature = "debug_view")]
+        _app.add_plugins(rustyrocket::debug_view::DebugViewPlugin);
+    }
+}
+
 fn main() {
-    App::new()
-        .add_plugins(
-            DefaultPlugins
-                .set(WindowPlugin {
-                    primary_window: Some(Window {
-                        title: "Rusty Rocket".to_string(),
-                        resolution: WindowResolution::new(1024.0, 1024.0 * 9.0 / 16.0),
-                        resizable: false,
-                        ..default()
-                    }),
-                    ..default()
-                })
-                .set(ImagePlugin {
-                    default_sampler: {
-                        ImageSamplerDescriptor {
-                            mag_filter: ImageFilterMode::Nearest,
-                            ..default()
-                        }
-                    },
-                })
-                .set(LogPlugin {
-                    level: Level::INFO,
+    if let Some(report) = std::env::args()
+        .skip_while(|arg| arg != "--crash-report")
+        .nth(1)
+    {
+        run_crash_screen(report.into());
+        return;
+    }
+    install_panic_hook();
+
+    let replay = std::env::args()
+        .skip_while(|arg| arg != "--replay")
+        .nth(1)
+        .map(|path| {
+            Replay::load(&path).unwrap_or_else(|e| {
+                eprintln!("couldn't load replay {}: {}", path, e);
+                std::process::exit(1);
+            })
+        });
+    let scroll_axis = if let Some(replay) = &replay {
+        replay.scroll_axis
+    } else if std::env::args().any(|arg| arg == "--vertical") {
+        ScrollAxis::Vertical
+    } else {
+        ScrollAxis::Horizontal
+    };
+    let velocity_tilt = std::env::args().any(|arg| arg == "--velocity-tilt");
+    // replays start each run on their own inputs, not the restart press.
+    let quick_restart = replay.is_none() && std::env::args().any(|arg| arg == "--quick-restart");
+    let post_process = !std::env::args().any(|arg| arg == "--no-post-process");
+    let bloom = !std::env::args().any(|arg| arg == "--no-bloom");
+    let rumble = !std::env::args().any(|arg| arg == "--no-rumble");
+    let training = std::env::args().any(|arg| arg == "--training");
+    let analytics = std::env::args().any(|arg| arg == "--analytics");
+    let reduced_motion = std::env::args().any(|arg| arg == "--reduced-motion");
+    let high_contrast = std::env::args().any(|arg| arg == "--high-contrast");
+    let mirror_hud = std::env::args().any(|arg| arg == "--mirror-hud");
+    let log_diagnostics = std::env::args().any(|arg| arg == "--log-diagnostics");
+    let text_scale = std::env::args()
+        .skip_while(|arg| arg != "--text-scale")
+        .nth(1)
+        .map(|arg| {
+            let (min, max) = TEXT_SCALE_RANGE;
+            match arg.parse::<f32>() {
+                Ok(scale) if (min..=max).contains(&scale) => scale,
+                Ok(scale) if scale.is_finite() => {
+                    eprintln!("text scale {} is outside {}..={}, clamping", arg, min, max);
+                    scale.clamp(min, max)
+                }
+                _ => {
+                    eprintln!("ignoring invalid text scale {}", arg);
+                    1.0
+                }
+            }
+        })
+        .unwrap_or(1.0);
+    let mode = if let Some(replay) = &replay {
+        replay.mode
+    } else if std::env::args().any(|arg| arg == "--easy") {
+        GameMode::Easy
+    } else if std::env::args().any(|arg| arg == "--pressure") {
+        GameMode::Pressure
+    } else if std::env::args().any(|arg| arg == "--wrap") {
+        GameMode::Wrap
+    } else if std::env::args().any(|arg| arg == "--flip-charge") {
+        GameMode::FlipCharge
+    } else if std::env::args().any(|arg| arg == "--manual-flip") {
+        GameMode::ManualFlip
+    } else {
+        GameMode::Standard
+    };
+    let fixed_seed = replay.as_ref().map(|replay| replay.seed).or_else(|| {
+        std::env::args()
+            .skip_while(|arg| arg != "--seed")
+            .nth(1)
+            .and_then(|seed| seed.parse().ok())
+    });
+    let max_jumps = replay.as_ref().map(|replay| replay.max_jumps).or_else(|| {
+        std::env::args()
+            .skip_while(|arg| arg != "--jumps")
+            .nth(1)
+            .and_then(|jumps| jumps.parse().ok())
+    });
+    let settings = load_or_default::<Settings>();
+    let physics = PhysicsSettings {
+        max_jumps: max_jumps.unwrap_or(1),
+        timestep: if std::env::args().any(|arg| arg == "--variable-timestep") {
+            PhysicsTimestep::Variable
+        } else {
+            settings.physics_timestep
+        },
+        ..default()
+    };
+
+    let mut app = App::new();
+    app.add_plugins(LoggingPlugin {
+        settings: settings.log.clone(),
+    });
+    // has to replace the default asset source before the asset plugin is built
+    #[cfg(feature = "embedded_assets")]
+    app.add_plugins(rustyrocket::embedded::EmbeddedAssetsPlugin);
+    // likewise has to add the mods asset source
+    app.add_plugins(ModsPlugin);
+    if let Some(replay) = replay {
+        app.insert_resource(ActiveMutators::new(replay.mutators.iter().copied()))
+            .insert_resource(ReplayPlayback::new(replay));
+    }
+
+    app.add_plugins(
+        DefaultPlugins
+            .set(WindowPlugin {
+                primary_window: Some(Window {
+                    title: "Rusty Rocket".to_string(),
+                    resolution: settings.graphics.window_resolution(scroll_axis),
+                    mode: settings.graphics.window_mode(),
+                    present_mode: settings.graphics.present_mode(),
+                    resizable: false,
                     ..default()
                 }),
-        )
-        .add_plugins((
-            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(96.0),
-            RapierDebugRenderPlugin {
-                enabled: false,
+                // debug windows, like the overview, close with the game.
+                exit_condition: ExitCondition::OnPrimaryClosed,
                 ..default()
-            },
-        ))
-        .add_plugins(BarrierPlugin)
-        .insert_resource(WorldSettings::default())
-        .add_event::<ResetEvent>()
-        .add_plugins(
-            bevy_inspector_egui::quick::WorldInspectorPlugin::default()
-                .run_if(input_toggle_active(false, KeyCode::I)),
-        )
-        .add_plugins(
-            ResourceInspectorPlugin::<LevelSettings>::default()
-                .run_if(input_toggle_active(false, KeyCode::L)),
-        )
-        .add_plugins(
-            ResourceInspectorPlugin::<WorldSettings>::default()
-                .run_if(input_toggle_active(false, KeyCode::W)),
-        )
-        .add_plugins(
-            ResourceInspectorPlugin::<Score>::default()
-                .run_if(input_toggle_active(false, KeyCode::S)),
-        )
-        .add_systems(
-            Update,
-            enable_physics_debugging.run_if(input_just_pressed(KeyCode::D)),
-        )
-        .add_systems(Update, toggle_time.run_if(input_just_pressed(KeyCode::P)))
-        .add_state::<GameState>()
-        .add_loading_state(
-            LoadingState::new(GameState::AssetLoading).continue_to_state(GameState::Ready),
-        )
-        .add_plugins(PlayerPlugin)
-        .add_plugins(SpawnerSettingsPlugin)
-        .add_plugins(LevelPlugin)
-        .add_plugins(ObstacleSpawnerPlugin)
-        .add_plugins(ScorePlugin)
-        .add_plugins(ScoringRegionPlugin)
-        .add_plugins(GravityShiftPlugin)
-        .add_plugins(TweeningPlugin)
-        .add_plugins(GameFontsPlugin)
-        .add_plugins(ScoreDisplayPlugin)
-        .add_plugins(DyingPlayerPlugin)
-        .add_plugins(CenterDisplayPlugin)
-        .add_plugins(GameBackgroundPlugin)
-        .add_systems(Startup, (setup_camera, setup_physics).in_set(WorldSet))
-        .add_systems(
-            Update,
-            send_event::<ResetEvent>
-                .run_if(in_state(GameState::Playing).and_then(input_just_pressed(KeyCode::R))),
-        )
-        .add_systems(
-            Update,
-            send_event::<HitBarrierEvent>
-                .run_if(in_state(GameState::Playing).and_then(input_just_pressed(KeyCode::Z))),
+            })
+            .set(ImagePlugin {
+                default_sampler: {
+                    ImageSamplerDescriptor {
+                        mag_filter: ImageFilterMode::Nearest,
+                        ..default()
+                    }
+                },
+            })
+            .disable::<LogPlugin>()
+            .set(AudioPlugin {
+                spatial_scale: SpatialScale::new_2d(SPATIAL_SCALE),
+                ..default()
+            })
+            .set(AssetPlugin {
+                watch_for_changes_override: cfg!(feature = "hot_reload").then_some(true),
+                ..default()
+            }),
+    )
+    .add_plugins((
+        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(physics.pixels_per_meter),
+        RapierDebugRenderPlugin {
+            enabled: false,
+            ..default()
+        },
+    ))
+    .insert_resource(WorldSettings {
+        scroll_axis,
+        ..default()
+    })
+    .register_type::<WorldSettings>()
+    .insert_resource(physics)
+    .register_type::<PhysicsSettings>()
+    .insert_resource(ControlSchemes {
+        keyboard: ControlScheme { velocity_tilt },
+        gamepad: ControlScheme { velocity_tilt },
+    })
+    .insert_resource(RunConfig {
+        fixed_seed,
+        mode,
+        quick_restart,
+    })
+    .add_event::<ResetEvent>()
+    .add_plugins(ContainersPlugin)
+    .add_plugins(
+        bevy_inspector_egui::quick::WorldInspectorPlugin::default()
+            .run_if(input_toggle_active(false, KeyCode::I)),
+    )
+    .add_plugins(
+        ResourceInspectorPlugin::<LevelSettings>::default()
+            .run_if(input_toggle_active(false, KeyCode::L)),
+    )
+    .add_plugins(
+        ResourceInspectorPlugin::<WorldSettings>::default()
+            .run_if(input_toggle_active(false, KeyCode::W)),
+    )
+    .add_plugins(
+        ResourceInspectorPlugin::<Score>::default().run_if(input_toggle_active(false, KeyCode::S)),
+    )
+    .add_plugins(
+        FilterQueryInspectorPlugin::<With<ObstacleSpawner>>::default()
+            .run_if(input_toggle_active(false, KeyCode::O)),
+    )
+    .add_plugins(
+        ResourceInspectorPlugin::<DifficultyMetrics>::default()
+            .run_if(input_toggle_active(false, KeyCode::M)),
+    )
+    .add_plugins((
+        AssetInspectorPlugin::<BackgroundMaterial>::default()
+            .run_if(input_toggle_active(false, KeyCode::V)),
+        AssetInspectorPlugin::<GravityShiftMaterial>::default()
+            .run_if(input_toggle_active(false, KeyCode::V)),
+    ))
+    .add_systems(Update, log_shader_reloads)
+    .add_systems(
+        Update,
+        enable_physics_debugging.run_if(input_just_pressed(KeyCode::D)),
+    )
+    .add_systems(Update, toggle_time.run_if(input_just_pressed(KeyCode::P)))
+    .add_systems(
+        Update,
+        toggle_spawn_audit.run_if(input_just_pressed(KeyCode::A)),
+    )
+    .add_systems(Update, export_scene.run_if(input_just_pressed(KeyCode::F9)))
+    .add_plugins(PhysicsStepsPlugin)
+    .add_plugins(ReplayPlugin)
+    .add_plugins(RewindPlugin)
+    .add_plugins(FlipChargePlugin)
+    .add_plugins(GameSnapshotPlugin)
+    .add_plugins(GhostPlugin)
+    .add_plugins(GameplayDiagnosticsPlugin {
+        log: log_diagnostics,
+    })
+    .add_plugins(LatencyOverlayPlugin)
+    .add_systems(
+        Update,
+        toggle_latency_overlay.run_if(input_just_pressed(KeyCode::F3)),
+    )
+    .add_plugins(SettingsPlugin)
+    .add_plugins(ThemePackPlugin {
+        theme: settings.theme_name().to_string(),
+    })
+    .add_plugins(GraphicsPlugin)
+    .add_plugins(PowerPlugin)
+    .insert_resource(settings)
+    .add_systems(
+        Update,
+        log_entity_summary.run_if(input_just_pressed(KeyCode::F8)),
+    )
+    .add_plugins((GameLogPlugin, CrashReportPlugin))
+    .add_plugins(AnalyticsPlugin)
+    .insert_resource(AnalyticsSettings { enabled: analytics })
+    .add_systems(
+        Update,
+        export_heatmap
+            .map(bevy::utils::error)
+            .run_if(input_just_pressed(KeyCode::F7)),
+    )
+    .add_state::<GameState>()
+    .add_plugins(ProgressPlugin::new(GameState::AssetLoading).continue_to(GameState::Ready))
+    .add_loading_state(
+        LoadingState::new(GameState::AssetLoading)
+            .on_failure_continue_to_state(GameState::LoadingFailed),
+    )
+    .add_plugins(LoadingScreenPlugin)
+    .add_plugins(AssetErrorsPlugin)
+    .add_plugins(BarrierPlugin)
+    .add_plugins(PlayerPlugin)
+    .add_plugins(SpawnerSettingsPlugin)
+    .add_plugins(ScenarioPlugin)
+    .add_plugins(ObstacleSetPlugin)
+    .add_plugins(LevelPlugin)
+    .add_plugins(ObstacleSpawnerPlugin)
+    .add_plugins(LevelTransitionPlugin)
+    .add_plugins(MutatorsPlugin)
+    .add_plugins(ScorePlugin)
+    .add_plugins(ComboPlugin)
+    .add_plugins(GameAudioPlugin)
+    .add_plugins(ScorePopupPlugin)
+    .add_plugins(PostProcessPlugin)
+    .insert_resource(PostProcessConfig {
+        enabled: post_process,
+        ..default()
+    })
+    .add_plugins(BloomPlugin)
+    .insert_resource(BloomConfig {
+        enabled: bloom,
+        ..default()
+    })
+    .add_plugins(AccessibilityPlugin)
+    .insert_resource(MotionSettings {
+        reduced: reduced_motion,
+    })
+    .add_plugins(UiThemePlugin)
+    .insert_resource(UiTheme {
+        high_contrast,
+        text_scale,
+    })
+    .insert_resource(HudLayout {
+        mirrored: mirror_hud,
+    })
+    .add_plugins(PauseMenuPlugin)
+    .add_plugins(AnnouncementsPlugin)
+    .add_plugins(HapticsPlugin)
+    .insert_resource(HapticsSettings { enabled: rumble })
+    .add_plugins(ScoringRegionPlugin)
+    .add_plugins(GrazePlugin)
+    .add_plugins(RegionPlugin)
+    .add_plugins(GravityShiftPlugin)
+    .add_plugins(DriftPlugin)
+    .add_plugins(BehaviorPlugin)
+    .add_plugins(EnemyPlugin)
+    .add_plugins(LaserPlugin)
+    .add_plugins(DoorPlugin)
+    .add_plugins(PowerUpsPlugin)
+    .add_plugins(TweeningPlugin)
+    .add_plugins(SpriteAnimationPlugin)
+    .add_plugins(GameFontsPlugin)
+    .add_plugins(ScoreDisplayPlugin)
+    .add_plugins(MinimapPlugin)
+    .add_plugins(DyingPlayerPlugin)
+    .add_plugins(HitstopPlugin)
+    .add_plugins(DeathMarkersPlugin)
+    .add_plugins(BoundaryPlugin)
+    .add_plugins(CenterDisplayPlugin)
+    .add_plugins(ControlsPlugin)
+    .add_plugins(AssetVariantsPlugin)
+    .add_plugins(GameBackgroundPlugin)
+    .add_plugins(SpawnAuditPlugin)
+    .add_plugins(SessionPlugin)
+    .add_plugins(RunPlugin)
+    .add_plugins(HighScorePlugin)
+    .add_plugins(LeaderboardPlugin)
+    .add_plugins(TrainingPlugin)
+    .insert_resource(TrainingSettings {
+        enabled: training,
+        ..default()
+    })
+    .add_plugins(IntegrationsPlugin)
+    .add_systems(Startup, (setup_camera, setup_physics).in_set(WorldSet))
+    .add_systems(
+        Update,
+        send_event::<HitBarrierEvent>
+            .run_if(in_state(GameState::Playing).and_then(input_just_pressed(KeyCode::Z))),
+    )
+    .add_systems(
+        Update,
+        grant_gravity_immunity
+            .run_if(in_state(GameState::Playing).and_then(input_just_pressed(KeyCode::G))),
+    )
+    .add_systems(
+        Update,
+        (
+            grant_shrink.run_if(input_just_pressed(KeyCode::Minus)),
+            grant_grow.run_if(input_just_pressed(KeyCode::Equals)),
         )
-        .add_systems(Update, (close_on_esc,))
-        .run()
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_systems(Update, resize_world_bounds)
+    .add_systems(Update, (close_on_esc,))
+    .run()
 }
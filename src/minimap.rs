@@ -0,0 +1,227 @@
+//! Minimap in a HUD corner, showing the visible field and the margin past
+//! its leading edge where items are spawned, so upcoming items show before
+//! they scroll into view.
+//!
+//! Items get simplified rectangle proxies on [`MINIMAP_LAYER`], which a
+//! second camera renders into a texture shown in the HUD.
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::*,
+    render::{
+        camera::{RenderTarget, ScalingMode},
+        render_resource::{
+            Extent3d, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+        },
+        view::RenderLayers,
+    },
+};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    barrier::Barrier,
+    gravity_shift::GravityRegion,
+    ui_theme::{HudPlacement, HudSide},
+    util::{spawn_named_child, Containers},
+    WorldSet, WorldSettings,
+};
+
+/// Render layer only seen by the minimap camera.
+pub const MINIMAP_LAYER: u8 = 6;
+
+/// Length of the spawn margin shown past the leading edge of the view, in
+/// screens. Items are spawned just past the edge, and pushed further back
+/// by at most an item width and gap.
+const SPAWN_MARGIN_SCREENS: f32 = 0.5;
+
+/// Length of the long side of the minimap, in pixels.
+const MINIMAP_LENGTH: f32 = 200.0;
+
+const BARRIER_COLOR: Color = Color::rgb(0.6, 0.6, 0.0);
+const GRAVITY_COLOR: Color = Color::rgb(0.3, 0.3, 1.0);
+
+/// Marker for the minimap camera.
+#[derive(Component)]
+pub struct MinimapCamera;
+
+/// Sprite in the HUD showing the minimap.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Minimap;
+
+/// Area shown on the minimap, in world space.
+struct MinimapView {
+    center: Vec2,
+    area: Vec2,
+}
+
+impl MinimapView {
+    /// The view and the spawn margin past its leading edge.
+    fn new(play_world: &WorldSettings) -> Self {
+        let axis = play_world.scroll_axis;
+        let level_bounds = play_world.level_bounds();
+        let margin = level_bounds.width() * SPAWN_MARGIN_SCREENS;
+        let level_area = Vec2::new(level_bounds.width() + margin, level_bounds.height());
+        Self {
+            center: axis.to_world(Vec2::new(
+                level_bounds.min.x + level_area.x * 0.5,
+                level_bounds.center().y,
+            )),
+            area: axis.to_world(level_area).abs(),
+        }
+    }
+
+    /// Size of the minimap in the HUD, in pixels.
+    fn display_size(&self) -> Vec2 {
+        self.area * (MINIMAP_LENGTH / self.area.max_element())
+    }
+
+    fn texture_size(&self) -> Extent3d {
+        let display_size = self.display_size();
+        Extent3d {
+            width: display_size.x as u32,
+            height: display_size.y as u32,
+            depth_or_array_layers: 1,
+        }
+    }
+}
+
+/// Set up the minimap texture, the camera drawing into it, and the HUD
+/// sprite showing it.
+fn setup_minimap(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    play_world: Res<WorldSettings>,
+    containers: Res<Containers>,
+) {
+    let view = MinimapView::new(&play_world);
+    let display_size = view.display_size();
+    let size = view.texture_size();
+    let mut image = Image {
+        texture_descriptor: TextureDescriptor {
+            label: Some("minimap"),
+            size,
+            dimension: TextureDimension::D2,
+            format: TextureFormat::Bgra8UnormSrgb,
+            mip_level_count: 1,
+            sample_count: 1,
+            usage: TextureUsages::TEXTURE_BINDING
+                | TextureUsages::COPY_DST
+                | TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        },
+        ..default()
+    };
+    image.resize(size);
+    let image = images.add(image);
+
+    let mut camera = Camera2dBundle::default();
+    camera.camera.target = RenderTarget::Image(image.clone());
+    camera.camera.order = -1;
+    camera.camera_2d.clear_color = ClearColorConfig::Custom(Color::rgba(0.0, 0.0, 0.0, 0.4));
+    camera.projection.scaling_mode = ScalingMode::Fixed {
+        width: view.area.x,
+        height: view.area.y,
+    };
+    camera.transform.translation = view.center.extend(camera.transform.translation.z);
+    commands.spawn((
+        camera,
+        RenderLayers::layer(MINIMAP_LAYER),
+        MinimapCamera,
+        Name::new("minimap_camera"),
+    ));
+
+    // below the clock, in the corner opposite the score.
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "minimap",
+        (
+            SpriteBundle {
+                texture: image,
+                sprite: Sprite {
+                    custom_size: Some(display_size),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                ..default()
+            },
+            HudPlacement::new(HudSide::Right, Vec2::new(12.0, 36.0)),
+            Minimap,
+        ),
+    );
+}
+
+/// Fit the minimap camera, its texture and the HUD sprite to new world
+/// bounds.
+fn resize_minimap(
+    mut cameras: Query<(&Camera, &mut OrthographicProjection, &mut Transform), With<MinimapCamera>>,
+    mut minimaps: Query<&mut Sprite, With<Minimap>>,
+    mut images: ResMut<Assets<Image>>,
+    play_world: Res<WorldSettings>,
+) {
+    let view = MinimapView::new(&play_world);
+    for (camera, mut projection, mut transform) in cameras.iter_mut() {
+        projection.scaling_mode = ScalingMode::Fixed {
+            width: view.area.x,
+            height: view.area.y,
+        };
+        transform.translation = view.center.extend(transform.translation.z);
+        if let RenderTarget::Image(image) = &camera.target {
+            if let Some(image) = images.get_mut(image) {
+                image.resize(view.texture_size());
+            }
+        }
+    }
+    for mut sprite in minimaps.iter_mut() {
+        sprite.custom_size = Some(view.display_size());
+    }
+}
+
+/// Give newly spawned barriers and gravity regions a rectangle on the
+/// minimap.
+#[allow(clippy::type_complexity)]
+fn add_minimap_proxies(
+    mut commands: Commands,
+    items: Query<(Entity, &Collider, Has<Barrier>), Or<(Added<Barrier>, Added<GravityRegion>)>>,
+) {
+    for (ent, collider, is_barrier) in items.iter() {
+        let Some(cuboid) = collider.as_cuboid() else {
+            continue;
+        };
+        let color = if is_barrier {
+            BARRIER_COLOR
+        } else {
+            GRAVITY_COLOR
+        };
+        commands.entity(ent).with_children(|parent| {
+            parent.spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(cuboid.half_extents() * 2.0),
+                        ..default()
+                    },
+                    ..default()
+                },
+                RenderLayers::layer(MINIMAP_LAYER),
+                Name::new("minimap_proxy"),
+            ));
+        });
+    }
+}
+
+pub struct MinimapPlugin;
+
+impl Plugin for MinimapPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Minimap>()
+            .add_systems(Startup, setup_minimap.after(WorldSet))
+            .add_systems(
+                Update,
+                (
+                    add_minimap_proxies,
+                    resize_minimap.run_if(resource_changed::<WorldSettings>()),
+                ),
+            );
+    }
+}
@@ -0,0 +1,331 @@
+//! Mods: extra levels and training scenarios, read from a `mods` folder next
+//! to the game, so that new difficulty tiers can be added without
+//! recompiling.
+//!
+//! Every folder in `mods` is a mod. Its `.spawner.ron` levels are played
+//! after the fast level, in file name order, each [`MOD_LEVEL_POINTS`]
+//! points after the one before, the obstacles in its `.obstacles.ron` files
+//! are mixed in with the items of every level, and the scenarios in its
+//! `.scenarios.ron` files are added to training. Files that fail to load or
+//! validate are skipped, though levels keep their place in the order, so
+//! the points of the levels after them don't depend on them.
+//!
+//! Mods are turned on and off from the settings page, and the change takes
+//! effect from the next launch.
+use std::path::Path;
+
+use bevy::{
+    asset::{
+        io::{file::FileAssetReader, AssetSource, AssetSourceId},
+        LoadState, UntypedAssetId,
+    },
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    obstacle::{custom::ObstacleSet, scenario::ScenarioSet, spawner_settings::SpawnerSettings},
+    settings::Settings,
+    training::TrainingScenarios,
+    WorldSettings,
+};
+
+/// Folder that mods are read from, next to the `assets` folder. It is also
+/// the name of the asset source they are loaded through.
+pub const MODS_DIR: &str = "mods";
+
+/// Points between the start of one mod level and the next.
+pub const MOD_LEVEL_POINTS: i32 = 10;
+
+#[derive(Reflect, Serialize, Deserialize, Default, Clone, Debug)]
+#[serde(default)]
+pub struct ModSettings {
+    /// Names of the mods that are turned off.
+    pub disabled: Vec<String>,
+}
+
+impl ModSettings {
+    pub fn is_enabled(&self, name: &str) -> bool {
+        !self.disabled.iter().any(|d| d == name)
+    }
+
+    pub fn toggle(&mut self, name: &str) {
+        if self.is_enabled(name) {
+            self.disabled.push(name.to_string());
+        } else {
+            self.disabled.retain(|d| d != name);
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModFileKind {
+    Level,
+    Obstacles,
+    Scenarios,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModFileStatus {
+    /// The mod is turned off.
+    NotLoaded,
+    Loading,
+    Ready,
+
+    /// The file couldn't be loaded, or isn't valid.
+    Failed,
+}
+
+enum ModContent {
+    Level(Handle<SpawnerSettings>),
+    Obstacles(Handle<ObstacleSet>),
+    Scenarios(Handle<ScenarioSet>),
+}
+
+pub struct ModFile {
+    /// Path within the mods folder.
+    pub path: String,
+    pub kind: ModFileKind,
+    pub status: ModFileStatus,
+    content: Option<ModContent>,
+}
+
+impl ModFile {
+    fn new(path: String) -> Option<Self> {
+        let kind = if path.ends_with(".spawner.ron") {
+            ModFileKind::Level
+        } else if path.ends_with(".obstacles.ron") {
+            ModFileKind::Obstacles
+        } else if path.ends_with(".scenarios.ron") {
+            ModFileKind::Scenarios
+        } else {
+            return None;
+        };
+        Some(Self {
+            path,
+            kind,
+            status: ModFileStatus::NotLoaded,
+            content: None,
+        })
+    }
+
+    fn untyped_id(&self) -> Option<UntypedAssetId> {
+        match self.content.as_ref()? {
+            ModContent::Level(handle) => Some(handle.id().untyped()),
+            ModContent::Obstacles(handle) => Some(handle.id().untyped()),
+            ModContent::Scenarios(handle) => Some(handle.id().untyped()),
+        }
+    }
+}
+
+pub struct Mod {
+    pub name: String,
+    pub files: Vec<ModFile>,
+}
+
+impl Mod {
+    fn count(&self, kind: ModFileKind, status: ModFileStatus) -> usize {
+        self.files
+            .iter()
+            .filter(|f| f.kind == kind && f.status == status)
+            .count()
+    }
+
+    /// Short description of how the mod's files loaded, for the settings
+    /// page.
+    pub fn status_label(&self) -> String {
+        let failed = self
+            .files
+            .iter()
+            .filter(|f| f.status == ModFileStatus::Failed)
+            .count();
+        if self
+            .files
+            .iter()
+            .any(|f| f.status == ModFileStatus::Loading)
+        {
+            "LOADING".to_string()
+        } else if self
+            .files
+            .iter()
+            .all(|f| f.status == ModFileStatus::NotLoaded)
+        {
+            "NOT LOADED".to_string()
+        } else {
+            let mut label = format!(
+                "{} LEVELS, {} OBSTACLE SETS, {} SCENARIO SETS",
+                self.count(ModFileKind::Level, ModFileStatus::Ready),
+                self.count(ModFileKind::Obstacles, ModFileStatus::Ready),
+                self.count(ModFileKind::Scenarios, ModFileStatus::Ready)
+            );
+            if failed > 0 {
+                label.push_str(&format!(", {} FAILED", failed));
+            }
+            label
+        }
+    }
+}
+
+/// Mods found in the mods folder at launch, by name.
+#[derive(Resource, Default)]
+pub struct Mods {
+    pub mods: Vec<Mod>,
+}
+
+impl Mods {
+    /// Find the mods in `dir`, and their level, obstacle and scenario files.
+    fn scan(dir: &Path) -> Self {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Self::default();
+        };
+        let mut mods: Vec<Mod> = entries
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| {
+                let name = entry.file_name().to_str()?.to_string();
+                let mut files: Vec<ModFile> = std::fs::read_dir(entry.path())
+                    .ok()?
+                    .flatten()
+                    .filter_map(|file| {
+                        let file_name = file.file_name().to_str()?.to_string();
+                        ModFile::new(format!("{}/{}", name, file_name))
+                    })
+                    .collect();
+                files.sort_by(|a, b| a.path.cmp(&b.path));
+                Some(Mod { name, files })
+            })
+            .collect();
+        mods.sort_by(|a, b| a.name.cmp(&b.name));
+        Self { mods }
+    }
+
+    /// Levels of the mods that are turned on, in the order they are played,
+    /// with those that aren't ready to play (yet) as `None`.
+    pub fn levels(&self) -> impl Iterator<Item = Option<&Handle<SpawnerSettings>>> {
+        self.mods
+            .iter()
+            .flat_map(|m| &m.files)
+            .filter(|f| f.kind == ModFileKind::Level && f.status != ModFileStatus::NotLoaded)
+            .map(|f| match (&f.content, f.status) {
+                (Some(ModContent::Level(handle)), ModFileStatus::Ready) => Some(handle),
+                _ => None,
+            })
+    }
+
+    /// Custom obstacle sets that are ready to spawn.
+    pub fn obstacles(&self) -> impl Iterator<Item = &Handle<ObstacleSet>> {
+        self.mods
+            .iter()
+            .flat_map(|m| &m.files)
+            .filter(|f| f.status == ModFileStatus::Ready)
+            .filter_map(|f| match &f.content {
+                Some(ModContent::Obstacles(handle)) => Some(handle),
+                _ => None,
+            })
+    }
+}
+
+/// Start loading the files of every mod that is turned on.
+fn load_mods(asset_server: Res<AssetServer>, settings: Res<Settings>, mut mods: ResMut<Mods>) {
+    for m in mods.mods.iter_mut() {
+        if !settings.mods.is_enabled(&m.name) {
+            continue;
+        }
+        info!("loading mod {}", m.name);
+        for file in m.files.iter_mut() {
+            let path = format!("{}://{}", MODS_DIR, file.path);
+            file.content = Some(match file.kind {
+                ModFileKind::Level => ModContent::Level(asset_server.load(path)),
+                ModFileKind::Obstacles => ModContent::Obstacles(asset_server.load(path)),
+                ModFileKind::Scenarios => ModContent::Scenarios(asset_server.load(path)),
+            });
+            file.status = ModFileStatus::Loading;
+        }
+    }
+}
+
+/// Validate mod files as they finish loading, and add their scenarios to
+/// training.
+fn check_mod_files(
+    asset_server: Res<AssetServer>,
+    mut mods: ResMut<Mods>,
+    mut levels: ResMut<Assets<SpawnerSettings>>,
+    obstacle_sets: Res<Assets<ObstacleSet>>,
+    mut scenario_sets: ResMut<Assets<ScenarioSet>>,
+    training: Option<Res<TrainingScenarios>>,
+    play_world: Res<WorldSettings>,
+) {
+    for file in mods.mods.iter_mut().flat_map(|m| m.files.iter_mut()) {
+        if file.status != ModFileStatus::Loading {
+            continue;
+        }
+        let Some(id) = file.untyped_id() else {
+            continue;
+        };
+        match asset_server.get_load_state(id) {
+            Some(LoadState::Failed) => {
+                warn!("mod file {} failed to load", file.path);
+                file.status = ModFileStatus::Failed;
+                continue;
+            }
+            Some(LoadState::Loaded) => {}
+            _ => continue,
+        }
+
+        let result = match &file.content {
+            Some(ModContent::Level(handle)) => {
+                let Some(level) = levels.get_mut(handle) else {
+                    continue;
+                };
+                if level.name.is_empty() {
+                    level.name = file.path.trim_end_matches(".spawner.ron").to_string();
+                }
+                level.validate(&play_world).map_err(|e| e.to_string())
+            }
+            Some(ModContent::Obstacles(handle)) => {
+                let Some(set) = obstacle_sets.get(handle) else {
+                    continue;
+                };
+                set.validate().map_err(|e| e.to_string())
+            }
+            Some(ModContent::Scenarios(handle)) => {
+                // training's own scenarios have to be in first.
+                let Some(training) = training.as_ref() else {
+                    continue;
+                };
+                let Some(set) = scenario_sets.get(handle).cloned() else {
+                    continue;
+                };
+                set.validate().map_err(|e| e.to_string()).map(|_| {
+                    if let Some(base) = scenario_sets.get_mut(&training.scenarios) {
+                        base.scenarios.extend(set.scenarios);
+                    }
+                })
+            }
+            None => continue,
+        };
+        file.status = match result {
+            Ok(()) => ModFileStatus::Ready,
+            Err(e) => {
+                warn!("skipping mod file {}: {}", file.path, e);
+                ModFileStatus::Failed
+            }
+        };
+    }
+}
+
+/// Has to be added before `DefaultPlugins`, as it registers the asset
+/// source that mods are loaded from.
+pub struct ModsPlugin;
+
+impl Plugin for ModsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_source(
+            AssetSourceId::from(MODS_DIR),
+            AssetSource::build().with_reader(AssetSource::get_default_reader(MODS_DIR.to_string())),
+        )
+        .insert_resource(Mods::scan(&FileAssetReader::get_base_path().join(MODS_DIR)))
+        .add_systems(Startup, load_mods)
+        .add_systems(Update, check_mod_files);
+    }
+}
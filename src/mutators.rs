@@ -0,0 +1,243 @@
+//! Mutators: optional twists on the rules, toggled on the ready screen
+//! before a run with the number keys.
+//!
+//! Each mutator is applied where the setting it changes lives: gravity by
+//! the level physics, spawning by the spawner, and size by the player. Runs
+//! played with mutators are tagged with them in the score records, so they
+//! are ranked apart from unmodified runs.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fonts::FontsCollection,
+    replay::live_input,
+    ui_theme::{ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    GameState, WorldSettings, ON_ASSETS_LOADED,
+};
+
+/// Size of the player with [`Mutator::TinyPlayer`], relative to normal.
+const TINY_PLAYER_SCALE: f32 = 0.6;
+
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum Mutator {
+    DoubleGravity,
+    TinyPlayer,
+
+    /// No gravity regions or gravity chains are spawned.
+    NoGravityRegions,
+
+    /// Gravity and jumps are upside down.
+    Mirror,
+}
+
+impl Mutator {
+    /// Every mutator, in the order of their keys.
+    pub const ALL: [Mutator; 4] = [
+        Mutator::DoubleGravity,
+        Mutator::TinyPlayer,
+        Mutator::NoGravityRegions,
+        Mutator::Mirror,
+    ];
+
+    /// Name, for the ready screen.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Mutator::DoubleGravity => "DOUBLE GRAVITY",
+            Mutator::TinyPlayer => "TINY PLAYER",
+            Mutator::NoGravityRegions => "NO GRAVITY REGIONS",
+            Mutator::Mirror => "MIRROR",
+        }
+    }
+
+    /// Short tag, for score tables.
+    pub fn tag(&self) -> &'static str {
+        match self {
+            Mutator::DoubleGravity => "2G",
+            Mutator::TinyPlayer => "TINY",
+            Mutator::NoGravityRegions => "NOGR",
+            Mutator::Mirror => "MIR",
+        }
+    }
+
+    fn key(&self) -> KeyCode {
+        match self {
+            Mutator::DoubleGravity => KeyCode::Key1,
+            Mutator::TinyPlayer => KeyCode::Key2,
+            Mutator::NoGravityRegions => KeyCode::Key3,
+            Mutator::Mirror => KeyCode::Key4,
+        }
+    }
+}
+
+/// Mutators that the next run is played with, in the order of
+/// [`Mutator::ALL`].
+#[derive(Resource, Reflect, Default, Clone, PartialEq, Eq, Debug)]
+#[reflect(Resource)]
+pub struct ActiveMutators(Vec<Mutator>);
+
+impl ActiveMutators {
+    pub fn new(mutators: impl IntoIterator<Item = Mutator>) -> Self {
+        let mut active: Vec<_> = mutators.into_iter().collect();
+        active.sort();
+        active.dedup();
+        Self(active)
+    }
+
+    pub fn list(&self) -> &[Mutator] {
+        &self.0
+    }
+
+    pub fn has(&self, mutator: Mutator) -> bool {
+        self.0.contains(&mutator)
+    }
+
+    pub fn toggle(&mut self, mutator: Mutator) {
+        if self.has(mutator) {
+            self.0.retain(|m| *m != mutator);
+        } else {
+            self.0.push(mutator);
+            self.0.sort();
+        }
+    }
+
+    /// Multiple of the level gravity, with its direction.
+    pub fn gravity_scale(&self) -> f32 {
+        let strength = if self.has(Mutator::DoubleGravity) {
+            2.0
+        } else {
+            1.0
+        };
+        strength * self.jump_scale()
+    }
+
+    /// Multiple of the level jump speed, with its direction.
+    pub fn jump_scale(&self) -> f32 {
+        if self.has(Mutator::Mirror) {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Size of the player when not changed by a power up.
+    pub fn player_scale(&self) -> f32 {
+        if self.has(Mutator::TinyPlayer) {
+            TINY_PLAYER_SCALE
+        } else {
+            1.0
+        }
+    }
+
+    pub fn spawns_gravity_regions(&self) -> bool {
+        !self.has(Mutator::NoGravityRegions)
+    }
+}
+
+/// Tags of a run's mutators, for score tables. Empty without mutators.
+pub fn mutator_tags(mutators: &[Mutator]) -> String {
+    mutators
+        .iter()
+        .map(|m| m.tag())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// List of mutators on the ready screen.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct MutatorList;
+
+const LIST_TEXT: ThemedText = ThemedText {
+    size: 16.0,
+    color: Color::ANTIQUE_WHITE,
+};
+
+fn spawn_mutator_list(
+    mut commands: Commands,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    containers: Res<Containers>,
+    play_world: Res<WorldSettings>,
+) {
+    let (text, themed) = theme.text("", fonts.menu_font.clone(), LIST_TEXT);
+    let bounds = play_world.bounds;
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "mutator_list",
+        (
+            Text2dBundle {
+                text: text.with_alignment(TextAlignment::Center),
+                transform: Transform::from_xyz(
+                    bounds.center().x,
+                    bounds.min.y + bounds.height() * 0.15,
+                    20.0,
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            MutatorList,
+            themed,
+        ),
+    );
+}
+
+/// Toggle mutators with the number keys.
+fn toggle_mutators(keys: Res<Input<KeyCode>>, mut mutators: ResMut<ActiveMutators>) {
+    for mutator in Mutator::ALL {
+        if keys.just_pressed(mutator.key()) {
+            mutators.toggle(mutator);
+            let state = if mutators.has(mutator) { "on" } else { "off" };
+            info!("mutator {:?} turned {}", mutator, state);
+        }
+    }
+}
+
+fn show_mutator_list(
+    mutators: Res<ActiveMutators>,
+    mut list: Query<(&mut Text, &mut Visibility), With<MutatorList>>,
+) {
+    for (mut text, mut vis) in list.iter_mut() {
+        *vis = Visibility::Visible;
+        text.sections[0].value = Mutator::ALL
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let check = if mutators.has(*m) { "X" } else { " " };
+                format!("{}: [{}] {}", i + 1, check, m.label())
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+    }
+}
+
+fn hide_mutator_list(mut list: Query<&mut Visibility, With<MutatorList>>) {
+    for mut vis in list.iter_mut() {
+        *vis = Visibility::Hidden;
+    }
+}
+
+pub struct MutatorsPlugin;
+
+impl Plugin for MutatorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ActiveMutators>()
+            .register_type::<Mutator>()
+            .register_type::<MutatorList>()
+            .init_resource::<ActiveMutators>()
+            .add_systems(ON_ASSETS_LOADED, spawn_mutator_list)
+            .add_systems(
+                Update,
+                (
+                    // replays play with the mutators they were recorded with.
+                    toggle_mutators.run_if(live_input),
+                    show_mutator_list.run_if(resource_changed::<ActiveMutators>()),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Ready)),
+            )
+            .add_systems(OnEnter(GameState::Ready), show_mutator_list)
+            .add_systems(OnExit(GameState::Ready), hide_mutator_list);
+    }
+}
@@ -0,0 +1,159 @@
+//! Debug visualization of spawned items, to see why a spawn feels unfair.
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    fonts::FontsCollection, level::RemoveWhenLeft, obstacle::SpawnParams,
+    scoring_region::ScoringRegion, GameState, WorldSettings,
+};
+
+const PATH_COLOR: Color = Color::CYAN;
+const REGION_COLOR: Color = Color::GREEN;
+const DESPAWN_COLOR: Color = Color::FUCHSIA;
+
+/// Whether the spawn audit overlay is shown.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct SpawnAudit {
+    pub enabled: bool,
+}
+
+/// Marker for text labels describing spawn parameters.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct SpawnAuditLabel;
+
+impl SpawnParams {
+    /// Short description of the parameters, for labels.
+    fn label(&self) -> String {
+        match self {
+            SpawnParams::Tunnel(tunnel) => format!(
+                "tunnel\ny {:.0}\ngap {:.0}",
+                tunnel.gap_center, tunnel.gap_height
+            ),
+            SpawnParams::Gravity { gravity_mult, .. } => format!("gravity\n{:+.1}", gravity_mult),
+            SpawnParams::Drift { drift, .. } => format!("drift\n{:+.0}", drift),
+            SpawnParams::Bouncer(bouncer) => {
+                format!("bouncer\nh {:.0}\nv {:+.0}", bouncer.height, bouncer.drift)
+            }
+            SpawnParams::Drone(drone) => format!("drone\nturn {:.1}", drone.turn_rate),
+            SpawnParams::Laser(laser) => format!(
+                "laser\non {:.1}s\noff {:.1}s",
+                laser.on_secs, laser.off_secs
+            ),
+            SpawnParams::Key(_) => "key".to_string(),
+            SpawnParams::PowerUp(power_up) => format!("power up\n{:?}", power_up.kind),
+            SpawnParams::Door(door) => format!("door\ny {:.0}", door.gap_center),
+        }
+    }
+}
+
+/// Attach a parameter label to each newly spawned item.
+fn label_spawned_items(
+    mut commands: Commands,
+    items: Query<(Entity, &SpawnParams), Added<SpawnParams>>,
+    fonts: Res<FontsCollection>,
+    audit: Res<SpawnAudit>,
+) {
+    for (ent, params) in items.iter() {
+        let label = commands
+            .spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        params.label(),
+                        TextStyle {
+                            font: fonts.menu_font.clone(),
+                            font_size: 14.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                    visibility: if audit.enabled {
+                        Visibility::Inherited
+                    } else {
+                        Visibility::Hidden
+                    },
+                    ..default()
+                },
+                SpawnAuditLabel,
+                Name::new("audit_label"),
+            ))
+            .id();
+        commands.entity(ent).add_child(label);
+    }
+}
+
+/// Show or hide labels when the audit is toggled.
+fn sync_label_visibility(
+    audit: Res<SpawnAudit>,
+    mut labels: Query<&mut Visibility, With<SpawnAuditLabel>>,
+) {
+    for mut v in labels.iter_mut() {
+        *v = if audit.enabled {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+/// Draw the path, scoring region and despawn boundary of each spawned item.
+fn draw_spawn_audit(
+    mut gizmos: Gizmos,
+    items: Query<(&GlobalTransform, &Velocity, &RemoveWhenLeft), With<SpawnParams>>,
+    regions: Query<(&GlobalTransform, &Collider), With<ScoringRegion>>,
+    play_world: Res<WorldSettings>,
+) {
+    let axis = play_world.scroll_axis;
+    let bounds = play_world.level_bounds();
+
+    for (global, vel, rwl) in items.iter() {
+        let pos = global.translation().truncate();
+        let despawn_x = bounds.min.x - rwl.0;
+
+        // path the item takes until it is despawned
+        let level_x = axis.to_level(pos).x;
+        let level_vel_x = axis.to_level(vel.linvel).x;
+        if level_vel_x < 0.0 && level_x > despawn_x {
+            let t = (despawn_x - level_x) / level_vel_x;
+            gizmos.line_2d(pos, pos + vel.linvel * t, PATH_COLOR);
+        }
+
+        gizmos.line_2d(
+            axis.to_world(Vec2::new(despawn_x, bounds.min.y)),
+            axis.to_world(Vec2::new(despawn_x, bounds.max.y)),
+            DESPAWN_COLOR,
+        );
+    }
+
+    for (global, collider) in regions.iter() {
+        if let Some(cuboid) = collider.as_cuboid() {
+            let (_, rotation, translation) = global.to_scale_rotation_translation();
+            gizmos.rect(
+                translation,
+                rotation,
+                cuboid.half_extents() * 2.0,
+                REGION_COLOR,
+            );
+        }
+    }
+}
+
+pub struct SpawnAuditPlugin;
+
+impl Plugin for SpawnAuditPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<SpawnAudit>()
+            .register_type::<SpawnParams>()
+            .register_type::<SpawnAuditLabel>()
+            .insert_resource(SpawnAudit::default())
+            .add_systems(
+                Update,
+                (
+                    label_spawned_items.run_if(in_state(GameState::Playing)),
+                    sync_label_visibility.run_if(resource_changed::<SpawnAudit>()),
+                    draw_spawn_audit.run_if(|audit: Res<SpawnAudit>| audit.enabled),
+                ),
+            );
+    }
+}
@@ -1,13 +1,22 @@
 use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_asset_loader::loading_state::LoadingStateAppExt;
 use bevy_rapier2d::prelude::*;
 
-use crate::WorldSettings;
+use crate::{
+    barrier_collision_groups,
+    player::Player,
+    scoring_region::ScoringRegion,
+    theme_pack::{ThemeAssets, ThemePack},
+    world_solver_groups, GameState, WorldSettings,
+};
 
 /// Marker trait for obstacles.
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Barrier;
 
-#[derive(Resource, Default, Reflect)]
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct BarrierAssets {
     /// basic quad mesh
     base_mesh: Handle<Mesh>,
@@ -21,19 +30,19 @@ pub struct BarrierAssets {
 
 /// Event spawned when the player hits an obstacle.
 #[derive(Event, Default)]
-pub struct HitBarrierEvent;
-
-#[derive(Component, Reflect)]
-pub struct RegionRef {
-    pub region: Entity,
+pub struct HitBarrierEvent {
+    /// Barrier that was hit, if the hit came from a collision.
+    pub barrier: Option<Entity>,
 }
 
-/// Spawn an barrier bundle off-screen
+/// Create a barrier bundle, to be attached as a child of a moving body.
+///
+/// `x` is the level space offset from the parent.
 pub fn new_barrier(
     from_top: bool,
     width: f32,
     height: f32,
-    start_x: f32,
+    x: f32,
     meshes: &mut ResMut<Assets<Mesh>>,
     play_world: &Res<WorldSettings>,
     obs_mat: &Res<BarrierAssets>,
@@ -43,65 +52,87 @@ pub fn new_barrier(
     let c = obs_mat.exit_mat.clone();
 
     let top_mult = if from_top { 1.0 } else { -1.0 };
-    let center_y = play_world.bounds.max.y - height / 2.0;
+    let center_y = play_world.level_bounds().max.y - height / 2.0;
     (
         MaterialMesh2dBundle {
             mesh: b.into(),
             material: c,
-            transform: Transform {
-                translation: Vec3::new(start_x, center_y * top_mult, 2.0),
-                scale: Vec3::new(1.0, 1.0, 1.0),
-                ..default()
-            },
+            transform: Transform::from_xyz(x, center_y * top_mult, 2.0),
             ..default()
         },
         Barrier,
         Collider::cuboid(width / 2.0, height / 2.0),
+        barrier_collision_groups(),
+        world_solver_groups(),
         ColliderMassProperties::Density(1.0),
-        RigidBody::KinematicVelocityBased,
         //Sensor,
         ActiveEvents::COLLISION_EVENTS,
     )
 }
 
-/// Setup the collection of materials used for obstacles.
-fn setup_barrier_assets(
-    mut materials: ResMut<Assets<ColorMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    play_world: Res<WorldSettings>,
-    mut obs_mat: ResMut<BarrierAssets>,
-) {
-    let height = play_world.bounds.height() / 2.0;
-    let quad_dim = Vec2::new(1.0, height);
-    obs_mat.base_mesh = meshes.add(Mesh::from(shape::Quad::new(quad_dim)));
+/// Setup the collection of materials used for obstacles, once assets have loaded.
+impl FromWorld for BarrierAssets {
+    fn from_world(world: &mut World) -> Self {
+        let height = world.resource::<WorldSettings>().level_bounds().height() / 2.0;
+        let quad_dim = Vec2::new(1.0, height);
+        let base_mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::Quad::new(quad_dim)));
+
+        // tools that build barriers without loading assets get the classic
+        // look.
+        let theme = world
+            .get_resource::<ThemePack>()
+            .cloned()
+            .unwrap_or_default();
+        let texture = world
+            .get_resource::<ThemeAssets>()
+            .and_then(|assets| assets.barrier_texture.clone());
 
-    obs_mat.enter_mat = materials.add(ColorMaterial {
-        color: Color::rgba(0.6, 0.2, 0.0, 1.0),
-        ..default()
-    });
-    obs_mat.exit_mat = materials.add(ColorMaterial {
-        color: Color::rgba(0.6, 0.6, 0.0, 1.0),
-        ..default()
-    });
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        BarrierAssets {
+            base_mesh,
+            enter_mat: materials.add(ColorMaterial {
+                color: theme.barrier_hit_color,
+                texture: texture.clone(),
+            }),
+            exit_mat: materials.add(ColorMaterial {
+                color: theme.barrier_color,
+                texture,
+            }),
+        }
+    }
 }
 
 fn react_to_barrier_collision(
     mut commands: Commands,
     mut events: EventReader<CollisionEvent>,
     mut hit_events: EventWriter<HitBarrierEvent>,
-    query: Query<(Entity, Option<&RegionRef>), With<Barrier>>,
+    query: Query<Option<&Parent>, With<Barrier>>,
+    players: Query<(), With<Player>>,
+    children: Query<&Children>,
+    regions: Query<(), With<ScoringRegion>>,
 ) {
     for event in events.read() {
         if let CollisionEvent::Started(a, b, _) = event {
-            for entity in [a, b] {
-                if let Ok(ent) = query.get(*entity) {
+            for (entity, other) in [(a, b), (b, a)] {
+                // only the player's body counts as a hit.
+                if !players.contains(*other) {
+                    continue;
+                }
+                if let Ok(parent) = query.get(*entity) {
                     // send the event that a barrier as hit.
-                    hit_events.send(HitBarrierEvent);
+                    hit_events.send(HitBarrierEvent {
+                        barrier: Some(*entity),
+                    });
 
                     // Remove any scoring regions from the parent
-                    if let Some(rr) = ent.1 {
-                        if let Some(mut region) = commands.get_entity(rr.region) {
-                            region.despawn();
+                    let Some(siblings) = parent.and_then(|p| children.get(p.get()).ok()) else {
+                        continue;
+                    };
+                    for sibling in siblings.iter() {
+                        if regions.contains(*sibling) {
+                            commands.entity(*sibling).despawn_recursive();
                         }
                     }
                 }
@@ -115,11 +146,10 @@ pub struct BarrierPlugin;
 
 impl Plugin for BarrierPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource::<BarrierAssets>(BarrierAssets::default())
+        app.init_resource_after_loading_state::<_, BarrierAssets>(GameState::AssetLoading)
             .register_type::<BarrierAssets>()
             .register_type::<Barrier>()
             .add_event::<HitBarrierEvent>()
-            .add_systems(Startup, setup_barrier_assets)
             .add_systems(Update, (react_to_barrier_collision,));
     }
 }
@@ -0,0 +1,209 @@
+//! Data driven obstacle behaviors: a sequence of timed actions, defined in
+//! a level's RON file for each obstacle type, so that moving and animated
+//! obstacles don't each need a system of their own.
+//!
+//! Each step's action takes effect as the step starts, and lasts until the
+//! next step changes it. For example, a tunnel that weaves up and down:
+//!
+//! ```ron
+//! behavior: Some((
+//!     steps: [
+//!         (secs: 1.0, action: MoveY(80.0)),
+//!         (secs: 1.0, action: MoveY(-80.0)),
+//!     ],
+//!     repeat: true,
+//! )),
+//! ```
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    obstacle::drift::{drift_new_tunnels, Drift},
+    GameState,
+};
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+pub enum BehaviorAction {
+    /// Move along the level space y axis at this speed, in pixels per
+    /// second, on top of the scrolling.
+    MoveY(f32),
+
+    /// Scroll at this multiple of the level speed.
+    SetSpeed(f32),
+
+    /// Spin at this speed, in radians per second.
+    Rotate(f32),
+
+    /// Turn the solid colliders off, or back on. Scoring regions and other
+    /// sensors are left alone.
+    ToggleCollider,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+pub struct BehaviorStep {
+    /// Time until the next step starts.
+    pub secs: f32,
+    pub action: BehaviorAction,
+}
+
+#[derive(Reflect, Serialize, Deserialize, Clone, Debug)]
+pub struct Behavior {
+    pub steps: Vec<BehaviorStep>,
+
+    /// Start over after the last step, instead of staying on it.
+    #[serde(default)]
+    pub repeat: bool,
+}
+
+impl Behavior {
+    /// Slowest and fastest multiples of the level speed that an obstacle
+    /// with this behavior scrolls at.
+    pub fn speed_scale_range(&self) -> [f32; 2] {
+        self.steps
+            .iter()
+            .filter_map(|step| match step.action {
+                BehaviorAction::SetSpeed(scale) => Some(scale),
+                _ => None,
+            })
+            .fold([1.0, 1.0], |[slowest, fastest], scale| {
+                [slowest.min(scale), fastest.max(scale)]
+            })
+    }
+}
+
+/// Multiple of the level speed that an obstacle scrolls at.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone, Copy)]
+#[reflect(Component)]
+pub struct SpeedScale(pub f32);
+
+/// A behavior playing on an obstacle.
+#[derive(Component, Reflect, Serialize, Deserialize, Clone)]
+#[reflect(Component)]
+pub struct ActiveBehavior {
+    behavior: Behavior,
+
+    /// Step being played, once started.
+    step: Option<usize>,
+
+    /// Time into the current step.
+    elapsed: f32,
+
+    colliders_on: bool,
+}
+
+impl ActiveBehavior {
+    pub fn new(behavior: Behavior) -> Self {
+        Self {
+            behavior,
+            step: None,
+            elapsed: 0.0,
+            colliders_on: true,
+        }
+    }
+
+    /// Advance by `delta` seconds, returning the actions of the steps
+    /// started along the way.
+    fn advance(&mut self, delta: f32) -> Vec<BehaviorAction> {
+        let steps = &self.behavior.steps;
+        let mut started = Vec::new();
+        let Some(mut step) = self.step else {
+            if let Some(first) = steps.first() {
+                self.step = Some(0);
+                started.push(first.action.clone());
+            }
+            return started;
+        };
+        self.elapsed += delta;
+        // each step takes at least a frame, so a behavior of zero length
+        // steps can't spin forever.
+        while self.elapsed >= steps[step].secs && started.len() < steps.len() {
+            let next = if step + 1 < steps.len() {
+                step + 1
+            } else if self.behavior.repeat {
+                0
+            } else {
+                break;
+            };
+            self.elapsed -= steps[step].secs;
+            step = next;
+            started.push(steps[step].action.clone());
+        }
+        self.step = Some(step);
+        started
+    }
+}
+
+/// Turn off the colliders of obstacles whose behavior was restored with
+/// them off. Other actions last in the obstacle's own components, which
+/// are restored along with it.
+fn restore_behavior_colliders(
+    mut commands: Commands,
+    obstacles: Query<(Entity, &ActiveBehavior), Added<ActiveBehavior>>,
+    children: Query<&Children>,
+    solid: Query<(), (With<Collider>, Without<Sensor>)>,
+) {
+    for (ent, behavior) in obstacles.iter() {
+        if behavior.colliders_on {
+            continue;
+        }
+        let parts = std::iter::once(ent).chain(children.iter_descendants(ent));
+        for part in parts.filter(|e| solid.contains(*e)) {
+            commands.entity(part).insert(ColliderDisabled);
+        }
+    }
+}
+
+/// Play the behaviors of obstacles, applying the actions of each step as it
+/// starts.
+fn run_behaviors(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut obstacles: Query<(Entity, &mut ActiveBehavior, &mut Velocity)>,
+    children: Query<&Children>,
+    solid: Query<(), (With<Collider>, Without<Sensor>)>,
+) {
+    for (ent, mut behavior, mut vel) in obstacles.iter_mut() {
+        for action in behavior.advance(time.delta_seconds()) {
+            match action {
+                BehaviorAction::MoveY(speed) => {
+                    commands.entity(ent).insert(Drift(speed));
+                }
+                BehaviorAction::SetSpeed(scale) => {
+                    commands.entity(ent).insert(SpeedScale(scale));
+                }
+                BehaviorAction::Rotate(speed) => vel.angvel = speed,
+                BehaviorAction::ToggleCollider => {
+                    behavior.colliders_on = !behavior.colliders_on;
+                    let parts = std::iter::once(ent).chain(children.iter_descendants(ent));
+                    for part in parts.filter(|e| solid.contains(*e)) {
+                        if behavior.colliders_on {
+                            commands.entity(part).remove::<ColliderDisabled>();
+                        } else {
+                            commands.entity(part).insert(ColliderDisabled);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub struct BehaviorPlugin;
+
+impl Plugin for BehaviorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ActiveBehavior>()
+            .register_type::<SpeedScale>()
+            .add_systems(
+                Update,
+                // after the level drift, so that a behavior's own drift
+                // wins on the frame an obstacle spawns.
+                (
+                    run_behaviors.after(drift_new_tunnels),
+                    restore_behavior_colliders,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
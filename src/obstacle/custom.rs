@@ -0,0 +1,114 @@
+//! Custom obstacles: items of a fixed shape, defined by mods, that are mixed
+//! in with the items of every level.
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use futures_lite::AsyncReadExt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::obstacle::SpawnParams;
+
+/// A named item, spawned as is whenever the spawner picks it.
+#[derive(Reflect, Debug, Serialize, Deserialize, Clone)]
+pub struct ObstacleDefinition {
+    pub name: String,
+
+    /// Chance of being picked, weighed against the item weights of the
+    /// level.
+    pub weight: f32,
+
+    pub params: SpawnParams,
+}
+
+/// Custom obstacles of one file.
+#[derive(Asset, Reflect, Debug, Serialize, Deserialize, Clone)]
+pub struct ObstacleSet {
+    pub obstacles: Vec<ObstacleDefinition>,
+}
+
+impl ObstacleSet {
+    /// Check that every obstacle can be spawned among the level's items.
+    pub fn validate(&self) -> Result<(), InvalidObstacle> {
+        for obstacle in &self.obstacles {
+            let name = || obstacle.name.clone();
+            if !(obstacle.weight.is_finite() && obstacle.weight >= 0.0) {
+                return Err(InvalidObstacle::BadWeight(name()));
+            }
+            let width = obstacle.params.width();
+            if !(width.is_finite() && width > 0.0) {
+                return Err(InvalidObstacle::BadWidth(name()));
+            }
+            // the spawner places these itself, alternating gravity and
+            // pairing doors with their keys.
+            if matches!(
+                obstacle.params,
+                SpawnParams::Gravity { .. } | SpawnParams::Door(_)
+            ) {
+                return Err(InvalidObstacle::LevelOnly(name()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reasons that a custom obstacle can't be spawned.
+#[derive(Debug, Error)]
+pub enum InvalidObstacle {
+    #[error("obstacle {0:?} needs a weight of zero or more")]
+    BadWeight(String),
+    #[error("obstacle {0:?} needs a positive width")]
+    BadWidth(String),
+    #[error("obstacle {0:?} is a gravity region or door, which only levels spawn")]
+    LevelOnly(String),
+}
+
+#[derive(Default)]
+pub struct ObstacleSetLoader;
+
+/// Possible errors that can be produced by [`ObstacleSetLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ObstacleSetLoaderError {
+    /// An [IO](std::io) Error
+    #[error("IO error while loading file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON](ron) Error
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for ObstacleSetLoader {
+    type Asset = ObstacleSet;
+    type Settings = ();
+    type Error = ObstacleSetLoaderError;
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<ObstacleSet>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["obstacles.ron"]
+    }
+}
+
+pub struct ObstacleSetPlugin;
+
+impl Plugin for ObstacleSetPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ObstacleSet>()
+            .register_type::<ObstacleSet>()
+            .register_type::<ObstacleDefinition>()
+            .init_asset_loader::<ObstacleSetLoader>();
+    }
+}
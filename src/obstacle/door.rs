@@ -0,0 +1,211 @@
+//! Locked doors, and the keys that open them.
+//!
+//! A door is a wall across the whole height of the level. The spawner
+//! spawns its key an item or two ahead of it, and a player holding the key
+//! as the door comes near spends it to slide the door open, leaving a gap
+//! to fly through. Without the key, the door can't be passed.
+use std::time::Duration;
+
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_asset_loader::loading_state::LoadingStateAppExt;
+use bevy_rapier2d::prelude::*;
+use bevy_tweening::{lens::TransformPositionLens, Animator, EaseFunction, Tween};
+
+use crate::{
+    obstacle::{DoorParams, KeyParams},
+    player::Player,
+    world_collision_groups, GameState, ResetEvent, WorldSettings,
+};
+
+/// Time for a door to slide open.
+const DOOR_OPEN_SECS: f32 = 0.4;
+
+/// Items held by the player in the current run.
+#[derive(Resource, Reflect, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Resource)]
+pub struct Inventory {
+    pub has_key: bool,
+}
+
+/// Key that opens the next locked door.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct KeyPickup;
+
+/// A door that hasn't been opened yet.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LockedDoor {
+    params: DoorParams,
+}
+
+impl LockedDoor {
+    pub fn new(params: &DoorParams) -> Self {
+        Self {
+            params: params.clone(),
+        }
+    }
+}
+
+/// Top or bottom half of a door, which slides away from the other as the
+/// door opens.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DoorHalf {
+    pub top: bool,
+}
+
+/// Sent when the player picks up a key.
+#[derive(Event)]
+pub struct KeyCollectedEvent;
+
+/// Sent when a door opens, with the door entity.
+#[derive(Event)]
+pub struct DoorOpenedEvent(pub Entity);
+
+/// Materials for doors and keys, set up once assets have loaded.
+#[derive(Resource)]
+pub struct DoorAssets {
+    pub(crate) door_mat: Handle<ColorMaterial>,
+    key_mat: Handle<ColorMaterial>,
+}
+
+impl FromWorld for DoorAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        DoorAssets {
+            door_mat: materials.add(ColorMaterial::from(Color::rgb(0.45, 0.3, 0.15))),
+            key_mat: materials.add(ColorMaterial::from(Color::rgb(1.0, 0.85, 0.2))),
+        }
+    }
+}
+
+/// Create a key pickup centered at the level space position `center`.
+pub fn new_key_pickup(
+    params: &KeyParams,
+    center: Vec2,
+    play_world: &WorldSettings,
+    meshes: &mut Assets<Mesh>,
+    assets: &DoorAssets,
+) -> impl Bundle {
+    let mesh = meshes.add(Mesh::from(shape::Quad::new(Vec2::splat(params.size))));
+    let mut transform = play_world.level_transform(center, 3.0);
+    // a diamond, to stand apart from the barriers.
+    transform.rotate_local_z(std::f32::consts::FRAC_PI_4);
+    (
+        MaterialMesh2dBundle {
+            mesh: mesh.into(),
+            material: assets.key_mat.clone(),
+            transform,
+            ..default()
+        },
+        Collider::cuboid(params.size / 2.0, params.size / 2.0),
+        world_collision_groups(),
+        Sensor,
+        RigidBody::KinematicVelocityBased,
+        TransformInterpolation::default(),
+        KeyPickup,
+    )
+}
+
+/// Level space y offset of a door half from the door's gap center, once
+/// the door is open.
+pub fn open_offset(params: &DoorParams, top: bool) -> f32 {
+    let offset = params.gap_height / 2.0;
+    if top {
+        offset
+    } else {
+        -offset
+    }
+}
+
+fn collect_keys(
+    mut commands: Commands,
+    rapier: Res<RapierContext>,
+    keys: Query<Entity, With<KeyPickup>>,
+    player: Query<Entity, With<Player>>,
+    mut inventory: ResMut<Inventory>,
+    mut collected: EventWriter<KeyCollectedEvent>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    for key in keys.iter() {
+        if rapier.intersection_pair(player, key) == Some(true) {
+            inventory.has_key = true;
+            collected.send(KeyCollectedEvent);
+            commands.entity(key).despawn_recursive();
+        }
+    }
+}
+
+/// Spend the key to open a locked door, once it is close enough to the
+/// player.
+fn open_doors(
+    mut commands: Commands,
+    doors: Query<(Entity, &Transform, &LockedDoor, &Children)>,
+    halves: Query<(&Transform, &DoorHalf)>,
+    player: Query<&Transform, With<Player>>,
+    mut inventory: ResMut<Inventory>,
+    play_world: Res<WorldSettings>,
+    mut opened: EventWriter<DoorOpenedEvent>,
+) {
+    if !inventory.has_key {
+        return;
+    }
+    let Ok(player_t) = player.get_single() else {
+        return;
+    };
+    let axis = play_world.scroll_axis;
+    let player_x = axis.to_level(player_t.translation.truncate()).x;
+    for (door, t, locked, children) in doors.iter() {
+        let door_x = axis.to_level(t.translation.truncate()).x;
+        let distance = door_x - player_x;
+        if distance < 0.0 || distance > locked.params.open_distance {
+            continue;
+        }
+        inventory.has_key = false;
+        for child in children.iter() {
+            let Ok((half_t, half)) = halves.get(*child) else {
+                continue;
+            };
+            let start = half_t.translation;
+            let end = start + Vec3::Y * open_offset(&locked.params, half.top);
+            commands.entity(*child).insert(Animator::new(Tween::new(
+                EaseFunction::QuadraticOut,
+                Duration::from_secs_f32(DOOR_OPEN_SECS),
+                TransformPositionLens { start, end },
+            )));
+        }
+        commands.entity(door).remove::<LockedDoor>();
+        opened.send(DoorOpenedEvent(door));
+        // one key opens one door.
+        break;
+    }
+}
+
+fn reset_inventory(mut inventory: ResMut<Inventory>) {
+    *inventory = Inventory::default();
+}
+
+pub struct DoorPlugin;
+
+impl Plugin for DoorPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Inventory>()
+            .register_type::<KeyPickup>()
+            .register_type::<LockedDoor>()
+            .register_type::<DoorHalf>()
+            .init_resource::<Inventory>()
+            .init_resource_after_loading_state::<_, DoorAssets>(GameState::AssetLoading)
+            .add_event::<KeyCollectedEvent>()
+            .add_event::<DoorOpenedEvent>()
+            .add_systems(
+                Update,
+                (collect_keys, open_doors)
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(PostUpdate, reset_inventory.run_if(on_event::<ResetEvent>()));
+    }
+}
@@ -0,0 +1,227 @@
+//! Drift regions, which set obstacles ahead of the player drifting up or
+//! down as it passes through them, without touching its gravity.
+//!
+//! Drifting obstacles keep scrolling at the level speed, with the level
+//! space y velocity of their [`Drift`] on top. Tunnels spawned after a drift
+//! region is passed drift the same way, so the stream keeps weaving until
+//! the next region flips it. A tunnel stops drifting once its gap reaches
+//! the top or bottom edge of the level.
+//!
+//! Obstacles with a [`Bounce`] drift from the start, and turn around at the
+//! top and bottom edges of the level.
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_asset_loader::loading_state::LoadingStateAppExt;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    obstacle::{
+        behavior::ActiveBehavior,
+        region::{Consumed, Region, RegionKind},
+        SpawnParams,
+    },
+    player::Player,
+    world_collision_groups, GameState, ResetEvent, WorldSettings,
+};
+
+/// Level space y velocity of an obstacle on top of the scroll speed, in
+/// pixels per second.
+#[derive(Component, Reflect, Clone, Copy, Default)]
+#[reflect(Component)]
+pub struct Drift(pub f32);
+
+/// Reverses the drift of an obstacle when it reaches the top or bottom edge
+/// of the level.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Bounce {
+    /// Half the level space height of the obstacle.
+    pub half_height: f32,
+}
+
+/// Drift given to tunnels as they spawn, from the last drift region passed.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct ObstacleDrift {
+    pub current: f32,
+}
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub(crate) struct DriftRegion {
+    drift: f32,
+}
+
+/// Materials for drift regions, set up once assets have loaded.
+#[derive(Resource)]
+pub struct DriftAssets {
+    up_mat: Handle<ColorMaterial>,
+    down_mat: Handle<ColorMaterial>,
+
+    /// material for regions that have been passed
+    passed_mat: Handle<ColorMaterial>,
+}
+
+impl FromWorld for DriftAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        DriftAssets {
+            up_mat: materials.add(ColorMaterial::from(Color::rgba(0.2, 0.8, 0.9, 0.35))),
+            down_mat: materials.add(ColorMaterial::from(Color::rgba(0.9, 0.4, 0.8, 0.35))),
+            passed_mat: materials.add(ColorMaterial::from(Color::rgba(0.5, 0.5, 0.5, 0.15))),
+        }
+    }
+}
+
+/// Create a new drift region, centered at the level space x position
+/// `start_x`.
+pub fn new_drift_region(
+    drift: f32,
+    start_x: f32,
+    width: f32,
+    play_world: &WorldSettings,
+    meshes: &mut Assets<Mesh>,
+    assets: &DriftAssets,
+) -> impl Bundle {
+    let height = play_world.level_bounds().height();
+    let mesh = meshes.add(Mesh::from(shape::Quad::new(Vec2::new(width, height))));
+    (
+        MaterialMesh2dBundle {
+            mesh: mesh.into(),
+            material: if drift > 0.0 {
+                assets.up_mat.clone()
+            } else {
+                assets.down_mat.clone()
+            },
+            transform: play_world.level_transform(Vec2::new(start_x, 0.0), 3.0),
+            ..default()
+        },
+        Collider::cuboid(width * 0.5, height * 0.5),
+        world_collision_groups(),
+        Sensor,
+        RigidBody::KinematicVelocityBased,
+        TransformInterpolation::default(),
+        DriftRegion { drift },
+        Region::new(RegionKind::Drift),
+    )
+}
+
+/// Set the drift of the tunnels ahead of the player when it passes through a
+/// drift region. Tunnels with a behavior move as it says instead.
+#[allow(clippy::too_many_arguments)]
+fn check_drift_region_collisions(
+    mut commands: Commands,
+    rapier: Res<RapierContext>,
+    regions: Query<(Entity, &DriftRegion), Without<Consumed>>,
+    player: Query<(Entity, &Transform), With<Player>>,
+    tunnels: Query<(Entity, &Transform, &SpawnParams), Without<ActiveBehavior>>,
+    assets: Res<DriftAssets>,
+    play_world: Res<WorldSettings>,
+    mut drift: ResMut<ObstacleDrift>,
+) {
+    let Ok((player, player_t)) = player.get_single() else {
+        return;
+    };
+    let axis = play_world.scroll_axis;
+    let player_x = axis.to_level(player_t.translation.truncate()).x;
+    for (region_entity, region) in regions.iter() {
+        if rapier.intersection_pair(player, region_entity) != Some(true) {
+            continue;
+        }
+        drift.current = region.drift;
+        for (tunnel, t, params) in tunnels.iter() {
+            let ahead = axis.to_level(t.translation.truncate()).x > player_x;
+            if ahead && matches!(params, SpawnParams::Tunnel(_)) {
+                commands.entity(tunnel).insert(Drift(region.drift));
+            }
+        }
+        commands
+            .entity(region_entity)
+            .insert((Consumed, assets.passed_mat.clone()));
+    }
+}
+
+/// Give newly spawned tunnels the current drift, unless they already
+/// have their own.
+#[allow(clippy::type_complexity)]
+pub(crate) fn drift_new_tunnels(
+    mut commands: Commands,
+    items: Query<(Entity, &SpawnParams), (Added<SpawnParams>, Without<Drift>)>,
+    drift: Res<ObstacleDrift>,
+) {
+    if drift.current == 0.0 {
+        return;
+    }
+    for (ent, params) in items.iter() {
+        if matches!(params, SpawnParams::Tunnel(_)) {
+            commands.entity(ent).insert(Drift(drift.current));
+        }
+    }
+}
+
+/// Stop drifting tunnels once their gap reaches the top or bottom edge of
+/// the level, so that it can still be passed through.
+fn hold_gaps_in_bounds(
+    mut tunnels: Query<(&Transform, &SpawnParams, &mut Drift)>,
+    play_world: Res<WorldSettings>,
+) {
+    let bounds = play_world.level_bounds();
+    for (t, params, mut drift) in tunnels.iter_mut() {
+        let SpawnParams::Tunnel(tunnel) = params else {
+            continue;
+        };
+        let y = play_world.scroll_axis.to_level(t.translation.truncate()).y;
+        let gap_center = tunnel.gap_center + y;
+        if gap_center + tunnel.gap_height / 2.0 >= bounds.max.y && drift.0 > 0.0 {
+            drift.0 = 0.0;
+        } else if gap_center - tunnel.gap_height / 2.0 <= bounds.min.y && drift.0 < 0.0 {
+            drift.0 = 0.0;
+        }
+    }
+}
+
+/// Turn bouncing obstacles around at the level edges. Kinematic bodies
+/// don't collide with the world bounds, so this stands in for a bounce.
+fn bounce_off_bounds(
+    mut obstacles: Query<(&Transform, &Bounce, &mut Drift)>,
+    play_world: Res<WorldSettings>,
+) {
+    let bounds = play_world.level_bounds();
+    for (t, bounce, mut drift) in obstacles.iter_mut() {
+        let y = play_world.scroll_axis.to_level(t.translation.truncate()).y;
+        // only ever turn towards the middle, so a bounce can't be undone by
+        // the next frame.
+        if y + bounce.half_height >= bounds.max.y && drift.0 > 0.0 {
+            drift.0 = -drift.0;
+        } else if y - bounce.half_height <= bounds.min.y && drift.0 < 0.0 {
+            drift.0 = -drift.0;
+        }
+    }
+}
+
+fn reset_drift(mut drift: ResMut<ObstacleDrift>) {
+    drift.current = 0.0;
+}
+
+pub struct DriftPlugin;
+
+impl Plugin for DriftPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Drift>()
+            .register_type::<ObstacleDrift>()
+            .register_type::<DriftRegion>()
+            .register_type::<Bounce>()
+            .init_resource::<ObstacleDrift>()
+            .init_resource_after_loading_state::<_, DriftAssets>(GameState::AssetLoading)
+            .add_systems(
+                Update,
+                (
+                    check_drift_region_collisions,
+                    drift_new_tunnels,
+                    bounce_off_bounds,
+                    hold_gaps_in_bounds,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(PostUpdate, reset_drift.run_if(on_event::<ResetEvent>()));
+    }
+}
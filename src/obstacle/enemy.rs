@@ -0,0 +1,123 @@
+//! Drones: enemies that fly in from the right, faster than the level
+//! scrolls, and steer towards the player.
+//!
+//! A drone turns towards the player's height at a limited rate, so a jump
+//! at the right moment leaves it flying past. Once it is behind the player
+//! it holds its heading. Its hull is a [`Barrier`], so touching a drone is a
+//! barrier hit, forgiven or blocked the same way.
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_asset_loader::loading_state::LoadingStateAppExt;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    barrier::Barrier, barrier_collision_groups, drift::Drift, obstacle::behavior::SpeedScale,
+    obstacle_spawner::SpeedController, player::Player, world_solver_groups, GameState,
+    WorldSettings,
+};
+
+/// Steepest heading a drone flies at, in radians from straight ahead.
+pub const MAX_DRONE_HEADING: f32 = 1.0;
+
+/// Steering state of a drone.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct Drone {
+    /// Level space angle of flight from straight ahead, in radians.
+    /// Positive is up.
+    pub heading: f32,
+
+    /// Fastest the drone turns, in radians per second.
+    pub turn_rate: f32,
+}
+
+/// Hull of a drone, rotated to its heading.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct DroneHull;
+
+/// Material for drone hulls, set up once assets have loaded.
+#[derive(Resource)]
+pub struct DroneAssets {
+    hull_mat: Handle<ColorMaterial>,
+}
+
+impl FromWorld for DroneAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        DroneAssets {
+            hull_mat: materials.add(ColorMaterial::from(Color::rgb(0.9, 0.15, 0.2))),
+        }
+    }
+}
+
+/// Create the hull of a drone, `size` pixels across, to be attached as a
+/// child of its moving body.
+pub fn new_drone_hull(size: f32, meshes: &mut Assets<Mesh>, assets: &DroneAssets) -> impl Bundle {
+    let mesh = meshes.add(Mesh::from(shape::Quad::new(Vec2::splat(size))));
+    (
+        MaterialMesh2dBundle {
+            mesh: mesh.into(),
+            material: assets.hull_mat.clone(),
+            transform: Transform::from_xyz(0.0, 0.0, 2.0),
+            ..default()
+        },
+        DroneHull,
+        Barrier,
+        Collider::cuboid(size / 2.0, size / 2.0),
+        barrier_collision_groups(),
+        world_solver_groups(),
+        ActiveEvents::COLLISION_EVENTS,
+    )
+}
+
+/// Level space y velocity of a drone flying `forward` pixels per second at
+/// `heading`.
+pub fn drone_drift(forward: f32, heading: f32) -> f32 {
+    forward * heading.tan()
+}
+
+/// Turn drones ahead of the player towards it, and set their drift to match
+/// their heading.
+#[allow(clippy::type_complexity)]
+fn steer_drones(
+    mut drones: Query<(&Transform, &mut Drone, &mut Drift, &SpeedScale, &Children)>,
+    mut hulls: Query<&mut Transform, (With<DroneHull>, Without<Drone>)>,
+    player: Query<&Transform, (With<Player>, Without<Drone>, Without<DroneHull>)>,
+    speed: Res<SpeedController>,
+    play_world: Res<WorldSettings>,
+    time: Res<Time>,
+) {
+    let axis = play_world.scroll_axis;
+    let player = player
+        .get_single()
+        .ok()
+        .map(|t| axis.to_level(t.translation.truncate()));
+    for (t, mut drone, mut drift, scale, children) in drones.iter_mut() {
+        let pos = axis.to_level(t.translation.truncate());
+        if let Some(target) = player.filter(|p| p.x < pos.x) {
+            let wanted = (target.y - pos.y)
+                .atan2(pos.x - target.x)
+                .clamp(-MAX_DRONE_HEADING, MAX_DRONE_HEADING);
+            let max_turn = drone.turn_rate * time.delta_seconds();
+            drone.heading += (wanted - drone.heading).clamp(-max_turn, max_turn);
+        }
+        drift.0 = drone_drift(speed.current.x.abs() * scale.0, drone.heading);
+        for child in children.iter() {
+            if let Ok(mut hull) = hulls.get_mut(*child) {
+                // the hull faces the way the drone flies, to the left.
+                hull.rotation = Quat::from_rotation_z(-drone.heading);
+            }
+        }
+    }
+}
+
+pub struct EnemyPlugin;
+
+impl Plugin for EnemyPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Drone>()
+            .register_type::<DroneHull>()
+            .init_resource_after_loading_state::<_, DroneAssets>(GameState::AssetLoading)
+            .add_systems(Update, steer_drones.run_if(in_state(GameState::Playing)));
+    }
+}
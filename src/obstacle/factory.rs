@@ -0,0 +1,748 @@
+//! Spawning of obstacles, shared by the spawner, session restore, dev
+//! tools and tests.
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_rapier2d::prelude::*;
+use rand::Rng;
+
+use crate::{
+    barrier::{new_barrier, BarrierAssets},
+    drift::{new_drift_region, Bounce, Drift, DriftAssets},
+    gravity_shift::{new_gravity_region, GravityMaterials, GravityShiftMaterial},
+    level::{RemoveOnReset, RemoveWhenLeft},
+    obstacle::{
+        behavior::{ActiveBehavior, SpeedScale},
+        door::{new_key_pickup, open_offset, DoorAssets, DoorHalf, LockedDoor},
+        enemy::{drone_drift, new_drone_hull, Drone, DroneAssets},
+        laser::{new_laser_beam, new_laser_post, LaserAssets, LaserGate},
+        spawner_settings::{
+            BouncerSpawnSettings, DoorSpawnSettings, DriftRegionSettings, DroneSpawnSettings,
+            GravityChainSettings, GravityRegionSettings, LaserSpawnSettings, PowerUpSpawnSettings,
+            TunnelSpawnSettings,
+        },
+        BouncerParams, DoorParams, DroneParams, KeyParams, LaserParams, Obstacle, PowerUpParams,
+        SpawnParams, TunnelParams,
+    },
+    obstacle_spawner::SpeedController,
+    power_ups::{new_power_up_pickup, PowerUpAssets, PowerUpKind},
+    scoring_region::new_scoring_region,
+    util::{spawn_named_child, Containers},
+    WorldSettings,
+};
+
+/// Entities of a spawned gravity chain.
+pub struct GravityChain {
+    pub first: Entity,
+    pub tunnel: Entity,
+    pub second: Entity,
+
+    /// Level space width of the whole chain.
+    pub width: f32,
+}
+
+/// Everything needed to spawn obstacles into the level.
+///
+/// Items are spawned moving at the speed of the [`SpeedController`], so they
+/// keep pace with the rest of the level.
+#[derive(SystemParam)]
+pub struct ObstacleFactory<'w, 's> {
+    pub commands: Commands<'w, 's>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    obs_mat: Res<'w, BarrierAssets>,
+    grav_mat: ResMut<'w, GravityMaterials>,
+    grav_materials: ResMut<'w, Assets<GravityShiftMaterial>>,
+    drift_assets: Res<'w, DriftAssets>,
+    drone_assets: Res<'w, DroneAssets>,
+    laser_assets: Res<'w, LaserAssets>,
+    door_assets: Res<'w, DoorAssets>,
+    power_up_assets: Res<'w, PowerUpAssets>,
+    play_world: Res<'w, WorldSettings>,
+    speed: Res<'w, SpeedController>,
+    containers: Res<'w, Containers>,
+}
+
+impl ObstacleFactory<'_, '_> {
+    /// Spawn a tunnel with its left edge at the level space x position `x`.
+    ///
+    /// Returns the tunnel and scoring region entities.
+    pub fn spawn_tunnel_at(&mut self, x: f32, params: &TunnelParams) -> (Entity, Entity) {
+        spawn_tunnel_at(
+            &mut self.commands,
+            self.containers.obstacles,
+            params,
+            x,
+            self.speed.current,
+            &mut self.meshes,
+            &self.play_world,
+            &self.obs_mat,
+        )
+    }
+
+    /// Spawn a tunnel with a random gap drawn from `settings`, with its left
+    /// edge at the level space x position `x`.
+    ///
+    /// Returns the tunnel entity and its gap height.
+    pub fn spawn_random_tunnel(
+        &mut self,
+        x: f32,
+        settings: &TunnelSpawnSettings,
+        rng: &mut impl Rng,
+    ) -> (Entity, f32) {
+        let params = settings.random_params(rng);
+        let (tunnel, _) = self.spawn_tunnel_at(x, &params);
+        if let Some(behavior) = &settings.behavior {
+            self.commands
+                .entity(tunnel)
+                .insert(ActiveBehavior::new(behavior.clone()));
+        }
+        (tunnel, params.gap_height)
+    }
+
+    /// Spawn a gravity region setting the gravity mult to `gravity_mult`,
+    /// with its left edge at the level space x position `x`.
+    pub fn spawn_gravity_at(
+        &mut self,
+        x: f32,
+        gravity_mult: f32,
+        settings: &GravityRegionSettings,
+    ) -> Entity {
+        let width = settings.gravity_width;
+        spawn_gravity_region(
+            &mut self.commands,
+            self.containers.obstacles,
+            gravity_mult,
+            settings.pass_score,
+            x + width * 0.5,
+            width,
+            self.speed.current,
+            &self.play_world,
+            &mut self.grav_mat,
+            &mut self.grav_materials,
+        )
+    }
+
+    /// Spawn a bouncer with its left edge at the level space x position `x`.
+    pub fn spawn_bouncer_at(&mut self, x: f32, params: &BouncerParams) -> Entity {
+        let vel = Velocity {
+            linvel: self
+                .play_world
+                .scroll_axis
+                .to_world(self.speed.current + Vec2::new(0.0, params.drift)),
+            ..default()
+        };
+        let bouncer = spawn_named_child(
+            &mut self.commands,
+            self.containers.obstacles,
+            "bouncer",
+            (
+                SpatialBundle {
+                    transform: self
+                        .play_world
+                        .level_transform(Vec2::new(x + params.width / 2.0, params.center_y), 0.0),
+                    ..default()
+                },
+                RigidBody::KinematicVelocityBased,
+                TransformInterpolation::default(),
+                RemoveWhenLeft(params.width),
+                RemoveOnReset,
+                vel,
+                Obstacle,
+                Drift(params.drift),
+                Bounce {
+                    half_height: params.height / 2.0,
+                },
+                SpawnParams::Bouncer(params.clone()),
+            ),
+        )
+        .id();
+        let barrier = new_barrier(
+            true,
+            params.width,
+            params.height,
+            0.0,
+            &mut self.meshes,
+            &self.play_world,
+            &self.obs_mat,
+        );
+        spawn_named_child(&mut self.commands, bouncer, "barrier", barrier)
+            // centered on the body, rather than hanging from the top.
+            .insert(Transform::from_xyz(0.0, 0.0, 2.0));
+        bouncer
+    }
+
+    /// Spawn a bouncer of random height and speed drawn from `settings`,
+    /// anywhere it fits in the level, with its left edge at the level space x
+    /// position `x`.
+    pub fn spawn_random_bouncer(
+        &mut self,
+        x: f32,
+        settings: &BouncerSpawnSettings,
+        rng: &mut impl Rng,
+    ) -> Entity {
+        let lerp = |range: [f32; 2], t: f32| range[0] + t * (range[1] - range[0]);
+        let height = lerp(settings.height_range, rng.gen());
+        let bounds = self.play_world.level_bounds();
+        let free = (bounds.height() - height).max(0.0);
+        let speed = lerp(settings.drift_speed_range, rng.gen());
+        let params = BouncerParams {
+            center_y: bounds.min.y + height / 2.0 + rng.gen::<f32>() * free,
+            width: settings.width,
+            height,
+            drift: if rng.gen() { speed } else { -speed },
+        };
+        let bouncer = self.spawn_bouncer_at(x, &params);
+        if let Some(behavior) = &settings.behavior {
+            self.commands
+                .entity(bouncer)
+                .insert(ActiveBehavior::new(behavior.clone()));
+        }
+        bouncer
+    }
+
+    /// Spawn a drone with its left edge at the level space x position `x`.
+    pub fn spawn_drone_at(&mut self, x: f32, params: &DroneParams) -> Entity {
+        let forward = self.speed.current * params.speed_scale;
+        let drift = drone_drift(forward.x.abs(), params.heading);
+        let vel = Velocity {
+            linvel: self
+                .play_world
+                .scroll_axis
+                .to_world(forward + Vec2::new(0.0, drift)),
+            ..default()
+        };
+        let drone = spawn_named_child(
+            &mut self.commands,
+            self.containers.obstacles,
+            "drone",
+            (
+                SpatialBundle {
+                    transform: self
+                        .play_world
+                        .level_transform(Vec2::new(x + params.size / 2.0, params.center_y), 0.0),
+                    ..default()
+                },
+                RigidBody::KinematicVelocityBased,
+                TransformInterpolation::default(),
+                RemoveWhenLeft(params.size),
+                RemoveOnReset,
+                vel,
+                Obstacle,
+                Drift(drift),
+                SpeedScale(params.speed_scale),
+                Drone {
+                    heading: params.heading,
+                    turn_rate: params.turn_rate,
+                },
+                SpawnParams::Drone(params.clone()),
+            ),
+        )
+        .id();
+        let hull = new_drone_hull(params.size, &mut self.meshes, &self.drone_assets);
+        spawn_named_child(&mut self.commands, drone, "hull", hull);
+        drone
+    }
+
+    /// Spawn a drone flying straight ahead from anywhere it fits in the
+    /// level, with its left edge at the level space x position `x`.
+    pub fn spawn_random_drone(
+        &mut self,
+        x: f32,
+        settings: &DroneSpawnSettings,
+        rng: &mut impl Rng,
+    ) -> Entity {
+        let bounds = self.play_world.level_bounds();
+        let free = (bounds.height() - settings.size).max(0.0);
+        let params = DroneParams {
+            center_y: bounds.min.y + settings.size / 2.0 + rng.gen::<f32>() * free,
+            size: settings.size,
+            heading: 0.0,
+            turn_rate: settings.turn_rate,
+            speed_scale: settings.speed_scale,
+        };
+        self.spawn_drone_at(x, &params)
+    }
+
+    /// Spawn a laser gate with its left edge at the level space x position
+    /// `x`.
+    pub fn spawn_laser_at(&mut self, x: f32, params: &LaserParams) -> Entity {
+        let vel = Velocity {
+            linvel: self.play_world.scroll_axis.to_world(self.speed.current),
+            ..default()
+        };
+        let gate = spawn_named_child(
+            &mut self.commands,
+            self.containers.obstacles,
+            "laser_gate",
+            (
+                SpatialBundle {
+                    transform: self
+                        .play_world
+                        .level_transform(Vec2::new(x + params.width / 2.0, 0.0), 0.0),
+                    ..default()
+                },
+                RigidBody::KinematicVelocityBased,
+                TransformInterpolation::default(),
+                RemoveWhenLeft(params.width),
+                RemoveOnReset,
+                vel,
+                Obstacle,
+                LaserGate::new(params),
+                SpawnParams::Laser(params.clone()),
+            ),
+        )
+        .id();
+        let height = self.play_world.level_bounds().height();
+        for (name, from_top) in [("top_post", true), ("bottom_post", false)] {
+            let post = new_laser_post(
+                from_top,
+                params,
+                height,
+                &mut self.meshes,
+                &self.laser_assets,
+            );
+            spawn_named_child(&mut self.commands, gate, name, post);
+        }
+        let beam = new_laser_beam(params, height, &mut self.meshes, &self.laser_assets);
+        spawn_named_child(&mut self.commands, gate, "beam", beam);
+        gate
+    }
+
+    /// Spawn a laser gate with its cycle from `settings`, starting at a
+    /// random point in the cycle, with its left edge at the level space x
+    /// position `x`.
+    pub fn spawn_random_laser(
+        &mut self,
+        x: f32,
+        settings: &LaserSpawnSettings,
+        rng: &mut impl Rng,
+    ) -> Entity {
+        let params = LaserParams {
+            width: settings.width,
+            post_height: settings.post_height,
+            beam_width: settings.beam_width,
+            on_secs: settings.on_secs,
+            off_secs: settings.off_secs,
+            warning_secs: settings.warning_secs,
+            phase_secs: rng.gen::<f32>() * (settings.on_secs + settings.off_secs),
+        };
+        self.spawn_laser_at(x, &params)
+    }
+
+    /// Spawn a key pickup with its left edge at the level space x position
+    /// `x`.
+    pub fn spawn_key_at(&mut self, x: f32, params: &KeyParams) -> Entity {
+        let vel = Velocity {
+            linvel: self.play_world.scroll_axis.to_world(self.speed.current),
+            ..default()
+        };
+        let key = new_key_pickup(
+            params,
+            Vec2::new(x + params.size / 2.0, params.center_y),
+            &self.play_world,
+            &mut self.meshes,
+            &self.door_assets,
+        );
+        spawn_named_child(&mut self.commands, self.containers.obstacles, "key", key)
+            .insert((
+                RemoveWhenLeft(params.size),
+                RemoveOnReset,
+                vel,
+                Obstacle,
+                SpawnParams::Key(params.clone()),
+            ))
+            .id()
+    }
+
+    /// Spawn a power up pickup with its left edge at the level space x
+    /// position `x`.
+    pub fn spawn_power_up_at(&mut self, x: f32, params: &PowerUpParams) -> Entity {
+        let vel = Velocity {
+            linvel: self.play_world.scroll_axis.to_world(self.speed.current),
+            ..default()
+        };
+        let pickup = new_power_up_pickup(
+            params,
+            Vec2::new(x + params.size / 2.0, params.center_y),
+            &self.play_world,
+            &mut self.meshes,
+            &self.power_up_assets,
+        );
+        spawn_named_child(
+            &mut self.commands,
+            self.containers.obstacles,
+            "power_up",
+            pickup,
+        )
+        .insert((
+            RemoveWhenLeft(params.size),
+            RemoveOnReset,
+            vel,
+            Obstacle,
+            SpawnParams::PowerUp(params.clone()),
+        ))
+        .id()
+    }
+
+    /// Spawn a pickup of a random power up at a random height, with its
+    /// left edge at the level space x position `x`.
+    pub fn spawn_random_power_up(
+        &mut self,
+        x: f32,
+        settings: &PowerUpSpawnSettings,
+        rng: &mut impl Rng,
+    ) -> Entity {
+        let bounds = self.play_world.level_bounds();
+        let free = (bounds.height() - settings.size).max(0.0);
+        let params = PowerUpParams {
+            center_y: bounds.min.y + settings.size / 2.0 + rng.gen::<f32>() * free,
+            size: settings.size,
+            kind: PowerUpKind::ALL[rng.gen_range(0..PowerUpKind::ALL.len())],
+        };
+        self.spawn_power_up_at(x, &params)
+    }
+
+    /// Spawn a door with its left edge at the level space x position `x`,
+    /// either locked or already open.
+    pub fn spawn_door_at(&mut self, x: f32, params: &DoorParams, locked: bool) -> Entity {
+        let vel = Velocity {
+            linvel: self.play_world.scroll_axis.to_world(self.speed.current),
+            ..default()
+        };
+        let door = spawn_named_child(
+            &mut self.commands,
+            self.containers.obstacles,
+            "door",
+            (
+                SpatialBundle {
+                    transform: self
+                        .play_world
+                        .level_transform(Vec2::new(x + params.width / 2.0, 0.0), 0.0),
+                    ..default()
+                },
+                RigidBody::KinematicVelocityBased,
+                TransformInterpolation::default(),
+                RemoveWhenLeft(params.width),
+                RemoveOnReset,
+                vel,
+                Obstacle,
+                SpawnParams::Door(params.clone()),
+            ),
+        )
+        .id();
+        if locked {
+            self.commands.entity(door).insert(LockedDoor::new(params));
+        }
+
+        let bounds = self.play_world.level_bounds();
+        for (name, top) in [("top_half", true), ("bottom_half", false)] {
+            let height = if top {
+                bounds.max.y - params.gap_center
+            } else {
+                params.gap_center - bounds.min.y
+            };
+            let half = new_barrier(
+                top,
+                params.width,
+                height,
+                0.0,
+                &mut self.meshes,
+                &self.play_world,
+                &self.obs_mat,
+            );
+            let mut half = spawn_named_child(&mut self.commands, door, name, half);
+            half.insert((DoorHalf { top }, self.door_assets.door_mat.clone()));
+            if !locked {
+                let center_y = if top {
+                    bounds.max.y - height / 2.0
+                } else {
+                    bounds.min.y + height / 2.0
+                };
+                half.insert(Transform::from_xyz(
+                    0.0,
+                    center_y + open_offset(params, top),
+                    2.0,
+                ));
+            }
+        }
+        door
+    }
+
+    /// Spawn a key with its left edge at the level space x position `x`,
+    /// anywhere it fits in the level.
+    pub fn spawn_random_key(
+        &mut self,
+        x: f32,
+        settings: &DoorSpawnSettings,
+        rng: &mut impl Rng,
+    ) -> Entity {
+        let bounds = self.play_world.level_bounds();
+        let key_free = (bounds.height() - settings.key_size).max(0.0);
+        let key = KeyParams {
+            center_y: bounds.min.y + settings.key_size / 2.0 + rng.gen::<f32>() * key_free,
+            size: settings.key_size,
+        };
+        self.spawn_key_at(x, &key)
+    }
+
+    /// Shape of a locked door with its gap anywhere it fits in the level,
+    /// to be spawned once its slot comes up.
+    pub fn random_door(&self, settings: &DoorSpawnSettings, rng: &mut impl Rng) -> DoorParams {
+        let bounds = self.play_world.level_bounds();
+        let gap_free = (bounds.height() - settings.gap_height).max(0.0);
+        DoorParams {
+            width: settings.width,
+            gap_center: bounds.min.y + settings.gap_height / 2.0 + rng.gen::<f32>() * gap_free,
+            gap_height: settings.gap_height,
+            open_distance: settings.open_distance,
+        }
+    }
+
+    /// Spawn a drift region setting tunnels drifting at `drift` pixels per
+    /// second, with its left edge at the level space x position `x`.
+    pub fn spawn_drift_at(&mut self, x: f32, drift: f32, settings: &DriftRegionSettings) -> Entity {
+        let width = settings.width;
+        let vel = Velocity {
+            linvel: self.play_world.scroll_axis.to_world(self.speed.current),
+            ..default()
+        };
+        let region = new_drift_region(
+            drift,
+            x + width * 0.5,
+            width,
+            &self.play_world,
+            &mut self.meshes,
+            &self.drift_assets,
+        );
+        spawn_named_child(
+            &mut self.commands,
+            self.containers.obstacles,
+            format!("drift {}", if drift > 0.0 { "up" } else { "down" }),
+            region,
+        )
+        .insert((
+            RemoveWhenLeft(width),
+            RemoveOnReset,
+            vel,
+            Obstacle,
+            SpawnParams::Drift { drift, width },
+        ))
+        .id()
+    }
+
+    /// Spawn the item described by `params`, with its left edge at the
+    /// level space x position `x`. Doors are spawned locked.
+    ///
+    /// Returns the entity holding the [`SpawnParams`].
+    pub fn spawn_params_at(&mut self, x: f32, params: &SpawnParams) -> Entity {
+        match params {
+            SpawnParams::Tunnel(params) => self.spawn_tunnel_at(x, params).0,
+            SpawnParams::Gravity {
+                gravity_mult,
+                width,
+                pass_score,
+            } => self.spawn_gravity_at(
+                x,
+                *gravity_mult,
+                &GravityRegionSettings {
+                    gravity_width: *width,
+                    pass_score: *pass_score,
+                },
+            ),
+            SpawnParams::Bouncer(params) => self.spawn_bouncer_at(x, params),
+            SpawnParams::Drone(params) => self.spawn_drone_at(x, params),
+            SpawnParams::Laser(params) => self.spawn_laser_at(x, params),
+            SpawnParams::Key(params) => self.spawn_key_at(x, params),
+            SpawnParams::PowerUp(params) => self.spawn_power_up_at(x, params),
+            SpawnParams::Door(params) => self.spawn_door_at(x, params, true),
+            SpawnParams::Drift { drift, width } => self.spawn_drift_at(
+                x,
+                *drift,
+                &DriftRegionSettings {
+                    width: *width,
+                    speed: drift.abs(),
+                },
+            ),
+        }
+    }
+
+    /// Spawn a gravity chain with its left edge at the level space x
+    /// position `x`. The first region sets the gravity mult to
+    /// `gravity_mult`, and the second sets it back to the opposite.
+    pub fn spawn_gravity_chain_at(
+        &mut self,
+        x: f32,
+        gravity_mult: f32,
+        chain: &GravityChainSettings,
+        gravity: &GravityRegionSettings,
+        tunnel: &TunnelSpawnSettings,
+    ) -> GravityChain {
+        let region_width = gravity.gravity_width;
+        let first = self.spawn_gravity_at(x, gravity_mult, gravity);
+
+        // jumps go against gravity, so the gap is on the side away from it.
+        let tunnel_x = x + region_width + chain.spacing;
+        let params = TunnelParams {
+            gap_center: chain.gap_offset * gravity_mult.signum(),
+            gap_height: chain.gap_height,
+            obstacle_width: tunnel.obstacle_width,
+            scoring_gap_width: tunnel.scoring_gap_width,
+            score_delta: tunnel.score_delta,
+        };
+        let (tunnel, _) = self.spawn_tunnel_at(tunnel_x, &params);
+
+        let second_x = tunnel_x + params.obstacle_width + chain.spacing;
+        let second = self.spawn_gravity_at(second_x, -gravity_mult, gravity);
+
+        GravityChain {
+            first,
+            tunnel,
+            second,
+            width: second_x + region_width - x,
+        }
+    }
+}
+
+/// Spawn a gravity region with the given gravity mult, centered at the level space x
+/// position `start_x`.
+///
+/// Returns the region entity.
+#[allow(clippy::too_many_arguments)]
+fn spawn_gravity_region(
+    commands: &mut Commands,
+    container: Entity,
+    gravity_mult: f32,
+    pass_score: i32,
+    start_x: f32,
+    width: f32,
+    item_vel: Vec2,
+    play_world: &WorldSettings,
+    grav_mat: &mut GravityMaterials,
+    materials: &mut Assets<GravityShiftMaterial>,
+) -> Entity {
+    let vel = Velocity {
+        linvel: play_world.scroll_axis.to_world(item_vel),
+        ..default()
+    };
+
+    let region = new_gravity_region(
+        gravity_mult,
+        pass_score,
+        start_x,
+        width,
+        play_world,
+        grav_mat,
+        materials,
+    );
+    spawn_named_child(
+        commands,
+        container,
+        format!("gravity {}", if gravity_mult > 0.0 { "down" } else { "up" }),
+        region,
+    )
+    .insert((
+        RemoveWhenLeft(width),
+        RemoveOnReset,
+        vel,
+        Obstacle,
+        SpawnParams::Gravity {
+            gravity_mult,
+            width,
+            pass_score,
+        },
+    ))
+    .id()
+}
+
+/// Spawn two barriers and a scoring region, with the left edge of the tunnel at the level
+/// space x position `start_x`, under `container`.
+///
+/// The barriers and scoring region are children of a single moving body, so they always stay
+/// in place relative to each other.
+///
+/// Returns the tunnel and scoring region entities.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_tunnel_at(
+    commands: &mut Commands,
+    container: Entity,
+    params: &TunnelParams,
+    start_x: f32,
+    item_vel: Vec2,
+    meshes: &mut ResMut<Assets<Mesh>>,
+    play_world: &Res<WorldSettings>,
+    obs_mat: &Res<BarrierAssets>,
+) -> (Entity, Entity) {
+    // create the level obstacles and the scoring region.
+    let vel = Velocity {
+        linvel: play_world.scroll_axis.to_world(item_vel),
+        ..default()
+    };
+    let level_bounds = play_world.level_bounds();
+
+    let gap_center = params.gap_center;
+    let gap_height = params.gap_height;
+    let obstacle_width = params.obstacle_width;
+
+    let top_height = level_bounds.max.y - (gap_center + gap_height / 2.0);
+    let bottom_height = (gap_center - gap_height / 2.0) - level_bounds.min.y;
+
+    let scoring_gap_height = level_bounds.height() - top_height - bottom_height;
+    let scoring_gap_width = params.scoring_gap_width;
+
+    let tunnel = spawn_named_child(
+        commands,
+        container,
+        "tunnel",
+        (
+            SpatialBundle {
+                transform: play_world
+                    .level_transform(Vec2::new(start_x + obstacle_width / 2.0, 0.0), 0.0),
+                ..default()
+            },
+            RigidBody::KinematicVelocityBased,
+            TransformInterpolation::default(),
+            RemoveWhenLeft(obstacle_width),
+            RemoveOnReset,
+            vel,
+            Obstacle,
+            SpawnParams::Tunnel(params.clone()),
+        ),
+    )
+    .id();
+
+    let region = new_scoring_region(
+        params.score_delta,
+        Vec2::new(obstacle_width / 2.0 - scoring_gap_width / 2.0, gap_center),
+        Vec2::new(scoring_gap_width, scoring_gap_height),
+    );
+    let region = spawn_named_child(commands, tunnel, "scoring_region", region).id();
+
+    // the barriers reach a level height past the edges, so that a drifting
+    // tunnel doesn't open a lane above or below it. Drift stops before the
+    // gap leaves the level, which is less than that.
+    let overhang = level_bounds.height();
+    let top = new_barrier(
+        true,
+        obstacle_width,
+        top_height + overhang,
+        0.0,
+        meshes,
+        play_world,
+        obs_mat,
+    );
+    let top_y = level_bounds.max.y - top_height / 2.0 + overhang / 2.0;
+    spawn_named_child(commands, tunnel, "top_barrier", top)
+        .insert(Transform::from_xyz(0.0, top_y, 2.0));
+    let bottom = new_barrier(
+        false,
+        obstacle_width,
+        bottom_height + overhang,
+        0.0,
+        meshes,
+        play_world,
+        obs_mat,
+    );
+    let bottom_y = level_bounds.min.y + bottom_height / 2.0 - overhang / 2.0;
+    spawn_named_child(commands, tunnel, "bottom_barrier", bottom)
+        .insert(Transform::from_xyz(0.0, bottom_y, 2.0));
+
+    (tunnel, region)
+}
@@ -1,13 +1,24 @@
 //! Gravity shifting 'obstacle. When the user runs into it, their gravity is shifted in teh corresponding direction.
-use crate::{level::LevelSettings, player::Player, GameState, WorldSettings};
+use crate::{
+    accessibility::MotionSettings,
+    asset_errors::GameCollectionAppExt,
+    level::LevelSettings,
+    obstacle::region::{track_regions, Consumed, Region, RegionEntered, RegionKind},
+    player::Player,
+    power_ups::GravityImmunity,
+    run::{GameMode, RunConfig},
+    score::{Score, ScoreSource},
+    world_collision_groups, GameState, WorldSettings,
+};
 use bevy::{
     prelude::*,
-    reflect::{TypePath, TypeUuid},
+    reflect::TypeUuid,
     render::{
         render_resource::AsBindGroup,
         texture::{ImageAddressMode, ImageSampler, ImageSamplerDescriptor},
     },
     sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
+    utils::HashMap,
 };
 use bevy_asset_loader::{asset_collection::AssetCollection, loading_state::LoadingStateAppExt};
 use bevy_rapier2d::prelude::*;
@@ -15,41 +26,193 @@ use bevy_rapier2d::prelude::*;
 /// Sent when the player hits a gravity event.
 #[derive(Event, Reflect)]
 pub struct GravityEvent {
-    region: Entity,
+    /// region that triggered the shift, if any
+    pub region: Option<Entity>,
 
     /// new gravity multipler to set in settings
     pub gravity_mult: f32,
 }
 
-#[derive(Default, Resource)]
+/// Sent when the player passes a gravity region in flip charge mode, which
+/// grants a charge rather than flipping gravity.
+#[derive(Event, Reflect)]
+pub struct FlipChargeEvent {
+    pub region: Entity,
+
+    /// Gravity multiplier of the region that granted the charge.
+    pub gravity_mult: f32,
+}
+
+/// How a gravity region currently looks.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Reflect)]
+pub enum RegionLook {
+    /// Waiting to be passed through.
+    Active,
+
+    /// Won't trigger, because the player is immune.
+    Dimmed,
+
+    /// Already passed through.
+    Consumed,
+}
+
+/// Everything that decides the material of a gravity region.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Reflect)]
+pub struct GravityMaterialKey {
+    /// Whether the region points gravity down, rather than up.
+    pub down: bool,
+
+    /// Strength of the region's gravity, in tenths of the base gravity.
+    pub strength_tenths: u32,
+
+    pub look: RegionLook,
+}
+
+impl GravityMaterialKey {
+    pub fn new(gravity_mult: f32, look: RegionLook) -> Self {
+        Self {
+            down: gravity_mult > 0.0,
+            strength_tenths: (gravity_mult.abs() * 10.0).round() as u32,
+            look,
+        }
+    }
+
+    fn strength(&self) -> f32 {
+        self.strength_tenths as f32 / 10.0
+    }
+
+    /// Speed that the arrows scroll at, when the arrows are moving at all.
+    ///
+    /// Stronger regions scroll faster, and consumed regions scroll
+    /// backwards.
+    fn scroll_speed(&self, arrow_speed: f32) -> f32 {
+        match self.look {
+            RegionLook::Active => arrow_speed * self.strength(),
+            RegionLook::Dimmed => 0.0,
+            RegionLook::Consumed => -arrow_speed * self.strength(),
+        }
+    }
+
+    fn color(&self) -> Color {
+        let base = if self.down { Color::RED } else { Color::BLUE };
+        match self.look {
+            // stronger regions are more opaque.
+            RegionLook::Active => base.with_a((0.4 + 0.6 * self.strength()).min(1.0)),
+            RegionLook::Dimmed => Color::rgba(0.5, 0.5, 0.5, 0.5),
+            RegionLook::Consumed => base.with_a(CONSUMED_ALPHA),
+        }
+    }
+}
+
+/// Builds gravity region materials on demand, sharing one material between
+/// all regions that look the same.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
 pub struct GravityMaterials {
-    scrolling_down_mat: Handle<GravityShiftMaterial>,
-    scrolling_up_mat: Handle<GravityShiftMaterial>,
+    arrow: Handle<Image>,
+    texture_y_mult: f32,
+
+    /// Speed of the arrows on a region of base strength.
+    arrow_speed: f32,
+
+    cache: HashMap<GravityMaterialKey, Handle<GravityShiftMaterial>>,
 
     mesh: Handle<Mesh>,
 }
 
+impl GravityMaterials {
+    /// Material for regions matching `key`, built the first time it is
+    /// needed.
+    pub fn material(
+        &mut self,
+        key: GravityMaterialKey,
+        materials: &mut Assets<GravityShiftMaterial>,
+    ) -> Handle<GravityShiftMaterial> {
+        if let Some(handle) = self.cache.get(&key) {
+            return handle.clone();
+        }
+        let handle = materials.add(GravityShiftMaterial {
+            color: key.color(),
+            scroll_speed: key.scroll_speed(self.arrow_speed),
+            scroll_direction: if key.down { -1.0 } else { 1.0 },
+            base_texture: Some(self.arrow.clone()),
+            texture_y_mult: self.texture_y_mult,
+        });
+        self.cache.insert(key, handle.clone());
+        handle
+    }
+
+    /// Change the speed of the arrows on every region.
+    fn set_arrow_speed(&mut self, speed: f32, materials: &mut Assets<GravityShiftMaterial>) {
+        self.arrow_speed = speed;
+        for (key, handle) in self.cache.iter() {
+            if let Some(mat) = materials.get_mut(handle) {
+                mat.scroll_speed = key.scroll_speed(speed);
+            }
+        }
+    }
+}
+
 /// Textures for rendering gravity regions.
 #[derive(Resource, AssetCollection)]
-struct GravityAssets {
-    #[asset(path = "images/grav_arrow_down.png")]
-    arrow: Handle<Image>,
+pub struct GravityAssets {
+    #[asset(key = "gravity.arrow")]
+    pub arrow: Handle<Image>,
 }
 
-#[derive(Component, Resource)]
-struct GravityRegion(f32);
+#[derive(Component, Resource, Reflect)]
+#[reflect(Component)]
+pub(crate) struct GravityRegion {
+    gravity_mult: f32,
+
+    /// score awarded when passing through without changing gravity
+    pass_score: i32,
+}
 
-#[derive(AsBindGroup, Clone, TypeUuid, TypePath, Debug, Asset)]
+/// Fade of a gravity region that the player has passed through,
+/// acknowledged by flashing white and then fading out as it scrolls away,
+/// with its arrows reversed. The region is [`Consumed`] as well.
+///
+/// Fading regions get their own copy of the consumed material to animate,
+/// which is freed with the region.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct RegionFade {
+    flash: Timer,
+
+    /// Color of the consumed material, that the flash fades to.
+    base_color: Color,
+
+    /// Level space x position of the region when it was consumed.
+    consumed_x: f32,
+}
+
+/// How long a consumed region flashes white for.
+const CONSUMED_FLASH_SECS: f32 = 0.2;
+
+/// Opacity of a consumed region once the flash is over. Animated regions
+/// fade from it to nothing as they leave the level.
+const CONSUMED_ALPHA: f32 = 0.6;
+
+/// Speed that the arrows scroll at when motion isn't reduced.
+const ARROW_SCROLL_SPEED: f32 = 1.0;
+
+/// Distance ahead of an immune player, in pixels, that gravity regions gray
+/// out.
+const DIM_DISTANCE: f32 = 240.0;
+
+#[derive(AsBindGroup, Clone, TypeUuid, Reflect, Debug, Asset)]
 #[uuid = "313dfd8f-51a7-4cf2-a5f2-8b1491988974"]
-struct GravityShiftMaterial {
+pub struct GravityShiftMaterial {
     #[texture(0)]
     #[sampler(1)]
     base_texture: Option<Handle<Image>>,
 
     #[uniform(2)]
     pub color: Color,
+    /// Speed that the arrows scroll at, in region widths per second.
     #[uniform(3)]
-    scroll_speed: f32,
+    pub scroll_speed: f32,
     #[uniform(4)]
     scroll_direction: f32,
     #[uniform(5)]
@@ -63,111 +226,260 @@ impl Material2d for GravityShiftMaterial {
     }
 }
 
-fn setup_gravity_assets(
-    mut images: ResMut<Assets<Image>>,
-    grav_assets: Res<GravityAssets>,
-    play_world: Res<WorldSettings>,
-    mut materials: ResMut<Assets<GravityShiftMaterial>>,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut grav_mat: ResMut<GravityMaterials>,
-) {
-    let image = images.get_mut(&grav_assets.arrow).unwrap();
-    let width = 32.0;
-
-    let sampler = ImageSamplerDescriptor {
-        address_mode_v: ImageAddressMode::Repeat,
-        ..default()
-    };
+/// Build the gravity region materials, once the arrow texture has loaded.
+impl FromWorld for GravityMaterials {
+    fn from_world(world: &mut World) -> Self {
+        let arrow = world.resource::<GravityAssets>().arrow.clone();
+        let height = world.resource::<WorldSettings>().level_bounds().height();
+        let width = 32.0;
 
-    image.sampler = ImageSampler::Descriptor(sampler);
+        let mut images = world.resource_mut::<Assets<Image>>();
+        let image = images.get_mut(&arrow).unwrap();
+        let sampler = ImageSamplerDescriptor {
+            address_mode_v: ImageAddressMode::Repeat,
+            ..default()
+        };
+        image.sampler = ImageSampler::Descriptor(sampler);
 
-    let texture_y_mult = play_world.bounds.height() / width;
+        let texture_y_mult = height / width;
 
-    grav_mat.scrolling_down_mat = materials.add(GravityShiftMaterial {
-        color: Color::RED,
-        scroll_speed: 1.0,
-        scroll_direction: -1.0,
-        base_texture: Some(grav_assets.arrow.clone()),
-        texture_y_mult,
-    });
+        let mesh = world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::from(shape::Quad::new(Vec2::new(width, height))));
 
-    grav_mat.scrolling_up_mat = materials.add(GravityShiftMaterial {
-        color: Color::BLUE,
-        scroll_speed: 1.0,
-        scroll_direction: 1.0,
-        base_texture: Some(grav_assets.arrow.clone()),
-        texture_y_mult,
-    });
+        let mut grav_mat = GravityMaterials {
+            arrow,
+            texture_y_mult,
+            arrow_speed: ARROW_SCROLL_SPEED,
+            cache: HashMap::new(),
+            mesh,
+        };
+        // build the materials of regular regions up front.
+        let mut materials = world.resource_mut::<Assets<GravityShiftMaterial>>();
+        for gravity_mult in [1.0, -1.0] {
+            for look in [RegionLook::Active, RegionLook::Dimmed, RegionLook::Consumed] {
+                grav_mat.material(GravityMaterialKey::new(gravity_mult, look), &mut materials);
+            }
+        }
+        grav_mat
+    }
+}
 
-    let height = play_world.bounds.height();
-    grav_mat.mesh = meshes.add(Mesh::from(shape::Quad::new(Vec2::new(width, height))));
+/// Stop the arrows scrolling when motion is reduced, leaving them static.
+fn sync_arrow_motion(
+    motion: Res<MotionSettings>,
+    grav_mat: Option<ResMut<GravityMaterials>>,
+    mut materials: ResMut<Assets<GravityShiftMaterial>>,
+) {
+    let Some(mut grav_mat) = grav_mat else {
+        return;
+    };
+    let speed = if motion.animate() {
+        ARROW_SCROLL_SPEED
+    } else {
+        0.0
+    };
+    grav_mat.set_arrow_speed(speed, &mut materials);
 }
 
 /// Create a new gravity region.
 pub fn new_gravity_region(
     new_gravity_mult: f32,
+    pass_score: i32,
     start_x: f32,
     width: f32,
-    play_world: &Res<WorldSettings>,
-    grav_mat: &Res<GravityMaterials>,
+    play_world: &WorldSettings,
+    grav_mat: &mut GravityMaterials,
+    materials: &mut Assets<GravityShiftMaterial>,
 ) -> impl Bundle {
-    let down = new_gravity_mult > 0.0;
-
-    let height = play_world.bounds.height();
+    let height = play_world.level_bounds().height();
     let q = grav_mat.mesh.clone();
-
-    let material = if down {
-        grav_mat.scrolling_down_mat.clone()
-    } else {
-        grav_mat.scrolling_up_mat.clone()
-    };
+    let material = grav_mat.material(
+        GravityMaterialKey::new(new_gravity_mult, RegionLook::Active),
+        materials,
+    );
     (
         MaterialMesh2dBundle {
             mesh: q.into(),
             material,
-            transform: Transform::from_xyz(start_x, 0.0, 3.0),
+            transform: play_world.level_transform(Vec2::new(start_x, 0.0), 3.0),
             ..default()
         },
         Collider::cuboid(width * 0.5, height * 0.5),
+        world_collision_groups(),
         Sensor,
         RigidBody::KinematicVelocityBased,
-        GravityRegion(new_gravity_mult),
+        TransformInterpolation::default(),
+        GravityRegion {
+            gravity_mult: new_gravity_mult,
+            pass_score,
+        },
+        Region::new(RegionKind::Gravity),
     )
 }
 
-/// Check for player interactions with any active gravity regions.
+/// Act on the player entering gravity regions that haven't been consumed.
+#[allow(clippy::too_many_arguments)]
 fn check_gravity_region_collisions(
     mut commands: Commands,
-    rapier: Res<RapierContext>,
-    regions: Query<(Entity, &GravityRegion)>,
-    player_q: Query<(Entity, &Player)>,
+    mut entered: EventReader<RegionEntered>,
+    regions: Query<&GravityRegion, Without<Consumed>>,
+    player_q: Query<Has<GravityImmunity>, With<Player>>,
+    level: Res<LevelSettings>,
+    config: Res<RunConfig>,
+    mut score: ResMut<Score>,
     mut gevs: EventWriter<GravityEvent>,
+    mut charges: EventWriter<FlipChargeEvent>,
 ) {
-    for player in player_q.iter() {
-        for (region_entity, region) in regions.iter() {
-            if rapier.intersection_pair(player.0, region_entity) == Some(true) {
-                // send a gravity changing event.
-                gevs.send(GravityEvent {
-                    region: region_entity,
-                    gravity_mult: region.0,
-                });
-
-                // kill the gravity region marker so we don't keep sending events.
-                commands.entity(region_entity).remove::<GravityRegion>();
-            }
+    let immune = player_q.iter().any(|immune| immune);
+    for ev in entered.read() {
+        let Ok(region) = regions.get(ev.region) else {
+            continue;
+        };
+        // each region only acts once. An immune player passes through
+        // without effect, and the region stays inert after the immunity
+        // runs out.
+        commands.entity(ev.region).insert(Consumed);
+        if immune {
+            continue;
+        }
+        if config.mode == GameMode::FlipCharge {
+            charges.send(FlipChargeEvent {
+                region: ev.region,
+                gravity_mult: region.gravity_mult,
+            });
+            continue;
+        }
+        // gravity already points this way, so nothing flips.
+        if region.gravity_mult == level.gravity_mult {
+            score.add(ScoreSource::Bonus, region.pass_score as f64);
+        } else {
+            score.add_gravity_flip();
         }
+
+        // send a gravity changing event.
+        gevs.send(GravityEvent {
+            region: Some(ev.region),
+            gravity_mult: region.gravity_mult,
+        });
+    }
+}
+
+/// Flash regions that triggered or granted a flip charge, and give them
+/// their own material to fade.
+fn consume_gravity_regions(
+    mut commands: Commands,
+    mut gevs: EventReader<GravityEvent>,
+    mut charges: EventReader<FlipChargeEvent>,
+    mut regions: Query<(&Transform, &mut Handle<GravityShiftMaterial>)>,
+    mut grav_mat: ResMut<GravityMaterials>,
+    mut materials: ResMut<Assets<GravityShiftMaterial>>,
+    play_world: Res<WorldSettings>,
+) {
+    let triggered = gevs
+        .read()
+        .filter_map(|ev| Some((ev.region?, ev.gravity_mult)))
+        .chain(charges.read().map(|ev| (ev.region, ev.gravity_mult)));
+    for (region, gravity_mult) in triggered {
+        let Ok((t, mut handle)) = regions.get_mut(region) else {
+            continue;
+        };
+        let consumed = grav_mat.material(
+            GravityMaterialKey::new(gravity_mult, RegionLook::Consumed),
+            &mut materials,
+        );
+        let Some(mut material) = materials.get(&consumed).cloned() else {
+            continue;
+        };
+        let base_color = material.color;
+        material.color = Color::WHITE;
+        *handle = materials.add(material);
+
+        commands.entity(region).insert(RegionFade {
+            flash: Timer::from_seconds(CONSUMED_FLASH_SECS, TimerMode::Once),
+            base_color,
+            consumed_x: play_world.scroll_axis.to_level(t.translation.truncate()).x,
+        });
     }
 }
 
-/// Change the level gravity mult.
+/// Fade consumed regions from white to their old color, and out as they
+/// scroll towards the edge of the level.
+fn fade_consumed_regions(
+    time: Res<Time>,
+    mut regions: Query<(&mut RegionFade, &Transform, &Handle<GravityShiftMaterial>)>,
+    mut materials: ResMut<Assets<GravityShiftMaterial>>,
+    play_world: Res<WorldSettings>,
+) {
+    let left = play_world.level_bounds().min.x;
+    for (mut consumed, t, handle) in regions.iter_mut() {
+        consumed.flash.tick(time.delta());
+        let Some(material) = materials.get_mut(handle) else {
+            continue;
+        };
+        let x = play_world.scroll_axis.to_level(t.translation.truncate()).x;
+        let remaining = if consumed.consumed_x > left {
+            ((x - left) / (consumed.consumed_x - left)).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let flash = consumed.flash.percent();
+        let alpha = 1.0 + (consumed.base_color.a() * remaining - 1.0) * flash;
+        let color = Vec4::from(Color::WHITE).lerp(Vec4::from(consumed.base_color), flash);
+        material.color = Color::from(color).with_a(alpha);
+    }
+}
+
+/// Gray out regions coming up on an immune player, so it is clear they
+/// won't trigger.
+fn dim_gravity_regions(
+    mut regions: Query<
+        (
+            &GravityRegion,
+            &Transform,
+            &mut Handle<GravityShiftMaterial>,
+        ),
+        Without<Consumed>,
+    >,
+    player: Query<(&Transform, Has<GravityImmunity>), With<Player>>,
+    mut grav_mat: ResMut<GravityMaterials>,
+    mut materials: ResMut<Assets<GravityShiftMaterial>>,
+    play_world: Res<WorldSettings>,
+) {
+    let Ok((player_t, immune)) = player.get_single() else {
+        return;
+    };
+    let axis = play_world.scroll_axis;
+    let player_x = axis.to_level(player_t.translation.truncate()).x;
+    for (region, t, mut material) in regions.iter_mut() {
+        let ahead = axis.to_level(t.translation.truncate()).x - player_x;
+        let look = if immune && ahead < DIM_DISTANCE {
+            RegionLook::Dimmed
+        } else {
+            RegionLook::Active
+        };
+        let wanted = grav_mat.material(
+            GravityMaterialKey::new(region.gravity_mult, look),
+            &mut materials,
+        );
+        if *material != wanted {
+            *material = wanted;
+        }
+    }
+}
+
+/// Change the level gravity mult, which the player picks up through its
+/// gravity scale. Rapier only needs syncing for levels that flip world
+/// gravity.
 fn on_gravity_event(
     mut level: ResMut<LevelSettings>,
     mut rapier_config: ResMut<RapierConfiguration>,
     mut gevs: EventReader<GravityEvent>,
+    world: Res<WorldSettings>,
 ) {
     for ev in gevs.read() {
         level.gravity_mult = ev.gravity_mult;
-        rapier_config.gravity = level.gravity_vector();
+        level.sync_to_rapier(&mut rapier_config, world.scroll_axis);
     }
 }
 
@@ -175,16 +487,145 @@ pub struct GravityShiftPlugin;
 
 impl Plugin for GravityShiftPlugin {
     fn build(&self, app: &mut App) {
-        app.add_collection_to_loading_state::<_, GravityAssets>(GameState::AssetLoading)
+        // the arrow image is registered by the theme pack.
+        app.add_game_collection::<GravityAssets>()
+            .init_resource_after_loading_state::<_, GravityMaterials>(GameState::AssetLoading)
+            .register_type::<GravityRegion>()
+            .register_type::<RegionFade>()
+            .register_type::<GravityMaterials>()
+            .register_type::<GravityMaterialKey>()
+            .register_type::<RegionLook>()
+            .register_type::<GravityEvent>()
+            .register_type::<FlipChargeEvent>()
             .add_plugins(Material2dPlugin::<GravityShiftMaterial>::default())
-            .insert_resource(GravityMaterials::default())
+            .register_asset_reflect::<GravityShiftMaterial>()
             .add_event::<GravityEvent>()
-            .add_systems(OnEnter(GameState::Playing), setup_gravity_assets)
+            .add_event::<FlipChargeEvent>()
+            .add_systems(
+                Update,
+                sync_arrow_motion.run_if(
+                    resource_changed::<MotionSettings>()
+                        .or_else(resource_added::<GravityMaterials>()),
+                ),
+            )
             .add_systems(
                 Update,
-                (check_gravity_region_collisions, on_gravity_event)
-                    .chain()
+                (
+                    (
+                        check_gravity_region_collisions,
+                        on_gravity_event,
+                        consume_gravity_regions,
+                    )
+                        .chain()
+                        .after(track_regions),
+                    dim_gravity_regions,
+                    fade_consumed_regions,
+                )
                     .run_if(in_state(GameState::Playing)),
             );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+    use bevy_rapier2d::prelude::RapierConfiguration;
+
+    use super::*;
+    use crate::{
+        controls::{Action, InputTimestamp},
+        level::LevelPlugin,
+        mutators::ActiveMutators,
+        obstacle::{
+            factory::ObstacleFactory, spawner_settings::GravityRegionSettings, TunnelParams,
+        },
+        obstacle_spawner::SpeedController,
+        physics::PhysicsSettings,
+        player::JumpEvent,
+        run::RunConfig,
+        test_support::insert_blank_item_assets,
+        util::Containers,
+        GameState, ResetEvent,
+    };
+
+    fn spawn_one_of_each(mut factory: ObstacleFactory) {
+        let params = TunnelParams {
+            gap_center: 0.0,
+            gap_height: 200.0,
+            obstacle_width: 96.0,
+            scoring_gap_width: 32.0,
+            score_delta: 1,
+        };
+        factory.spawn_tunnel_at(0.0, &params);
+        factory.spawn_gravity_at(
+            184.0,
+            -1.0,
+            &GravityRegionSettings {
+                gravity_width: 32.0,
+                pass_score: 0,
+            },
+        );
+    }
+
+    /// Playing and resetting several runs should not grow the asset collections.
+    #[test]
+    fn replays_do_not_leak_assets() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, AssetPlugin::default(), LevelPlugin))
+            .add_state::<GameState>()
+            .add_event::<Action>()
+            .add_event::<JumpEvent>()
+            .add_event::<ResetEvent>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<RapierConfiguration>()
+            .init_resource::<PhysicsSettings>()
+            .init_resource::<ActiveMutators>()
+            .init_resource::<RunConfig>()
+            .init_resource::<InputTimestamp>()
+            .init_resource::<SpeedController>()
+            .init_resource::<Containers>()
+            .insert_resource(WorldSettings {
+                bounds: Rect::new(-512.0, -288.0, 512.0, 288.0),
+                ..default()
+            });
+        insert_blank_item_assets(&mut app);
+        app.world
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Ready);
+        app.update();
+        app.update();
+        assert_eq!(
+            app.world.resource::<State<GameState>>().get(),
+            &GameState::Ready
+        );
+
+        let counts = |app: &App| {
+            (
+                app.world.resource::<Assets<Mesh>>().len(),
+                app.world.resource::<Assets<GravityShiftMaterial>>().len(),
+            )
+        };
+        let initial = counts(&app);
+
+        for _ in 0..5 {
+            app.world
+                .resource_mut::<NextState<GameState>>()
+                .set(GameState::Playing);
+            app.update();
+            app.world.run_system_once(spawn_one_of_each);
+            app.update();
+            assert!(counts(&app) != initial);
+
+            // what the restart action sends, left to the level plugin to handle.
+            app.world.send_event(ResetEvent);
+            app.update();
+            app.update();
+            assert_eq!(
+                app.world.resource::<State<GameState>>().get(),
+                &GameState::Ready
+            );
+            assert_eq!(counts(&app), initial);
+        }
+    }
+}
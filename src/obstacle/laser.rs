@@ -0,0 +1,208 @@
+//! Laser gates: a pair of emitter posts at the top and bottom of the level,
+//! with a beam between them that turns on and off on a fixed cycle.
+//!
+//! The posts are barriers. The beam only kills while it is on, and blinks
+//! for a moment before it turns on as a warning. Gates run off the
+//! [`RunClock`], so a restored or replayed run sees every gate in the same
+//! part of its cycle.
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_asset_loader::loading_state::LoadingStateAppExt;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    barrier::Barrier,
+    barrier_collision_groups,
+    obstacle::LaserParams,
+    run::{tick_run_clock, RunClock},
+    world_solver_groups, GameState,
+};
+
+/// Blinks per second of a beam about to turn on.
+const WARNING_BLINK_RATE: f32 = 8.0;
+
+/// Part of its cycle that a laser gate is in.
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LaserPhase {
+    On,
+    Off,
+
+    /// Off, and about to turn on.
+    Warning,
+}
+
+/// On and off cycle of a laser gate.
+#[derive(Component, Reflect, Clone)]
+#[reflect(Component)]
+pub struct LaserGate {
+    params: LaserParams,
+    phase: LaserPhase,
+}
+
+impl LaserGate {
+    pub fn new(params: &LaserParams) -> Self {
+        Self {
+            params: params.clone(),
+            // as the beam is spawned.
+            phase: LaserPhase::Off,
+        }
+    }
+
+    pub fn phase(&self) -> LaserPhase {
+        self.phase
+    }
+
+    /// Part of the cycle at `secs` into the run.
+    fn phase_at(&self, secs: f32) -> LaserPhase {
+        let p = &self.params;
+        let t = (secs + p.phase_secs).rem_euclid(p.on_secs + p.off_secs);
+        if t < p.on_secs {
+            LaserPhase::On
+        } else if t >= p.on_secs + p.off_secs - p.warning_secs.min(p.off_secs) {
+            LaserPhase::Warning
+        } else {
+            LaserPhase::Off
+        }
+    }
+}
+
+/// Beam of a laser gate.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct LaserBeam;
+
+/// Materials for laser gates, set up once assets have loaded.
+#[derive(Resource)]
+pub struct LaserAssets {
+    post_mat: Handle<ColorMaterial>,
+    on_mat: Handle<ColorMaterial>,
+    off_mat: Handle<ColorMaterial>,
+    warning_mat: Handle<ColorMaterial>,
+}
+
+impl FromWorld for LaserAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        LaserAssets {
+            post_mat: materials.add(ColorMaterial::from(Color::rgb(0.5, 0.5, 0.6))),
+            on_mat: materials.add(ColorMaterial::from(Color::rgb(1.0, 0.1, 0.3))),
+            off_mat: materials.add(ColorMaterial::from(Color::rgba(1.0, 0.1, 0.3, 0.1))),
+            warning_mat: materials.add(ColorMaterial::from(Color::rgba(1.0, 0.6, 0.2, 0.6))),
+        }
+    }
+}
+
+/// Create an emitter post of a laser gate, at the top or bottom edge of a
+/// level `level_height` high, to be attached as a child of its moving body.
+pub fn new_laser_post(
+    from_top: bool,
+    params: &LaserParams,
+    level_height: f32,
+    meshes: &mut Assets<Mesh>,
+    assets: &LaserAssets,
+) -> impl Bundle {
+    let size = Vec2::new(params.width, params.post_height);
+    let mesh = meshes.add(Mesh::from(shape::Quad::new(size)));
+    let top_mult = if from_top { 1.0 } else { -1.0 };
+    (
+        MaterialMesh2dBundle {
+            mesh: mesh.into(),
+            material: assets.post_mat.clone(),
+            transform: Transform::from_xyz(
+                0.0,
+                (level_height - params.post_height) / 2.0 * top_mult,
+                2.0,
+            ),
+            ..default()
+        },
+        Barrier,
+        Collider::cuboid(size.x / 2.0, size.y / 2.0),
+        barrier_collision_groups(),
+        world_solver_groups(),
+        ActiveEvents::COLLISION_EVENTS,
+    )
+}
+
+/// Create the beam of a laser gate, spanning the level between its posts,
+/// to be attached as a child of its moving body.
+///
+/// The beam is a sensor, so it kills rather than pushes, and starts off
+/// disabled until the gate's cycle first turns it on.
+pub fn new_laser_beam(
+    params: &LaserParams,
+    level_height: f32,
+    meshes: &mut Assets<Mesh>,
+    assets: &LaserAssets,
+) -> impl Bundle {
+    let size = Vec2::new(params.beam_width, level_height - 2.0 * params.post_height);
+    let mesh = meshes.add(Mesh::from(shape::Quad::new(size)));
+    (
+        MaterialMesh2dBundle {
+            mesh: mesh.into(),
+            material: assets.off_mat.clone(),
+            transform: Transform::from_xyz(0.0, 0.0, 1.5),
+            ..default()
+        },
+        LaserBeam,
+        Barrier,
+        Collider::cuboid(size.x / 2.0, size.y / 2.0),
+        ColliderDisabled,
+        Sensor,
+        barrier_collision_groups(),
+        ActiveEvents::COLLISION_EVENTS,
+    )
+}
+
+/// Move laser gates through their cycles, turning the beam collider on and
+/// off, and blinking the beam while it warns.
+fn update_laser_gates(
+    mut commands: Commands,
+    mut gates: Query<(&mut LaserGate, &Children)>,
+    mut beams: Query<&mut Handle<ColorMaterial>, With<LaserBeam>>,
+    assets: Res<LaserAssets>,
+    clock: Res<RunClock>,
+) {
+    let secs = clock.elapsed().as_secs_f32();
+    for (mut gate, children) in gates.iter_mut() {
+        let phase = gate.phase_at(secs);
+        let changed = phase != gate.phase;
+        gate.phase = phase;
+
+        let blink_on = (secs * WARNING_BLINK_RATE) as u32 % 2 == 0;
+        let material = match phase {
+            LaserPhase::On => &assets.on_mat,
+            LaserPhase::Warning if blink_on => &assets.warning_mat,
+            LaserPhase::Warning | LaserPhase::Off => &assets.off_mat,
+        };
+        for child in children.iter() {
+            let Ok(mut beam_mat) = beams.get_mut(*child) else {
+                continue;
+            };
+            beam_mat.set_if_neq(material.clone());
+            if !changed {
+                continue;
+            }
+            if phase == LaserPhase::On {
+                commands.entity(*child).remove::<ColliderDisabled>();
+            } else {
+                commands.entity(*child).insert(ColliderDisabled);
+            }
+        }
+    }
+}
+
+pub struct LaserPlugin;
+
+impl Plugin for LaserPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<LaserGate>()
+            .register_type::<LaserPhase>()
+            .register_type::<LaserBeam>()
+            .init_resource_after_loading_state::<_, LaserAssets>(GameState::AssetLoading)
+            .add_systems(
+                Update,
+                update_laser_gates
+                    .after(tick_run_clock)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
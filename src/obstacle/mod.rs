@@ -1,8 +1,158 @@
-use bevy::prelude::Component;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+use crate::power_ups::PowerUpKind;
+
+pub mod audit;
 pub mod barrier;
+pub mod behavior;
+pub mod custom;
+pub mod door;
+pub mod drift;
+pub mod enemy;
+pub mod factory;
 pub mod gravity_shift;
+pub mod laser;
+pub mod region;
+pub mod scenario;
 pub mod spawner_settings;
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Obstacle;
+
+/// Parameters that a spawned item was created with.
+///
+/// Attached to one entity of each spawned item, so that spawns can be
+/// inspected after the fact.
+#[derive(Component, Reflect, Clone, Debug, Serialize, Deserialize)]
+#[reflect(Component)]
+pub enum SpawnParams {
+    Tunnel(TunnelParams),
+    Gravity {
+        gravity_mult: f32,
+        width: f32,
+        #[serde(default)]
+        pass_score: i32,
+    },
+    Drift {
+        drift: f32,
+        width: f32,
+    },
+    Bouncer(BouncerParams),
+    Drone(DroneParams),
+    Laser(LaserParams),
+    Key(KeyParams),
+    Door(DoorParams),
+    PowerUp(PowerUpParams),
+}
+
+impl SpawnParams {
+    /// Level space width of the item.
+    pub fn width(&self) -> f32 {
+        match self {
+            SpawnParams::Tunnel(params) => params.obstacle_width,
+            SpawnParams::Gravity { width, .. } | SpawnParams::Drift { width, .. } => *width,
+            SpawnParams::Bouncer(params) => params.width,
+            SpawnParams::Drone(params) => params.size,
+            SpawnParams::Laser(params) => params.width,
+            SpawnParams::Key(params) => params.size,
+            SpawnParams::Door(params) => params.width,
+            SpawnParams::PowerUp(params) => params.size,
+        }
+    }
+}
+
+/// Shape of a single spawned tunnel.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct TunnelParams {
+    pub gap_center: f32,
+    pub gap_height: f32,
+    pub obstacle_width: f32,
+    pub scoring_gap_width: f32,
+    #[serde(default = "default_score_delta")]
+    pub score_delta: i32,
+}
+
+/// Shape and motion of a single barrier bouncing between the level edges.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct BouncerParams {
+    /// Level space y position of the barrier center.
+    pub center_y: f32,
+    pub width: f32,
+    pub height: f32,
+
+    /// Initial level space y velocity, in pixels per second.
+    pub drift: f32,
+}
+
+/// Size and steering of a single drone.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct DroneParams {
+    /// Level space y position of the drone center.
+    pub center_y: f32,
+    pub size: f32,
+
+    /// Level space angle of flight from straight ahead, in radians.
+    pub heading: f32,
+
+    /// Fastest the drone turns, in radians per second.
+    pub turn_rate: f32,
+
+    /// Forward speed, as a multiple of the level speed.
+    pub speed_scale: f32,
+}
+
+/// Shape and cycle of a single laser gate.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct LaserParams {
+    /// Level space width of the posts.
+    pub width: f32,
+    pub post_height: f32,
+    pub beam_width: f32,
+
+    pub on_secs: f32,
+    pub off_secs: f32,
+
+    /// Time at the end of the off part of the cycle that the beam blinks
+    /// before turning on.
+    pub warning_secs: f32,
+
+    /// Offset of the cycle from the start of the run, in seconds.
+    pub phase_secs: f32,
+}
+
+/// Position and size of a key pickup.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct KeyParams {
+    /// Level space y position of the key center.
+    pub center_y: f32,
+    pub size: f32,
+}
+
+/// Position, size and ability of a power up pickup.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct PowerUpParams {
+    /// Level space y position of the pickup center.
+    pub center_y: f32,
+    pub size: f32,
+    pub kind: PowerUpKind,
+}
+
+/// Shape of a locked door.
+#[derive(Reflect, Clone, Debug, Serialize, Deserialize)]
+pub struct DoorParams {
+    pub width: f32,
+
+    /// Level space y position where the halves meet, and the middle of the
+    /// gap once open.
+    pub gap_center: f32,
+    pub gap_height: f32,
+
+    /// Level space distance ahead of the player that the door opens at.
+    pub open_distance: f32,
+}
+
+pub(crate) fn default_score_delta() -> i32 {
+    1
+}
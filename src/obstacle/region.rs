@@ -0,0 +1,124 @@
+//! Entry and exit of the player into regions: the sensor areas across the
+//! level, such as gravity and drift regions, that act on the player as it
+//! flies through.
+//!
+//! Each [`Region`] sends a [`RegionEntered`] as the player starts touching
+//! it, and a [`RegionExited`] as it stops, for anything that reacts to
+//! regions without caring what they do, like sounds, camera effects and
+//! stats. Regions that only act once are marked [`Consumed`] after, and
+//! still send their events. Leaving [`GameState::Playing`] exits the
+//! regions the player is in, so every entry has its exit.
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{player::Player, GameState};
+
+/// What a region does to the player.
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RegionKind {
+    Gravity,
+    Drift,
+}
+
+/// Region that reports the player coming and going.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Region {
+    pub kind: RegionKind,
+
+    /// Whether the player is in the region.
+    occupied: bool,
+}
+
+impl Region {
+    pub fn new(kind: RegionKind) -> Self {
+        Self {
+            kind,
+            occupied: false,
+        }
+    }
+}
+
+/// Region that has already acted, and won't again.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Consumed;
+
+/// Sent when the player enters a region.
+#[derive(Event, Reflect, Clone, Copy, Debug)]
+pub struct RegionEntered {
+    pub region: Entity,
+    pub kind: RegionKind,
+}
+
+/// Sent when the player leaves a region.
+#[derive(Event, Reflect, Clone, Copy, Debug)]
+pub struct RegionExited {
+    pub region: Entity,
+    pub kind: RegionKind,
+}
+
+/// Send region events for the regions the player has started or stopped
+/// touching since the last frame.
+pub fn track_regions(
+    rapier: Res<RapierContext>,
+    mut regions: Query<(Entity, &mut Region)>,
+    player: Query<Entity, With<Player>>,
+    mut entered: EventWriter<RegionEntered>,
+    mut exited: EventWriter<RegionExited>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    for (region_entity, mut region) in regions.iter_mut() {
+        let touching = rapier.intersection_pair(player, region_entity) == Some(true);
+        if touching == region.occupied {
+            continue;
+        }
+        region.occupied = touching;
+        if touching {
+            entered.send(RegionEntered {
+                region: region_entity,
+                kind: region.kind,
+            });
+        } else {
+            exited.send(RegionExited {
+                region: region_entity,
+                kind: region.kind,
+            });
+        }
+    }
+}
+
+/// Exit the regions the player is in, once play stops and they are no
+/// longer tracked.
+fn exit_occupied_regions(
+    mut regions: Query<(Entity, &mut Region)>,
+    mut exited: EventWriter<RegionExited>,
+) {
+    for (region_entity, mut region) in regions.iter_mut() {
+        if region.occupied {
+            region.occupied = false;
+            exited.send(RegionExited {
+                region: region_entity,
+                kind: region.kind,
+            });
+        }
+    }
+}
+
+pub struct RegionPlugin;
+
+impl Plugin for RegionPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Region>()
+            .register_type::<RegionKind>()
+            .register_type::<Consumed>()
+            .register_type::<RegionEntered>()
+            .register_type::<RegionExited>()
+            .add_event::<RegionEntered>()
+            .add_event::<RegionExited>()
+            .add_systems(Update, track_regions.run_if(in_state(GameState::Playing)))
+            .add_systems(OnExit(GameState::Playing), exit_occupied_regions);
+    }
+}
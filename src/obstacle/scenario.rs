@@ -0,0 +1,104 @@
+//! Training scenarios: fixed sequences of items, repeated so that players
+//! can drill a single situation.
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use futures_lite::AsyncReadExt;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::obstacle::SpawnParams;
+
+/// A named sequence of items, spawned in order and then repeated.
+#[derive(Reflect, Debug, Serialize, Deserialize, Clone)]
+pub struct Scenario {
+    pub name: String,
+
+    /// Time between items at the base speed. Spacing is kept the same at
+    /// other speeds.
+    pub seconds_per_item: f32,
+
+    /// Items of one repetition, in order.
+    pub items: Vec<SpawnParams>,
+}
+
+/// Every scenario available for training.
+#[derive(Asset, Reflect, Debug, Serialize, Deserialize, Clone)]
+pub struct ScenarioSet {
+    pub scenarios: Vec<Scenario>,
+}
+
+impl ScenarioSet {
+    /// Check that every scenario can be played, for scenarios that don't
+    /// ship with the game.
+    pub fn validate(&self) -> Result<(), InvalidScenario> {
+        for scenario in &self.scenarios {
+            if scenario.items.is_empty() {
+                return Err(InvalidScenario::NoItems(scenario.name.clone()));
+            }
+            if !(scenario.seconds_per_item.is_finite() && scenario.seconds_per_item > 0.0) {
+                return Err(InvalidScenario::NoSpawnRate(scenario.name.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Reasons that a scenario can't be played.
+#[derive(Debug, Error)]
+pub enum InvalidScenario {
+    #[error("scenario {0:?} has no items")]
+    NoItems(String),
+    #[error("scenario {0:?} needs a positive seconds_per_item")]
+    NoSpawnRate(String),
+}
+
+#[derive(Default)]
+pub struct ScenarioSetLoader;
+
+/// Possible errors that can be produced by [`ScenarioSetLoader`]
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum ScenarioSetLoaderError {
+    /// An [IO](std::io) Error
+    #[error("IO error while loading file: {0}")]
+    Io(#[from] std::io::Error),
+    /// A [RON](ron) Error
+    #[error("Could not parse RON: {0}")]
+    RonSpannedError(#[from] ron::error::SpannedError),
+}
+
+impl AssetLoader for ScenarioSetLoader {
+    type Asset = ScenarioSet;
+    type Settings = ();
+    type Error = ScenarioSetLoaderError;
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(ron::de::from_bytes::<ScenarioSet>(&bytes)?)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["scenarios.ron"]
+    }
+}
+
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<ScenarioSet>()
+            .register_type::<ScenarioSet>()
+            .register_type::<Scenario>()
+            .init_asset_loader::<ScenarioSetLoader>();
+    }
+}
@@ -4,34 +4,131 @@ use bevy::{
     utils::BoxedFuture,
 };
 use futures_lite::AsyncReadExt;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::WorldSettings;
+use crate::{
+    obstacle::{
+        behavior::{Behavior, BehaviorAction},
+        default_score_delta, TunnelParams,
+    },
+    physics::PhysicsSettings,
+    WorldSettings,
+};
 
 /// Settings for overall object spawning.
-#[derive(Asset, TypePath, Debug, Serialize, Deserialize, Clone)]
+///
+/// Speeds and accelerations are in meters and seconds, as in
+/// [`PhysicsSettings`]. Sizes, gaps and positions are in pixels.
+#[derive(Asset, Reflect, Debug, Serialize, Deserialize, Clone)]
 pub struct SpawnerSettings {
+    /// Identifier for the level, recorded with scores.
+    #[serde(default)]
+    pub name: String,
+
+    /// Level space velocity of spawned items, in m/s.
     pub item_vel: Vec2,
+
+    /// Distance past the level edge that items are spawned at, in seconds
+    /// of scrolling.
     pub(crate) start_offset_secs: f32,
 
-    /// Spawn rate for obstacles and other spawned items in the level.
+    /// Spawn rate for obstacles and other spawned items in the level, in
+    /// seconds per item.
     pub(crate) seconds_per_item: f32,
 
+    /// Time after a run starts before the first item is spawned. With no
+    /// warm-up, the first item is spawned just off screen as play begins.
+    #[serde(default)]
+    pub(crate) warm_up_secs: f32,
+
+    /// Number of tunnels already on screen when a run starts, spaced as if
+    /// the level had been scrolling.
+    #[serde(default)]
+    pub(crate) prefill_count: u32,
+
     pub(crate) tunnel_weight: f32,
     pub(crate) tunnel_settings: TunnelSpawnSettings,
 
     pub(crate) gravity_weight: f32,
     pub min_items_between_gravity: u32,
     pub(crate) gravity_settings: GravityRegionSettings,
+
+    /// Weight of gravity chains, which count as gravity regions for
+    /// `min_items_between_gravity`.
+    #[serde(default)]
+    pub(crate) chain_weight: f32,
+    #[serde(default)]
+    pub(crate) chain_settings: GravityChainSettings,
+
+    #[serde(default)]
+    pub(crate) drift_weight: f32,
+    #[serde(default)]
+    pub(crate) drift_settings: DriftRegionSettings,
+
+    #[serde(default)]
+    pub(crate) bouncer_weight: f32,
+    #[serde(default)]
+    pub(crate) bouncer_settings: BouncerSpawnSettings,
+
+    #[serde(default)]
+    pub(crate) drone_weight: f32,
+    #[serde(default)]
+    pub(crate) drone_settings: DroneSpawnSettings,
+
+    #[serde(default)]
+    pub(crate) laser_weight: f32,
+    #[serde(default)]
+    pub(crate) laser_settings: LaserSpawnSettings,
+
+    /// Weight of a key followed by a locked door, which count as one item.
+    #[serde(default)]
+    pub(crate) door_weight: f32,
+    #[serde(default)]
+    pub(crate) door_settings: DoorSpawnSettings,
+
+    /// Weight of power up pickups.
+    #[serde(default)]
+    pub(crate) power_up_weight: f32,
+    #[serde(default)]
+    pub(crate) power_up_settings: PowerUpSpawnSettings,
+
+    /// Minimum level space gap between consecutive items, in pixels. Items
+    /// are pushed back to respect it.
+    #[serde(default)]
+    pub(crate) min_gap_between_items: f32,
+
+    /// Where and how the player starts the level.
+    #[serde(default)]
+    pub player_start: PlayerStart,
+
+    /// Magnitude of gravity in the level, in m/s^2, in place of the one in
+    /// the physics settings.
+    #[serde(default)]
+    pub gravity: Option<f32>,
+
+    /// Jump speed in the level, in m/s, in place of the one in the physics
+    /// settings.
+    #[serde(default)]
+    pub jump_speed: Option<f32>,
+
+    /// Flip the gravity of every body at gravity regions, rather than only
+    /// the rocket's, as levels made before flips went through the rocket's
+    /// gravity scale expect.
+    #[serde(default)]
+    pub flip_world_gravity: bool,
 }
 
 impl SpawnerSettings {
     pub fn new() -> SpawnerSettings {
         SpawnerSettings {
-            item_vel: Vec2::new(-200.0, 0.0),
+            name: "base".to_string(),
+            item_vel: Vec2::new(-2.083_333, 0.0),
             start_offset_secs: 0.1,
             seconds_per_item: 2.0,
+            warm_up_secs: 0.0,
+            prefill_count: 0,
 
             tunnel_weight: 0.8,
             tunnel_settings: TunnelSpawnSettings::default(),
@@ -40,7 +137,27 @@ impl SpawnerSettings {
             min_items_between_gravity: 3,
             gravity_settings: GravityRegionSettings {
                 gravity_width: 32.0,
+                pass_score: 0,
             },
+            chain_weight: 0.0,
+            chain_settings: GravityChainSettings::default(),
+            drift_weight: 0.0,
+            drift_settings: DriftRegionSettings::default(),
+            bouncer_weight: 0.0,
+            bouncer_settings: BouncerSpawnSettings::default(),
+            drone_weight: 0.0,
+            drone_settings: DroneSpawnSettings::default(),
+            laser_weight: 0.0,
+            laser_settings: LaserSpawnSettings::default(),
+            door_weight: 0.0,
+            door_settings: DoorSpawnSettings::default(),
+            power_up_weight: 0.0,
+            power_up_settings: PowerUpSpawnSettings::default(),
+            min_gap_between_items: 0.0,
+            player_start: PlayerStart::default(),
+            gravity: None,
+            jump_speed: None,
+            flip_world_gravity: false,
         }
     }
 
@@ -48,11 +165,145 @@ impl SpawnerSettings {
         *self = SpawnerSettings::new();
     }
 
-    /// Return the x offset where obstacles should start.
+    /// Check that the level can be played in `play_world`, for levels that
+    /// don't ship with the game.
+    pub fn validate(&self, play_world: &WorldSettings) -> Result<(), InvalidLevel> {
+        if !positive(-self.item_vel.x) {
+            return Err(InvalidLevel::NotScrolling);
+        }
+        if !positive(self.seconds_per_item) {
+            return Err(InvalidLevel::NotPositive("seconds_per_item"));
+        }
+        let weights = [
+            self.tunnel_weight,
+            self.gravity_weight,
+            self.chain_weight,
+            self.drift_weight,
+            self.bouncer_weight,
+            self.drone_weight,
+            self.laser_weight,
+            self.door_weight,
+            self.power_up_weight,
+        ];
+        if weights.iter().any(|w| w.is_nan() || *w < 0.0) || !positive(weights.iter().sum()) {
+            return Err(InvalidLevel::NoItems);
+        }
+        let tunnel = &self.tunnel_settings;
+        for (name, value) in [("gravity", self.gravity), ("jump_speed", self.jump_speed)] {
+            if value.is_some_and(|v| !positive(v)) {
+                return Err(InvalidLevel::NotPositive(name));
+            }
+        }
+        for (name, value) in [
+            ("tunnel_settings.obstacle_width", tunnel.obstacle_width),
+            (
+                "tunnel_settings.gap_height_range",
+                tunnel.gap_height_range[0],
+            ),
+            (
+                "gravity_settings.gravity_width",
+                self.gravity_settings.gravity_width,
+            ),
+            ("drift_settings.width", self.drift_settings.width),
+            ("bouncer_settings.width", self.bouncer_settings.width),
+            (
+                "bouncer_settings.height_range",
+                self.bouncer_settings.height_range[0],
+            ),
+            ("drone_settings.size", self.drone_settings.size),
+            ("drone_settings.turn_rate", self.drone_settings.turn_rate),
+            (
+                "drone_settings.speed_scale",
+                self.drone_settings.speed_scale,
+            ),
+            ("laser_settings.width", self.laser_settings.width),
+            (
+                "laser_settings.post_height",
+                self.laser_settings.post_height,
+            ),
+            ("laser_settings.beam_width", self.laser_settings.beam_width),
+            ("laser_settings.on_secs", self.laser_settings.on_secs),
+            ("laser_settings.off_secs", self.laser_settings.off_secs),
+            ("door_settings.width", self.door_settings.width),
+            ("door_settings.gap_height", self.door_settings.gap_height),
+            ("door_settings.key_size", self.door_settings.key_size),
+            ("power_up_settings.size", self.power_up_settings.size),
+        ] {
+            if !positive(value) {
+                return Err(InvalidLevel::NotPositive(name));
+            }
+        }
+        for (name, [min, max]) in [
+            ("tunnel_settings.center_y_range", tunnel.center_y_range),
+            ("tunnel_settings.gap_height_range", tunnel.gap_height_range),
+            (
+                "bouncer_settings.height_range",
+                self.bouncer_settings.height_range,
+            ),
+            (
+                "bouncer_settings.drift_speed_range",
+                self.bouncer_settings.drift_speed_range,
+            ),
+        ] {
+            if min.is_nan() || max.is_nan() || min > max {
+                return Err(InvalidLevel::BadRange(name));
+            }
+        }
+        // the posts stand at both edges, with the beam between them.
+        if self.laser_settings.post_height >= play_world.level_bounds().height() / 2.0 {
+            return Err(InvalidLevel::TooTall("laser_settings.post_height"));
+        }
+        let warning_secs = self.laser_settings.warning_secs;
+        if !(warning_secs.is_finite() && warning_secs >= 0.0) {
+            return Err(InvalidLevel::Negative("laser_settings.warning_secs"));
+        }
+        let [min_between, max_between] = self.door_settings.items_between;
+        if min_between > max_between {
+            return Err(InvalidLevel::BadRange("door_settings.items_between"));
+        }
+        for (name, behavior) in [
+            ("tunnel_settings.behavior", &tunnel.behavior),
+            ("bouncer_settings.behavior", &self.bouncer_settings.behavior),
+        ] {
+            let steps = behavior.iter().flat_map(|b| &b.steps);
+            if steps.clone().any(|s| !s.secs.is_finite() || s.secs < 0.0) {
+                return Err(InvalidLevel::BadBehavior(name));
+            }
+            // items have to keep scrolling, to leave the level.
+            if steps.clone().any(
+                |s| matches!(s.action, BehaviorAction::SetSpeed(scale) if !(scale.is_finite() && scale > 0.0)),
+            ) {
+                return Err(InvalidLevel::BadSpeedScale(name));
+            }
+        }
+        Ok(())
+    }
+
+    /// Return the level space x offset where obstacles should start.
     ///
     /// Most obstacles should be shifted so that left boundary begins at start_offset.
-    pub fn start_offset_x(&self, play_world: &WorldSettings) -> f32 {
-        play_world.bounds.max.x - self.item_vel.x * self.start_offset_secs
+    pub fn start_offset_x(&self, play_world: &WorldSettings, physics: &PhysicsSettings) -> f32 {
+        play_world.level_bounds().max.x
+            - physics.to_pixels(self.item_vel.x) * self.start_offset_secs
+    }
+}
+
+/// Starting position and velocity of the player in a level.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Reflect)]
+pub struct PlayerStart {
+    /// Level space position, relative to the level bounds: (0, 0) is the
+    /// center, and each coordinate runs from -1.0 to 1.0 between the edges.
+    pub position: Vec2,
+
+    /// Level space velocity, in m/s.
+    pub velocity: Vec2,
+}
+
+impl PlayerStart {
+    /// Level space position, in pixels.
+    pub fn level_position(&self, play_world: &WorldSettings) -> Vec2 {
+        let bounds = play_world.level_bounds();
+        bounds.center() + self.position * bounds.half_size()
     }
 }
 
@@ -60,6 +311,182 @@ impl SpawnerSettings {
 #[derive(Clone, Debug, Deserialize, Serialize, Reflect)]
 pub struct GravityRegionSettings {
     pub gravity_width: f32,
+
+    /// Score for passing through the region without flipping gravity.
+    #[serde(default)]
+    pub pass_score: i32,
+}
+
+/// Settings for a gravity chain: a gravity region flipping gravity, then a
+/// tunnel, then a second region flipping it back, spawned as one item.
+///
+/// The tunnel gap is off to the side that the player jumps towards after
+/// the first flip, so the chain can only be passed by jumping right after
+/// it. The tunnel otherwise uses the level's [`TunnelSpawnSettings`].
+#[derive(Clone, Debug, Deserialize, Serialize, Reflect)]
+#[serde(default)]
+pub struct GravityChainSettings {
+    /// Level space distance between each region and the tunnel.
+    pub spacing: f32,
+
+    /// Distance of the gap center from the middle of the level.
+    pub gap_offset: f32,
+
+    pub gap_height: f32,
+}
+
+impl Default for GravityChainSettings {
+    fn default() -> Self {
+        Self {
+            spacing: 48.0,
+            gap_offset: 120.0,
+            gap_height: 200.0,
+        }
+    }
+}
+
+/// Per instance settings for a drift region, which sets the tunnels ahead of
+/// the player drifting up or down.
+#[derive(Clone, Debug, Deserialize, Serialize, Reflect)]
+#[serde(default)]
+pub struct DriftRegionSettings {
+    pub width: f32,
+
+    /// Speed that tunnels drift at, in pixels per second.
+    pub speed: f32,
+}
+
+impl Default for DriftRegionSettings {
+    fn default() -> Self {
+        Self {
+            width: 32.0,
+            speed: 60.0,
+        }
+    }
+}
+
+/// Per instance settings for a bouncer: a single barrier spawned drifting
+/// up or down, that bounces between the top and bottom of the level.
+#[derive(Clone, Debug, Deserialize, Serialize, Reflect)]
+#[serde(default)]
+pub struct BouncerSpawnSettings {
+    pub width: f32,
+    pub height_range: [f32; 2],
+
+    /// Range of vertical speeds to start at, in pixels per second. The
+    /// direction is random.
+    pub drift_speed_range: [f32; 2],
+
+    /// Behavior played by every bouncer.
+    pub behavior: Option<Behavior>,
+}
+
+impl Default for BouncerSpawnSettings {
+    fn default() -> Self {
+        Self {
+            width: 64.0,
+            height_range: [80.0, 160.0],
+            drift_speed_range: [60.0, 140.0],
+            behavior: None,
+        }
+    }
+}
+
+/// Per instance settings for a drone: an enemy flying in faster than the
+/// level scrolls, that steers towards the player.
+#[derive(Clone, Debug, Deserialize, Serialize, Reflect)]
+#[serde(default)]
+pub struct DroneSpawnSettings {
+    pub size: f32,
+
+    /// Fastest a drone turns, in radians per second.
+    pub turn_rate: f32,
+
+    /// Forward speed, as a multiple of the level speed.
+    pub speed_scale: f32,
+}
+
+impl Default for DroneSpawnSettings {
+    fn default() -> Self {
+        Self {
+            size: 40.0,
+            turn_rate: 0.8,
+            speed_scale: 1.5,
+        }
+    }
+}
+
+/// Per instance settings for a laser gate: emitter posts at the top and
+/// bottom of the level, with a beam between them that turns on and off.
+#[derive(Clone, Debug, Deserialize, Serialize, Reflect)]
+#[serde(default)]
+pub struct LaserSpawnSettings {
+    /// Level space width of the posts.
+    pub width: f32,
+    pub post_height: f32,
+    pub beam_width: f32,
+
+    /// Time the beam stays on each cycle.
+    pub on_secs: f32,
+
+    /// Time the beam stays off each cycle, including the warning.
+    pub off_secs: f32,
+
+    /// Time the beam blinks before turning on.
+    pub warning_secs: f32,
+}
+
+impl Default for LaserSpawnSettings {
+    fn default() -> Self {
+        Self {
+            width: 24.0,
+            post_height: 40.0,
+            beam_width: 8.0,
+            on_secs: 1.2,
+            off_secs: 1.4,
+            warning_secs: 0.4,
+        }
+    }
+}
+
+/// Settings for a locked door, and the key spawned ahead of it.
+#[derive(Clone, Debug, Deserialize, Serialize, Reflect)]
+#[serde(default)]
+pub struct DoorSpawnSettings {
+    pub width: f32,
+    pub gap_height: f32,
+    pub key_size: f32,
+
+    /// Range of the number of items spawned between the key and the door.
+    pub items_between: [u32; 2],
+
+    /// Level space distance ahead of the player that the door opens at.
+    pub open_distance: f32,
+}
+
+impl Default for DoorSpawnSettings {
+    fn default() -> Self {
+        Self {
+            width: 48.0,
+            gap_height: 220.0,
+            key_size: 28.0,
+            items_between: [1, 2],
+            open_distance: 300.0,
+        }
+    }
+}
+
+/// Settings for power up pickups, which grant a random power up.
+#[derive(Clone, Debug, Deserialize, Serialize, Reflect)]
+#[serde(default)]
+pub struct PowerUpSpawnSettings {
+    pub size: f32,
+}
+
+impl Default for PowerUpSpawnSettings {
+    fn default() -> Self {
+        Self { size: 28.0 }
+    }
 }
 
 /// Per instance settings for a tunnel barrier.
@@ -71,6 +498,39 @@ pub struct TunnelSpawnSettings {
     pub gap_height_range: [f32; 2],
     pub obstacle_width: f32,
     pub scoring_gap_width: f32,
+
+    /// Score for passing through the tunnel.
+    #[serde(default = "default_score_delta")]
+    pub score_delta: i32,
+
+    /// Behavior played by every tunnel.
+    #[serde(default)]
+    pub behavior: Option<Behavior>,
+}
+
+impl TunnelSpawnSettings {
+    /// Pick the shape of a tunnel from the ranges.
+    pub fn random_params(&self, rng: &mut impl Rng) -> TunnelParams {
+        TunnelParams {
+            gap_center: self.center_y_range[0]
+                + rng.gen::<f32>() * (self.center_y_range[1] - self.center_y_range[0]),
+            gap_height: self.gap_height_range[0]
+                + rng.gen::<f32>() * (self.gap_height_range[1] - self.gap_height_range[0]),
+            obstacle_width: self.obstacle_width,
+            scoring_gap_width: self.scoring_gap_width,
+            score_delta: self.score_delta,
+        }
+    }
+
+    /// Settings drawing gaps from only the lowest `fraction` of the gap
+    /// height range.
+    pub fn with_tighter_gaps(&self, fraction: f32) -> Self {
+        let [low, high] = self.gap_height_range;
+        Self {
+            gap_height_range: [low, low + (high - low) * fraction.clamp(0.0, 1.0)],
+            ..self.clone()
+        }
+    }
 }
 
 impl Default for TunnelSpawnSettings {
@@ -80,10 +540,37 @@ impl Default for TunnelSpawnSettings {
             gap_height_range: [200.0, 300.0],
             obstacle_width: 96.0,
             scoring_gap_width: 32.0,
+            score_delta: 1,
+            behavior: None,
         }
     }
 }
 
+fn positive(value: f32) -> bool {
+    value.is_finite() && value > 0.0
+}
+
+/// Reasons that a level can't be played.
+#[derive(Debug, Error)]
+pub enum InvalidLevel {
+    #[error("items have to scroll towards -x")]
+    NotScrolling,
+    #[error("{0} has to be positive")]
+    NotPositive(&'static str),
+    #[error("{0} can't be negative")]
+    Negative(&'static str),
+    #[error("{0} has to be under half the level height")]
+    TooTall(&'static str),
+    #[error("no item has a positive weight")]
+    NoItems,
+    #[error("{0} has its minimum above its maximum")]
+    BadRange(&'static str),
+    #[error("{0} has a step with a negative or infinite length")]
+    BadBehavior(&'static str),
+    #[error("{0} has a step that doesn't set a positive speed")]
+    BadSpeedScale(&'static str),
+}
+
 #[derive(Default)]
 pub struct SpawnerSettingsLoader;
 
@@ -127,6 +614,15 @@ pub struct SpawnerSettingsPlugin;
 impl Plugin for SpawnerSettingsPlugin {
     fn build(&self, app: &mut App) {
         app.init_asset::<SpawnerSettings>()
+            .register_type::<SpawnerSettings>()
+            .register_type::<TunnelSpawnSettings>()
+            .register_type::<GravityRegionSettings>()
+            .register_type::<GravityChainSettings>()
+            .register_type::<DriftRegionSettings>()
+            .register_type::<BouncerSpawnSettings>()
+            .register_type::<DroneSpawnSettings>()
+            .register_type::<LaserSpawnSettings>()
+            .register_type::<DoorSpawnSettings>()
             .init_asset_loader::<SpawnerSettingsLoader>();
     }
 }
@@ -1,38 +1,70 @@
 use bevy_asset_loader::asset_collection::AssetCollection;
-use bevy_asset_loader::loading_state::LoadingStateAppExt;
 use rand::Rng;
-use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, collections::VecDeque, time::Duration};
 
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic};
 use bevy::prelude::*;
 use bevy::utils::tracing;
 use bevy_rapier2d::prelude::*;
-use bevy_tweening::{component_animator_system, AnimationSystem, Animator, EaseMethod, Tween};
 
-use crate::level::{RemoveOnReset, RemoveWhenLeft};
-use crate::obstacle::spawner_settings::{
-    GravityRegionSettings, SpawnerSettings, TunnelSpawnSettings,
-};
-use crate::obstacle::Obstacle;
+use crate::asset_errors::GameCollectionAppExt;
+use crate::diagnostics::RateWindow;
+use crate::mods::{Mods, MOD_LEVEL_POINTS};
+use crate::mutators::ActiveMutators;
+use crate::obstacle::behavior::{Behavior, SpeedScale};
+use crate::obstacle::custom::ObstacleSet;
+use crate::obstacle::drift::Drift;
+use crate::obstacle::factory::ObstacleFactory;
+use crate::obstacle::spawner_settings::{SpawnerSettings, TunnelSpawnSettings};
+use crate::obstacle::{DoorParams, Obstacle};
+use crate::physics::{count_physics_steps, PhysicsSettings, PhysicsSteps};
+use crate::run::{GameMode, RunConfig, RunRng};
 use crate::score::Score;
-use crate::util::LinearVelocityLens;
-use crate::{
-    barrier::{new_barrier, BarrierAssets, RegionRef},
-    gravity_shift::{new_gravity_region, GravityMaterials},
-    scoring_region::new_scoring_region,
-};
+use crate::training::training_active;
 use crate::{level::LevelSettings, WorldSettings};
-use crate::{GameState, ResetEvent};
+use crate::{GameState, ResetEvent, ON_ASSETS_LOADED};
 
 /// Available options for spawning from a spawner.
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
 enum SpawnOption {
     Tunnel,
     Gravity,
+    Chain,
+    Drift,
+    Bouncer,
+    Drone,
+    Laser,
+    KeyDoor,
+    PowerUp,
+    /// Custom obstacle of a mod, by its index among those of every mod.
+    Custom(usize),
 }
 
+/// Sent when the settings of the current level change: a new level took
+/// effect, or the speed of the level was scaled.
 #[derive(Event)]
 pub struct LevelChangeEvent;
 
+/// Sent when a new level takes effect.
+#[derive(Event)]
+pub struct LevelUpEvent;
+
+/// Request to spawn an item outside of the regular spawn schedule.
+#[derive(Event)]
+pub enum SpawnRequestEvent {
+    /// Gravity region switching gravity down (`true`) or up (`false`).
+    Gravity { down: bool },
+}
+
+/// Request to scale the item speed of the current level.
+#[derive(Event)]
+pub struct SpeedScaleEvent(pub f32);
+
+/// Range that the requested speed scales can take the item speed to, as a
+/// multiple of each level's own speed.
+const SPEED_MULT_RANGE: (f32, f32) = (0.5, 2.0);
+
 #[derive(AssetCollection, Resource)]
 pub struct Levels {
     #[asset(path = "levels/base.spawner.ron")]
@@ -43,13 +75,35 @@ pub struct Levels {
 }
 
 /// Track statistics based on spawning, for determining later spawns.
-#[derive(Reflect, Default)]
+#[derive(Reflect, Default, Clone, Serialize, Deserialize)]
 pub struct SpawnStats {
     /// Total number of logical items sent since reset.
     num_items: u32,
 
     /// Number of items spawned since the last gravity shfit.
     since_last_gravity: u32,
+
+    /// Names of the levels played since reset, in order.
+    #[serde(default)]
+    levels: Vec<String>,
+
+    /// Whether the last drift region spawned set tunnels drifting up.
+    #[serde(default)]
+    last_drift_up: bool,
+
+    /// Items still to spawn before the slot of the door of the last key.
+    #[serde(default)]
+    door_in: Option<u32>,
+
+    /// Door of the last key, spawned once its slot comes up, so that it is
+    /// placed like any other item even if the speed or level changes.
+    #[serde(default)]
+    door: Option<PendingDoor>,
+
+    /// Score thresholds crossed since reset, counting the fast level's as
+    /// the first, so that each level is queued once.
+    #[serde(default)]
+    thresholds_crossed: usize,
 }
 
 impl SpawnStats {
@@ -57,23 +111,219 @@ impl SpawnStats {
     fn reset(&mut self) {
         self.num_items = 0;
         self.since_last_gravity = 0;
+        self.levels.clear();
+        self.last_drift_up = false;
+        self.door_in = None;
+        self.door = None;
+        self.thresholds_crossed = 0;
+    }
+}
+
+/// Locked door waiting for its slot.
+#[derive(Reflect, Clone, Serialize, Deserialize)]
+struct PendingDoor {
+    params: DoorParams,
+
+    /// Spawn index of its key, which it shares, as a single item.
+    index: u64,
+}
+
+/// Items spawned per second of play.
+pub const OBSTACLE_SPAWN_RATE: DiagnosticId =
+    DiagnosticId::from_u128(217046894946757240594689008288199761481);
+
+/// Number of recent tunnels that the average gap height is taken over.
+const GAP_HEIGHT_WINDOW: usize = 10;
+
+/// Live measures of the difficulty of the current run, for tuning.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct DifficultyMetrics {
+    /// Current item velocity, in level space.
+    pub item_vel: Vec2,
+
+    /// Time between spawned items.
+    pub seconds_per_item: f32,
+
+    /// Average gap height of the last [`GAP_HEIGHT_WINDOW`] tunnels.
+    pub average_gap_height: f32,
+
+    /// Time since gravity last flipped (or the run started).
+    pub secs_since_gravity_flip: f32,
+
+    recent_gap_heights: VecDeque<f32>,
+}
+
+impl DifficultyMetrics {
+    fn record_gap_height(&mut self, gap_height: f32) {
+        if self.recent_gap_heights.len() == GAP_HEIGHT_WINDOW {
+            self.recent_gap_heights.pop_front();
+        }
+        self.recent_gap_heights.push_back(gap_height);
+        self.average_gap_height =
+            self.recent_gap_heights.iter().sum::<f32>() / self.recent_gap_heights.len() as f32;
     }
 }
 
+/// Time taken to tween obstacles to a new level speed.
+const SPEED_TWEEN_SECS: f32 = 0.5;
+
+/// Scroll speed of the level, shared by every obstacle.
+///
+/// Owns the current and target item velocity, and tweens between them when
+/// the level speed changes. Obstacles don't animate their own velocity;
+/// `apply_obstacle_speeds` writes the current velocity to all of them each
+/// frame, so items spawned or removed mid-tween can never fall out of step.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct SpeedController {
+    /// Velocity of the base level, that other speeds are relative to.
+    base: Vec2,
+    start: Vec2,
+    target: Vec2,
+    timer: Timer,
+
+    /// Velocity of every obstacle this frame, in level space.
+    pub current: Vec2,
+}
+
+impl SpeedController {
+    /// Start a new run at the base level velocity.
+    fn reset(&mut self, base: Vec2) {
+        self.base = base;
+        self.set(base);
+    }
+
+    /// Current speed as a multiple of the base level speed.
+    pub fn relative(&self) -> f32 {
+        let base = self.base.length();
+        if base > 0.0 {
+            self.current.length() / base
+        } else {
+            1.0
+        }
+    }
+
+    /// Immediately set the velocity, cancelling any tween.
+    pub fn set(&mut self, vel: Vec2) {
+        self.start = vel;
+        self.target = vel;
+        self.current = vel;
+        self.timer = Timer::new(Duration::ZERO, TimerMode::Once);
+    }
+
+    /// Start tweening from the current velocity to a new velocity.
+    fn tween_to(&mut self, vel: Vec2) {
+        self.start = self.current;
+        self.target = vel;
+        self.timer = Timer::from_seconds(SPEED_TWEEN_SECS, TimerMode::Once);
+    }
+
+    fn tick(&mut self, delta: Duration) {
+        self.timer.tick(delta);
+        self.current = self.start.lerp(self.target, self.timer.percent());
+    }
+}
+
+/// Total distance the level has scrolled in the current run, and how many
+/// items have been spawned in it.
+///
+/// Anything that needs to line up with the obstacles of a run (death
+/// markers, checkpoints, replays) should key off this, rather than tracking
+/// time or speed separately.
+#[derive(Resource, Reflect, Serialize, Deserialize, Default, Clone, Copy, Debug)]
+#[reflect(Resource)]
+pub struct ScrollOdometer {
+    /// Level space distance scrolled, in pixels.
+    pub distance: f32,
+
+    /// Index that the next spawned item will get.
+    pub spawn_index: u64,
+}
+
+impl ScrollOdometer {
+    /// Claim the index for a newly spawned item.
+    fn next_index(&mut self) -> SpawnIndex {
+        let index = SpawnIndex(self.spawn_index);
+        self.spawn_index += 1;
+        index
+    }
+}
+
+/// Order an item was spawned in, within its run.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[reflect(Component)]
+pub struct SpawnIndex(pub u64);
+
+/// Most recently spawned item, tracked by the odometer rather than by its
+/// transform. Transforms are interpolated between physics steps, so they
+/// would place the next item differently in each physics timestep mode.
+#[derive(Reflect, Serialize, Deserialize, Clone, Copy, Debug)]
+struct LastItem {
+    /// Level space right edge of the item when it was spawned.
+    right_x: f32,
+
+    /// Odometer distance when the item was spawned.
+    distance: f32,
+
+    /// Slowest multiple of the level speed that the item scrolls at.
+    #[serde(default = "default_speed_scale")]
+    slowest: f32,
+}
+
+fn default_speed_scale() -> f32 {
+    1.0
+}
+
 /// Obstacle spawning component.
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct ObstacleSpawner {
     timer: Timer,
     level: SpawnerSettings,
     next_level: Option<SpawnerSettings>,
     stats: SpawnStats,
+
+    last_item: Option<LastItem>,
+
+    /// Multiple of the level's item speed requested with
+    /// [`SpeedScaleEvent`]s, within [`SPEED_MULT_RANGE`].
+    speed_mult: f32,
 }
 
 impl ObstacleSpawner {
+    /// Settings of the level currently being spawned.
+    pub fn level(&self) -> &SpawnerSettings {
+        &self.level
+    }
+
     /// Set the new spawner settings, and update the time to match the new level settings.
+    ///
+    /// Time already run past the last spawn carries over, so the spacing
+    /// doesn't depend on how long the frame of a level change was.
     fn set_level(&mut self, level: SpawnerSettings) {
+        let elapsed = self.timer.elapsed();
         self.level = level;
         self.timer = Timer::from_seconds(self.level.seconds_per_item, TimerMode::Repeating);
+        self.timer.set_elapsed(elapsed.min(self.timer.duration()));
+    }
+
+    /// Remember an item spawned at the level space x position `x`, whose
+    /// right edge extends `extent` past it.
+    fn record_item(&mut self, x: f32, extent: f32, odometer: &ScrollOdometer) {
+        self.last_item = Some(LastItem {
+            right_x: x + extent,
+            distance: odometer.distance,
+            slowest: default_speed_scale(),
+        });
+    }
+
+    /// Note that the item just recorded has a behavior, which changes its
+    /// speed.
+    fn record_behavior(&mut self, behavior: Option<&Behavior>) {
+        if let (Some(last), Some(behavior)) = (self.last_item.as_mut(), behavior) {
+            last.slowest = behavior.speed_scale_range()[0];
+        }
     }
 
     /// If there is a queued next level, set the level to this new level to take effect, and clear
@@ -84,250 +334,704 @@ impl ObstacleSpawner {
     fn advance_queued_level(&mut self) -> bool {
         if let Some(next_level) = self.next_level.take() {
             self.set_level(next_level);
+            self.stats.levels.push(self.level.name.clone());
             true
         } else {
             false
         }
     }
 
+    /// Reset for a new run. The timer is fast-forwarded so that the first
+    /// item spawns once the level's warm-up has passed.
     fn reset(&mut self) {
         self.timer = Timer::from_seconds(self.level.seconds_per_item, TimerMode::Repeating);
+        let until_first = self
+            .level
+            .warm_up_secs
+            .clamp(0.0, self.level.seconds_per_item);
+        self.timer.set_elapsed(Duration::from_secs_f32(
+            self.level.seconds_per_item - until_first,
+        ));
         self.stats.reset();
+        self.stats.levels.push(self.level.name.clone());
+        self.last_item = None;
+        self.speed_mult = 1.0;
+    }
+
+    /// Level space x position to spawn the next item at.
+    ///
+    /// Normally the level start offset, but shifted back if it would be too
+    /// close to the previous item.
+    ///
+    /// `late_x` is how far items have scrolled since the spawn was due, so
+    /// that items land at the same spacing at any frame rate.
+    ///
+    /// `fastest` is the largest multiple of the level speed that the new
+    /// item scrolls at. An item faster than the one before closes in on it
+    /// until that one leaves the level, so it starts back by as much.
+    fn next_start_x(
+        &self,
+        play_world: &WorldSettings,
+        physics: &PhysicsSettings,
+        odometer: &ScrollOdometer,
+        late_x: f32,
+        fastest: f32,
+    ) -> f32 {
+        let start_x = self.level.start_offset_x(play_world, physics) + late_x;
+        let Some(last) = self.last_item else {
+            return start_x;
+        };
+        // assume the last item has scrolled as slowly as it can.
+        let last_right = last.right_x - (odometer.distance - last.distance) * last.slowest;
+        let to_leave = (last_right - play_world.level_bounds().min.x).max(0.0);
+        let catch_up = (fastest / last.slowest - 1.0).max(0.0) * to_leave;
+        start_x.max(last_right + self.level.min_gap_between_items + catch_up)
+    }
+
+    /// Level space x position that items are spawned at, when not pushed
+    /// back by the previous item.
+    pub fn start_offset_x(&self, play_world: &WorldSettings, physics: &PhysicsSettings) -> f32 {
+        self.level.start_offset_x(play_world, physics)
+    }
+
+    /// Names of the levels played in this run, in order.
+    pub fn level_history(&self) -> &[String] {
+        &self.stats.levels
+    }
+
+    /// Item velocity of the current level, in pixels per second, scaled by
+    /// any requested speed changes.
+    pub fn item_vel(&self, physics: &PhysicsSettings) -> Vec2 {
+        physics.vec_to_pixels(self.level.item_vel) * self.speed_mult
+    }
+
+    /// Scale the item speed by `scale`, keeping the total within
+    /// [`SPEED_MULT_RANGE`].
+    fn scale_speed(&mut self, scale: f32) {
+        let (min, max) = SPEED_MULT_RANGE;
+        if scale.is_finite() {
+            self.speed_mult = (self.speed_mult * scale).clamp(min, max);
+        }
+    }
+
+    /// Capture the state of the spawner, so that it can be restored later.
+    pub fn save_state(&self) -> SpawnerState {
+        SpawnerState {
+            level: self.level.clone(),
+            next_level: self.next_level.clone(),
+            timer_elapsed_secs: self.timer.elapsed_secs(),
+            stats: self.stats.clone(),
+            last_item: self.last_item,
+            speed_mult: self.speed_mult,
+        }
+    }
+
+    /// Restore a previously saved spawner state.
+    pub fn restore_state(&mut self, state: SpawnerState) {
+        self.set_level(state.level);
+        self.next_level = state.next_level;
+        self.timer
+            .set_elapsed(Duration::from_secs_f32(state.timer_elapsed_secs));
+        self.stats = state.stats;
+        self.last_item = state.last_item;
+        self.speed_mult = state.speed_mult;
+    }
+}
+
+/// Serializable state of an [`ObstacleSpawner`].
+#[derive(Serialize, Deserialize)]
+pub struct SpawnerState {
+    level: SpawnerSettings,
+    next_level: Option<SpawnerSettings>,
+    timer_elapsed_secs: f32,
+    stats: SpawnStats,
+    /// Kept, along with the odometer, so that the first items spawned
+    /// after continuing keep their spacing from the restored ones.
+    #[serde(default)]
+    last_item: Option<LastItem>,
+    #[serde(default = "default_speed_scale")]
+    speed_mult: f32,
+}
+
+/// Fraction of the gap height range that tunnels are drawn from in manual
+/// flip mode, where flipping at will would make the usual gaps easy.
+const MANUAL_FLIP_GAP_FRACTION: f32 = 0.5;
+
+/// Tunnel settings of a level, adjusted for the mode of the run.
+fn tunnel_settings(level: &SpawnerSettings, mode: GameMode) -> Cow<'_, TunnelSpawnSettings> {
+    if mode == GameMode::ManualFlip {
+        Cow::Owned(
+            level
+                .tunnel_settings
+                .with_tighter_gaps(MANUAL_FLIP_GAP_FRACTION),
+        )
+    } else {
+        Cow::Borrowed(&level.tunnel_settings)
     }
 }
 
-/// Update the timers on the obstacle spawners
-fn update_spawner_timers(time: Res<Time>, mut query: Query<&mut ObstacleSpawner>) {
+/// Whether the run spawns gravity regions, from the level, mod obstacles or
+/// training scenarios alike. Manual flips stand in for them.
+pub fn spawns_gravity_regions(mutators: &ActiveMutators, config: &RunConfig) -> bool {
+    mutators.spawns_gravity_regions() && config.mode != GameMode::ManualFlip
+}
+
+/// Update the timers on the obstacle spawners by the time the physics
+/// stepped through last frame, so that items are spawned after the same
+/// steps (and so in the same places) however the frames fall.
+fn update_spawner_timers(steps: Res<PhysicsSteps>, mut query: Query<&mut ObstacleSpawner>) {
     for mut spawner in query.iter_mut() {
-        spawner.timer.tick(time.delta());
+        spawner.timer.tick(steps.last_frame_delta());
     }
 }
 
 /// On a timer, spawn one of many items.
+#[allow(clippy::too_many_arguments)]
 fn spawn_items(
-    mut commands: Commands,
+    mut factory: ObstacleFactory,
     mut spawner_query: Query<&mut ObstacleSpawner>,
     level_settings: Res<LevelSettings>,
-    mut meshes: ResMut<Assets<Mesh>>,
     play_world: Res<WorldSettings>,
-    obs_mat: Res<BarrierAssets>,
-    grav_mat: Res<GravityMaterials>,
+    mut odometer: ResMut<ScrollOdometer>,
+    physics: Res<PhysicsSettings>,
+    mut rng: ResMut<RunRng>,
+    mut metrics: ResMut<DifficultyMetrics>,
+    speed: Res<SpeedController>,
     mut change_level: EventWriter<LevelChangeEvent>,
+    mut level_up: EventWriter<LevelUpEvent>,
+    mutators: Res<ActiveMutators>,
+    config: Res<RunConfig>,
+    mods: Res<Mods>,
+    obstacle_sets: Res<Assets<ObstacleSet>>,
 ) {
-    let mut rng = rand::thread_rng();
+    let custom: Vec<_> = mods
+        .obstacles()
+        .filter_map(|set| obstacle_sets.get(set))
+        .flat_map(|set| &set.obstacles)
+        .collect();
+    let gravity_regions = spawns_gravity_regions(&mutators, &config);
     for mut spawner in spawner_query.iter_mut() {
         if spawner.timer.just_finished() {
-            let mut choices = vec![(SpawnOption::Tunnel, spawner.level.tunnel_weight)];
+            // the timer finished partway through the frame, and the item
+            // would have scrolled since.
+            let late_x = speed.current.x * spawner.timer.elapsed_secs();
+
+            // the door of the last key fills this slot.
+            if spawner.stats.door_in == Some(0) {
+                let start_x = spawner.next_start_x(&play_world, &physics, &odometer, late_x, 1.0);
+                spawner.stats.door_in = None;
+                let mut width = spawner.level.door_settings.width;
+                // sessions saved before doors waited for their slot had
+                // spawned the door along with its key.
+                if let Some(door) = spawner.stats.door.take() {
+                    let ent = factory.spawn_door_at(start_x, &door.params, true);
+                    factory.commands.entity(ent).insert(SpawnIndex(door.index));
+                    width = door.params.width;
+                }
+                spawner.record_item(start_x, width * 0.5, &odometer);
+                if spawner.advance_queued_level() {
+                    change_level.send(LevelChangeEvent);
+                    level_up.send(LevelUpEvent);
+                }
+                continue;
+            }
+            if let Some(left) = spawner.stats.door_in.as_mut() {
+                *left -= 1;
+            }
+
+            let mut choices = vec![
+                (SpawnOption::Tunnel, spawner.level.tunnel_weight),
+                (SpawnOption::Drift, spawner.level.drift_weight),
+                (SpawnOption::Bouncer, spawner.level.bouncer_weight),
+                (SpawnOption::Drone, spawner.level.drone_weight),
+                (SpawnOption::Laser, spawner.level.laser_weight),
+                (SpawnOption::PowerUp, spawner.level.power_up_weight),
+            ];
+            choices.extend(custom.iter().enumerate().map(|(i, obstacle)| {
+                let gravity = matches!(obstacle.params, SpawnParams::Gravity { .. });
+                let weight = if gravity && !gravity_regions {
+                    0.0
+                } else {
+                    obstacle.weight
+                };
+                (SpawnOption::Custom(i), weight)
+            }));
+            // one door at a time, so each key opens the door after it.
+            if spawner.stats.door_in.is_none() {
+                choices.push((SpawnOption::KeyDoor, spawner.level.door_weight));
+            }
 
-            if spawner.stats.since_last_gravity >= spawner.level.min_items_between_gravity {
+            if gravity_regions
+                && spawner.stats.since_last_gravity >= spawner.level.min_items_between_gravity
+            {
                 choices.push((SpawnOption::Gravity, spawner.level.gravity_weight));
+                choices.push((SpawnOption::Chain, spawner.level.chain_weight));
             }
             spawner.stats.num_items += 1;
-            let dist =
-                rand::distributions::WeightedIndex::new(choices.iter().map(|x| x.1)).unwrap();
-            match choices[rng.sample(dist)].0 {
+            // levels of only gravity regions fall back to tunnels when they
+            // can't spawn.
+            let choice = rand::distributions::WeightedIndex::new(choices.iter().map(|x| x.1))
+                .map_or(SpawnOption::Tunnel, |dist| {
+                    choices[rng.sample(dist)].0.clone()
+                });
+            let behavior = match choice {
+                SpawnOption::Tunnel => spawner.level.tunnel_settings.behavior.clone(),
+                SpawnOption::Bouncer => spawner.level.bouncer_settings.behavior.clone(),
+                _ => None,
+            };
+            let fastest = behavior.as_ref().map_or(1.0, |b| b.speed_scale_range()[1]);
+            let start_x = spawner.next_start_x(&play_world, &physics, &odometer, late_x, fastest);
+            match choice {
                 SpawnOption::Tunnel => {
                     spawner.stats.since_last_gravity += 1;
-                    spawn_tunnel(
-                        &spawner.level.tunnel_settings,
-                        &mut commands,
-                        &spawner.level,
-                        &mut meshes,
-                        &play_world,
-                        &obs_mat,
+                    let (tunnel, gap_height) = factory.spawn_random_tunnel(
+                        start_x,
+                        &tunnel_settings(&spawner.level, config.mode),
+                        &mut **rng,
                     );
+                    factory
+                        .commands
+                        .entity(tunnel)
+                        .insert(odometer.next_index());
+                    let width = spawner.level.tunnel_settings.obstacle_width;
+                    spawner.record_item(start_x, width * 0.5, &odometer);
+                    spawner.record_behavior(behavior.as_ref());
+                    metrics.record_gap_height(gap_height);
                 }
                 SpawnOption::Gravity => {
                     spawner.stats.since_last_gravity = 0;
-                    let gs = &spawner.level.gravity_settings;
-                    let start_x =
-                        spawner.level.start_offset_x(&play_world) + gs.gravity_width * 0.5;
-                    spawn_gravity_region(
-                        &mut commands,
+                    let region = factory.spawn_gravity_at(
+                        start_x,
+                        -level_settings.gravity_mult,
+                        &spawner.level.gravity_settings,
+                    );
+                    factory
+                        .commands
+                        .entity(region)
+                        .insert(odometer.next_index());
+                    let width = spawner.level.gravity_settings.gravity_width;
+                    spawner.record_item(start_x, width * 0.5, &odometer);
+                }
+                SpawnOption::Chain => {
+                    spawner.stats.since_last_gravity = 0;
+                    let level = &spawner.level;
+                    let chain = factory.spawn_gravity_chain_at(
+                        start_x,
                         -level_settings.gravity_mult,
+                        &level.chain_settings,
+                        &level.gravity_settings,
+                        &level.tunnel_settings,
+                    );
+                    // the parts share an index, as a single item.
+                    let index = odometer.next_index();
+                    for part in [chain.first, chain.tunnel, chain.second] {
+                        factory.commands.entity(part).insert(index);
+                    }
+                    let width = spawner.level.gravity_settings.gravity_width;
+                    spawner.record_item(start_x, chain.width - width * 0.5, &odometer);
+                    metrics.record_gap_height(spawner.level.chain_settings.gap_height);
+                }
+                SpawnOption::Drift => {
+                    spawner.stats.since_last_gravity += 1;
+                    // alternate directions, so the stream weaves.
+                    let up = !spawner.stats.last_drift_up;
+                    spawner.stats.last_drift_up = up;
+                    let settings = &spawner.level.drift_settings;
+                    let drift = if up { settings.speed } else { -settings.speed };
+                    let region = factory.spawn_drift_at(start_x, drift, settings);
+                    factory
+                        .commands
+                        .entity(region)
+                        .insert(odometer.next_index());
+                    let width = spawner.level.drift_settings.width;
+                    spawner.record_item(start_x, width * 0.5, &odometer);
+                }
+                SpawnOption::Bouncer => {
+                    spawner.stats.since_last_gravity += 1;
+                    let bouncer = factory.spawn_random_bouncer(
+                        start_x,
+                        &spawner.level.bouncer_settings,
+                        &mut **rng,
+                    );
+                    factory
+                        .commands
+                        .entity(bouncer)
+                        .insert(odometer.next_index());
+                    let width = spawner.level.bouncer_settings.width;
+                    spawner.record_item(start_x, width * 0.5, &odometer);
+                    spawner.record_behavior(behavior.as_ref());
+                }
+                SpawnOption::Drone => {
+                    spawner.stats.since_last_gravity += 1;
+                    let drone = factory.spawn_random_drone(
+                        start_x,
+                        &spawner.level.drone_settings,
+                        &mut **rng,
+                    );
+                    factory.commands.entity(drone).insert(odometer.next_index());
+                    let size = spawner.level.drone_settings.size;
+                    spawner.record_item(start_x, size * 0.5, &odometer);
+                }
+                SpawnOption::Laser => {
+                    spawner.stats.since_last_gravity += 1;
+                    let gate = factory.spawn_random_laser(
+                        start_x,
+                        &spawner.level.laser_settings,
+                        &mut **rng,
+                    );
+                    factory.commands.entity(gate).insert(odometer.next_index());
+                    let width = spawner.level.laser_settings.width;
+                    spawner.record_item(start_x, width * 0.5, &odometer);
+                }
+                SpawnOption::KeyDoor => {
+                    spawner.stats.since_last_gravity += 1;
+                    let [min, max] = spawner.level.door_settings.items_between;
+                    let between = rng.gen_range(min..=max);
+                    let key =
+                        factory.spawn_random_key(start_x, &spawner.level.door_settings, &mut **rng);
+                    let index = odometer.next_index();
+                    factory.commands.entity(key).insert(index);
+                    // the door takes the slot `between` items after the key.
+                    let params = factory.random_door(&spawner.level.door_settings, &mut **rng);
+                    spawner.stats.door = Some(PendingDoor {
+                        params,
+                        index: index.0,
+                    });
+                    spawner.stats.door_in = Some(between);
+                    let size = spawner.level.door_settings.key_size;
+                    spawner.record_item(start_x, size * 0.5, &odometer);
+                }
+                SpawnOption::PowerUp => {
+                    spawner.stats.since_last_gravity += 1;
+                    let pickup = factory.spawn_random_power_up(
                         start_x,
-                        gs,
-                        &spawner.level,
-                        &play_world,
-                        &grav_mat,
+                        &spawner.level.power_up_settings,
+                        &mut **rng,
                     );
+                    factory
+                        .commands
+                        .entity(pickup)
+                        .insert(odometer.next_index());
+                    let size = spawner.level.power_up_settings.size;
+                    spawner.record_item(start_x, size * 0.5, &odometer);
+                }
+                SpawnOption::Custom(i) => {
+                    spawner.stats.since_last_gravity += 1;
+                    let params = &custom[i].params;
+                    let item = factory.spawn_params_at(start_x, params);
+                    factory.commands.entity(item).insert(odometer.next_index());
+                    spawner.record_item(start_x, params.width() * 0.5, &odometer);
                 }
             }
 
             // Set the level to the next level if there is a level queued.
             if spawner.advance_queued_level() {
                 change_level.send(LevelChangeEvent);
+                level_up.send(LevelUpEvent);
             }
         }
     }
 }
-/// Spawn a gravity region with the given gravity mult.
-fn spawn_gravity_region(
-    commands: &mut Commands,
-    gravity_mult: f32,
-    start_x: f32,
-    gs: &GravityRegionSettings,
-    spawn_settings: &SpawnerSettings,
-    play_world: &Res<WorldSettings>,
-    grav_mat: &Res<GravityMaterials>,
+
+/// Closest that prefilled tunnels are placed ahead of the player start, in
+/// pixels.
+const PREFILL_CLEARANCE: f32 = 240.0;
+
+/// Fill the field with tunnels as a new run begins, as if the level had
+/// already been scrolling.
+///
+/// Tunnels are placed back from the first spawn position, one item's travel
+/// apart, skipping any that would be too close to the player.
+#[allow(clippy::too_many_arguments)]
+fn prefill_items(
+    mut factory: ObstacleFactory,
+    mut spawner_query: Query<&mut ObstacleSpawner>,
+    play_world: Res<WorldSettings>,
+    speed: Res<SpeedController>,
+    mut odometer: ResMut<ScrollOdometer>,
+    physics: Res<PhysicsSettings>,
+    mut rng: ResMut<RunRng>,
+    mut metrics: ResMut<DifficultyMetrics>,
+    config: Res<RunConfig>,
 ) {
-    let vel = Velocity {
-        linvel: spawn_settings.item_vel,
-        ..default()
-    };
+    for mut spawner in spawner_query.iter_mut() {
+        // continued sessions already have their items.
+        if spawner.stats.num_items > 0 {
+            continue;
+        }
+        let spacing = -speed.current.x * spawner.level.seconds_per_item;
+        let start_x = spawner.start_offset_x(&play_world, &physics);
+        let min_x = spawner.level.player_start.level_position(&play_world).x + PREFILL_CLEARANCE;
 
-    let width = gs.gravity_width;
-    commands
-        .spawn(new_gravity_region(
-            gravity_mult,
-            start_x,
-            width,
-            &play_world,
-            &grav_mat,
-        ))
-        .insert((
-            Name::new(format!(
-                "gravity {}",
-                if gravity_mult > 0.0 { "down" } else { "up" }
-            )),
-            RemoveWhenLeft(width),
-            RemoveOnReset,
-            vel,
-            Obstacle,
-        ));
+        for k in (1..=spawner.level.prefill_count).rev() {
+            let x = start_x - k as f32 * spacing;
+            if x < min_x {
+                continue;
+            }
+            let (tunnel, gap_height) = factory.spawn_random_tunnel(
+                x,
+                &tunnel_settings(&spawner.level, config.mode),
+                &mut **rng,
+            );
+            factory
+                .commands
+                .entity(tunnel)
+                .insert(odometer.next_index());
+            spawner.stats.num_items += 1;
+            spawner.stats.since_last_gravity += 1;
+            let width = spawner.level.tunnel_settings.obstacle_width;
+            spawner.record_item(x, width * 0.5, &odometer);
+            metrics.record_gap_height(gap_height);
+        }
+    }
 }
 
-/// Spawn two barriers and a scoring region.
-fn spawn_tunnel(
-    tunnel: &TunnelSpawnSettings,
-    commands: &mut Commands,
-    spawn: &SpawnerSettings,
-    mut meshes: &mut ResMut<Assets<Mesh>>,
-    play_world: &Res<WorldSettings>,
-    obs_mat: &Res<BarrierAssets>,
+/// Spawn items requested through [`SpawnRequestEvent`].
+#[allow(clippy::too_many_arguments)]
+fn spawn_requested_items(
+    mut factory: ObstacleFactory,
+    mut requests: EventReader<SpawnRequestEvent>,
+    mut spawner_query: Query<&mut ObstacleSpawner>,
+    level_settings: Res<LevelSettings>,
+    play_world: Res<WorldSettings>,
+    mut odometer: ResMut<ScrollOdometer>,
+    physics: Res<PhysicsSettings>,
+    mutators: Res<ActiveMutators>,
+    config: Res<RunConfig>,
 ) {
-    // create the level obstacles and the scoring region.
-    let vel = Velocity {
-        linvel: spawn.item_vel,
-        ..default()
+    let Ok(mut spawner) = spawner_query.get_single_mut() else {
+        return;
     };
-    let mut rng = rand::thread_rng();
-
-    let gap_center = tunnel.center_y_range[0]
-        + rng.gen::<f32>() * (tunnel.center_y_range[1] - tunnel.center_y_range[0]);
-    let gap_height = tunnel.gap_height_range[0]
-        + rng.gen::<f32>() * (tunnel.gap_height_range[1] - tunnel.gap_height_range[0]);
-
-    let top_height = play_world.bounds.max.y - (gap_center + gap_height / 2.0);
-    let bottom_height = (gap_center - gap_height / 2.0) - play_world.bounds.min.y;
-
-    let scoring_gap_height = play_world.bounds.height() - top_height - bottom_height;
-    let scoring_gap_width = tunnel.scoring_gap_width;
-    let region = commands
-        .spawn(new_scoring_region(
-            1,
-            Vec2::new(
-                spawn.start_offset_x(&play_world) + tunnel.obstacle_width - scoring_gap_width / 2.0,
-                gap_center,
-            ),
-            Vec2::new(scoring_gap_width, scoring_gap_height),
-        ))
-        .insert((
-            RemoveWhenLeft(scoring_gap_width),
-            RemoveOnReset,
-            vel,
-            Obstacle,
-        ))
-        .id();
-
-    commands
-        .spawn(new_barrier(
-            true,
-            tunnel.obstacle_width,
-            top_height,
-            spawn.start_offset_x(&play_world) + tunnel.obstacle_width / 2.0,
-            &mut meshes,
-            &play_world,
-            &obs_mat,
-        ))
-        .insert((
-            Name::new("top_barrier"),
-            RegionRef { region },
-            RemoveWhenLeft(tunnel.obstacle_width),
-            RemoveOnReset,
-            vel,
-            Obstacle,
-        ));
-    commands
-        .spawn(new_barrier(
-            false,
-            tunnel.obstacle_width,
-            bottom_height,
-            spawn.start_offset_x(&play_world) + tunnel.obstacle_width / 2.0,
-            &mut meshes,
-            &play_world,
-            &obs_mat,
-        ))
-        .insert((
-            Name::new("bottom_barrier"),
-            RegionRef { region },
-            RemoveWhenLeft(tunnel.obstacle_width),
-            RemoveOnReset,
-            vel,
-            Obstacle,
-        ));
+    for request in requests.read() {
+        let start_x = spawner.next_start_x(&play_world, &physics, &odometer, 0.0, 1.0);
+        match request {
+            SpawnRequestEvent::Gravity { down } => {
+                if !spawns_gravity_regions(&mutators, &config) {
+                    bevy::log::debug!("dropping gravity region request, as the run has none");
+                    continue;
+                }
+                let magnitude = level_settings.gravity_mult.abs();
+                let region = factory.spawn_gravity_at(
+                    start_x,
+                    if *down { magnitude } else { -magnitude },
+                    &spawner.level.gravity_settings,
+                );
+                factory
+                    .commands
+                    .entity(region)
+                    .insert(odometer.next_index());
+                let width = spawner.level.gravity_settings.gravity_width;
+                spawner.record_item(start_x, width * 0.5, &odometer);
+            }
+        }
+    }
+}
+
+/// Scale the current level speed.
+fn scale_level_speed(
+    mut events: EventReader<SpeedScaleEvent>,
+    mut spawners: Query<&mut ObstacleSpawner>,
+    mut change_level: EventWriter<LevelChangeEvent>,
+) {
+    for SpeedScaleEvent(scale) in events.read() {
+        for mut spawner in spawners.iter_mut() {
+            spawner.scale_speed(*scale);
+            change_level.send(LevelChangeEvent);
+        }
+    }
 }
+/// Score at which the fast level is queued.
+const FAST_LEVEL_SCORE: i32 = 2;
 
-/// Update spawner when the score reaches a certain amount.
+/// Update spawner when the score reaches a certain amount: the fast level,
+/// then each mod level in turn.
 fn update_spawner_by_score(
     mut spawners: Query<&mut ObstacleSpawner>,
     score: Res<Score>,
+    mut last_points: Local<i32>,
     ss: Res<Assets<SpawnerSettings>>,
     levels: Res<Levels>,
+    mods: Res<Mods>,
 ) {
-    if score.score == 2 && score.is_changed() {
-        for mut spawner in spawners.iter_mut() {
-            tracing::event!(tracing::Level::INFO, "queued level change");
-            spawner.next_level = Some(ss.get(&levels.fast_level).unwrap().clone());
+    // the score changes every frame while grazing, so only act when the
+    // whole points change.
+    let points = score.points();
+    let changed = *last_points != points;
+    *last_points = points;
+    if !changed {
+        return;
+    }
+    for mut spawner in spawners.iter_mut() {
+        // a big award can cross several thresholds at once, in which case
+        // the last level crossed is queued. Mod levels that aren't ready keep
+        // their place, so that the points of the levels after them don't
+        // depend on how quickly they load.
+        let mut reached = None;
+        for (i, level) in std::iter::once(Some(&levels.fast_level))
+            .chain(mods.levels())
+            .enumerate()
+            .skip(spawner.stats.thresholds_crossed)
+        {
+            if points < FAST_LEVEL_SCORE + i as i32 * MOD_LEVEL_POINTS {
+                break;
+            }
+            spawner.stats.thresholds_crossed = i + 1;
+            reached = level.or(reached);
+        }
+        let Some(level) = reached else {
+            continue;
+        };
+        match ss.get(level) {
+            Some(settings) => {
+                tracing::event!(tracing::Level::INFO, "queued level change");
+                spawner.next_level = Some(settings.clone());
+            }
+            None => warn!("skipping level {:?}, which isn't loaded", level.path()),
         }
     }
 }
 
-/// Use a tweener to update obstacle speeds when the level changes.
-fn update_obstacle_speeds(
-    mut commands: Commands,
+/// Set the gravity and jump of a level, as it takes effect.
+fn set_level_physics(
+    level: &SpawnerSettings,
+    level_settings: &mut ResMut<LevelSettings>,
+    rapier_config: &mut ResMut<RapierConfiguration>,
+    physics: &PhysicsSettings,
+    play_world: &WorldSettings,
+    mutators: &ActiveMutators,
+) {
+    level_settings.set_base_physics(physics, level.gravity, level.jump_speed, mutators);
+    level_settings.flip_world_gravity = level.flip_world_gravity;
+    level_settings.sync_to_rapier(rapier_config, play_world.scroll_axis);
+}
+
+/// Apply the gravity and jump overrides of the new level when the level
+/// changes, or the mutators do.
+fn apply_level_physics(
+    obstacle_spawner: Query<&ObstacleSpawner>,
+    mut level_settings: ResMut<LevelSettings>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    physics: Res<PhysicsSettings>,
+    play_world: Res<WorldSettings>,
+    mutators: Res<ActiveMutators>,
+) {
+    for spawner in obstacle_spawner.iter() {
+        set_level_physics(
+            &spawner.level,
+            &mut level_settings,
+            &mut rapier_config,
+            &physics,
+            &play_world,
+            &mutators,
+        );
+    }
+}
+
+/// Start tweening to the new level speed when the level changes.
+fn retarget_speed(
     obstacle_spawner: Query<&ObstacleSpawner>,
-    obstacles: Query<(Entity, &Velocity), With<Obstacle>>,
+    mut speed: ResMut<SpeedController>,
+    physics: Res<PhysicsSettings>,
 ) {
-    let Ok(item_vel) = obstacle_spawner.get_single().map(|x| x.level.item_vel) else {
+    let Ok(target) = obstacle_spawner.get_single().map(|x| x.item_vel(&physics)) else {
         return;
     };
-    for (ent, vel) in obstacles.iter() {
-        let anim = Animator::new(Tween::new(
-            EaseMethod::Linear,
-            Duration::from_secs_f64(0.5),
-            LinearVelocityLens {
-                start_linvel: vel.linvel,
-                end_linvel: item_vel,
-            },
-        ));
-        commands
-            .entity(ent)
-            .remove::<Animator<Velocity>>()
-            .insert(anim);
+    speed.tween_to(target);
+}
+
+/// Refresh the difficulty metrics from the spawner.
+fn update_difficulty_metrics(
+    spawners: Query<&ObstacleSpawner>,
+    speed: Res<SpeedController>,
+    score: Res<Score>,
+    time: Res<Time>,
+    mut last_flips: Local<u32>,
+    mut metrics: ResMut<DifficultyMetrics>,
+) {
+    metrics.item_vel = speed.current;
+    if let Ok(spawner) = spawners.get_single() {
+        metrics.seconds_per_item = spawner.level.seconds_per_item;
+    }
+    if score.gravity_flips != *last_flips {
+        *last_flips = score.gravity_flips;
+        metrics.secs_since_gravity_flip = 0.0;
+    } else {
+        metrics.secs_since_gravity_flip += time.delta_seconds();
+    }
+}
+
+fn measure_spawn_rate(
+    time: Res<Time>,
+    odometer: Res<ScrollOdometer>,
+    mut diagnostics: Diagnostics,
+    mut window: Local<RateWindow>,
+) {
+    if let Some(rate) = window.sample(time.delta_seconds(), odometer.spawn_index as f64) {
+        diagnostics.add_measurement(OBSTACLE_SPAWN_RATE, || rate);
+    }
+}
+
+fn reset_difficulty_metrics(mut metrics: ResMut<DifficultyMetrics>) {
+    *metrics = DifficultyMetrics::default();
+}
+
+/// Add the distance that the physics steps of this frame scrolled, then
+/// advance the speed tween by as long.
+fn update_speed_controller(
+    steps: Res<PhysicsSteps>,
+    mut speed: ResMut<SpeedController>,
+    mut odometer: ResMut<ScrollOdometer>,
+) {
+    // obstacles moved at the current speed for every step of the frame.
+    odometer.distance -= speed.current.x * steps.last_frame_secs;
+    speed.tick(steps.last_frame_delta());
+}
+
+/// Move every obstacle at the current speed, scaled by any speed scale of
+/// its own, plus any drift of its own.
+#[allow(clippy::type_complexity)]
+fn apply_obstacle_speeds(
+    speed: Res<SpeedController>,
+    play_world: Res<WorldSettings>,
+    mut obstacles: Query<(&mut Velocity, Option<&Drift>, Option<&SpeedScale>), With<Obstacle>>,
+) {
+    for (mut vel, drift, scale) in obstacles.iter_mut() {
+        let drift = drift.map_or(0.0, |d| d.0);
+        let scale = scale.map_or(1.0, |s| s.0);
+        vel.linvel = play_world
+            .scroll_axis
+            .to_world(speed.current * scale + Vec2::new(0.0, drift));
     }
 }
 
 /// Reset the state of the obstacle spawners.
-fn reset_obstacle_spawner(
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn reset_obstacle_spawner(
     mut spawners: Query<&mut ObstacleSpawner>,
     levels: Res<Levels>,
     s: Res<Assets<SpawnerSettings>>,
+    mut speed: ResMut<SpeedController>,
+    mut odometer: ResMut<ScrollOdometer>,
+    physics: Res<PhysicsSettings>,
+    mut level_settings: ResMut<LevelSettings>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    play_world: Res<WorldSettings>,
+    mutators: Res<ActiveMutators>,
 ) {
+    *odometer = ScrollOdometer::default();
     for mut spawner in spawners.iter_mut() {
         // reset the level back to the base level.
         spawner.set_level(s.get(&levels.base_level).unwrap().clone());
         spawner.reset();
+        speed.reset(spawner.item_vel(&physics));
+        set_level_physics(
+            &spawner.level,
+            &mut level_settings,
+            &mut rapier_config,
+            &physics,
+            &play_world,
+            &mutators,
+        );
     }
 }
 
@@ -336,47 +1040,90 @@ fn setup_obstacle_spawner(
     mut commands: Commands,
     levels: Res<Levels>,
     s: Res<Assets<SpawnerSettings>>,
+    mut speed: ResMut<SpeedController>,
+    physics: Res<PhysicsSettings>,
 ) {
     let settings = s.get(&levels.base_level).unwrap();
-    commands.spawn(ObstacleSpawner {
+    speed.reset(physics.vec_to_pixels(settings.item_vel));
+    let mut spawner = ObstacleSpawner {
         timer: Timer::from_seconds(settings.seconds_per_item, TimerMode::Repeating),
-        level: s.get(&levels.base_level).unwrap().clone(),
+        level: settings.clone(),
         next_level: None,
         stats: SpawnStats::default(),
-    });
+        last_item: None,
+        speed_mult: 1.0,
+    };
+    spawner.reset();
+    commands.spawn(spawner);
 }
 
 pub struct ObstacleSpawnerPlugin;
 
 impl Plugin for ObstacleSpawnerPlugin {
     fn build(&self, app: &mut App) {
-        let initial_secs_per_item = 2.0;
-
-        let mut timer = Timer::from_seconds(initial_secs_per_item, TimerMode::Repeating);
-        timer.tick(Duration::from_secs_f32(initial_secs_per_item - 0.01));
-
-        app.add_collection_to_loading_state::<_, Levels>(GameState::AssetLoading)
+        app.add_game_collection::<Levels>()
+            .register_diagnostic(
+                Diagnostic::new(OBSTACLE_SPAWN_RATE, "obstacle_spawn_rate", 20).with_suffix("/s"),
+            )
             .add_event::<LevelChangeEvent>()
-            .add_systems(OnExit(GameState::AssetLoading), setup_obstacle_spawner)
-            .add_systems(PreUpdate, update_spawner_timers)
+            .add_event::<LevelUpEvent>()
+            .add_event::<SpawnRequestEvent>()
+            .add_event::<SpeedScaleEvent>()
+            .register_type::<Obstacle>()
+            .register_type::<ObstacleSpawner>()
+            .register_type::<SpawnStats>()
+            .register_type::<SpeedController>()
+            .init_resource::<SpeedController>()
+            .register_type::<ScrollOdometer>()
+            .register_type::<SpawnIndex>()
+            .init_resource::<ScrollOdometer>()
+            .register_type::<DifficultyMetrics>()
+            .init_resource::<DifficultyMetrics>()
+            .add_systems(ON_ASSETS_LOADED, setup_obstacle_spawner)
             .add_systems(
-                Update,
-                component_animator_system::<Velocity>.in_set(AnimationSystem::AnimationUpdate),
+                OnEnter(GameState::Playing),
+                prefill_items.run_if(not(training_active)),
+            )
+            .add_systems(
+                PreUpdate,
+                update_spawner_timers.run_if(in_state(GameState::Playing)),
             )
             .add_systems(
                 Update,
                 (
-                    spawn_items,
-                    update_spawner_by_score,
-                    update_obstacle_speeds.run_if(on_event::<LevelChangeEvent>()),
+                    (
+                        scale_level_speed,
+                        retarget_speed.run_if(on_event::<LevelChangeEvent>()),
+                        spawn_items.run_if(not(training_active)),
+                        spawn_requested_items,
+                        apply_obstacle_speeds,
+                    )
+                        .chain(),
+                    update_spawner_by_score.run_if(not(training_active)),
+                    apply_level_physics
+                        .after(spawn_items)
+                        .run_if(on_event::<LevelChangeEvent>()),
+                    update_difficulty_metrics.after(spawn_items),
+                    measure_spawn_rate.after(spawn_requested_items),
                     // spawn_tunnel.run_if(input_just_pressed(KeyCode::O)),
                     // spawn_gravity_region.run_if(input_just_pressed(KeyCode::G)),
                 )
                     .run_if(in_state(GameState::Playing)),
             )
+            .add_systems(
+                Update,
+                apply_level_physics.run_if(resource_changed::<ActiveMutators>()),
+            )
+            .add_systems(
+                PostUpdate,
+                update_speed_controller
+                    .after(count_physics_steps)
+                    .before(reset_obstacle_spawner)
+                    .run_if(in_state(GameState::Playing)),
+            )
             .add_systems(
                 PostUpdate,
-                reset_obstacle_spawner.run_if(on_event::<ResetEvent>()),
+                (reset_obstacle_spawner, reset_difficulty_metrics).run_if(on_event::<ResetEvent>()),
             );
     }
 }
@@ -0,0 +1,95 @@
+//! Overlay shown while the game is paused, with the settings that can be
+//! changed from it.
+use bevy::prelude::*;
+
+use crate::{
+    fonts::FontsCollection,
+    hitstop::Hitstop,
+    settings::Settings,
+    ui_theme::{ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    ON_ASSETS_LOADED,
+};
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct PauseMenu;
+
+fn spawn_pause_menu(
+    mut commands: Commands,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    containers: Res<Containers>,
+) {
+    let (text, themed) = theme.text(
+        "",
+        fonts.menu_font.clone(),
+        ThemedText {
+            size: 40.0,
+            color: Color::ANTIQUE_WHITE,
+        },
+    );
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "pause_menu",
+        (
+            Text2dBundle {
+                text: text.with_alignment(TextAlignment::Center),
+                transform: Transform::from_xyz(0.0, 0.0, 30.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            PauseMenu,
+            themed,
+        ),
+    );
+}
+
+/// Change the game speed with the bracket keys while paused.
+fn adjust_game_speed(
+    time: Res<Time<Virtual>>,
+    hitstop: Res<Hitstop>,
+    keys: Res<Input<KeyCode>>,
+    mut settings: ResMut<Settings>,
+) {
+    if !time.is_paused() || hitstop.is_active() {
+        return;
+    }
+    if keys.just_pressed(KeyCode::BracketLeft) {
+        settings.game_speed.step(-1);
+    }
+    if keys.just_pressed(KeyCode::BracketRight) {
+        settings.game_speed.step(1);
+    }
+}
+
+fn update_pause_menu(
+    time: Res<Time<Virtual>>,
+    hitstop: Res<Hitstop>,
+    settings: Res<Settings>,
+    mut menu: Query<(&mut Text, &mut Visibility), With<PauseMenu>>,
+) {
+    for (mut text, mut vis) in menu.iter_mut() {
+        // a hitstop freezes the game, but isn't a pause.
+        if !time.is_paused() || hitstop.is_active() {
+            *vis = Visibility::Hidden;
+            continue;
+        }
+        *vis = Visibility::Visible;
+        text.sections[0].value = format!(
+            "PAUSED\nGAME SPEED: {:.0}%\n[ / ] TO CHANGE",
+            settings.game_speed.clamped() * 100.0
+        );
+    }
+}
+
+pub struct PauseMenuPlugin;
+
+impl Plugin for PauseMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PauseMenu>()
+            .add_systems(ON_ASSETS_LOADED, spawn_pause_menu)
+            .add_systems(Update, (adjust_game_speed, update_pause_menu).chain());
+    }
+}
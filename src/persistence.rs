@@ -0,0 +1,190 @@
+//! Saving and loading of data that persists between launches.
+//!
+//! Files are written atomically, so a crash mid-save never leaves a
+//! truncated file behind, and wrapped with a schema version so that older
+//! files can be migrated.
+use std::{
+    io::Write,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
+use bevy::{app::AppExit, prelude::*};
+use ron::ser::PrettyConfig;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+/// Time to wait after the last change to a resource before autosaving it.
+const AUTOSAVE_DEBOUNCE_SECS: f32 = 1.0;
+
+#[derive(Serialize)]
+struct VersionedRef<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct Versioned<T> {
+    version: u32,
+    data: T,
+}
+
+/// Write a file, so that it is either fully replaced or left untouched.
+///
+/// Missing parent directories are created.
+pub fn write_atomic(path: impl AsRef<Path>, contents: &[u8]) -> std::io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    drop(file);
+
+    std::fs::rename(&tmp, path)
+}
+
+/// Read a file, returning `None` if it does not exist.
+fn read_if_exists(path: impl AsRef<Path>) -> std::io::Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Save `data` as RON, tagged with a schema version.
+pub fn save_versioned<T: Serialize>(
+    path: impl AsRef<Path>,
+    version: u32,
+    data: &T,
+) -> anyhow::Result<()> {
+    let contents =
+        ron::ser::to_string_pretty(&VersionedRef { version, data }, PrettyConfig::default())?;
+    write_atomic(path, contents.as_bytes())?;
+    Ok(())
+}
+
+/// Load data written by [`save_versioned`], along with its schema version.
+///
+/// Returns `None` if there is no file. Unknown fields are ignored and
+/// missing fields fall back to their serde defaults, so files from both
+/// older and newer versions can be read.
+pub fn load_versioned<T: DeserializeOwned>(
+    path: impl AsRef<Path>,
+) -> anyhow::Result<Option<(u32, T)>> {
+    let Some(contents) = read_if_exists(path)? else {
+        return Ok(None);
+    };
+    let versioned: Versioned<T> = ron::from_str(&contents)?;
+    Ok(Some((versioned.version, versioned.data)))
+}
+
+/// A resource that is loaded on startup and autosaved when it changes.
+pub trait Persistent: Resource + Default + Serialize + DeserializeOwned {
+    /// File the resource is stored in.
+    const PATH: &'static str;
+
+    /// Current schema version. Bump when the meaning of saved data changes.
+    const VERSION: u32;
+
+    /// Update data that was saved with an older schema version.
+    fn migrate(&mut self, _from_version: u32) {}
+
+    /// Parse a file written before the data was versioned.
+    fn load_unversioned(_contents: &str) -> Option<Self> {
+        None
+    }
+}
+
+fn load_persistent<T: Persistent>() -> anyhow::Result<Option<T>> {
+    let Some(contents) = read_if_exists(T::PATH)? else {
+        return Ok(None);
+    };
+    match ron::from_str::<Versioned<T>>(&contents) {
+        Ok(Versioned { version, mut data }) => {
+            if version > T::VERSION {
+                bevy::log::warn!(
+                    "{} was saved by a newer version ({} > {})",
+                    T::PATH,
+                    version,
+                    T::VERSION
+                );
+            } else if version < T::VERSION {
+                data.migrate(version);
+            }
+            Ok(Some(data))
+        }
+        Err(e) => T::load_unversioned(&contents)
+            .map(Some)
+            .ok_or_else(|| e.into()),
+    }
+}
+
+/// Load the saved copy of a resource right away, for settings that are
+/// needed before the app is built. Falls back to the default if there is no
+/// readable copy.
+///
+/// Logging may not be set up yet, so problems are printed instead.
+pub fn load_or_default<T: Persistent>() -> T {
+    match load_persistent::<T>() {
+        Ok(loaded) => loaded.unwrap_or_default(),
+        Err(e) => {
+            eprintln!("ignoring unreadable {}: {}", T::PATH, e);
+            T::default()
+        }
+    }
+}
+
+/// Replace the resource with the saved copy, if there is one.
+fn load_resource<T: Persistent>(mut data: ResMut<T>) {
+    match load_persistent::<T>() {
+        Ok(Some(loaded)) => *data = loaded,
+        Ok(None) => {}
+        Err(e) => bevy::log::warn!("ignoring unreadable {}: {}", T::PATH, e),
+    }
+}
+
+/// Save the resource once it has stopped changing for a while, or when
+/// exiting.
+fn autosave_resource<T: Persistent>(
+    data: Res<T>,
+    time: Res<Time<Real>>,
+    mut pending: Local<Option<Timer>>,
+    mut exit: EventReader<AppExit>,
+) {
+    // the resource is always added on the first run, which is not a change
+    // worth saving.
+    if data.is_changed() && !data.is_added() {
+        *pending = Some(Timer::from_seconds(AUTOSAVE_DEBOUNCE_SECS, TimerMode::Once));
+    }
+    let exiting = exit.read().count() > 0;
+
+    let Some(timer) = pending.as_mut() else {
+        return;
+    };
+    if !timer.tick(time.delta()).finished() && !exiting {
+        return;
+    }
+    *pending = None;
+
+    if let Err(e) = save_versioned(T::PATH, T::VERSION, &*data) {
+        bevy::log::error!("could not save {}: {}", T::PATH, e);
+    }
+}
+
+/// Plugin that loads and autosaves a [`Persistent`] resource.
+#[derive(Default)]
+pub struct PersistencePlugin<T>(PhantomData<T>);
+
+impl<T: Persistent> Plugin for PersistencePlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<T>()
+            .add_systems(Startup, load_resource::<T>)
+            .add_systems(Last, autosave_resource::<T>);
+    }
+}
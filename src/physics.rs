@@ -0,0 +1,170 @@
+//! Physics scale, and the gameplay speeds and accelerations defined in
+//! physical units.
+//!
+//! Speeds and accelerations (here and in the level files) are in meters
+//! and seconds. They are converted to pixels with
+//! [`PhysicsSettings::to_pixels`], the same scale that rapier is configured
+//! with, so the two never disagree. Lengths and positions, such as obstacle
+//! sizes and offsets, stay in pixels, to match the sprites.
+use bevy::prelude::*;
+use bevy_rapier2d::plugin::{
+    PhysicsSet, RapierConfiguration, SimulationToRenderTime, TimestepMode,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Longest frame that a variable physics step covers, in steps. Virtual time
+/// is capped to the same length, so that the rest of the game never runs
+/// ahead of the physics.
+pub const MAX_VARIABLE_STEPS: usize = 4;
+
+/// How rapier advances the simulation.
+#[derive(Reflect, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PhysicsTimestep {
+    /// Steps of a fixed length, at [`PhysicsSettings::steps_per_second`],
+    /// with transforms interpolated between them.
+    #[default]
+    Fixed,
+
+    /// A single step per frame, covering the whole frame.
+    Variable,
+}
+
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct PhysicsSettings {
+    /// Screen pixels per meter. The speeds here and in the level files
+    /// are tuned to this scale.
+    pub pixels_per_meter: f32,
+
+    /// Speed of the player at the start of a jump, in m/s.
+    pub jump_speed: f32,
+
+    /// Magnitude of gravity, in m/s^2.
+    pub gravity: f32,
+
+    /// Speed that death pieces fly apart at, in m/s.
+    pub explosion_speed: f32,
+
+    /// Jumps that can be chained before the last one is over. With 1, each
+    /// jump has to finish before the next.
+    pub max_jumps: u32,
+
+    /// Physics steps per second. Obstacles and the player are interpolated
+    /// between steps, so they move smoothly on faster displays.
+    pub steps_per_second: f32,
+
+    /// Whether rapier runs fixed steps or a step per frame. Picked at
+    /// launch, from the settings file.
+    pub timestep: PhysicsTimestep,
+}
+
+impl Default for PhysicsSettings {
+    fn default() -> Self {
+        Self {
+            pixels_per_meter: 96.0,
+            jump_speed: 3.125,
+            gravity: 5.208_333,
+            explosion_speed: 6.25,
+            max_jumps: 1,
+            steps_per_second: 60.0,
+            timestep: PhysicsTimestep::Fixed,
+        }
+    }
+}
+
+impl PhysicsSettings {
+    /// Convert a length (or speed, or acceleration) in meters into pixels.
+    pub fn to_pixels(&self, meters: f32) -> f32 {
+        meters * self.pixels_per_meter
+    }
+
+    /// Convert a vector in meters into pixels.
+    pub fn vec_to_pixels(&self, meters: Vec2) -> Vec2 {
+        meters * self.pixels_per_meter
+    }
+
+    /// Timestep mode for rapier.
+    ///
+    /// Rapier runs on virtual time, which already has the game speed
+    /// applied, so the time scale stays at 1.0. Variable frames are split
+    /// into substeps no longer than a fixed step, so fast obstacles don't
+    /// skip over the player on slow frames.
+    pub fn timestep_mode(&self) -> TimestepMode {
+        match self.timestep {
+            PhysicsTimestep::Fixed => TimestepMode::Interpolated {
+                dt: 1.0 / self.steps_per_second,
+                time_scale: 1.0,
+                substeps: 1,
+            },
+            PhysicsTimestep::Variable => TimestepMode::Variable {
+                max_dt: self.max_frame_secs(),
+                time_scale: 1.0,
+                substeps: MAX_VARIABLE_STEPS,
+            },
+        }
+    }
+
+    /// Longest frame that the physics covers in full. Longer frames slow
+    /// the game down, rather than letting it run ahead of the physics.
+    pub fn max_frame_secs(&self) -> f32 {
+        MAX_VARIABLE_STEPS as f32 / self.steps_per_second
+    }
+}
+
+/// Physics steps that rapier ran in the last frame. With fixed steps, a
+/// frame can run none, or several.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct PhysicsSteps {
+    pub last_frame: u32,
+
+    /// Time that the steps of the last frame simulated, in seconds.
+    pub last_frame_secs: f32,
+}
+
+impl PhysicsSteps {
+    /// Time that the steps of the last frame simulated.
+    pub fn last_frame_delta(&self) -> Duration {
+        Duration::from_secs_f32(self.last_frame_secs)
+    }
+}
+
+/// Count the steps rapier just ran. With fixed steps, each frame adds its
+/// time to what the simulation owes, and each step takes a step's worth
+/// back.
+pub fn count_physics_steps(
+    config: Res<RapierConfiguration>,
+    sim_time: Res<SimulationToRenderTime>,
+    time: Res<Time>,
+    mut steps: ResMut<PhysicsSteps>,
+    mut last_diff: Local<f32>,
+) {
+    let last = std::mem::replace(&mut *last_diff, sim_time.diff);
+    (steps.last_frame, steps.last_frame_secs) = if !config.physics_pipeline_active {
+        (0, 0.0)
+    } else {
+        match config.timestep_mode {
+            TimestepMode::Interpolated { dt, .. } => {
+                let count = ((last + time.delta_seconds() - sim_time.diff) / dt).round() as u32;
+                (count, count as f32 * dt)
+            }
+            _ => (1, time.delta_seconds()),
+        }
+    };
+}
+
+pub struct PhysicsStepsPlugin;
+
+impl Plugin for PhysicsStepsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PhysicsSteps>()
+            .init_resource::<PhysicsSteps>()
+            .add_systems(
+                PostUpdate,
+                count_physics_steps
+                    .after(PhysicsSet::StepSimulation)
+                    .before(PhysicsSet::Writeback),
+            );
+    }
+}
@@ -1,27 +1,112 @@
 use std::time::Duration;
 
-use bevy::prelude::*;
+use bevy::{prelude::*, sprite::Anchor, utils::Instant};
 use bevy_asset_loader::{asset_collection::AssetCollection, loading_state::LoadingStateAppExt};
 use bevy_rapier2d::prelude::*;
 use bevy_tweening::{lens::TransformRotationLens, Animator, EaseFunction, Tween};
 
 use crate::{
-    gravity_shift::GravityEvent, level::LevelSettings, GameState, LevelSet, WorldSettings,
+    animation::{animate_sprites, SpriteAnimation},
+    asset_errors::GameCollectionAppExt,
+    asset_variants::{AssetScale, AssetVariantAppExt},
+    barrier::Barrier,
+    controls::{jump_pressed, ControlSchemes, InputTimestamp, LastInputDevice},
+    gravity_shift::GravityEvent,
+    level::LevelSettings,
+    mutators::ActiveMutators,
+    obstacle::spawner_settings::PlayerStart,
+    obstacle_spawner::ObstacleSpawner,
+    physics::PhysicsSettings,
+    player_collision_groups, player_solver_groups,
+    replay::live_input,
+    run::{GameMode, RunConfig},
+    scoring_region::GatePassedEvent,
+    ui_theme::HudLayout,
+    GameState, LevelSet, ScrollAxis, WorldSettings, ON_ASSETS_LOADED,
 };
 
 const JUMP_ANIM_FRAMES: u32 = 4;
 const JUMP_ANIM_TIME: f32 = 0.1;
 
+/// World units per logical pixel of the player sprites.
 pub const PLAYER_SCALE: f32 = 2.0;
 
+/// Size of a rocket frame, in logical pixels.
+pub(crate) const PLAYER_FRAME_SIZE: Vec2 = Vec2::new(32.0, 32.0);
+
+/// Size of a flame frame, in logical pixels.
+const FLAME_FRAME_SIZE: Vec2 = Vec2::new(8.0, 16.0);
+
+/// Half extents of the rocket's collider, at normal size.
+const PLAYER_HALF_EXTENTS: Vec2 = Vec2::new(20.0, 28.0);
+
+/// Offset of the flame from the rocket center, at the nozzle, at normal size.
+const FLAME_NOZZLE_Y: f32 = -PLAYER_FRAME_SIZE.y * PLAYER_SCALE * 0.4;
+
 /// Time in seconds to complete a full rotation.
 const ROTATION_TIME: f32 = 0.25;
 
+/// Largest tilt from velocity, in radians.
+const MAX_VELOCITY_TILT: f32 = 0.5;
+
+/// Speed along the rocket's nose at which the tilt is largest, in m/s.
+const VELOCITY_TILT_SPEED: f32 = 4.166_667;
+
+/// How quickly the rotation follows the velocity tilt, per second.
+const VELOCITY_TILT_RESPONSE: f32 = 12.0;
+
+const FLAME_ANIM_FRAMES: usize = 4;
+
+/// Flame frames shown per second.
+const FLAME_FLICKER_RATE: f32 = 20.0;
+
+/// Player sprite sheets, at the resolution picked for the window.
 #[derive(Resource, AssetCollection)]
 struct PlayerSprites {
-    #[asset(texture_atlas(tile_size_x = 32., tile_size_y = 32., columns = 4, rows = 1))]
-    #[asset(path = "images/rocketman.png")]
-    player_atlas: Handle<TextureAtlas>,
+    #[asset(key = "player.rocket")]
+    rocket: Handle<Image>,
+
+    #[asset(key = "player.flame")]
+    flame: Handle<Image>,
+}
+
+/// Atlases over the player sprite sheets, sized for the loaded resolution.
+#[derive(Resource)]
+pub(crate) struct PlayerAtlases {
+    pub(crate) player_atlas: Handle<TextureAtlas>,
+    flame_atlas: Handle<TextureAtlas>,
+
+    /// Texture pixels per logical pixel.
+    texture_scale: f32,
+}
+
+impl FromWorld for PlayerAtlases {
+    fn from_world(world: &mut World) -> Self {
+        let texture_scale = world.resource::<AssetScale>().factor();
+        let sprites = world.resource::<PlayerSprites>();
+        let (rocket, flame) = (sprites.rocket.clone(), sprites.flame.clone());
+
+        let mut atlases = world.resource_mut::<Assets<TextureAtlas>>();
+        PlayerAtlases {
+            player_atlas: atlases.add(TextureAtlas::from_grid(
+                rocket,
+                PLAYER_FRAME_SIZE * texture_scale,
+                JUMP_ANIM_FRAMES as usize,
+                1,
+                None,
+                None,
+            )),
+            flame_atlas: atlases.add(TextureAtlas::from_grid(
+                flame,
+                FLAME_FRAME_SIZE * texture_scale,
+                FLAME_ANIM_FRAMES,
+                1,
+                None,
+                None,
+            )),
+            texture_scale,
+        }
+    }
 }
 
 #[derive(Component, Reflect, PartialEq, Eq)]
@@ -30,12 +115,33 @@ enum PlayerState {
     Falling,
 }
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct Player;
 
 #[derive(Clone, PartialEq, Eq, Hash, Debug, SystemSet)]
 pub struct PlayerSet;
 
+/// Size of a rocket relative to normal, such as from a size power up. The
+/// sprite, collider and flame are resized to match.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct RocketScale(pub f32);
+
+impl Default for RocketScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+/// Exhaust flame, attached as a child of the player.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct PlayerFlame {
+    /// Unscaled size of the flame sprite.
+    base_size: Vec2,
+}
+
 #[derive(Component, Default, Reflect, PartialEq, Eq, Clone, Copy, Debug)]
 enum PlayerRotTarget {
     #[default]
@@ -52,32 +158,114 @@ impl PlayerRotTarget {
     }
 }
 
+/// Jumps a rocket has left to chain. Refilled when a jump is over, so a
+/// rocket that isn't jumping always has a full set.
+#[derive(Component, Reflect, Default, Clone, Copy, PartialEq, Eq, Debug)]
+#[reflect(Component)]
+pub struct JumpsLeft(pub u32);
+
 #[derive(Component, Reflect, PartialEq)]
-struct PlayerAnim {
+pub(crate) struct PlayerAnim {
     tick: f32,
     state: PlayerState,
     rotation_target: PlayerRotTarget,
 }
 
+impl PlayerAnim {
+    /// Start a jump, if there is one left. A jump started while another is
+    /// underway chains on from it, restarting the animation.
+    ///
+    /// Returns true if the jump started.
+    pub(crate) fn start_jump(&mut self, jumps_left: &mut JumpsLeft) -> bool {
+        if jumps_left.0 == 0 {
+            return false;
+        }
+        jumps_left.0 -= 1;
+        if self.state == PlayerState::Jumping {
+            self.tick = 0.0;
+        }
+        self.state = PlayerState::Jumping;
+        true
+    }
+}
+
 #[derive(Event)]
 pub struct OutOfBoundsEvent;
 
-/// Create the initial player.
-fn spawn_player(
-    mut commands: Commands,
-    atlases: Res<Assets<TextureAtlas>>,
-    sprites: Res<PlayerSprites>,
-) {
-    let r = atlases.get(&sprites.player_atlas).unwrap();
-    let cs = r.textures[0].size() * PLAYER_SCALE;
-    commands.spawn((
+/// Window after a gravity change during which the player is held at the
+/// edge of the world, rather than killed for leaving it.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct OutOfBoundsGrace {
+    /// Length of the window, in seconds.
+    pub duration: f32,
+
+    /// Time left in the current window, if any.
+    timer: Option<Timer>,
+}
+
+impl Default for OutOfBoundsGrace {
+    fn default() -> Self {
+        Self {
+            duration: 0.5,
+            timer: None,
+        }
+    }
+}
+
+impl OutOfBoundsGrace {
+    pub fn is_active(&self) -> bool {
+        self.timer.as_ref().is_some_and(|t| !t.finished())
+    }
+}
+
+/// Request for the player to jump.
+#[derive(Event)]
+pub struct JumpEvent {
+    /// When the input that asked for the jump was read.
+    pub input_at: Instant,
+}
+
+impl JumpEvent {
+    /// Jump for an input read this frame.
+    pub fn from_input(stamp: &InputTimestamp) -> Self {
+        Self { input_at: **stamp }
+    }
+}
+
+impl Default for JumpEvent {
+    /// Jump with no input behind it, timestamped now.
+    fn default() -> Self {
+        Self {
+            input_at: Instant::now(),
+        }
+    }
+}
+
+/// Sent when a jump has been applied to the player's velocity.
+#[derive(Event)]
+pub struct JumpedEvent {
+    /// When the input that asked for the jump was read.
+    pub input_at: Instant,
+}
+
+/// Rocket shared by the player and ghosts: its sprite, jump animation and
+/// physics body, starting at the start of the level. Collisions are left to
+/// the caller.
+pub(crate) fn rocket_body(
+    sprites: &PlayerAtlases,
+    world: &WorldSettings,
+    start: &PlayerStart,
+    physics: &PhysicsSettings,
+) -> impl Bundle {
+    (
         SpriteSheetBundle {
             sprite: TextureAtlasSprite {
-                custom_size: Some(cs),
+                custom_size: Some(PLAYER_FRAME_SIZE * PLAYER_SCALE),
                 index: 0,
                 ..default()
             },
-            transform: Transform::from_translation(Vec3::new(0.0, 0.0, 10.0)),
+            transform: world.level_transform(start.level_position(world), 10.0),
             texture_atlas: sprites.player_atlas.clone(),
             ..default()
         },
@@ -86,47 +274,160 @@ fn spawn_player(
             state: PlayerState::Jumping,
             rotation_target: PlayerRotTarget::Up,
         },
-        Player,
-        Collider::cuboid(20.0, 28.0),
+        // the first jump is still playing out.
+        JumpsLeft(0),
+        RocketScale::default(),
+        Collider::cuboid(PLAYER_HALF_EXTENTS.x, PLAYER_HALF_EXTENTS.y),
         RigidBody::Dynamic,
-	GravityScale::default(),
-        Velocity::default(),
+        GravityScale::default(),
+        Velocity::linear(
+            world
+                .scroll_axis
+                .to_world(physics.vec_to_pixels(start.velocity)),
+        ),
         Sensor,
-        Name::new("Player"),
-    ));
+    )
+}
+
+/// Create the initial player, at the start of the level.
+fn spawn_player(
+    mut commands: Commands,
+    sprites: Res<PlayerAtlases>,
+    world: Res<WorldSettings>,
+    start: &PlayerStart,
+    physics: &PhysicsSettings,
+) {
+    commands
+        .spawn((
+            rocket_body(&sprites, &world, start, physics),
+            Player,
+            player_collision_groups(),
+            player_solver_groups(),
+            TransformInterpolation::default(),
+            Name::new("Player"),
+        ))
+        .with_children(|parent| {
+            let base_size = FLAME_FRAME_SIZE * PLAYER_SCALE;
+            parent.spawn((
+                SpriteSheetBundle {
+                    sprite: TextureAtlasSprite {
+                        custom_size: Some(base_size),
+                        anchor: Anchor::TopCenter,
+                        ..default()
+                    },
+                    // hang from the nozzle, just behind the rocket
+                    transform: Transform::from_xyz(0.0, FLAME_NOZZLE_Y, -0.1),
+                    texture_atlas: sprites.flame_atlas.clone(),
+                    ..default()
+                },
+                PlayerFlame { base_size },
+                SpriteAnimation::looping(0..FLAME_ANIM_FRAMES, FLAME_FLICKER_RATE),
+                Name::new("flame"),
+            ));
+        });
+}
+
+/// Face the rocket the other way when the HUD is mirrored.
+#[allow(clippy::type_complexity)]
+fn mirror_player_sprite(
+    layout: Res<HudLayout>,
+    mut sprites: Query<&mut TextureAtlasSprite, Or<(With<Player>, With<PlayerFlame>)>>,
+) {
+    for mut sprite in sprites.iter_mut() {
+        // only write on a change, to keep change detection quiet.
+        if sprite.flip_x != layout.mirrored {
+            sprite.flip_x = layout.mirrored;
+        }
+    }
 }
 
 /// Handle jumping inputs for the player.
 fn handle_input(
-    mut player: Query<(&mut PlayerAnim, &mut Velocity)>,
     keys: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    stamp: Res<InputTimestamp>,
+    mut jumps: EventWriter<JumpEvent>,
+) {
+    if jump_pressed(&keys, &buttons) {
+        jumps.send(JumpEvent::from_input(&stamp));
+    }
+}
+
+/// Start a jump when requested.
+fn jump_player(
+    mut jumps: EventReader<JumpEvent>,
+    mut jumped: EventWriter<JumpedEvent>,
+    mut player: Query<(&mut PlayerAnim, &mut JumpsLeft, &mut Velocity), With<Player>>,
     level: Res<LevelSettings>,
+    world: Res<WorldSettings>,
 ) {
-    for (mut p, mut v) in player.iter_mut() {
-        if keys.just_pressed(KeyCode::Space) && p.state != PlayerState::Jumping {
-            p.state = PlayerState::Jumping;
-            v.linvel = level.jump_vector();
+    let Some(input_at) = jumps.read().map(|jump| jump.input_at).min() else {
+        return;
+    };
+    for (mut p, mut jumps_left, mut v) in player.iter_mut() {
+        // jumps always push against the player's gravity, whichever way it
+        // points.
+        if p.start_jump(&mut jumps_left) {
+            v.linvel = world.scroll_axis.to_world(level.jump_vector());
+            jumped.send(JumpedEvent { input_at });
         }
     }
 }
 
+/// Give a rocket back the extra jumps of its chain, as it passes a gate.
+pub(crate) fn refill_jumps(jumps_left: &mut JumpsLeft, level: &LevelSettings) {
+    jumps_left.0 = jumps_left.0.max(level.max_jumps.saturating_sub(1));
+}
+
+/// Give the player back the extra jumps of its chain when it passes a gate.
+fn refill_jumps_on_gates(
+    mut gates: EventReader<GatePassedEvent>,
+    mut player: Query<&mut JumpsLeft, With<Player>>,
+    level: Res<LevelSettings>,
+) {
+    if gates.read().count() == 0 {
+        return;
+    }
+    for mut jumps_left in player.iter_mut() {
+        refill_jumps(&mut jumps_left, &level);
+    }
+}
+
+/// Gravity scale for a rocket with gravity mult `gravity_mult`, moving at
+/// world space `vel`, so that it falls faster than it rises.
+///
+/// Rapier's gravity is the level's world gravity, shared by every body, so
+/// the gravity mult is applied here, and only flips rockets.
+pub(crate) fn gravity_scale(
+    vel: Vec2,
+    level: &LevelSettings,
+    gravity_mult: f32,
+    axis: ScrollAxis,
+) -> f32 {
+    let gravity = axis.to_world(level.gravity_vector_for(gravity_mult));
+    let fall = if vel.dot(gravity) > 0.0 { 1.2 } else { 1.0 };
+    level.rocket_gravity_mult_for(gravity_mult) * fall
+}
+
 fn update_player_gravity(
     mut player: Query<(&Velocity, &mut GravityScale), With<Player>>,
-    rapier_config: Res<RapierConfiguration>,
+    level: Res<LevelSettings>,
+    world: Res<WorldSettings>,
 ) -> anyhow::Result<()> {
     let (vel, mut gs) = player.get_single_mut()?;
-    if vel.linvel.y * rapier_config.gravity.y > 0.0 {
-	gs.0 = 1.2;
-    } else {
-	gs.0 = 1.0;
-    }
+    gs.0 = gravity_scale(vel.linvel, &level, level.gravity_mult, world.scroll_axis);
 
     Ok(())
 }
 
-/// Update the animation state of the player based on its action state.
-fn update_anim(mut player: Query<(&mut PlayerAnim, &mut TextureAtlasSprite)>, time: Res<Time>) {
-    for (mut anim, mut sprite) in player.iter_mut() {
+/// Update the animation state of the player based on its action state, and
+/// give back its jumps once a jump is over.
+fn update_anim(
+    mut player: Query<(&mut PlayerAnim, &mut JumpsLeft, &mut TextureAtlasSprite)>,
+    level: Res<LevelSettings>,
+    time: Res<Time>,
+) {
+    for (mut anim, mut jumps_left, mut sprite) in player.iter_mut() {
         if anim.state == PlayerState::Jumping {
             anim.tick += time.delta_seconds() / JUMP_ANIM_TIME;
         } else {
@@ -135,40 +436,226 @@ fn update_anim(mut player: Query<(&mut PlayerAnim, &mut TextureAtlasSprite)>, ti
         anim.tick = anim.tick.clamp(0.0, 1.0);
         if anim.tick == 1.0 {
             anim.state = PlayerState::Falling;
+            jumps_left.0 = level.max_jumps;
         }
 
         sprite.index = (anim.tick * (JUMP_ANIM_FRAMES - 1) as f32) as usize;
     }
 }
 
-/// Move the player back to the center of the play window if the
-/// player center leaves the environment bounding box.
+/// Size the exhaust flame with the jump state: a long, bright flame while
+/// jumping, and a short flicker while falling. Its frames are played by its
+/// [`SpriteAnimation`].
+fn update_flame(
+    player: Query<(&PlayerAnim, &RocketScale), With<Player>>,
+    mut flames: Query<(
+        &Parent,
+        &PlayerFlame,
+        &mut TextureAtlasSprite,
+        &mut Transform,
+    )>,
+) {
+    for (parent, flame, mut sprite, mut t) in flames.iter_mut() {
+        let Ok((anim, scale)) = player.get(parent.get()) else {
+            continue;
+        };
+        let (length, alpha) = match anim.state {
+            PlayerState::Jumping => (1.0 + anim.tick * 0.5, 1.0),
+            PlayerState::Falling => (0.5, if sprite.index % 2 == 0 { 0.8 } else { 0.4 }),
+        };
+        sprite.custom_size = Some(flame.base_size * Vec2::new(1.0, length) * scale.0);
+        sprite.color.set_a(alpha);
+        t.translation.y = FLAME_NOZZLE_Y * scale.0;
+    }
+}
+
+/// Resize the sprite and collider of rockets whose scale changed, or when
+/// the mutators change their base size.
+fn apply_rocket_scale(
+    mut rockets: Query<(Ref<RocketScale>, &mut TextureAtlasSprite, &mut Collider)>,
+    mutators: Res<ActiveMutators>,
+) {
+    for (scale, mut sprite, mut collider) in rockets.iter_mut() {
+        if !scale.is_changed() && !mutators.is_changed() {
+            continue;
+        }
+        let scale = scale.0 * mutators.player_scale();
+        sprite.custom_size = Some(PLAYER_FRAME_SIZE * PLAYER_SCALE * scale);
+        let half = PLAYER_HALF_EXTENTS * scale;
+        *collider = Collider::cuboid(half.x, half.y);
+    }
+}
+
+/// Open the out of bounds grace window on each gravity change.
+fn start_out_of_bounds_grace(
+    mut grace: ResMut<OutOfBoundsGrace>,
+    mut gevs: EventReader<GravityEvent>,
+) {
+    if gevs.read().count() > 0 {
+        grace.timer = Some(Timer::from_seconds(grace.duration, TimerMode::Once));
+    }
+}
+
+/// Where a player that has left the top or bottom of the level comes back
+/// in from the other side, or `None` if it left from the sides.
+///
+/// The part of the move past the edge is swept in from the opposite edge,
+/// so that the player stops at the first barrier there rather than jumping
+/// over it.
+fn wrap_position(
+    pos: Vec2,
+    rot: f32,
+    collider: &Collider,
+    filter: QueryFilter,
+    rapier: &RapierContext,
+    play_world: &WorldSettings,
+) -> Option<Vec2> {
+    let axis = play_world.scroll_axis;
+    let bounds = play_world.level_bounds();
+    let level_pos = axis.to_level(pos);
+    if level_pos.x < bounds.min.x || level_pos.x > bounds.max.x {
+        return None;
+    }
+    let (entry_y, over) = if level_pos.y > bounds.max.y {
+        (bounds.min.y, level_pos.y - bounds.max.y)
+    } else if level_pos.y < bounds.min.y {
+        (bounds.max.y, level_pos.y - bounds.min.y)
+    } else {
+        return None;
+    };
+
+    let entry = axis.to_world(Vec2::new(level_pos.x, entry_y));
+    let sweep = axis.to_world(Vec2::new(0.0, over));
+    let toi = rapier
+        .cast_shape(entry, rot, sweep, collider, 1.0, filter)
+        .map_or(1.0, |(_, hit)| hit.toi);
+    Some(entry + sweep * toi)
+}
+
+/// Signal when the player center leaves the environment bounding box.
+/// During the grace window, the player is clamped to the edge instead. In
+/// wrap mode, leaving the top or bottom brings the player back in from the
+/// other side, at the same velocity.
+#[allow(clippy::too_many_arguments)]
 fn signal_player_out_of_bounds(
-    mut player: Query<&mut Transform, With<Player>>,
+    mut player: Query<(Entity, &mut Transform, &mut Velocity, &Collider), With<Player>>,
+    barriers: Query<(), With<Barrier>>,
     mut oob: EventWriter<OutOfBoundsEvent>,
+    mut grace: ResMut<OutOfBoundsGrace>,
+    rapier: Res<RapierContext>,
+    config: Res<RunConfig>,
     play_world: Res<WorldSettings>,
+    time: Res<Time>,
 ) {
-    for trans in player.iter_mut() {
-        if !play_world.bounds.contains(trans.translation.truncate()) {
+    if let Some(timer) = grace.timer.as_mut() {
+        timer.tick(time.delta());
+    }
+    let bounds = play_world.bounds;
+    let is_barrier = |e: Entity| barriers.contains(e);
+    for (ent, mut trans, mut vel, collider) in player.iter_mut() {
+        let pos = trans.translation.truncate();
+        if bounds.contains(pos) {
+            continue;
+        }
+        if config.mode == GameMode::Wrap {
+            let filter = QueryFilter::new()
+                .groups(player_collision_groups())
+                .exclude_collider(ent)
+                .predicate(&is_barrier);
+            let rot = trans.rotation.to_euler(EulerRot::XYZ).2;
+            if let Some(wrapped) = wrap_position(pos, rot, collider, filter, &rapier, &play_world) {
+                trans.translation = wrapped.extend(trans.translation.z);
+                continue;
+            }
+        }
+        if !grace.is_active() {
             oob.send(OutOfBoundsEvent);
+            continue;
+        }
+        let clamped = pos.clamp(bounds.min, bounds.max);
+        // stop moving further out along any clamped axis
+        if clamped.x != pos.x {
+            vel.linvel.x = 0.0;
+        }
+        if clamped.y != pos.y {
+            vel.linvel.y = 0.0;
         }
+        trans.translation = clamped.extend(trans.translation.z);
     }
 }
 
+fn reset_out_of_bounds_grace(mut grace: ResMut<OutOfBoundsGrace>) {
+    grace.timer = None;
+}
+
 /// System to kill and spawn the player.
 fn respawn_player(
     mut commands: Commands,
-    atlases: Res<Assets<TextureAtlas>>,
-    sprites: Res<PlayerSprites>,
+    sprites: Res<PlayerAtlases>,
     player: Query<Entity, With<Player>>,
+    spawners: Query<&ObstacleSpawner>,
+    world: Res<WorldSettings>,
+    physics: Res<PhysicsSettings>,
 ) {
     for ent in player.iter() {
-        commands.entity(ent).despawn();
+        commands.entity(ent).despawn_recursive();
+    }
+    let start = spawners
+        .get_single()
+        .map(|spawner| spawner.level().player_start.clone())
+        .unwrap_or_default();
+    spawn_player(commands, sprites, world, &start, &physics);
+}
+
+/// Turn a rocket to face away from gravity with mult `gravity_mult`, unless
+/// it is already turning that way.
+pub(crate) fn rotate_rocket(
+    commands: &mut Commands,
+    ent: Entity,
+    trans: &Transform,
+    anim: &mut PlayerAnim,
+    animator: Option<&Animator<Transform>>,
+    gravity_mult: f32,
+    axis: ScrollAxis,
+) {
+    let target_rotation = if gravity_mult > 0.0 {
+        PlayerRotTarget::Up
+    } else {
+        PlayerRotTarget::Down
+    };
+
+    // check to see if we're already rotating to there
+    if anim.rotation_target != target_rotation {
+        // Delete an existing tweener, then add a new a
+        // tweener. with the correct target.
+        if animator.is_some() {
+            commands.entity(ent).remove::<Animator<Transform>>();
+        }
+
+        let current_rot = trans.rotation;
+        let target_rot = axis.rotation() * target_rotation.rot();
+        let anim_time =
+            current_rot.angle_between(target_rot).abs() / std::f32::consts::PI * ROTATION_TIME;
+
+        let new_tween = Tween::new(
+            EaseFunction::QuadraticInOut,
+            Duration::from_secs_f32(anim_time),
+            TransformRotationLens {
+                start: current_rot,
+                end: target_rot,
+            },
+        );
+
+        // Add a new animator with the target proper target.
+        commands.entity(ent).insert(Animator::new(new_tween));
+
+        anim.rotation_target = target_rotation;
     }
-    spawn_player(commands, atlases, sprites);
 }
 
-/// Change the rotation based on a gravity multiplier.
+/// Change the rotation of the player based on a gravity multiplier. Ghosts
+/// turn with their own gravity.
+#[allow(clippy::type_complexity)]
 fn rotate_player_on_gravity_change(
     mut commands: Commands,
     mut player_q: Query<
@@ -181,54 +668,77 @@ fn rotate_player_on_gravity_change(
         With<Player>,
     >,
     mut gevs: EventReader<GravityEvent>,
+    world: Res<WorldSettings>,
 ) {
     // Check the current ratio, and see if we need to add a tweener.
     for ev in gevs.read() {
-        let target_rotation = if ev.gravity_mult > 0.0 {
-            PlayerRotTarget::Up
-        } else {
-            PlayerRotTarget::Down
-        };
-
-        // check to see if we're already rotating to there
         for (ent, trans, mut anim, animator) in player_q.iter_mut() {
-            if anim.rotation_target != target_rotation {
-                // Delete an existing tweener, then add a new a
-                // tweener. with the correct target.
-                if animator.is_some() {
-                    commands.entity(ent).remove::<Animator<Transform>>();
-                }
-
-                let current_rot = trans.rotation;
-                let target_rot = target_rotation.rot();
-                let anim_time = current_rot.angle_between(target_rot).abs() / std::f32::consts::PI
-                    * ROTATION_TIME;
-
-                let new_tween = Tween::new(
-                    EaseFunction::QuadraticInOut,
-                    Duration::from_secs_f32(anim_time),
-                    TransformRotationLens {
-                        start: current_rot,
-                        end: target_rotation.rot(),
-                    },
-                );
-
-                // Add a new animator with the target proper target.
-                commands.entity(ent).insert(Animator::new(new_tween));
+            rotate_rocket(
+                &mut commands,
+                ent,
+                trans,
+                &mut anim,
+                animator,
+                ev.gravity_mult,
+                world.scroll_axis,
+            );
+        }
+    }
+}
 
-                anim.rotation_target = target_rotation;
-            }
+/// Tilt the rocket with its velocity (nose up when rising, down when
+/// falling), on top of the orientation set by gravity.
+#[allow(clippy::type_complexity)]
+fn tilt_player_with_velocity(
+    mut player_q: Query<
+        (
+            &mut Transform,
+            &Velocity,
+            &PlayerAnim,
+            Option<&Animator<Transform>>,
+        ),
+        With<Player>,
+    >,
+    schemes: Res<ControlSchemes>,
+    device: Res<LastInputDevice>,
+    world: Res<WorldSettings>,
+    physics: Res<PhysicsSettings>,
+    time: Res<Time>,
+) {
+    if !schemes.get(device.0).velocity_tilt {
+        return;
+    }
+    for (mut t, v, anim, animator) in player_q.iter_mut() {
+        // let a gravity flip finish first.
+        if animator.is_some_and(|a| a.tweenable().progress() < 1.0) {
+            continue;
         }
+        let base = world.scroll_axis.rotation() * anim.rotation_target.rot();
+        let nose_speed = (base.inverse() * v.linvel.extend(0.0)).y;
+        let tilt = (nose_speed / physics.to_pixels(VELOCITY_TILT_SPEED)).clamp(-1.0, 1.0)
+            * MAX_VELOCITY_TILT;
+        let target = base * Quat::from_rotation_z(tilt);
+        let s = (VELOCITY_TILT_RESPONSE * time.delta_seconds()).min(1.0);
+        t.rotation = t.rotation.slerp(target, s);
     }
 }
 
 #[derive(Resource)]
 pub struct DecomposedSprite {
+    /// Offset of each pixel from the sprite center, in logical pixels, and
+    /// its color.
     pub pixels: Vec<(Vec2, Color)>,
+
+    /// Size of a single pixel, in logical pixels.
+    pub pixel_size: f32,
 }
 
 impl DecomposedSprite {
-    fn from_img_rect(img: &Image, rect: Rect) -> anyhow::Result<DecomposedSprite> {
+    fn from_img_rect(
+        img: &Image,
+        rect: Rect,
+        texture_scale: f32,
+    ) -> anyhow::Result<DecomposedSprite> {
         let center = rect.center();
         let dynamic_image = img.clone().try_into_dynamic()?;
         let buf = dynamic_image.into_rgba8();
@@ -239,7 +749,7 @@ impl DecomposedSprite {
                 .filter_map(|(x, y, c)| {
                     if c.0[3] != 0 {
                         Some((
-                            Vec2::new(x as f32 - center.x, y as f32 - center.y),
+                            Vec2::new(x as f32 - center.x, y as f32 - center.y) / texture_scale,
                             Color::rgba_u8(c.0[0], c.0[1], c.0[2], c.0[3]),
                         ))
                     } else {
@@ -247,6 +757,7 @@ impl DecomposedSprite {
                     }
                 })
                 .collect(),
+            pixel_size: 1.0 / texture_scale,
         })
     }
 }
@@ -254,7 +765,7 @@ impl DecomposedSprite {
 /// Insert the decomposed version of the player sprite.
 fn insert_decomposed_sprite(world: &mut World) {
     let atlas: &Assets<TextureAtlas> = world.get_resource().unwrap();
-    let ps: &PlayerSprites = world.get_resource().unwrap();
+    let ps: &PlayerAtlases = world.get_resource().unwrap();
     let images: &Assets<Image> = world.get_resource().unwrap();
 
     let ta = atlas.get(&ps.player_atlas).unwrap();
@@ -262,8 +773,8 @@ fn insert_decomposed_sprite(world: &mut World) {
     let img = images.get(&ta.texture).unwrap();
     let rect = ta.textures[0];
 
-    let ds = DecomposedSprite::from_img_rect(img, rect).unwrap();
-    bevy::log::warn!("{}", ds.pixels.len());
+    let ds = DecomposedSprite::from_img_rect(img, rect, ps.texture_scale).unwrap();
+    bevy::log::debug!(pixels = ds.pixels.len(), "decomposed player sprite");
     world.insert_resource(ds);
 }
 
@@ -273,21 +784,44 @@ impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
         app.register_type::<PlayerState>()
             .register_type::<PlayerAnim>()
+            .register_type::<Player>()
+            .register_type::<PlayerRotTarget>()
+            .register_type::<PlayerFlame>()
+            .register_type::<RocketScale>()
+            .register_type::<JumpsLeft>()
+            .register_type::<OutOfBoundsGrace>()
+            .init_resource::<OutOfBoundsGrace>()
             .add_event::<OutOfBoundsEvent>()
-            .add_collection_to_loading_state::<_, PlayerSprites>(GameState::AssetLoading)
-            .add_systems(OnExit(GameState::AssetLoading), insert_decomposed_sprite)
-            .add_systems(OnEnter(GameState::Ready), respawn_player.after(LevelSet))
+            .add_event::<JumpEvent>()
+            .add_event::<JumpedEvent>()
+            .add_asset_variant("player.rocket", "images/rocketman.png")
+            .add_asset_variant("player.flame", "images/flame.png")
+            .add_game_collection::<PlayerSprites>()
+            .init_resource_after_loading_state::<_, PlayerAtlases>(GameState::AssetLoading)
+            .add_systems(ON_ASSETS_LOADED, insert_decomposed_sprite)
+            .add_systems(
+                OnEnter(GameState::Ready),
+                (respawn_player.after(LevelSet), reset_out_of_bounds_grace),
+            )
             .add_systems(
                 Update,
                 (
                     update_anim,
-                    handle_input,
-                    signal_player_out_of_bounds,
+                    update_flame.after(update_anim).after(animate_sprites),
+                    (
+                        handle_input.run_if(live_input),
+                        refill_jumps_on_gates,
+                        jump_player,
+                    )
+                        .chain(),
+                    (start_out_of_bounds_grace, signal_player_out_of_bounds).chain(),
                     rotate_player_on_gravity_change,
-		    update_player_gravity.map(std::mem::drop),
+                    tilt_player_with_velocity.after(rotate_player_on_gravity_change),
+                    update_player_gravity.map(std::mem::drop),
                 )
                     .in_set(PlayerSet)
                     .run_if(in_state(GameState::Playing)),
-            );
+            )
+            .add_systems(Update, (mirror_player_sprite, apply_rocket_scale));
     }
 }
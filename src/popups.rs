@@ -0,0 +1,95 @@
+//! Short lived text popups over gates the player passes.
+use bevy::prelude::*;
+
+use crate::{
+    combo::{ComboFeedback, ComboStepEvent},
+    fonts::FontsCollection,
+    level::RemoveOnReset,
+    ui_theme::{ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    GameState,
+};
+
+/// How long a popup stays on screen.
+const POPUP_SECS: f32 = 0.6;
+
+/// How fast popups rise, in pixels per second.
+const POPUP_RISE_SPEED: f32 = 60.0;
+
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct ScorePopup {
+    timer: Timer,
+}
+
+/// Show the score for each gate, colored and sized by the streak.
+fn spawn_score_popups(
+    mut commands: Commands,
+    mut steps: EventReader<ComboStepEvent>,
+    feedback: Res<ComboFeedback>,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    containers: Res<Containers>,
+) {
+    for step in steps.read() {
+        let label = if step.streak > 1 {
+            format!("{:+} x{}", step.score_delta, step.streak)
+        } else {
+            format!("{:+}", step.score_delta)
+        };
+        spawn_named_child(
+            &mut commands,
+            containers.effects,
+            "score_popup",
+            (
+                Text2dBundle {
+                    text: Text::from_section(
+                        label,
+                        theme.text_style(
+                            fonts.score_font.clone(),
+                            ThemedText {
+                                size: 20.0 + 12.0 * feedback.intensity(step.streak),
+                                color: feedback.color(step.streak),
+                            },
+                        ),
+                    ),
+                    transform: Transform::from_translation(step.position.extend(15.0)),
+                    ..default()
+                },
+                ScorePopup {
+                    timer: Timer::from_seconds(POPUP_SECS, TimerMode::Once),
+                },
+                RemoveOnReset,
+            ),
+        );
+    }
+}
+
+/// Float popups upwards and fade them out.
+fn update_score_popups(
+    mut commands: Commands,
+    mut popups: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+    time: Res<Time<Real>>,
+) {
+    for (ent, mut popup, mut t, mut text) in popups.iter_mut() {
+        if popup.timer.tick(time.delta()).finished() {
+            commands.entity(ent).despawn_recursive();
+            continue;
+        }
+        t.translation.y += POPUP_RISE_SPEED * time.delta_seconds();
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(popup.timer.percent_left());
+        }
+    }
+}
+
+pub struct ScorePopupPlugin;
+
+impl Plugin for ScorePopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ScorePopup>().add_systems(
+            Update,
+            (spawn_score_popups, update_score_popups).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! Full screen effects applied to the main camera: a subtle vignette, a red
+//! chromatic aberration pulse on barrier hits, and desaturation while dying.
+//!
+//! The effects run in a single pass of the 2d render graph, after
+//! tonemapping. [`PostProcessConfig::enabled`] removes the pass entirely,
+//! for slower machines.
+use bevy::{
+    core_pipeline::{core_2d, fullscreen_vertex_shader::fullscreen_shader_vertex_state},
+    ecs::query::QueryItem,
+    prelude::*,
+    render::{
+        extract_component::{
+            ComponentUniforms, DynamicUniformIndex, ExtractComponent, ExtractComponentPlugin,
+            UniformComponentPlugin,
+        },
+        render_graph::{
+            NodeRunError, RenderGraphApp, RenderGraphContext, ViewNode, ViewNodeRunner,
+        },
+        render_resource::*,
+        renderer::{RenderContext, RenderDevice},
+        texture::BevyDefault,
+        view::ViewTarget,
+        RenderApp,
+    },
+};
+
+use crate::{barrier::HitBarrierEvent, GameState, MainCamera};
+
+/// Effect strengths for a camera, sent to the post process shader.
+#[derive(Component, Clone, Copy, Default, ExtractComponent, ShaderType)]
+pub struct PostProcessSettings {
+    /// Darkening at the corners of the screen.
+    pub vignette: f32,
+
+    /// Offset of the red channel, as a fraction of the distance from the
+    /// center of the screen.
+    pub aberration: f32,
+
+    /// Blend towards grayscale, from 0.0 to 1.0.
+    pub desaturation: f32,
+}
+
+/// Tuning for the post process effects.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct PostProcessConfig {
+    pub enabled: bool,
+
+    pub vignette: f32,
+
+    /// Aberration at the start of a pulse.
+    pub hit_aberration: f32,
+
+    /// Length of the aberration pulse after a barrier hit, in seconds.
+    pub hit_pulse_secs: f32,
+
+    /// Desaturation while dying.
+    pub dying_desaturation: f32,
+
+    /// Rate that desaturation fades in and out, per second.
+    pub desaturation_speed: f32,
+}
+
+impl Default for PostProcessConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            vignette: 0.4,
+            hit_aberration: 0.04,
+            hit_pulse_secs: 0.4,
+            dying_desaturation: 0.85,
+            desaturation_speed: 2.0,
+        }
+    }
+}
+
+/// Add or remove the effects from the main camera, to match the config.
+fn toggle_post_process(
+    mut commands: Commands,
+    config: Res<PostProcessConfig>,
+    cameras: Query<(Entity, Has<PostProcessSettings>), With<MainCamera>>,
+) {
+    for (ent, has_settings) in cameras.iter() {
+        if config.enabled && !has_settings {
+            commands.entity(ent).insert(PostProcessSettings {
+                vignette: config.vignette,
+                ..default()
+            });
+        } else if !config.enabled && has_settings {
+            commands.entity(ent).remove::<PostProcessSettings>();
+        }
+    }
+}
+
+/// Drive the effect strengths from the game.
+fn update_post_process(
+    mut settings: Query<&mut PostProcessSettings, With<MainCamera>>,
+    mut hits: EventReader<HitBarrierEvent>,
+    mut pulse: Local<Option<Timer>>,
+    config: Res<PostProcessConfig>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+) {
+    if hits.read().count() > 0 {
+        *pulse = Some(Timer::from_seconds(config.hit_pulse_secs, TimerMode::Once));
+    }
+    let aberration = match pulse.as_mut() {
+        Some(timer) if !timer.tick(time.delta()).finished() => {
+            config.hit_aberration * timer.percent_left()
+        }
+        _ => 0.0,
+    };
+    let target_desaturation = if *state.get() == GameState::Dying {
+        config.dying_desaturation
+    } else {
+        0.0
+    };
+    let max_step = config.desaturation_speed * time.delta_seconds();
+
+    for mut s in settings.iter_mut() {
+        s.vignette = config.vignette;
+        s.aberration = aberration;
+        s.desaturation += (target_desaturation - s.desaturation).clamp(-max_step, max_step);
+    }
+}
+
+#[derive(Default)]
+struct PostProcessNode;
+
+impl PostProcessNode {
+    const NAME: &'static str = "rustyrocket_post_process";
+}
+
+impl ViewNode for PostProcessNode {
+    type ViewQuery = (
+        &'static ViewTarget,
+        &'static DynamicUniformIndex<PostProcessSettings>,
+    );
+
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        (view_target, uniform_index): QueryItem<Self::ViewQuery>,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let post_process_pipeline = world.resource::<PostProcessPipeline>();
+        let pipeline_cache = world.resource::<PipelineCache>();
+        let pipeline_id = if view_target.is_hdr() {
+            post_process_pipeline.hdr_pipeline_id
+        } else {
+            post_process_pipeline.pipeline_id
+        };
+        // the shader may still be loading
+        let Some(pipeline) = pipeline_cache.get_render_pipeline(pipeline_id) else {
+            return Ok(());
+        };
+        let uniforms = world.resource::<ComponentUniforms<PostProcessSettings>>();
+        let Some(uniforms) = uniforms.binding() else {
+            return Ok(());
+        };
+
+        let post_process = view_target.post_process_write();
+        let bind_group = render_context.render_device().create_bind_group(
+            "post_process_bind_group",
+            &post_process_pipeline.layout,
+            &BindGroupEntries::sequential((
+                post_process.source,
+                &post_process_pipeline.sampler,
+                uniforms,
+            )),
+        );
+
+        let mut render_pass = render_context.begin_tracked_render_pass(RenderPassDescriptor {
+            label: Some("post_process_pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: post_process.destination,
+                resolve_target: None,
+                ops: Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+        });
+        render_pass.set_render_pipeline(pipeline);
+        render_pass.set_bind_group(0, &bind_group, &[uniform_index.index()]);
+        render_pass.draw(0..3, 0..1);
+
+        Ok(())
+    }
+}
+
+#[derive(Resource)]
+struct PostProcessPipeline {
+    layout: BindGroupLayout,
+    sampler: Sampler,
+    pipeline_id: CachedRenderPipelineId,
+
+    /// Pipeline for cameras with HDR enabled, such as for bloom.
+    hdr_pipeline_id: CachedRenderPipelineId,
+}
+
+impl FromWorld for PostProcessPipeline {
+    fn from_world(world: &mut World) -> Self {
+        let render_device = world.resource::<RenderDevice>();
+        let layout = render_device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("post_process_bind_group_layout"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        has_dynamic_offset: true,
+                        min_binding_size: Some(PostProcessSettings::min_size()),
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let sampler = render_device.create_sampler(&SamplerDescriptor::default());
+
+        let shader = world
+            .resource::<AssetServer>()
+            .load("shaders/post_process.wgsl");
+        let mut pipeline_cache = world.resource_mut::<PipelineCache>();
+        let mut queue_pipeline = |format: TextureFormat| {
+            pipeline_cache.queue_render_pipeline(RenderPipelineDescriptor {
+                label: Some("post_process_pipeline".into()),
+                layout: vec![layout.clone()],
+                vertex: fullscreen_shader_vertex_state(),
+                fragment: Some(FragmentState {
+                    shader: shader.clone(),
+                    shader_defs: vec![],
+                    entry_point: "fragment".into(),
+                    targets: vec![Some(ColorTargetState {
+                        format,
+                        blend: None,
+                        write_mask: ColorWrites::ALL,
+                    })],
+                }),
+                primitive: PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: MultisampleState::default(),
+                push_constant_ranges: vec![],
+            })
+        };
+        let pipeline_id = queue_pipeline(TextureFormat::bevy_default());
+        let hdr_pipeline_id = queue_pipeline(ViewTarget::TEXTURE_FORMAT_HDR);
+
+        Self {
+            layout,
+            sampler,
+            pipeline_id,
+            hdr_pipeline_id,
+        }
+    }
+}
+
+pub struct PostProcessPlugin;
+
+impl Plugin for PostProcessPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<PostProcessConfig>()
+            .init_resource::<PostProcessConfig>()
+            .add_plugins((
+                ExtractComponentPlugin::<PostProcessSettings>::default(),
+                UniformComponentPlugin::<PostProcessSettings>::default(),
+            ))
+            .add_systems(Update, (toggle_post_process, update_post_process).chain());
+
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app
+            .add_render_graph_node::<ViewNodeRunner<PostProcessNode>>(
+                core_2d::graph::NAME,
+                PostProcessNode::NAME,
+            )
+            .add_render_graph_edges(
+                core_2d::graph::NAME,
+                &[
+                    core_2d::graph::node::TONEMAPPING,
+                    PostProcessNode::NAME,
+                    core_2d::graph::node::END_MAIN_PASS_POST_PROCESSING,
+                ],
+            );
+    }
+
+    fn finish(&self, app: &mut App) {
+        let Ok(render_app) = app.get_sub_app_mut(RenderApp) else {
+            return;
+        };
+        render_app.init_resource::<PostProcessPipeline>();
+    }
+}
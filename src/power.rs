@@ -0,0 +1,50 @@
+//! Power saving for laptops: on the ready prompt and while loading the game
+//! only updates at about [`IDLE_FPS`], and the frame rate can be capped with
+//! the `fps_cap` graphics setting.
+//!
+//! The idle rate comes from winit's reactive update mode, so input is still
+//! handled as soon as it arrives.
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    utils::Instant,
+    winit::{UpdateMode, WinitSettings},
+};
+
+use crate::{settings::Settings, GameState};
+
+/// Frame rate while on the ready prompt or loading.
+pub const IDLE_FPS: f64 = 30.0;
+
+/// Update continuously while playing and dying, so the death animation and
+/// its physics run smoothly, and throttle everywhere else.
+fn set_update_mode(state: Res<State<GameState>>, mut winit: ResMut<WinitSettings>) {
+    winit.focused_mode = match state.get() {
+        GameState::Playing | GameState::Dying => UpdateMode::Continuous,
+        _ => UpdateMode::Reactive {
+            wait: Duration::from_secs_f64(1.0 / IDLE_FPS),
+        },
+    };
+}
+
+/// Sleep away the rest of the frame, when a frame rate cap is set.
+fn limit_frame_rate(settings: Res<Settings>, mut last_frame: Local<Option<Instant>>) {
+    if let (Some(cap), Some(last)) = (settings.graphics.fps_cap, *last_frame) {
+        let target = Duration::from_secs_f64(1.0 / cap.max(1) as f64);
+        if let Some(left) = target.checked_sub(last.elapsed()) {
+            std::thread::sleep(left);
+        }
+    }
+    *last_frame = Some(Instant::now());
+}
+
+pub struct PowerPlugin;
+
+impl Plugin for PowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(WinitSettings::game())
+            .add_systems(Update, set_update_mode.run_if(state_changed::<GameState>()))
+            .add_systems(Last, limit_frame_rate);
+    }
+}
@@ -0,0 +1,242 @@
+//! Temporary abilities granted to the player, by flying through pickups
+//! that the spawner scatters through the level.
+use bevy::{prelude::*, sprite::MaterialMesh2dBundle};
+use bevy_asset_loader::loading_state::LoadingStateAppExt;
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    obstacle::PowerUpParams,
+    player::{Player, RocketScale},
+    world_collision_groups, GameState, WorldSettings,
+};
+
+/// How long gravity immunity lasts once granted.
+const GRAVITY_IMMUNITY_SECS: f32 = 5.0;
+
+/// How long a size change lasts once granted.
+const SIZE_CHANGE_SECS: f32 = 8.0;
+
+/// Player passes through gravity regions without them changing gravity.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct GravityImmunity {
+    timer: Timer,
+}
+
+impl Default for GravityImmunity {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(GRAVITY_IMMUNITY_SECS, TimerMode::Once),
+        }
+    }
+}
+
+/// Give the player gravity immunity, restarting it if already active.
+pub fn grant_gravity_immunity(mut commands: Commands, player: Query<Entity, With<Player>>) {
+    for ent in player.iter() {
+        PowerUpKind::GravityImmunity.grant(&mut commands, ent);
+    }
+}
+
+/// Player is shrunk or grown, making gaps easier or harder to fit through.
+/// Points scored in the meantime are scaled to match.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct SizeChange {
+    /// Size of the player relative to normal.
+    pub scale: f32,
+    timer: Timer,
+}
+
+impl SizeChange {
+    pub const SHRINK: f32 = 0.5;
+    pub const GROW: f32 = 1.5;
+
+    pub fn new(scale: f32) -> Self {
+        Self {
+            scale,
+            timer: Timer::from_seconds(SIZE_CHANGE_SECS, TimerMode::Once),
+        }
+    }
+
+    /// Multiplier for points scored while the size change lasts. A smaller
+    /// rocket has an easier time, so scores less.
+    pub fn score_multiplier(&self) -> f32 {
+        self.scale
+    }
+}
+
+/// Shrink the player to half size, replacing any size change already
+/// active.
+pub fn grant_shrink(mut commands: Commands, player: Query<Entity, With<Player>>) {
+    for ent in player.iter() {
+        PowerUpKind::Shrink.grant(&mut commands, ent);
+    }
+}
+
+/// Grow the player to one and a half times its size, replacing any size
+/// change already active.
+pub fn grant_grow(mut commands: Commands, player: Query<Entity, With<Player>>) {
+    for ent in player.iter() {
+        PowerUpKind::Grow.grant(&mut commands, ent);
+    }
+}
+
+/// Ability granted by a pickup.
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum PowerUpKind {
+    GravityImmunity,
+    Shrink,
+    Grow,
+}
+
+impl PowerUpKind {
+    pub const ALL: [PowerUpKind; 3] = [Self::GravityImmunity, Self::Shrink, Self::Grow];
+
+    fn color(&self) -> Color {
+        match self {
+            Self::GravityImmunity => Color::rgb(0.4, 0.6, 1.0),
+            Self::Shrink => Color::rgb(0.3, 0.9, 0.4),
+            Self::Grow => Color::rgb(0.9, 0.3, 0.8),
+        }
+    }
+
+    /// Give the ability to the player entity `player`.
+    fn grant(&self, commands: &mut Commands, player: Entity) {
+        let mut player = commands.entity(player);
+        match self {
+            Self::GravityImmunity => player.insert(GravityImmunity::default()),
+            Self::Shrink => player.insert((
+                SizeChange::new(SizeChange::SHRINK),
+                RocketScale(SizeChange::SHRINK),
+            )),
+            Self::Grow => player.insert((
+                SizeChange::new(SizeChange::GROW),
+                RocketScale(SizeChange::GROW),
+            )),
+        };
+    }
+}
+
+/// Pickup granting a power up to the player that flies through it.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct PowerUpPickup(pub PowerUpKind);
+
+/// Sent when the player picks up a power up.
+#[derive(Event)]
+pub struct PowerUpCollectedEvent(pub PowerUpKind);
+
+/// Materials for pickups, set up once assets have loaded.
+#[derive(Resource)]
+pub struct PowerUpAssets {
+    gravity_immunity: Handle<ColorMaterial>,
+    shrink: Handle<ColorMaterial>,
+    grow: Handle<ColorMaterial>,
+}
+
+impl PowerUpAssets {
+    fn material(&self, kind: PowerUpKind) -> Handle<ColorMaterial> {
+        match kind {
+            PowerUpKind::GravityImmunity => self.gravity_immunity.clone(),
+            PowerUpKind::Shrink => self.shrink.clone(),
+            PowerUpKind::Grow => self.grow.clone(),
+        }
+    }
+}
+
+impl FromWorld for PowerUpAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<ColorMaterial>>();
+        let mut add = |kind: PowerUpKind| materials.add(ColorMaterial::from(kind.color()));
+        PowerUpAssets {
+            gravity_immunity: add(PowerUpKind::GravityImmunity),
+            shrink: add(PowerUpKind::Shrink),
+            grow: add(PowerUpKind::Grow),
+        }
+    }
+}
+
+/// Create a power up pickup centered at the level space position `center`.
+pub fn new_power_up_pickup(
+    params: &PowerUpParams,
+    center: Vec2,
+    play_world: &WorldSettings,
+    meshes: &mut Assets<Mesh>,
+    assets: &PowerUpAssets,
+) -> impl Bundle {
+    // round, to stand apart from keys.
+    let mesh = meshes.add(Mesh::from(shape::Circle::new(params.size / 2.0)));
+    (
+        MaterialMesh2dBundle {
+            mesh: mesh.into(),
+            material: assets.material(params.kind),
+            transform: play_world.level_transform(center, 3.0),
+            ..default()
+        },
+        Collider::ball(params.size / 2.0),
+        world_collision_groups(),
+        Sensor,
+        RigidBody::KinematicVelocityBased,
+        TransformInterpolation::default(),
+        PowerUpPickup(params.kind),
+    )
+}
+
+fn collect_power_ups(
+    mut commands: Commands,
+    rapier: Res<RapierContext>,
+    pickups: Query<(Entity, &PowerUpPickup)>,
+    player: Query<Entity, With<Player>>,
+    mut collected: EventWriter<PowerUpCollectedEvent>,
+) {
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    for (ent, pickup) in pickups.iter() {
+        if rapier.intersection_pair(player, ent) == Some(true) {
+            pickup.0.grant(&mut commands, player);
+            collected.send(PowerUpCollectedEvent(pickup.0));
+            commands.entity(ent).despawn_recursive();
+        }
+    }
+}
+
+/// Remove power ups once they run out.
+fn expire_power_ups(
+    mut commands: Commands,
+    mut immunities: Query<(Entity, &mut GravityImmunity)>,
+    mut size_changes: Query<(Entity, &mut SizeChange)>,
+    time: Res<Time>,
+) {
+    for (ent, mut immunity) in immunities.iter_mut() {
+        if immunity.timer.tick(time.delta()).finished() {
+            commands.entity(ent).remove::<GravityImmunity>();
+        }
+    }
+    for (ent, mut size_change) in size_changes.iter_mut() {
+        if size_change.timer.tick(time.delta()).finished() {
+            commands
+                .entity(ent)
+                .remove::<SizeChange>()
+                .insert(RocketScale::default());
+        }
+    }
+}
+
+pub struct PowerUpsPlugin;
+
+impl Plugin for PowerUpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<GravityImmunity>()
+            .register_type::<SizeChange>()
+            .register_type::<PowerUpPickup>()
+            .init_resource_after_loading_state::<_, PowerUpAssets>(GameState::AssetLoading)
+            .add_event::<PowerUpCollectedEvent>()
+            .add_systems(
+                Update,
+                (collect_power_ups, expire_power_ups).run_if(in_state(GameState::Playing)),
+            );
+    }
+}
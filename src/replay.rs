@@ -0,0 +1,257 @@
+//! Recording of runs as their seed and a stream of inputs, each tagged with
+//! the physics step it was played before, so they can be saved and played
+//! back.
+//!
+//! Replays are played back by seeding the run with the recorded seed, and
+//! sending each input once the run has run its physics steps. The layout
+//! of the run is the same every time, and since inputs are tied to steps
+//! rather than frames, playback doesn't drift with the frame rate.
+use std::path::Path;
+
+use anyhow::anyhow;
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    flip_charge::FlipEvent,
+    level::LevelSettings,
+    mutators::{ActiveMutators, Mutator},
+    persistence::{load_versioned, save_versioned},
+    physics::PhysicsSettings,
+    player::{JumpEvent, PlayerSet},
+    run::{tick_run_clock, GameMode, RunClock, RunConfig, RunRng},
+    GameState, ScrollAxis, WorldSettings,
+};
+
+/// Current version of the replay format.
+///
+/// Version 2 times inputs by physics step rather than run time, and adds
+/// flips, mutators and chained jumps.
+pub const REPLAY_VERSION: u32 = 2;
+
+/// File that the last run is exported to.
+const EXPORT_PATH: &str = "last_run.replay.ron";
+
+/// An input that can be replayed.
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ReplayInput {
+    Jump,
+
+    /// Gravity flip, in flip charge mode.
+    Flip,
+}
+
+/// An input, and the physics step it was played before.
+#[derive(Reflect, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct TimedInput {
+    /// Physics steps run in the run before the input.
+    #[serde(default)]
+    pub step: u64,
+
+    /// Run clock time, in seconds, which replays before version 2 have
+    /// instead of the step.
+    #[serde(default, skip_serializing)]
+    at: f32,
+
+    pub input: ReplayInput,
+}
+
+impl TimedInput {
+    pub fn new(step: u64, input: ReplayInput) -> Self {
+        Self {
+            step,
+            at: 0.0,
+            input,
+        }
+    }
+}
+
+/// Everything needed to play a run back.
+#[derive(Reflect, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub mode: GameMode,
+    pub scroll_axis: ScrollAxis,
+
+    #[serde(default)]
+    pub mutators: Vec<Mutator>,
+
+    /// Jumps that could be chained, which replays made before multi-jump
+    /// don't record.
+    #[serde(default = "default_max_jumps")]
+    pub max_jumps: u32,
+
+    /// Inputs in the order they were played.
+    pub inputs: Vec<TimedInput>,
+}
+
+fn default_max_jumps() -> u32 {
+    1
+}
+
+impl Replay {
+    pub fn save(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        save_versioned(path, REPLAY_VERSION, self)
+    }
+
+    /// Load a replay, failing if it was saved by a newer version of the
+    /// format.
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+        let (version, mut replay): (u32, Self) =
+            load_versioned(path)?.ok_or_else(|| anyhow!("no replay at {}", path.display()))?;
+        if version > REPLAY_VERSION {
+            return Err(anyhow!(
+                "{} was saved by a newer version ({} > {})",
+                path.display(),
+                version,
+                REPLAY_VERSION
+            ));
+        }
+        if version < 2 {
+            // timed in seconds, at the steps per second of the time.
+            let steps_per_second = PhysicsSettings::default().steps_per_second;
+            for timed in replay.inputs.iter_mut() {
+                timed.step = (timed.at * steps_per_second).round() as u64;
+            }
+        }
+        Ok(replay)
+    }
+}
+
+/// Recording of the current run, and the last one that finished.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct ReplayRecorder {
+    current: Replay,
+    pub last_run: Option<Replay>,
+}
+
+/// Replay being played back in place of live input.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+
+    /// Index of the next input to send.
+    next: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, next: 0 }
+    }
+}
+
+/// Run condition for reading live gameplay input, which is ignored while a
+/// replay plays back.
+pub fn live_input(playback: Option<Res<ReplayPlayback>>) -> bool {
+    playback.is_none()
+}
+
+fn start_recording(
+    config: Res<RunConfig>,
+    run_rng: Res<RunRng>,
+    play_world: Res<WorldSettings>,
+    mutators: Res<ActiveMutators>,
+    level: Res<LevelSettings>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    recorder.current = Replay {
+        seed: run_rng.seed(),
+        mode: config.mode,
+        scroll_axis: play_world.scroll_axis,
+        mutators: mutators.list().to_vec(),
+        max_jumps: level.max_jumps,
+        inputs: Vec::new(),
+    };
+}
+
+fn record_inputs(
+    mut jumps: EventReader<JumpEvent>,
+    mut flips: EventReader<FlipEvent>,
+    clock: Res<RunClock>,
+    mut recorder: ResMut<ReplayRecorder>,
+) {
+    let step = clock.physics_steps();
+    let inputs = jumps
+        .read()
+        .map(|_| ReplayInput::Jump)
+        .chain(flips.read().map(|_| ReplayInput::Flip));
+    for input in inputs {
+        recorder.current.inputs.push(TimedInput::new(step, input));
+    }
+}
+
+fn finish_recording(mut recorder: ResMut<ReplayRecorder>) {
+    recorder.last_run = Some(std::mem::take(&mut recorder.current));
+}
+
+/// Save the last finished run to [`EXPORT_PATH`].
+fn export_last_run(recorder: Res<ReplayRecorder>) -> anyhow::Result<()> {
+    let Some(replay) = recorder.last_run.as_ref() else {
+        bevy::log::info!("no finished run to export");
+        return Ok(());
+    };
+    replay.save(EXPORT_PATH)?;
+    bevy::log::info!("exported last run to {}", EXPORT_PATH);
+    Ok(())
+}
+
+fn restart_playback(mut playback: ResMut<ReplayPlayback>) {
+    playback.next = 0;
+}
+
+/// Send the recorded inputs that are due.
+fn play_back_inputs(
+    clock: Res<RunClock>,
+    mut playback: ResMut<ReplayPlayback>,
+    mut jumps: EventWriter<JumpEvent>,
+    mut flips: EventWriter<FlipEvent>,
+) {
+    let step = clock.physics_steps();
+    while let Some(timed) = playback.replay.inputs.get(playback.next).copied() {
+        if timed.step > step {
+            break;
+        }
+        match timed.input {
+            ReplayInput::Jump => jumps.send(JumpEvent::default()),
+            ReplayInput::Flip => flips.send(FlipEvent),
+        }
+        playback.next += 1;
+    }
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<ReplayRecorder>()
+            .register_type::<ReplayPlayback>()
+            .register_type::<Replay>()
+            .init_resource::<ReplayRecorder>()
+            .add_systems(OnEnter(GameState::Playing), start_recording)
+            .add_systems(OnEnter(GameState::Dying), finish_recording)
+            .add_systems(
+                OnEnter(GameState::Playing),
+                restart_playback.run_if(resource_exists::<ReplayPlayback>()),
+            )
+            .add_systems(
+                Update,
+                (
+                    record_inputs.after(tick_run_clock).after(PlayerSet),
+                    play_back_inputs
+                        .after(tick_run_clock)
+                        .before(PlayerSet)
+                        .run_if(resource_exists::<ReplayPlayback>()),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                export_last_run
+                    .map(bevy::utils::error)
+                    .run_if(input_just_pressed(KeyCode::F6)),
+            );
+    }
+}
@@ -0,0 +1,109 @@
+//! Debug rewind: the last few seconds of a run are kept as session
+//! snapshots, and `Backspace` rewinds to the oldest of them, to replay a
+//! tricky collision from just before it happened.
+//!
+//! Rewinding resets the level and continues the snapshot the way a saved
+//! session is continued, so obstacles are rebuilt by the same code that
+//! restores a saved run, and the run's generator picks up where it was, so
+//! the same items spawn again. This works from the game over screen too,
+//! which is why it's only in debug builds.
+use std::collections::VecDeque;
+
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
+
+use crate::{
+    session::{ResumeSession, SessionSnapshot, SnapshotCapture},
+    GameState, ResetEvent,
+};
+
+/// How far back a rewind goes, in seconds of play.
+const REWIND_SECS: f32 = 5.0;
+
+/// Snapshots taken per second of play.
+const SNAPSHOT_RATE: f32 = 10.0;
+
+const REWIND_KEY: KeyCode = KeyCode::Back;
+
+/// Snapshots of the last [`REWIND_SECS`] of the run, oldest first.
+#[derive(Resource)]
+pub struct RewindBuffer {
+    snapshots: VecDeque<SessionSnapshot>,
+    timer: Timer,
+}
+
+impl Default for RewindBuffer {
+    fn default() -> Self {
+        Self {
+            snapshots: VecDeque::new(),
+            timer: Timer::from_seconds(1.0 / SNAPSHOT_RATE, TimerMode::Repeating),
+        }
+    }
+}
+
+impl RewindBuffer {
+    fn capacity() -> usize {
+        (REWIND_SECS * SNAPSHOT_RATE) as usize
+    }
+}
+
+fn take_snapshots(time: Res<Time>, capture: SnapshotCapture, mut buffer: ResMut<RewindBuffer>) {
+    if !buffer.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    match capture.capture() {
+        Ok(snapshot) => {
+            if buffer.snapshots.len() == RewindBuffer::capacity() {
+                buffer.snapshots.pop_front();
+            }
+            buffer.snapshots.push_back(snapshot);
+        }
+        Err(e) => debug!("skipping rewind snapshot: {}", e),
+    }
+}
+
+/// Reset, and continue from the oldest snapshot.
+fn rewind(
+    mut buffer: ResMut<RewindBuffer>,
+    mut resume: ResMut<ResumeSession>,
+    mut resets: EventWriter<ResetEvent>,
+) {
+    let Some(snapshot) = buffer.snapshots.pop_front() else {
+        info!("nothing to rewind to");
+        return;
+    };
+    info!(
+        "rewinding {:.1}s",
+        (buffer.snapshots.len() + 1) as f32 / SNAPSHOT_RATE
+    );
+    resume.0 = Some(snapshot);
+    resets.send(ResetEvent);
+}
+
+fn clear_rewind_buffer(mut buffer: ResMut<RewindBuffer>) {
+    *buffer = RewindBuffer::default();
+}
+
+pub struct RewindPlugin;
+
+impl Plugin for RewindPlugin {
+    fn build(&self, app: &mut App) {
+        // rewinding undoes deaths, so players don't get it.
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        app.init_resource::<RewindBuffer>()
+            .add_systems(Update, take_snapshots.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                Update,
+                rewind.run_if(
+                    in_state(GameState::Playing)
+                        .or_else(in_state(GameState::Dying))
+                        .and_then(input_just_pressed(REWIND_KEY)),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                clear_rewind_buffer.run_if(on_event::<ResetEvent>()),
+            );
+    }
+}
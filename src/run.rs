@@ -0,0 +1,221 @@
+//! Parameters of a single run, the seeded random number generator that
+//! drives it, and its clock.
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    physics::{count_physics_steps, PhysicsSteps},
+    scoring_region::GatePassedEvent,
+    GameState, ResetEvent,
+};
+
+/// Window over which the gate rate is measured.
+const GATE_RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Rule set that a run is played with.
+#[derive(Reflect, Default, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Standard,
+
+    /// The first barrier hit in each run bounces the player off, rather
+    /// than killing them.
+    Easy,
+
+    /// The score decays unless gates keep being passed, and the run ends
+    /// when it runs out.
+    Pressure,
+
+    /// Leaving the top or bottom of the world brings the player back in
+    /// from the other side, rather than ending the run.
+    Wrap,
+
+    /// Gravity regions grant a flip charge rather than flipping gravity,
+    /// and the player spends charges to flip when they choose.
+    FlipCharge,
+
+    /// No gravity regions are spawned, and the player flips gravity at will
+    /// on a cooldown. Tunnels are tighter to make up for it.
+    ManualFlip,
+}
+
+/// Configuration for every run in this launch.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct RunConfig {
+    /// Seed to start every run with. When not set, each run picks a new
+    /// random seed.
+    pub fixed_seed: Option<u64>,
+
+    pub mode: GameMode,
+
+    /// After dying, start the next run on the next jump, skipping the
+    /// ready prompt.
+    pub quick_restart: bool,
+}
+
+/// Random number generator for gameplay, seeded at the start of each run so
+/// that runs can be reproduced.
+///
+/// This is the generator behind rand's `StdRng`, so seeds give the same
+/// runs, but used directly so that its place in the stream can be saved.
+#[derive(Resource, Deref, DerefMut)]
+pub struct RunRng {
+    seed: u64,
+
+    #[deref]
+    rng: ChaCha12Rng,
+}
+
+/// Place of a [`RunRng`] in its stream, for continuing a run.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct RunRngState {
+    seed: u64,
+
+    /// Words of the stream used so far.
+    word_pos: u64,
+}
+
+impl RunRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+        }
+    }
+
+    /// Seed that the current run started with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn save_state(&self) -> RunRngState {
+        RunRngState {
+            seed: self.seed,
+            word_pos: self.rng.get_word_pos() as u64,
+        }
+    }
+
+    /// Pick up the stream where a saved generator left off.
+    pub fn restore(state: RunRngState) -> Self {
+        let mut rng = Self::new(state.seed);
+        rng.rng.set_word_pos(state.word_pos as u128);
+        rng
+    }
+}
+
+impl Default for RunRng {
+    fn default() -> Self {
+        Self::new(rand::thread_rng().gen())
+    }
+}
+
+/// Time spent playing in the current run.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct RunClock {
+    elapsed: Duration,
+
+    /// Physics steps run in the run.
+    physics_steps: u64,
+
+    /// Run times of the gates passed within the last [`GATE_RATE_WINDOW`].
+    recent_gates: VecDeque<Duration>,
+}
+
+impl RunClock {
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    pub fn physics_steps(&self) -> u64 {
+        self.physics_steps
+    }
+
+    /// Continue a run from a previously saved time.
+    pub fn restore(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+        self.recent_gates.clear();
+    }
+
+    fn tick(&mut self, delta: Duration) {
+        self.elapsed += delta;
+        while self
+            .recent_gates
+            .front()
+            .is_some_and(|t| self.elapsed - *t > GATE_RATE_WINDOW)
+        {
+            self.recent_gates.pop_front();
+        }
+    }
+
+    fn record_gate(&mut self) {
+        self.recent_gates.push_back(self.elapsed);
+    }
+
+    /// Gates passed per minute, over the last minute of play (or the whole
+    /// run, if shorter).
+    pub fn gates_per_minute(&self) -> f32 {
+        let window = self.elapsed.min(GATE_RATE_WINDOW).as_secs_f32();
+        if window <= 0.0 {
+            return 0.0;
+        }
+        self.recent_gates.len() as f32 * 60.0 / window
+    }
+}
+
+pub(crate) fn tick_run_clock(
+    time: Res<Time>,
+    mut clock: ResMut<RunClock>,
+    mut gates: EventReader<GatePassedEvent>,
+) {
+    clock.tick(time.delta());
+    for _ in gates.read() {
+        clock.record_gate();
+    }
+}
+
+fn count_run_steps(steps: Res<PhysicsSteps>, mut clock: ResMut<RunClock>) {
+    clock.physics_steps += steps.last_frame as u64;
+}
+
+fn reset_run_clock(mut clock: ResMut<RunClock>) {
+    *clock = RunClock::default();
+}
+
+/// Reseed the run rng for a new run.
+fn reseed_run_rng(config: Res<RunConfig>, mut run_rng: ResMut<RunRng>) {
+    let seed = config
+        .fixed_seed
+        .unwrap_or_else(|| rand::thread_rng().gen());
+    *run_rng = RunRng::new(seed);
+    bevy::log::info!("starting run with seed {}", seed);
+}
+
+pub struct RunPlugin;
+
+impl Plugin for RunPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<RunConfig>()
+            .register_type::<GameMode>()
+            .init_resource::<RunConfig>()
+            .init_resource::<RunRng>()
+            .register_type::<RunClock>()
+            .init_resource::<RunClock>()
+            .add_systems(Update, tick_run_clock.run_if(in_state(GameState::Playing)))
+            .add_systems(
+                PostUpdate,
+                count_run_steps
+                    .after(count_physics_steps)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                PostUpdate,
+                (reseed_run_rng, reset_run_clock).run_if(on_event::<ResetEvent>()),
+            );
+    }
+}
@@ -0,0 +1,90 @@
+//! Export of the game world as a bevy scene, for reproducing bugs.
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    animation::SpriteAnimation,
+    barrier::Barrier,
+    dying_player::PlayerDeathPiece,
+    gravity_shift::GravityRegion,
+    level::{LevelSettings, RemoveOnReset, RemoveWhenLeft},
+    obstacle::{region::Consumed, Obstacle, SpawnParams},
+    player::{Player, PlayerFlame},
+    score::Score,
+    scoring_region::ScoringRegion,
+    util::Container,
+    WorldSettings,
+};
+
+const EXPORT_DIR: &str = "scene_exports";
+
+/// Export game entities and resources to a `.scn.ron` file in [`EXPORT_DIR`].
+pub fn export_scene(world: &mut World) {
+    let mut entities: Vec<Entity> = world
+        .query_filtered::<Entity, Or<(With<Player>, With<Container>)>>()
+        .iter(world)
+        .collect();
+
+    // include children (obstacles and death pieces in their containers,
+    // barriers, the player flame), so the hierarchy is complete.
+    let mut children = world.query::<&Children>();
+    let mut i = 0;
+    while i < entities.len() {
+        if let Ok(c) = children.get(world, entities[i]) {
+            entities.extend(c.iter().copied());
+        }
+        i += 1;
+    }
+
+    let scene = DynamicSceneBuilder::from_world(world)
+        .with_filter(
+            SceneFilter::deny_all()
+                .allow::<Name>()
+                .allow::<Container>()
+                .allow::<Transform>()
+                .allow::<Velocity>()
+                .allow::<Player>()
+                .allow::<PlayerFlame>()
+                .allow::<SpriteAnimation>()
+                .allow::<PlayerDeathPiece>()
+                .allow::<Obstacle>()
+                .allow::<SpawnParams>()
+                .allow::<Barrier>()
+                .allow::<Parent>()
+                .allow::<Children>()
+                .allow::<ScoringRegion>()
+                .allow::<GravityRegion>()
+                .allow::<Consumed>()
+                .allow::<RemoveWhenLeft>()
+                .allow::<RemoveOnReset>(),
+        )
+        .with_resource_filter(
+            SceneFilter::deny_all()
+                .allow::<Score>()
+                .allow::<LevelSettings>()
+                .allow::<WorldSettings>(),
+        )
+        .extract_entities(entities.into_iter())
+        .extract_resources()
+        .build();
+
+    let serialized = match scene.serialize_ron(world.resource::<AppTypeRegistry>()) {
+        Ok(s) => s,
+        Err(e) => {
+            bevy::log::error!("could not serialize scene: {}", e);
+            return;
+        }
+    };
+
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    let path = std::path::Path::new(EXPORT_DIR).join(format!("{}.scn.ron", secs));
+    let result =
+        std::fs::create_dir_all(EXPORT_DIR).and_then(|_| std::fs::write(&path, serialized));
+    match result {
+        Ok(()) => bevy::log::info!("exported scene to {}", path.display()),
+        Err(e) => bevy::log::error!("could not write scene export: {}", e),
+    }
+}
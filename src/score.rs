@@ -1,21 +1,364 @@
-use bevy::prelude::*;
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics, RegisterDiagnostic},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
 
-use crate::ResetEvent;
+use crate::{
+    diagnostics::RateWindow,
+    run::{GameMode, RunConfig},
+    scoring_region::GatePassedEvent,
+    GameState, ResetEvent,
+};
 
+/// Points scored per second of play.
+pub const SCORE_RATE: DiagnosticId =
+    DiagnosticId::from_u128(27256326894719030568260213924802756031);
+
+/// Style points awarded at game over for each gravity flip survived.
+pub const STYLE_POINTS_PER_FLIP: i32 = 1;
+
+/// Score decay rate when decay starts, in points per second.
+const DECAY_START_RATE: f32 = 0.3;
+
+/// Increase in the decay rate for every second of decay, in points per
+/// second.
+const DECAY_RAMP: f32 = 0.01;
+
+/// Fastest that the score can decay, in points per second.
+const DECAY_MAX_RATE: f32 = 2.0;
+
+/// Time after passing a gate that the score doesn't decay.
+const DECAY_GRACE_SECS: f32 = 1.0;
+
+/// Points between score milestones.
+pub const MILESTONE_INTERVAL: i32 = 10;
+
+/// Slack allowed when rounding the score down to whole points, so that
+/// awards scaled by exactly one can't fall just short of a point.
+const POINT_EPSILON: f64 = 1e-6;
+
+/// Where awarded points came from.
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScoreSource {
+    /// Passing through a gate.
+    Gate,
+
+    /// Flying close to barriers.
+    Graze,
+
+    /// Anything else: passing gravity regions, style points at game over.
+    Bonus,
+}
+
+/// Points of a run, split by where they came from.
+#[derive(Reflect, Serialize, Deserialize, Default, Clone, Copy, Debug)]
+#[serde(default)]
+pub struct ScoreBreakdown {
+    pub gates: f64,
+    pub grazes: f64,
+    pub bonuses: f64,
+
+    /// Points taken away, by decay or forgiven hits.
+    pub penalties: f64,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> f64 {
+        self.gates + self.grazes + self.bonuses - self.penalties
+    }
+}
+
+/// Score of the current run.
+///
+/// Points are kept fractional, so that scaled awards and grazes add up
+/// exactly, and shown and recorded as whole [`points`](Score::points).
 #[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
 pub struct Score {
-    pub score: i32,
+    pub breakdown: ScoreBreakdown,
+
+    /// Number of times gravity has flipped this run.
+    pub gravity_flips: u32,
+
+    /// Number of milestones reached this run.
+    milestones: i32,
 }
 
-fn reset_score(mut score: ResMut<Score>) {
-    score.score = 0;
+impl Score {
+    /// Score with earlier points, such as from a saved session.
+    pub fn restore(breakdown: ScoreBreakdown, gravity_flips: u32) -> Self {
+        let mut score = Self {
+            breakdown,
+            gravity_flips,
+            milestones: 0,
+        };
+        score.milestones = score.points() / MILESTONE_INTERVAL;
+        score
+    }
+
+    /// Exact points, never below zero.
+    pub fn total(&self) -> f64 {
+        self.breakdown.total().max(0.0)
+    }
+
+    /// Whole points, as shown and recorded. Saturates rather than
+    /// overflowing on very long runs.
+    pub fn points(&self) -> i32 {
+        (self.total() + POINT_EPSILON).floor().min(i32::MAX as f64) as i32
+    }
+
+    /// Award points from `source`. Negative awards, such as gates and
+    /// gravity regions set to cost points, are [taken](Score::take) as
+    /// penalties instead. Invalid awards are ignored.
+    ///
+    /// Returns the change in whole points.
+    pub fn add(&mut self, source: ScoreSource, points: f64) -> i32 {
+        if !points.is_finite() || points == 0.0 {
+            return 0;
+        }
+        let before = self.points();
+        if points < 0.0 {
+            self.take(-points);
+            return self.points().saturating_sub(before);
+        }
+        let breakdown = &mut self.breakdown;
+        match source {
+            ScoreSource::Gate => breakdown.gates += points,
+            ScoreSource::Graze => breakdown.grazes += points,
+            ScoreSource::Bonus => breakdown.bonuses += points,
+        }
+        self.points().saturating_sub(before)
+    }
+
+    /// Take points away, stopping at zero.
+    ///
+    /// Returns false if there weren't enough points to take.
+    pub fn take(&mut self, points: f64) -> bool {
+        if !points.is_finite() || points <= 0.0 {
+            return true;
+        }
+        let taken = points.min(self.total());
+        self.breakdown.penalties += taken;
+        taken == points
+    }
+
+    /// Count a gravity flip.
+    pub fn add_gravity_flip(&mut self) {
+        self.gravity_flips = self.gravity_flips.saturating_add(1);
+    }
+
+    /// Bonus awarded at game over for the flips in this run.
+    pub fn style_bonus(&self) -> i32 {
+        i32::try_from(self.gravity_flips)
+            .unwrap_or(i32::MAX)
+            .saturating_mul(STYLE_POINTS_PER_FLIP)
+    }
+}
+
+/// Sent when the score first reaches another multiple of
+/// [`MILESTONE_INTERVAL`] in a run.
+#[derive(Event)]
+pub struct ScoreMilestoneEvent {
+    /// Points of the milestone reached.
+    pub points: i32,
+}
+
+/// Steady loss of score in pressure mode.
+///
+/// Decay starts with the first gate passed, pauses briefly after every
+/// gate, and speeds up the longer it has been running.
+#[derive(Resource, Default, Reflect)]
+#[reflect(Resource)]
+pub struct ScoreDecay {
+    /// Current decay rate, in points per second. Zero while paused.
+    pub rate: f32,
+
+    /// Time that decay has been running this run.
+    decay_secs: f32,
+
+    /// Time left before decay resumes.
+    grace_secs: f32,
+
+    started: bool,
+}
+
+/// Sent when decay would take the score below zero, ending the run.
+#[derive(Event)]
+pub struct ScoreDepletedEvent;
+
+fn reset_score(mut score: ResMut<Score>, mut decay: ResMut<ScoreDecay>) {
+    *score = Score::default();
+    *decay = ScoreDecay::default();
+}
+
+/// Run condition for pressure mode.
+pub fn pressure_mode(config: Res<RunConfig>) -> bool {
+    config.mode == GameMode::Pressure
+}
+
+/// Take decayed points from the score, ending the run once there are none
+/// left to take.
+fn decay_score(
+    time: Res<Time>,
+    mut gates: EventReader<GatePassedEvent>,
+    mut decay: ResMut<ScoreDecay>,
+    mut score: ResMut<Score>,
+    mut depleted: EventWriter<ScoreDepletedEvent>,
+) {
+    if gates.read().count() > 0 {
+        decay.started = true;
+        decay.grace_secs = DECAY_GRACE_SECS;
+    }
+    if !decay.started || decay.grace_secs > 0.0 {
+        decay.grace_secs = (decay.grace_secs - time.delta_seconds()).max(0.0);
+        decay.rate = 0.0;
+        return;
+    }
+
+    decay.decay_secs += time.delta_seconds();
+    decay.rate = (DECAY_START_RATE + DECAY_RAMP * decay.decay_secs).min(DECAY_MAX_RATE);
+    if !score.take((decay.rate * time.delta_seconds()) as f64) {
+        depleted.send(ScoreDepletedEvent);
+    }
+}
+
+/// Send an event for each milestone the score reaches for the first time
+/// this run.
+fn send_score_milestones(
+    mut score: ResMut<Score>,
+    mut milestones: EventWriter<ScoreMilestoneEvent>,
+) {
+    let reached = score.points() / MILESTONE_INTERVAL;
+    if reached <= score.milestones {
+        return;
+    }
+    for milestone in score.milestones + 1..=reached {
+        milestones.send(ScoreMilestoneEvent {
+            points: milestone.saturating_mul(MILESTONE_INTERVAL),
+        });
+    }
+    score.milestones = reached;
+}
+
+/// Add the style bonus to the final score.
+pub fn award_style_bonus(mut score: ResMut<Score>) {
+    let bonus = score.style_bonus();
+    score.add(ScoreSource::Bonus, bonus as f64);
+}
+
+fn measure_score_rate(
+    time: Res<Time>,
+    score: Res<Score>,
+    mut diagnostics: Diagnostics,
+    mut window: Local<RateWindow>,
+) {
+    if let Some(rate) = window.sample(time.delta_seconds(), score.total()) {
+        diagnostics.add_measurement(SCORE_RATE, || rate);
+    }
 }
 
 pub struct ScorePlugin;
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<Score>()
+        app.register_diagnostic(Diagnostic::new(SCORE_RATE, "score_rate", 20).with_suffix("/s"))
+            .register_type::<Score>()
+            .register_type::<ScoreBreakdown>()
+            .register_type::<ScoreSource>()
+            .register_type::<ScoreDecay>()
             .insert_resource(Score::default())
-            .add_systems(Update, reset_score.run_if(on_event::<ResetEvent>()));
+            .init_resource::<ScoreDecay>()
+            .add_event::<ScoreDepletedEvent>()
+            .add_event::<ScoreMilestoneEvent>()
+            .add_systems(Update, reset_score.run_if(on_event::<ResetEvent>()))
+            .add_systems(
+                Update,
+                send_score_milestones.run_if(resource_changed::<Score>()),
+            )
+            .add_systems(
+                Update,
+                decay_score.run_if(in_state(GameState::Playing).and_then(pressure_mode)),
+            )
+            .add_systems(
+                Update,
+                measure_score_rate.run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(OnEnter(GameState::Dying), award_style_bonus);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn awards_add_up_by_source() {
+        let mut score = Score::default();
+        assert_eq!(score.add(ScoreSource::Gate, 1.0), 1);
+        assert_eq!(score.add(ScoreSource::Graze, 0.25), 0);
+        assert_eq!(score.add(ScoreSource::Graze, 0.75), 1);
+        assert_eq!(score.add(ScoreSource::Bonus, 3.0), 3);
+
+        assert_eq!(score.breakdown.gates, 1.0);
+        assert_eq!(score.breakdown.grazes, 1.0);
+        assert_eq!(score.breakdown.bonuses, 3.0);
+        assert_eq!(score.points(), 5);
+    }
+
+    #[test]
+    fn scaled_awards_round_to_whole_points() {
+        let mut score = Score::default();
+        // 0.1 doesn't add up to exactly one in floating point.
+        for _ in 0..10 {
+            score.add(ScoreSource::Gate, 0.1);
+        }
+        assert_eq!(score.points(), 1);
+    }
+
+    #[test]
+    fn negative_awards_are_taken() {
+        let mut score = Score::default();
+        score.add(ScoreSource::Gate, 3.0);
+        assert_eq!(score.add(ScoreSource::Gate, -1.0), -1);
+        assert_eq!(score.breakdown.gates, 3.0);
+        assert_eq!(score.breakdown.penalties, 1.0);
+        assert_eq!(score.points(), 2);
+
+        // only the points there are can be taken.
+        assert_eq!(score.add(ScoreSource::Bonus, -5.0), -2);
+        assert_eq!(score.breakdown.penalties, 3.0);
+        assert_eq!(score.points(), 0);
+    }
+
+    #[test]
+    fn invalid_awards_are_ignored() {
+        let mut score = Score::default();
+        assert_eq!(score.add(ScoreSource::Gate, f64::NAN), 0);
+        assert_eq!(score.add(ScoreSource::Gate, f64::INFINITY), 0);
+        assert_eq!(score.total(), 0.0);
+    }
+
+    #[test]
+    fn take_stops_at_zero() {
+        let mut score = Score::default();
+        score.add(ScoreSource::Gate, 2.0);
+        assert!(score.take(1.5));
+        assert!(!score.take(1.0));
+        assert_eq!(score.breakdown.penalties, 2.0);
+        assert_eq!(score.total(), 0.0);
+        assert_eq!(score.points(), 0);
+    }
+
+    #[test]
+    fn restore_keeps_milestones_reached() {
+        let breakdown = ScoreBreakdown {
+            gates: 20.0,
+            grazes: 4.5,
+            ..default()
+        };
+        let score = Score::restore(breakdown, 3);
+        assert_eq!(score.points(), 24);
+        assert_eq!(score.milestones, 2);
+        assert_eq!(score.style_bonus(), 3 * STYLE_POINTS_PER_FLIP);
     }
 }
@@ -1,47 +1,385 @@
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{input::common_conditions::input_just_pressed, prelude::*};
 
-use crate::{fonts::FontsCollection, score::Score, GameState, WorldSettings};
+use crate::{
+    announcements::AccessibleText,
+    flip_charge::{flip_charge_mode, FlipCharges, MAX_FLIP_CHARGES},
+    fonts::FontsCollection,
+    level::LevelSettings,
+    mutators::{mutator_tags, ActiveMutators},
+    player::{JumpsLeft, Player},
+    run::{GameMode, RunClock, RunConfig, RunRng},
+    score::{pressure_mode, Score, ScoreDecay},
+    ui_theme::{HudPlacement, HudSide, ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    GameState, ON_ASSETS_LOADED,
+};
+
+const HUD_TEXT: ThemedText = ThemedText {
+    size: 24.0,
+    color: Color::BLACK,
+};
+
+/// Distance of the HUD text from the edges of the screen.
+const HUD_INSET: Vec2 = Vec2::new(12.0, 0.0);
+
+const RUN_INFO_TEXT: ThemedText = ThemedText {
+    size: 14.0,
+    color: Color::DARK_GRAY,
+};
+
+/// Key that shows or hides the run info.
+const RUN_INFO_KEY: KeyCode = KeyCode::F4;
 
 pub struct ScoreDisplayPlugin;
 
-#[derive(Component)]
+#[derive(Component, Reflect)]
+#[reflect(Component)]
 struct ScoreDisplay;
 
-fn setup_score(mut commands: Commands, world: Res<WorldSettings>, fonts: Res<FontsCollection>) {
-    commands.spawn((
-        Text2dBundle {
-            text: Text::from_section(
-                "Score: 000".to_string(),
-                TextStyle {
-                    font: fonts.score_font.clone(),
-                    font_size: 24.0,
-                    color: Color::BLACK,
+/// Run time and gate rate, shown opposite the score.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct ClockDisplay;
+
+/// Current score decay rate, shown under the score in pressure mode.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct DecayDisplay;
+
+/// Flip charges held, shown under the score in flip charge mode.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct FlipChargeDisplay;
+
+/// Jumps the player has left to chain, shown when more than one can be.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct JumpsDisplay;
+
+/// Seed, mode and mutators of the run, shown under the score so that
+/// screenshots and bug reports carry them.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+struct RunInfoDisplay;
+
+/// Whether the run info is shown while playing.
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct ShowRunInfo(pub bool);
+
+impl Default for ShowRunInfo {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+fn setup_score(
+    mut commands: Commands,
+    config: Res<RunConfig>,
+    level: Res<LevelSettings>,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    containers: Res<Containers>,
+) {
+    let (text, themed) = theme.text("Score: 000  Flips: 0", fonts.score_font.clone(), HUD_TEXT);
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "score_display",
+        (
+            Text2dBundle {
+                text,
+                transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                ..default()
+            },
+            HudPlacement::new(HudSide::Left, HUD_INSET),
+            ScoreDisplay,
+            AccessibleText,
+            themed,
+        ),
+    );
+    let (text, themed) = theme.text(
+        format_clock(&RunClock::default()),
+        fonts.score_font.clone(),
+        HUD_TEXT,
+    );
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "clock_display",
+        (
+            Text2dBundle {
+                text,
+                transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                ..default()
+            },
+            HudPlacement::new(HudSide::Right, HUD_INSET),
+            ClockDisplay,
+            AccessibleText,
+            themed,
+        ),
+    );
+
+    if config.mode == GameMode::Pressure {
+        let (text, themed) = theme.text(
+            format_decay(&ScoreDecay::default()),
+            fonts.score_font.clone(),
+            HUD_TEXT,
+        );
+        spawn_named_child(
+            &mut commands,
+            containers.ui,
+            "decay_display",
+            (
+                Text2dBundle {
+                    text,
+                    transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                    ..default()
+                },
+                HudPlacement::new(
+                    HudSide::Left,
+                    HUD_INSET + Vec2::new(0.0, HUD_TEXT.size * 1.2),
+                ),
+                DecayDisplay,
+                themed,
+            ),
+        );
+    }
+
+    if config.mode == GameMode::FlipCharge {
+        let (text, themed) = theme.text(
+            format_flip_charges(FlipCharges::default()),
+            fonts.score_font.clone(),
+            HUD_TEXT,
+        );
+        spawn_named_child(
+            &mut commands,
+            containers.ui,
+            "flip_charge_display",
+            (
+                Text2dBundle {
+                    text,
+                    transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                    ..default()
                 },
+                HudPlacement::new(
+                    HudSide::Left,
+                    HUD_INSET + Vec2::new(0.0, HUD_TEXT.size * 1.2),
+                ),
+                FlipChargeDisplay,
+                AccessibleText,
+                themed,
+            ),
+        );
+    }
+
+    let (text, themed) = theme.text("", fonts.score_font.clone(), RUN_INFO_TEXT);
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "run_info_display",
+        (
+            Text2dBundle {
+                text,
+                transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            // below the decay or flip charge display, whether or not one
+            // is shown.
+            HudPlacement::new(
+                HudSide::Left,
+                HUD_INSET + Vec2::new(0.0, HUD_TEXT.size * 2.4),
             ),
-            text_anchor: Anchor::TopLeft,
-            transform: Transform::from_translation(Vec3::new(
-                world.bounds.min.x + 12.0,
-                world.bounds.max.y,
-                10.0,
-            )),
-            ..default()
-        },
-        ScoreDisplay,
-    ));
+            RunInfoDisplay,
+            themed,
+        ),
+    );
+
+    if level.max_jumps > 1 {
+        let (text, themed) = theme.text(
+            format_jumps(level.max_jumps, level.max_jumps),
+            fonts.score_font.clone(),
+            HUD_TEXT,
+        );
+        spawn_named_child(
+            &mut commands,
+            containers.ui,
+            "jumps_display",
+            (
+                Text2dBundle {
+                    text,
+                    transform: Transform::from_xyz(0.0, 0.0, 10.0),
+                    ..default()
+                },
+                // below the run info.
+                HudPlacement::new(
+                    HudSide::Left,
+                    HUD_INSET + Vec2::new(0.0, HUD_TEXT.size * 3.2),
+                ),
+                JumpsDisplay,
+                themed,
+            ),
+        );
+    }
+}
+
+fn format_jumps(left: u32, max: u32) -> String {
+    format!("Jumps: {}/{}", left, max)
+}
+
+fn format_clock(clock: &RunClock) -> String {
+    let secs = clock.elapsed().as_secs();
+    format!(
+        "{}:{:02}  {:.1} gates/min",
+        secs / 60,
+        secs % 60,
+        clock.gates_per_minute()
+    )
+}
+
+fn format_decay(decay: &ScoreDecay) -> String {
+    if decay.rate > 0.0 {
+        format!("Decay: {:.1}/s", decay.rate)
+    } else {
+        "Decay: --".to_string()
+    }
+}
+
+/// Meter of the flip charges held, filled from the left.
+fn format_flip_charges(charges: FlipCharges) -> String {
+    let held = charges.0.min(MAX_FLIP_CHARGES) as usize;
+    let empty = MAX_FLIP_CHARGES as usize - held;
+    format!("Charges: [{}{}]", "#".repeat(held), "-".repeat(empty))
 }
 
 /// System to update the score display.
 fn update_score(score: ResMut<Score>, mut query: Query<&mut Text, With<ScoreDisplay>>) {
     if score.is_changed() {
         for mut score_text in query.iter_mut() {
-            score_text.sections[0].value = format!("Score: {:03}", score.score);
+            score_text.sections[0].value = format!(
+                "Score: {:03}  Flips: {}",
+                score.points(),
+                score.gravity_flips
+            );
+        }
+    }
+}
+
+/// Update the clock display whenever another second has passed, or the clock
+/// was reset.
+fn update_clock(
+    clock: Res<RunClock>,
+    mut shown_secs: Local<Option<u64>>,
+    mut query: Query<&mut Text, With<ClockDisplay>>,
+) {
+    let secs = clock.elapsed().as_secs();
+    if *shown_secs == Some(secs) {
+        return;
+    }
+    *shown_secs = Some(secs);
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format_clock(&clock);
+    }
+}
+
+fn update_decay(decay: Res<ScoreDecay>, mut query: Query<&mut Text, With<DecayDisplay>>) {
+    if decay.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[0].value = format_decay(&decay);
+        }
+    }
+}
+
+fn update_flip_charges(
+    charges: Res<FlipCharges>,
+    mut query: Query<&mut Text, With<FlipChargeDisplay>>,
+) {
+    if charges.is_changed() {
+        for mut text in query.iter_mut() {
+            text.sections[0].value = format_flip_charges(*charges);
         }
     }
 }
 
+fn update_jumps(
+    player: Query<&JumpsLeft, (With<Player>, Changed<JumpsLeft>)>,
+    level: Res<LevelSettings>,
+    mut query: Query<&mut Text, With<JumpsDisplay>>,
+) {
+    let Ok(jumps_left) = player.get_single() else {
+        return;
+    };
+    for mut text in query.iter_mut() {
+        text.sections[0].value = format_jumps(jumps_left.0, level.max_jumps);
+    }
+}
+
+fn format_run_info(seed: u64, mode: GameMode, mutators: &ActiveMutators) -> String {
+    let mode = format!("{:?}", mode).to_uppercase();
+    let mut info = format!("SEED {}  {}", seed, mode);
+    if !mutators.list().is_empty() {
+        info.push_str(&format!("  {}", mutator_tags(mutators.list())));
+    }
+    info
+}
+
+/// Fill in the run info, and show it if it is turned on.
+fn show_run_info(
+    shown: Res<ShowRunInfo>,
+    config: Res<RunConfig>,
+    run_rng: Res<RunRng>,
+    mutators: Res<ActiveMutators>,
+    mut query: Query<(&mut Text, &mut Visibility), With<RunInfoDisplay>>,
+) {
+    for (mut text, mut vis) in query.iter_mut() {
+        text.sections[0].value = format_run_info(run_rng.seed(), config.mode, &mutators);
+        *vis = if shown.0 {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn hide_run_info(mut query: Query<&mut Visibility, With<RunInfoDisplay>>) {
+    for mut vis in query.iter_mut() {
+        *vis = Visibility::Hidden;
+    }
+}
+
+fn toggle_run_info(mut shown: ResMut<ShowRunInfo>) {
+    shown.0 = !shown.0;
+}
+
 impl Plugin for ScoreDisplayPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnExit(GameState::AssetLoading), setup_score)
-            .add_systems(Update, update_score.run_if(in_state(GameState::Playing)));
+        app.register_type::<ScoreDisplay>()
+            .register_type::<ClockDisplay>()
+            .register_type::<DecayDisplay>()
+            .register_type::<FlipChargeDisplay>()
+            .register_type::<JumpsDisplay>()
+            .register_type::<RunInfoDisplay>()
+            .register_type::<ShowRunInfo>()
+            .init_resource::<ShowRunInfo>()
+            .add_systems(ON_ASSETS_LOADED, setup_score)
+            .add_systems(OnEnter(GameState::Playing), show_run_info)
+            .add_systems(OnExit(GameState::Playing), hide_run_info)
+            .add_systems(
+                Update,
+                (
+                    update_score,
+                    update_clock,
+                    update_decay.run_if(pressure_mode),
+                    update_flip_charges.run_if(flip_charge_mode),
+                    update_jumps,
+                    (
+                        toggle_run_info.run_if(input_just_pressed(RUN_INFO_KEY)),
+                        show_run_info.run_if(resource_changed::<ShowRunInfo>()),
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
     }
 }
@@ -1,16 +1,31 @@
+use crate::obstacle_spawner::SpeedController;
 use crate::player::Player;
-use crate::GameState;
+use crate::power_ups::SizeChange;
+use crate::{world_collision_groups, GameState};
 use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 
-use crate::score::Score;
+use crate::score::{Score, ScoreSource};
 
 #[derive(Component, Reflect)]
+#[reflect(Component)]
 pub struct ScoringRegion {
     score_delta: i32,
 }
 
+/// Sent when the player passes through a scoring region.
+#[derive(Event)]
+pub struct GatePassedEvent {
+    /// World position of the region.
+    pub position: Vec2,
+
+    pub score_delta: i32,
+}
+
 /// A scoring region is an area that can change your score by the specify amount.
+///
+/// The region is meant to be attached as a child of a moving body, and
+/// `offset` is the level space offset from the parent.
 pub fn new_scoring_region(score_delta: i32, offset: Vec2, dim: Vec2) -> impl Bundle {
     (
         ScoringRegion { score_delta },
@@ -19,28 +34,38 @@ pub fn new_scoring_region(score_delta: i32, offset: Vec2, dim: Vec2) -> impl Bun
             ..default()
         },
         Collider::cuboid(dim.x * 0.5, dim.y * 0.5),
+        world_collision_groups(),
         Sensor,
-        RigidBody::KinematicVelocityBased,
         ActiveEvents::COLLISION_EVENTS,
-        Name::new("scoring_region"),
     )
 }
 
 /// Increment the score and despawn the region when intersecting.
+///
+/// Points are scaled by the level speed relative to the base level, so
+/// faster levels pay more per gate, and by any size change of the player.
 fn check_scoring_region_collisions(
     mut commands: Commands,
     rapier: Res<RapierContext>,
-    regions: Query<(Entity, &ScoringRegion)>,
+    regions: Query<(Entity, &ScoringRegion, &GlobalTransform)>,
     mut score: ResMut<Score>,
-    player_q: Query<(Entity, &Player)>,
+    speed: Res<SpeedController>,
+    player_q: Query<(Entity, Option<&SizeChange>), With<Player>>,
+    mut gates: EventWriter<GatePassedEvent>,
 ) {
-    for player in player_q.iter() {
-        for (region_entity, region) in regions.iter() {
-            if rapier.intersection_pair(player.0, region_entity) == Some(true) {
-                score.score += region.score_delta;
+    for (player, size_change) in player_q.iter() {
+        let scale = speed.relative() * size_change.map_or(1.0, |s| s.score_multiplier());
+        for (region_entity, region, global) in regions.iter() {
+            if rapier.intersection_pair(player, region_entity) == Some(true) {
+                let score_delta =
+                    score.add(ScoreSource::Gate, region.score_delta as f64 * scale as f64);
+                gates.send(GatePassedEvent {
+                    position: global.translation().truncate(),
+                    score_delta,
+                });
 
                 // despawn the region, so this only happens once
-                commands.entity(region_entity).despawn();
+                commands.entity(region_entity).despawn_recursive();
             }
         }
     }
@@ -49,9 +74,11 @@ fn check_scoring_region_collisions(
 pub struct ScoringRegionPlugin;
 impl Plugin for ScoringRegionPlugin {
     fn build(&self, app: &mut App) {
-        app.register_type::<ScoringRegion>().add_systems(
-            Update,
-            check_scoring_region_collisions.run_if(in_state(GameState::Playing)),
-        );
+        app.register_type::<ScoringRegion>()
+            .add_event::<GatePassedEvent>()
+            .add_systems(
+                Update,
+                check_scoring_region_collisions.run_if(in_state(GameState::Playing)),
+            );
     }
 }
@@ -0,0 +1,502 @@
+//! Saving a run in progress, and continuing it on the next launch.
+use bevy::{
+    app::AppExit, ecs::system::SystemParam, input::common_conditions::input_just_pressed,
+    prelude::*,
+};
+use bevy_rapier2d::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    center_display::{show_ready, CenterDisplay},
+    flip_charge::FlipCharges,
+    gravity_shift::GravityEvent,
+    level::LevelSettings,
+    mutators::{ActiveMutators, Mutator},
+    obstacle::{
+        behavior::{ActiveBehavior, SpeedScale},
+        door::{Inventory, LockedDoor},
+        drift::{Drift, ObstacleDrift},
+        factory::ObstacleFactory,
+        region::Consumed,
+        spawner_settings::{DriftRegionSettings, GravityRegionSettings},
+        BouncerParams, DroneParams, SpawnParams,
+    },
+    obstacle_spawner::{
+        ObstacleSpawner, ScrollOdometer, SpawnIndex, SpawnerState, SpeedController,
+    },
+    persistence,
+    physics::PhysicsSettings,
+    player::Player,
+    run::{RunClock, RunRng, RunRngState},
+    score::{Score, ScoreBreakdown},
+    scoring_region::ScoringRegion,
+    training::{ScenarioSpawner, ScenarioState, TrainingSettings},
+    GameState, WorldSettings,
+};
+
+const SESSION_PATH: &str = "session.ron";
+/// Version 2 stores level speeds in m/s, rather than pixels.
+const SESSION_VERSION: u32 = 2;
+
+/// Position and velocity of a body, in level space.
+#[derive(Serialize, Deserialize)]
+struct SavedBody {
+    position: Vec2,
+    linvel: Vec2,
+}
+
+/// A spawned item that was live when the session was saved.
+#[derive(Serialize, Deserialize)]
+struct SavedItem {
+    params: SpawnParams,
+    body: SavedBody,
+    #[serde(default)]
+    index: Option<u64>,
+
+    /// Whether the item can still be triggered (scoring region not
+    /// passed, gravity region not consumed).
+    active: bool,
+
+    /// Level space y velocity the item drifts at, on top of the scroll.
+    #[serde(default)]
+    drift: Option<f32>,
+
+    #[serde(default)]
+    behavior: Option<SavedBehavior>,
+}
+
+/// A behavior playing on an item, and the state its actions left it in.
+#[derive(Serialize, Deserialize)]
+struct SavedBehavior {
+    behavior: ActiveBehavior,
+    #[serde(default)]
+    speed_scale: Option<f32>,
+    /// Level space rotation, in radians.
+    angle: f32,
+    angvel: f32,
+}
+
+/// Everything needed to continue a run.
+#[derive(Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    score: i32,
+    #[serde(default)]
+    score_breakdown: Option<ScoreBreakdown>,
+    #[serde(default)]
+    gravity_flips: u32,
+    #[serde(default)]
+    elapsed_secs: f32,
+    gravity_mult: f32,
+    #[serde(default)]
+    flip_charges: u32,
+    #[serde(default)]
+    has_key: bool,
+    /// Drift given to tunnels as they spawn.
+    #[serde(default)]
+    obstacle_drift: f32,
+    /// Mutators the run is played with, and recorded under.
+    #[serde(default)]
+    mutators: Vec<Mutator>,
+    /// Random number generator of the run, so that items spawned after
+    /// continuing are the ones that would have been spawned anyway.
+    #[serde(default)]
+    rng: Option<RunRngState>,
+    spawner: SpawnerState,
+    /// Progress through the training scenario, for runs in training mode.
+    #[serde(default)]
+    scenario: Option<ScenarioState>,
+    #[serde(default)]
+    odometer: ScrollOdometer,
+    player: SavedBody,
+    items: Vec<SavedItem>,
+}
+
+/// Session saved by a previous launch, available to continue.
+#[derive(Resource, Default)]
+pub struct SavedSession(pub Option<SessionSnapshot>);
+
+/// Snapshot to continue as soon as the ready screen shows, without waiting
+/// for the continue key, such as a rewind point. Unlike the saved session,
+/// it isn't kept on disk.
+#[derive(Resource, Default)]
+pub struct ResumeSession(pub Option<SessionSnapshot>);
+
+/// Everything read to take a [`SessionSnapshot`] of the current run.
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct SnapshotCapture<'w, 's> {
+    score: Res<'w, Score>,
+    clock: Res<'w, RunClock>,
+    level: Res<'w, LevelSettings>,
+    flip_charges: Res<'w, FlipCharges>,
+    inventory: Res<'w, Inventory>,
+    obstacle_drift: Res<'w, ObstacleDrift>,
+    mutators: Res<'w, ActiveMutators>,
+    rng: Res<'w, RunRng>,
+    spawners: Query<'w, 's, &'static ObstacleSpawner>,
+    training: Res<'w, TrainingSettings>,
+    scenario: Res<'w, ScenarioSpawner>,
+    odometer: Res<'w, ScrollOdometer>,
+    player: Query<'w, 's, (&'static Transform, &'static Velocity), With<Player>>,
+    items: Query<
+        'w,
+        's,
+        (
+            &'static SpawnParams,
+            &'static Transform,
+            &'static Velocity,
+            Option<&'static SpawnIndex>,
+            Option<&'static Children>,
+            Has<Consumed>,
+            Option<&'static LockedDoor>,
+            Option<&'static Drift>,
+            Option<&'static ActiveBehavior>,
+            Option<&'static SpeedScale>,
+        ),
+    >,
+    regions: Query<'w, 's, (), With<ScoringRegion>>,
+    play_world: Res<'w, WorldSettings>,
+}
+
+impl SnapshotCapture<'_, '_> {
+    /// Snapshot the current run. Fails when there is no player or spawner.
+    pub fn capture(&self) -> anyhow::Result<SessionSnapshot> {
+        let axis = self.play_world.scroll_axis;
+        let level_body = |t: &Transform, v: &Velocity| SavedBody {
+            position: axis.to_level(t.translation.truncate()),
+            linvel: axis.to_level(v.linvel),
+        };
+
+        let (player_t, player_v) = self.player.get_single()?;
+        Ok(SessionSnapshot {
+            score: self.score.points(),
+            score_breakdown: Some(self.score.breakdown),
+            gravity_flips: self.score.gravity_flips,
+            elapsed_secs: self.clock.elapsed().as_secs_f32(),
+            gravity_mult: self.level.gravity_mult,
+            flip_charges: self.flip_charges.0,
+            has_key: self.inventory.has_key,
+            obstacle_drift: self.obstacle_drift.current,
+            mutators: self.mutators.list().to_vec(),
+            rng: Some(self.rng.save_state()),
+            spawner: self.spawners.get_single()?.save_state(),
+            scenario: self
+                .training
+                .enabled
+                .then(|| self.scenario.save_state(&self.training)),
+            odometer: *self.odometer,
+            player: level_body(player_t, player_v),
+            items: self
+                .items
+                .iter()
+                .map(
+                    |(
+                        params,
+                        t,
+                        v,
+                        index,
+                        children,
+                        consumed,
+                        locked,
+                        drift,
+                        behavior,
+                        speed_scale,
+                    )| {
+                        SavedItem {
+                            params: params.clone(),
+                            body: level_body(t, v),
+                            index: index.map(|i| i.0),
+                            active: match params {
+                                SpawnParams::Tunnel(_) => children
+                                    .is_some_and(|c| c.iter().any(|e| self.regions.contains(*e))),
+                                SpawnParams::Gravity { .. } | SpawnParams::Drift { .. } => {
+                                    !consumed
+                                }
+                                SpawnParams::Door(_) => locked.is_some(),
+                                SpawnParams::Bouncer(_)
+                                | SpawnParams::Drone(_)
+                                | SpawnParams::Laser(_)
+                                | SpawnParams::Key(_)
+                                | SpawnParams::PowerUp(_) => true,
+                            },
+                            drift: drift.map(|d| d.0),
+                            behavior: behavior.map(|behavior| SavedBehavior {
+                                behavior: behavior.clone(),
+                                speed_scale: speed_scale.map(|s| s.0),
+                                angle: (axis.rotation().inverse() * t.rotation)
+                                    .to_euler(EulerRot::XYZ)
+                                    .2,
+                                angvel: v.angvel,
+                            }),
+                        }
+                    },
+                )
+                .collect(),
+        })
+    }
+}
+
+/// Load a saved session from disk, if there is one.
+fn load_saved_session(mut saved: ResMut<SavedSession>) {
+    match persistence::load_versioned(SESSION_PATH) {
+        Ok(Some((version, _))) if version < SESSION_VERSION => {
+            bevy::log::info!("discarding session saved by an older version")
+        }
+        Ok(snapshot) => saved.0 = snapshot.map(|(_, snapshot)| snapshot),
+        Err(e) => bevy::log::warn!("ignoring unreadable saved session: {}", e),
+    }
+}
+
+/// Write the current run to disk.
+fn save_session(capture: SnapshotCapture) -> anyhow::Result<()> {
+    let snapshot = capture.capture()?;
+    persistence::save_versioned(SESSION_PATH, SESSION_VERSION, &snapshot)?;
+    bevy::log::info!("saved session to {}", SESSION_PATH);
+    Ok(())
+}
+
+/// Forget about any saved session, once a new run has started or ended.
+fn discard_saved_session(mut saved: ResMut<SavedSession>) {
+    saved.0 = None;
+    if std::path::Path::new(SESSION_PATH).exists() {
+        if let Err(e) = std::fs::remove_file(SESSION_PATH) {
+            bevy::log::warn!("could not remove saved session: {}", e);
+        }
+    }
+}
+
+/// Offer to continue the saved session on the ready screen.
+fn show_continue_prompt(saved: Res<SavedSession>, mut text: Query<&mut Text, With<CenterDisplay>>) {
+    if saved.0.is_some() {
+        for mut t in text.iter_mut() {
+            t.sections[0].value.push_str("\nC: CONTINUE");
+        }
+    }
+}
+
+/// Run state that a [`SessionSnapshot`] is restored into directly.
+#[derive(SystemParam)]
+pub struct SnapshotRestore<'w> {
+    score: ResMut<'w, Score>,
+    clock: ResMut<'w, RunClock>,
+    flip_charges: ResMut<'w, FlipCharges>,
+    inventory: ResMut<'w, Inventory>,
+    odometer: ResMut<'w, ScrollOdometer>,
+    obstacle_drift: ResMut<'w, ObstacleDrift>,
+    mutators: ResMut<'w, ActiveMutators>,
+    rng: ResMut<'w, RunRng>,
+    training: ResMut<'w, TrainingSettings>,
+    scenario: ResMut<'w, ScenarioSpawner>,
+}
+
+/// Rebuild the run to resume, or else the saved run, and start playing.
+#[allow(clippy::too_many_arguments)]
+fn continue_session(
+    mut saved: ResMut<SavedSession>,
+    mut resume: ResMut<ResumeSession>,
+    mut run: SnapshotRestore,
+    mut spawners: Query<&mut ObstacleSpawner>,
+    // the factory reads the item velocity, so it is restored first
+    mut factory_and_vel: ParamSet<(ObstacleFactory, ResMut<SpeedController>)>,
+    physics: Res<PhysicsSettings>,
+    mut level_settings: ResMut<LevelSettings>,
+    mut rapier_config: ResMut<RapierConfiguration>,
+    mut player: Query<(&mut Transform, &mut Velocity), With<Player>>,
+    play_world: Res<WorldSettings>,
+    mut gevs: EventWriter<GravityEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    let Some(snapshot) = resume.0.take().or_else(|| saved.0.take()) else {
+        return;
+    };
+    let axis = play_world.scroll_axis;
+
+    // sessions saved before the breakdown was kept only had gate points.
+    let breakdown = snapshot.score_breakdown.unwrap_or(ScoreBreakdown {
+        gates: snapshot.score as f64,
+        ..default()
+    });
+    *run.score = Score::restore(breakdown, snapshot.gravity_flips);
+    run.clock
+        .restore(std::time::Duration::from_secs_f32(snapshot.elapsed_secs));
+    run.flip_charges.0 = snapshot.flip_charges;
+    run.inventory.has_key = snapshot.has_key;
+    run.obstacle_drift.current = snapshot.obstacle_drift;
+    *run.mutators = ActiveMutators::new(snapshot.mutators);
+    // sessions saved before the generator was kept carry on with the new
+    // seed.
+    if let Some(rng) = snapshot.rng {
+        *run.rng = RunRng::restore(rng);
+    }
+    // training runs carry on with the scenario where it was, at the speed
+    // it was played at.
+    let mut speed = 1.0;
+    if let Some(scenario) = snapshot.scenario {
+        speed = scenario.speed();
+        run.scenario.restore_state(scenario, &mut run.training);
+    }
+    if let Ok(mut spawner) = spawners.get_single_mut() {
+        spawner.restore_state(snapshot.spawner);
+        factory_and_vel.p1().set(spawner.item_vel(&physics) * speed);
+        let level = spawner.level();
+        level_settings.set_base_physics(&physics, level.gravity, level.jump_speed, &run.mutators);
+        level_settings.flip_world_gravity = level.flip_world_gravity;
+        level_settings.sync_to_rapier(&mut rapier_config, axis);
+    }
+    *run.odometer = snapshot.odometer;
+    for (mut t, mut v) in player.iter_mut() {
+        t.translation = axis
+            .to_world(snapshot.player.position)
+            .extend(t.translation.z);
+        v.linvel = axis.to_world(snapshot.player.linvel);
+    }
+
+    let mut factory = factory_and_vel.p0();
+    for item in snapshot.items {
+        let ent = match &item.params {
+            SpawnParams::Tunnel(tunnel) => {
+                let (tunnel_ent, region) = factory
+                    .spawn_tunnel_at(item.body.position.x - tunnel.obstacle_width / 2.0, tunnel);
+                if !item.active {
+                    factory.commands.entity(region).despawn_recursive();
+                }
+                tunnel_ent
+            }
+            SpawnParams::Gravity {
+                gravity_mult,
+                width,
+                pass_score,
+            } => {
+                let region = factory.spawn_gravity_at(
+                    item.body.position.x - width / 2.0,
+                    *gravity_mult,
+                    &GravityRegionSettings {
+                        gravity_width: *width,
+                        pass_score: *pass_score,
+                    },
+                );
+                if !item.active {
+                    factory.commands.entity(region).insert(Consumed);
+                }
+                region
+            }
+            SpawnParams::Drift { drift, width } => {
+                let region = factory.spawn_drift_at(
+                    item.body.position.x - width / 2.0,
+                    *drift,
+                    &DriftRegionSettings {
+                        width: *width,
+                        speed: drift.abs(),
+                    },
+                );
+                if !item.active {
+                    factory.commands.entity(region).insert(Consumed);
+                }
+                region
+            }
+            SpawnParams::Bouncer(bouncer) => {
+                // carry on from where it had bounced to.
+                let params = BouncerParams {
+                    center_y: item.body.position.y,
+                    drift: item.body.linvel.y,
+                    ..bouncer.clone()
+                };
+                factory.spawn_bouncer_at(item.body.position.x - params.width / 2.0, &params)
+            }
+            SpawnParams::Drone(drone) => {
+                // carry on from where it had steered to.
+                let params = DroneParams {
+                    center_y: item.body.position.y,
+                    heading: item.body.linvel.y.atan2(item.body.linvel.x.abs()),
+                    ..drone.clone()
+                };
+                factory.spawn_drone_at(item.body.position.x - params.size / 2.0, &params)
+            }
+            // the cycle follows the run clock, which is restored with the
+            // session.
+            SpawnParams::Laser(laser) => {
+                factory.spawn_laser_at(item.body.position.x - laser.width / 2.0, laser)
+            }
+            SpawnParams::Key(key) => {
+                factory.spawn_key_at(item.body.position.x - key.size / 2.0, key)
+            }
+            SpawnParams::PowerUp(power_up) => {
+                factory.spawn_power_up_at(item.body.position.x - power_up.size / 2.0, power_up)
+            }
+            SpawnParams::Door(door) => {
+                factory.spawn_door_at(item.body.position.x - door.width / 2.0, door, item.active)
+            }
+        };
+        if let Some(index) = item.index {
+            factory.commands.entity(ent).insert(SpawnIndex(index));
+        }
+        // tunnels without one are given zero drift, so that they aren't
+        // taken for newly spawned tunnels and given the current drift.
+        let drift = match item.params {
+            SpawnParams::Tunnel(_) => Some(item.drift.unwrap_or_default()),
+            _ => item.drift,
+        };
+        if let Some(drift) = drift {
+            factory.commands.entity(ent).insert(Drift(drift));
+        }
+        if let Some(saved) = item.behavior {
+            let mut commands = factory.commands.entity(ent);
+            commands.insert(saved.behavior);
+            if let Some(scale) = saved.speed_scale {
+                commands.insert(SpeedScale(scale));
+            }
+            // the item was spawned upright and still.
+            let rotation = axis.rotation() * Quat::from_rotation_z(saved.angle);
+            commands.add(move |ent: Entity, world: &mut World| {
+                let mut ent = world.entity_mut(ent);
+                if let Some(mut t) = ent.get_mut::<Transform>() {
+                    t.rotation = rotation;
+                }
+                if let Some(mut v) = ent.get_mut::<Velocity>() {
+                    v.angvel = saved.angvel;
+                }
+            });
+        }
+    }
+
+    // Gravity (and the player orientation) is restored once playing.
+    gevs.send(GravityEvent {
+        region: None,
+        gravity_mult: snapshot.gravity_mult,
+    });
+
+    next_state.set(GameState::Playing);
+}
+
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SavedSession::default())
+            .init_resource::<ResumeSession>()
+            .add_systems(Startup, load_saved_session)
+            .add_systems(
+                OnEnter(GameState::Ready),
+                show_continue_prompt.after(show_ready),
+            )
+            .add_systems(
+                Update,
+                continue_session.run_if(
+                    in_state(GameState::Ready).and_then(
+                        input_just_pressed(KeyCode::C)
+                            .and_then(|saved: Res<SavedSession>| saved.0.is_some())
+                            .or_else(|resume: Res<ResumeSession>| resume.0.is_some()),
+                    ),
+                ),
+            )
+            .add_systems(OnEnter(GameState::Playing), discard_saved_session)
+            .add_systems(OnEnter(GameState::Dying), discard_saved_session)
+            .add_systems(
+                Last,
+                save_session.map(bevy::utils::error).run_if(
+                    in_state(GameState::Playing)
+                        .and_then(input_just_pressed(KeyCode::F5).or_else(on_event::<AppExit>())),
+                ),
+            );
+    }
+}
@@ -0,0 +1,53 @@
+//! The settings file, `settings.ron`, for options that take effect from
+//! launch (such as which mods are loaded, and the theme pack), and the
+//! graphics options that can also be changed while playing.
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accessibility::GameSpeed,
+    graphics::GraphicsSettings,
+    logging::LogSettings,
+    mods::ModSettings,
+    persistence::{PersistencePlugin, Persistent},
+    physics::PhysicsTimestep,
+    theme_pack::CLASSIC_THEME,
+};
+
+#[derive(Resource, Reflect, Serialize, Deserialize, Default, Clone, Debug)]
+#[reflect(Resource)]
+#[serde(default)]
+pub struct Settings {
+    pub log: LogSettings,
+    pub graphics: GraphicsSettings,
+    pub mods: ModSettings,
+
+    /// Name of the theme pack, or the classic theme if not set.
+    pub theme: Option<String>,
+
+    /// Speed of gameplay, changed from the pause menu.
+    pub game_speed: GameSpeed,
+
+    /// How the physics is stepped. Fixed steps unless set.
+    pub physics_timestep: PhysicsTimestep,
+}
+
+impl Settings {
+    pub fn theme_name(&self) -> &str {
+        self.theme.as_deref().unwrap_or(CLASSIC_THEME)
+    }
+}
+
+impl Persistent for Settings {
+    const PATH: &'static str = "settings.ron";
+    const VERSION: u32 = 1;
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Settings>()
+            .add_plugins(PersistencePlugin::<Settings>::default());
+    }
+}
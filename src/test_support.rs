@@ -0,0 +1,32 @@
+//! Stand-ins for what the loading state sets up, for apps that run the
+//! game's plugins without loading any assets: unit tests and the soak run.
+use bevy::prelude::*;
+
+use crate::{
+    barrier::BarrierAssets,
+    gravity_shift::{GravityAssets, GravityMaterials, GravityShiftMaterial},
+    obstacle::{door::DoorAssets, drift::DriftAssets, enemy::DroneAssets, laser::LaserAssets},
+    power_ups::PowerUpAssets,
+};
+
+/// Add the asset collections that items are spawned with, and the item
+/// assets that the loading state would insert once assets are ready, with
+/// blank images in place of the loaded ones.
+pub fn insert_blank_item_assets(app: &mut App) {
+    app.init_asset::<Mesh>()
+        .init_asset::<Image>()
+        .init_asset::<ColorMaterial>()
+        .init_asset::<GravityShiftMaterial>();
+    let arrow = app
+        .world
+        .resource_mut::<Assets<Image>>()
+        .add(Image::default());
+    app.insert_resource(GravityAssets { arrow });
+    app.world.init_resource::<GravityMaterials>();
+    app.world.init_resource::<BarrierAssets>();
+    app.world.init_resource::<DriftAssets>();
+    app.world.init_resource::<DroneAssets>();
+    app.world.init_resource::<LaserAssets>();
+    app.world.init_resource::<DoorAssets>();
+    app.world.init_resource::<PowerUpAssets>();
+}
@@ -0,0 +1,206 @@
+//! Theme packs: sets of colors, images and fonts that reskin the game,
+//! read from `.theme.ron` files in the `themes` folder of the assets.
+//!
+//! The pack is picked on the settings page and takes effect from the next
+//! launch, since its images and fonts are loaded with the rest of the
+//! assets. Collections refer to themed assets by dynamic key, registered
+//! here from the pack's paths. Images need an `@2x` variant next to them,
+//! like the game's own sprites.
+//!
+//! A pack only has to give the fields it changes. The rest, and the whole
+//! pack when the file is missing or broken, come from the built-in
+//! [`CLASSIC_THEME`]. Builds with embedded assets also offer the packs
+//! embedded with them.
+use std::path::{Path, PathBuf};
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use bevy_asset_loader::{
+    asset_collection::AssetCollection, dynamic_asset::DynamicAssets,
+    standard_dynamic_asset::StandardDynamicAsset,
+};
+use serde::Deserialize;
+
+use crate::{
+    asset_errors::GameCollectionAppExt, asset_variants::AssetVariantAppExt,
+    background::LEVEL_PALETTES,
+};
+
+/// Name of the built-in theme, which is used without a file.
+pub const CLASSIC_THEME: &str = "classic";
+
+/// Folder of the assets that theme packs are read from.
+const THEMES_DIR: &str = "themes";
+
+const THEME_EXTENSION: &str = ".theme.ron";
+
+#[derive(Resource, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct ThemePack {
+    /// Name of the pack, from its file name.
+    #[serde(skip)]
+    pub name: String,
+
+    /// Background colors of each level in a run, starting with the base
+    /// level. Runs with more levels cycle back through them.
+    pub level_palettes: Vec<[Color; 2]>,
+
+    pub barrier_color: Color,
+
+    /// Color of barriers that are being touched.
+    pub barrier_hit_color: Color,
+
+    /// Image drawn on barriers, tinted by their color.
+    pub barrier_texture: Option<String>,
+
+    /// Arrow image scrolling across gravity regions.
+    pub gravity_arrow: String,
+
+    pub score_font: String,
+    pub menu_font: String,
+}
+
+impl Default for ThemePack {
+    fn default() -> Self {
+        Self {
+            name: CLASSIC_THEME.to_string(),
+            level_palettes: LEVEL_PALETTES.to_vec(),
+            barrier_color: Color::rgba(0.6, 0.6, 0.0, 1.0),
+            barrier_hit_color: Color::rgba(0.6, 0.2, 0.0, 1.0),
+            barrier_texture: None,
+            gravity_arrow: "images/grav_arrow_down.png".to_string(),
+            score_font: "fonts/PixelOperatorMono-Bold.ttf".to_string(),
+            menu_font: "fonts/PixelOperatorMono-Bold.ttf".to_string(),
+        }
+    }
+}
+
+impl ThemePack {
+    /// Background colors of the level at `level_index` in a run.
+    pub fn palette(&self, level_index: usize) -> [Color; 2] {
+        if self.level_palettes.is_empty() {
+            return LEVEL_PALETTES[level_index % LEVEL_PALETTES.len()];
+        }
+        self.level_palettes[level_index % self.level_palettes.len()]
+    }
+
+    /// Read the pack `name` from `dir`, falling back to the classic theme.
+    fn load(dir: &Path, name: &str) -> Self {
+        if name == CLASSIC_THEME {
+            return Self::default();
+        }
+        let file_name = format!("{}{}", name, THEME_EXTENSION);
+        let path = dir.join(&file_name);
+        let pack = read_theme_file(dir, &file_name)
+            .and_then(|contents| ron::from_str::<ThemePack>(&contents).map_err(Into::into));
+        match pack {
+            Ok(pack) => Self {
+                name: name.to_string(),
+                ..pack
+            },
+            Err(e) => {
+                warn!(
+                    "using the classic theme, as {:?} can't be read: {}",
+                    path, e
+                );
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Read the pack file `file_name` from `dir`, or its embedded copy when it
+/// isn't on disk.
+fn read_theme_file(dir: &Path, file_name: &str) -> anyhow::Result<String> {
+    let contents = std::fs::read_to_string(dir.join(file_name));
+    #[cfg(feature = "embedded_assets")]
+    if matches!(&contents, Err(e) if e.kind() == std::io::ErrorKind::NotFound) {
+        let embedded_path = format!("{}/{}", THEMES_DIR, file_name);
+        if let Some(bytes) = crate::embedded::embedded_asset(&embedded_path) {
+            return Ok(std::str::from_utf8(bytes)?.to_string());
+        }
+    }
+    Ok(contents?)
+}
+
+/// Names of the theme packs found at launch, starting with the classic
+/// theme.
+#[derive(Resource)]
+pub struct ThemePacks(pub Vec<String>);
+
+impl ThemePacks {
+    fn scan(dir: &Path) -> Self {
+        let file_names = std::fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string));
+        #[cfg(feature = "embedded_assets")]
+        let file_names =
+            file_names.chain(crate::embedded::embedded_files(THEMES_DIR).map(str::to_string));
+        let mut names: Vec<String> = file_names
+            .filter_map(|file_name| Some(file_name.strip_suffix(THEME_EXTENSION)?.to_string()))
+            .filter(|name| name != CLASSIC_THEME)
+            .collect();
+        names.sort();
+        names.dedup();
+        names.insert(0, CLASSIC_THEME.to_string());
+        Self(names)
+    }
+
+    /// The pack `step` places along the list from `name`.
+    pub fn step(&self, name: &str, step: isize) -> &str {
+        let idx = self.0.iter().position(|n| n == name).unwrap_or(0) as isize;
+        &self.0[(idx + step).rem_euclid(self.0.len() as isize) as usize]
+    }
+}
+
+/// Themed images that not every pack has.
+#[derive(Resource, AssetCollection)]
+pub struct ThemeAssets {
+    #[asset(key = "barrier.texture", optional)]
+    pub barrier_texture: Option<Handle<Image>>,
+}
+
+/// Register the pack's fonts under their dynamic keys. Images are
+/// registered as variants, to pick up their `@2x` copies.
+fn register_theme_fonts(theme: Res<ThemePack>, mut dynamic_assets: ResMut<DynamicAssets>) {
+    info!("using the {} theme", theme.name);
+    for (key, path) in [
+        ("fonts.score", &theme.score_font),
+        ("fonts.menu", &theme.menu_font),
+    ] {
+        dynamic_assets.register_asset(
+            key,
+            Box::new(StandardDynamicAsset::File { path: path.clone() }),
+        );
+    }
+}
+
+/// Loads the theme pack named in the settings. Has to be added before the
+/// plugins whose assets it themes are built.
+pub struct ThemePackPlugin {
+    pub theme: String,
+}
+
+impl ThemePackPlugin {
+    fn themes_dir() -> PathBuf {
+        FileAssetReader::get_base_path()
+            .join("assets")
+            .join(THEMES_DIR)
+    }
+}
+
+impl Plugin for ThemePackPlugin {
+    fn build(&self, app: &mut App) {
+        let dir = Self::themes_dir();
+        let theme = ThemePack::load(&dir, &self.theme);
+        app.add_asset_variant("gravity.arrow", &theme.gravity_arrow);
+        if let Some(texture) = &theme.barrier_texture {
+            app.add_asset_variant("barrier.texture", texture);
+        }
+        app.insert_resource(ThemePacks::scan(&dir))
+            .insert_resource(theme)
+            .add_game_collection::<ThemeAssets>()
+            .add_systems(Startup, register_theme_fonts);
+    }
+}
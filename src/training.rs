@@ -0,0 +1,329 @@
+//! Training mode, which replaces the regular spawner with a single chosen
+//! scenario repeated at an adjustable speed.
+//!
+//! Scenarios are picked, and their speed set, from the ready prompt.
+use std::time::Duration;
+
+use bevy::{prelude::*, sprite::Anchor};
+use bevy_asset_loader::asset_collection::AssetCollection;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    asset_errors::GameCollectionAppExt,
+    fonts::FontsCollection,
+    graphics::graphics_page_closed,
+    mutators::ActiveMutators,
+    obstacle::{
+        factory::ObstacleFactory,
+        scenario::{Scenario, ScenarioSet},
+        SpawnParams,
+    },
+    obstacle_spawner::{spawns_gravity_regions, ObstacleSpawner, SpeedController},
+    physics::PhysicsSettings,
+    run::{RunClock, RunConfig},
+    ui_theme::{ThemedText, UiTheme},
+    util::{spawn_named_child, Containers},
+    GameState, WorldSettings, ON_ASSETS_LOADED,
+};
+
+const LABEL_TEXT: ThemedText = ThemedText {
+    size: 18.0,
+    color: Color::ANTIQUE_WHITE,
+};
+
+#[derive(AssetCollection, Resource)]
+pub struct TrainingScenarios {
+    #[asset(path = "levels/training.scenarios.ron")]
+    pub scenarios: Handle<ScenarioSet>,
+}
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct TrainingSettings {
+    /// Play scenarios instead of the regular levels.
+    pub enabled: bool,
+
+    /// Index of the chosen scenario.
+    pub scenario: usize,
+
+    /// Item speed, as a multiple of the base level speed.
+    pub speed: f32,
+}
+
+impl TrainingSettings {
+    pub const MIN_SPEED: f32 = 0.5;
+    pub const MAX_SPEED: f32 = 2.0;
+    pub const SPEED_STEP: f32 = 0.1;
+
+    fn step_speed(&mut self, steps: i32) {
+        let stepped = self.speed + steps as f32 * Self::SPEED_STEP;
+        self.speed = ((stepped / Self::SPEED_STEP).round() * Self::SPEED_STEP)
+            .clamp(Self::MIN_SPEED, Self::MAX_SPEED);
+    }
+
+    /// The chosen scenario, if there are any.
+    pub fn chosen<'a>(&self, set: &'a ScenarioSet) -> Option<&'a Scenario> {
+        set.scenarios
+            .get(self.scenario % set.scenarios.len().max(1))
+    }
+}
+
+impl Default for TrainingSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            scenario: 0,
+            speed: 1.0,
+        }
+    }
+}
+
+/// Run condition for training mode.
+pub fn training_active(settings: Res<TrainingSettings>) -> bool {
+    settings.enabled
+}
+
+/// Progress through the repetitions of the chosen scenario.
+#[derive(Resource, Default)]
+pub struct ScenarioSpawner {
+    timer: Timer,
+
+    /// Number of items spawned in the run.
+    spawned: usize,
+}
+
+impl ScenarioSpawner {
+    /// Save the progress of the run, along with the scenario and speed it
+    /// is played at.
+    pub fn save_state(&self, settings: &TrainingSettings) -> ScenarioState {
+        ScenarioState {
+            scenario: settings.scenario,
+            speed: settings.speed,
+            secs_per_item: self.timer.duration().as_secs_f32(),
+            timer_elapsed_secs: self.timer.elapsed_secs(),
+            spawned: self.spawned,
+        }
+    }
+
+    /// Restore previously saved progress, and the scenario and speed it was
+    /// played at, in training mode.
+    pub fn restore_state(&mut self, state: ScenarioState, settings: &mut TrainingSettings) {
+        settings.enabled = true;
+        settings.scenario = state.scenario;
+        settings.speed = state.speed;
+        self.timer = Timer::from_seconds(state.secs_per_item, TimerMode::Repeating);
+        self.timer
+            .set_elapsed(Duration::from_secs_f32(state.timer_elapsed_secs));
+        self.spawned = state.spawned;
+    }
+}
+
+/// Serializable state of a [`ScenarioSpawner`].
+#[derive(Serialize, Deserialize)]
+pub struct ScenarioState {
+    scenario: usize,
+    speed: f32,
+    secs_per_item: f32,
+    timer_elapsed_secs: f32,
+    spawned: usize,
+}
+
+impl ScenarioState {
+    /// Item speed the scenario was played at, as a multiple of the base
+    /// level speed.
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+}
+
+/// Pick a scenario with the left and right arrows, and its speed with up
+/// and down.
+fn pick_scenario(
+    keys: Res<Input<KeyCode>>,
+    mut settings: ResMut<TrainingSettings>,
+    scenarios: Res<TrainingScenarios>,
+    sets: Res<Assets<ScenarioSet>>,
+) {
+    let count = sets
+        .get(&scenarios.scenarios)
+        .map_or(0, |set| set.scenarios.len());
+    if count > 0 {
+        if keys.just_pressed(KeyCode::Left) {
+            settings.scenario = (settings.scenario + count - 1) % count;
+        }
+        if keys.just_pressed(KeyCode::Right) {
+            settings.scenario = (settings.scenario + 1) % count;
+        }
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        settings.step_speed(-1);
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        settings.step_speed(1);
+    }
+}
+
+/// Set the speed of a new run, and spawn its first item straight away.
+#[allow(clippy::too_many_arguments)]
+fn start_scenario(
+    settings: Res<TrainingSettings>,
+    scenarios: Res<TrainingScenarios>,
+    sets: Res<Assets<ScenarioSet>>,
+    clock: Res<RunClock>,
+    spawners: Query<&ObstacleSpawner>,
+    physics: Res<PhysicsSettings>,
+    mut speed: ResMut<SpeedController>,
+    mut spawner: ResMut<ScenarioSpawner>,
+) {
+    // continued runs keep going from where they were saved.
+    if !clock.elapsed().is_zero() {
+        return;
+    }
+    let Some(scenario) = sets
+        .get(&scenarios.scenarios)
+        .and_then(|set| settings.chosen(set))
+    else {
+        return;
+    };
+    if let Ok(level_spawner) = spawners.get_single() {
+        speed.set(level_spawner.item_vel(&physics) * settings.speed);
+    }
+    // keep the spacing between items the same at any speed.
+    let secs = scenario.seconds_per_item / settings.speed;
+    spawner.timer = Timer::from_seconds(secs, TimerMode::Repeating);
+    spawner.timer.set_elapsed(Duration::from_secs_f32(secs));
+    spawner.spawned = 0;
+}
+
+/// Spawn the next item of the scenario when it's due.
+#[allow(clippy::too_many_arguments)]
+fn spawn_scenario_items(
+    mut factory: ObstacleFactory,
+    time: Res<Time>,
+    settings: Res<TrainingSettings>,
+    scenarios: Res<TrainingScenarios>,
+    sets: Res<Assets<ScenarioSet>>,
+    spawners: Query<&ObstacleSpawner>,
+    play_world: Res<WorldSettings>,
+    physics: Res<PhysicsSettings>,
+    speed: Res<SpeedController>,
+    mutators: Res<ActiveMutators>,
+    config: Res<RunConfig>,
+    mut spawner: ResMut<ScenarioSpawner>,
+) {
+    spawner.timer.tick(time.delta());
+    if !spawner.timer.just_finished() {
+        return;
+    }
+    let Some(scenario) = sets
+        .get(&scenarios.scenarios)
+        .and_then(|set| settings.chosen(set))
+    else {
+        return;
+    };
+    let Ok(level_spawner) = spawners.get_single() else {
+        return;
+    };
+    if scenario.items.is_empty() {
+        return;
+    }
+
+    let params = &scenario.items[spawner.spawned % scenario.items.len()];
+    spawner.spawned += 1;
+    // the slot of a gravity region is left empty, to keep the rhythm.
+    if matches!(params, SpawnParams::Gravity { .. }) && !spawns_gravity_regions(&mutators, &config)
+    {
+        return;
+    }
+
+    // items scroll between the timer finishing and the end of the frame.
+    let start_x = level_spawner.start_offset_x(&play_world, &physics)
+        + speed.current.x * spawner.timer.elapsed_secs();
+    factory.spawn_params_at(start_x, params);
+}
+
+#[derive(Component)]
+struct TrainingLabel;
+
+fn spawn_label(
+    mut commands: Commands,
+    fonts: Res<FontsCollection>,
+    theme: Res<UiTheme>,
+    containers: Res<Containers>,
+) {
+    let (text, themed) = theme.text("", fonts.menu_font.clone(), LABEL_TEXT);
+    spawn_named_child(
+        &mut commands,
+        containers.ui,
+        "training_label",
+        (
+            Text2dBundle {
+                text: text.with_alignment(TextAlignment::Center),
+                text_anchor: Anchor::BottomCenter,
+                transform: Transform::from_xyz(0.0, 80.0, 20.0),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            TrainingLabel,
+            themed,
+        ),
+    );
+}
+
+/// Show the chosen scenario and speed on the ready prompt.
+fn update_label(
+    settings: Res<TrainingSettings>,
+    scenarios: Res<TrainingScenarios>,
+    sets: Res<Assets<ScenarioSet>>,
+    state: Res<State<GameState>>,
+    mut label: Query<(&mut Text, &mut Visibility), With<TrainingLabel>>,
+) {
+    let name = sets
+        .get(&scenarios.scenarios)
+        .and_then(|set| settings.chosen(set))
+        .map_or("NONE".to_string(), |scenario| scenario.name.to_uppercase());
+    for (mut text, mut vis) in label.iter_mut() {
+        if !settings.enabled || *state.get() != GameState::Ready {
+            *vis = Visibility::Hidden;
+            continue;
+        }
+        *vis = Visibility::Visible;
+        text.sections[0].value = format!(
+            "TRAINING: {}\nSPEED: {:.0}%\nLEFT / RIGHT: SCENARIO  UP / DOWN: SPEED",
+            name,
+            settings.speed * 100.0
+        );
+    }
+}
+
+pub struct TrainingPlugin;
+
+impl Plugin for TrainingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_collection::<TrainingScenarios>()
+            .register_type::<TrainingSettings>()
+            .init_resource::<TrainingSettings>()
+            .init_resource::<ScenarioSpawner>()
+            .add_systems(ON_ASSETS_LOADED, spawn_label)
+            .add_systems(
+                Update,
+                pick_scenario.run_if(
+                    in_state(GameState::Ready)
+                        .and_then(training_active)
+                        .and_then(graphics_page_closed),
+                ),
+            )
+            .add_systems(
+                OnEnter(GameState::Playing),
+                start_scenario.run_if(training_active),
+            )
+            .add_systems(
+                Update,
+                spawn_scenario_items.run_if(in_state(GameState::Playing).and_then(training_active)),
+            )
+            .add_systems(
+                Update,
+                update_label.run_if(resource_exists::<TrainingScenarios>()),
+            );
+    }
+}
@@ -0,0 +1,245 @@
+//! Shared styling for HUD and menu text, so that accessibility options
+//! apply everywhere text is shown.
+//!
+//! Text built through [`UiTheme::text`] is tagged with [`ThemedText`], and
+//! restyled whenever the theme changes. HUD elements are placed in the top
+//! corners through [`HudPlacement`], following the [`HudLayout`].
+use bevy::{prelude::*, sprite::Anchor, text::TextLayoutInfo, utils::HashSet};
+
+use crate::WorldSettings;
+
+/// Text color used by every themed text in high contrast mode.
+const HIGH_CONTRAST_COLOR: Color = Color::WHITE;
+
+/// Color of the plate behind text in high contrast mode.
+const BACKPLATE_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.85);
+
+/// Space between text and the edge of its backplate, in pixels.
+const BACKPLATE_PADDING: f32 = 6.0;
+
+/// Range that the text scale given on the command line is kept within.
+pub const TEXT_SCALE_RANGE: (f32, f32) = (0.5, 3.0);
+
+#[derive(Resource, Reflect)]
+#[reflect(Resource)]
+pub struct UiTheme {
+    /// Draw text in a single bright color, on a dark backplate.
+    pub high_contrast: bool,
+
+    /// Multiplier for the size of all themed text.
+    pub text_scale: f32,
+}
+
+impl Default for UiTheme {
+    fn default() -> Self {
+        Self {
+            high_contrast: false,
+            text_scale: 1.0,
+        }
+    }
+}
+
+/// Arrangement of the HUD around the screen.
+#[derive(Resource, Reflect, Default)]
+#[reflect(Resource)]
+pub struct HudLayout {
+    /// Swap the HUD left for right, and face the player sprite the other
+    /// way. Gameplay still scrolls in the same direction.
+    pub mirrored: bool,
+}
+
+impl HudLayout {
+    /// Side of the screen that an element placed on `side` ends up on.
+    pub fn side(&self, side: HudSide) -> HudSide {
+        match (side, self.mirrored) {
+            (side, false) => side,
+            (HudSide::Left, true) => HudSide::Right,
+            (HudSide::Right, true) => HudSide::Left,
+        }
+    }
+}
+
+#[derive(Reflect, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HudSide {
+    Left,
+    Right,
+}
+
+/// Place in a top corner of the screen for a HUD text or sprite.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct HudPlacement {
+    /// Side of the screen in the unmirrored layout.
+    pub side: HudSide,
+
+    /// Distance in from the side and down from the top of the screen.
+    pub inset: Vec2,
+}
+
+impl HudPlacement {
+    pub fn new(side: HudSide, inset: Vec2) -> Self {
+        Self { side, inset }
+    }
+}
+
+/// Size and color of a text before the theme is applied.
+#[derive(Component, Reflect, Clone, Copy)]
+#[reflect(Component)]
+pub struct ThemedText {
+    pub size: f32,
+    pub color: Color,
+}
+
+/// Dark plate drawn behind a themed text in high contrast mode.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Backplate;
+
+impl UiTheme {
+    pub fn text_style(&self, font: Handle<Font>, base: ThemedText) -> TextStyle {
+        TextStyle {
+            font,
+            font_size: base.size * self.text_scale,
+            color: if self.high_contrast {
+                HIGH_CONTRAST_COLOR
+            } else {
+                base.color
+            },
+        }
+    }
+
+    /// Single section text, along with the tag that keeps it in sync with
+    /// the theme.
+    pub fn text(
+        &self,
+        value: impl Into<String>,
+        font: Handle<Font>,
+        base: ThemedText,
+    ) -> (Text, ThemedText) {
+        (Text::from_section(value, self.text_style(font, base)), base)
+    }
+}
+
+/// Apply a changed theme to existing text.
+fn restyle_text(theme: Res<UiTheme>, mut texts: Query<(&mut Text, &ThemedText)>) {
+    for (mut text, base) in texts.iter_mut() {
+        for section in text.sections.iter_mut() {
+            section.style = theme.text_style(section.style.font.clone(), *base);
+        }
+    }
+}
+
+/// Move HUD elements to their corner of the screen, anchored on the side
+/// they are on.
+#[allow(clippy::type_complexity)]
+fn place_hud(
+    layout: Res<HudLayout>,
+    play_world: Res<WorldSettings>,
+    mut placed: Query<(
+        Ref<HudPlacement>,
+        &mut Transform,
+        Option<&mut Anchor>,
+        Option<&mut Sprite>,
+    )>,
+) {
+    let bounds = play_world.bounds;
+    for (placement, mut t, text_anchor, sprite) in placed.iter_mut() {
+        if !layout.is_changed() && !placement.is_changed() {
+            continue;
+        }
+        let (x, anchor) = match layout.side(placement.side) {
+            HudSide::Left => (bounds.min.x + placement.inset.x, Anchor::TopLeft),
+            HudSide::Right => (bounds.max.x - placement.inset.x, Anchor::TopRight),
+        };
+        t.translation.x = x;
+        t.translation.y = bounds.max.y - placement.inset.y;
+        if let Some(mut text_anchor) = text_anchor {
+            *text_anchor = anchor.clone();
+        }
+        if let Some(mut sprite) = sprite {
+            sprite.anchor = anchor;
+        }
+    }
+}
+
+/// Keep a backplate behind each themed text while in high contrast mode,
+/// sized to the laid out text.
+fn sync_backplates(
+    mut commands: Commands,
+    theme: Res<UiTheme>,
+    texts: Query<(Entity, &TextLayoutInfo, &Anchor), With<ThemedText>>,
+    mut plates: Query<(Entity, &Parent, &mut Sprite, &mut Transform), With<Backplate>>,
+) {
+    if !theme.high_contrast {
+        for (ent, ..) in plates.iter() {
+            commands.entity(ent).despawn_recursive();
+        }
+        return;
+    }
+
+    let plate_size = |layout: &TextLayoutInfo| {
+        if layout.logical_size == Vec2::ZERO {
+            Vec2::ZERO
+        } else {
+            layout.logical_size + Vec2::splat(BACKPLATE_PADDING * 2.0)
+        }
+    };
+    // offsets the plate so the padding surrounds the text whatever its anchor
+    let plate_offset = |anchor: &Anchor| (anchor.as_vec() * BACKPLATE_PADDING * 2.0).extend(-0.5);
+
+    let mut plated = HashSet::new();
+    for (_, parent, mut sprite, mut t) in plates.iter_mut() {
+        let Ok((_, layout, anchor)) = texts.get(parent.get()) else {
+            continue;
+        };
+        plated.insert(parent.get());
+        sprite.custom_size = Some(plate_size(layout));
+        sprite.anchor = anchor.clone();
+        t.translation = plate_offset(anchor);
+    }
+
+    for (ent, layout, anchor) in texts.iter() {
+        if plated.contains(&ent) {
+            continue;
+        }
+        let plate = commands
+            .spawn((
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: BACKPLATE_COLOR,
+                        custom_size: Some(plate_size(layout)),
+                        anchor: anchor.clone(),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(plate_offset(anchor)),
+                    ..default()
+                },
+                Backplate,
+                Name::new("backplate"),
+            ))
+            .id();
+        commands.entity(ent).add_child(plate);
+    }
+}
+
+pub struct UiThemePlugin;
+
+impl Plugin for UiThemePlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<UiTheme>()
+            .register_type::<ThemedText>()
+            .register_type::<Backplate>()
+            .register_type::<HudLayout>()
+            .register_type::<HudPlacement>()
+            .init_resource::<UiTheme>()
+            .init_resource::<HudLayout>()
+            .add_systems(
+                Update,
+                (
+                    restyle_text.run_if(resource_changed::<UiTheme>()),
+                    place_hud,
+                ),
+            )
+            .add_systems(PostUpdate, sync_backplates);
+    }
+}
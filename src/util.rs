@@ -1,14 +1,60 @@
-use bevy::prelude::*;
-use bevy_rapier2d::prelude::*;
-use bevy_tweening::Lens;
+//! Grouping of spawned entities under a few named containers, so that the
+//! world inspector stays navigable while debugging.
+//!
+//! Obstacles go under "Obstacles", text and prompts under "UI", and short
+//! lived visual effects under "Effects". Entities that only live in a
+//! container should be removed with `despawn_recursive`, so that the
+//! container's children stay up to date.
+use std::borrow::Cow;
 
-pub struct LinearVelocityLens {
-    pub start_linvel: Vec2,
-    pub end_linvel: Vec2,
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+/// Marks a top level container entity.
+#[derive(Component, Reflect)]
+#[reflect(Component)]
+pub struct Container;
+
+/// The container entities, created along with the app.
+#[derive(Resource)]
+pub struct Containers {
+    pub obstacles: Entity,
+    pub ui: Entity,
+    pub effects: Entity,
+}
+
+impl FromWorld for Containers {
+    fn from_world(world: &mut World) -> Self {
+        let mut container = |name: &'static str| {
+            world
+                .spawn((SpatialBundle::default(), Container, Name::new(name)))
+                .id()
+        };
+        Containers {
+            obstacles: container("Obstacles"),
+            ui: container("UI"),
+            effects: container("Effects"),
+        }
+    }
+}
+
+/// Spawn `bundle` as a child of `parent`, named `name`. The bundle shouldn't
+/// have a [`Name`] of its own.
+pub fn spawn_named_child<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    parent: Entity,
+    name: impl Into<Cow<'static, str>>,
+    bundle: impl Bundle,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut child = commands.spawn((bundle, Name::new(name)));
+    child.set_parent(parent);
+    child
 }
 
-impl Lens<Velocity> for LinearVelocityLens {
-    fn lerp(&mut self, target: &mut Velocity, ratio: f32) {
-        target.linvel = self.start_linvel * (1.0 - ratio) + self.end_linvel * ratio;
+pub struct ContainersPlugin;
+
+impl Plugin for ContainersPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<Container>()
+            .init_resource::<Containers>();
     }
 }